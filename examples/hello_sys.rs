@@ -1,13 +1,12 @@
 /// Test to showcase basic APIs of `llvm-model` as well as interoperation with `llvm-sys`.
 fn main() {
-    use llvm_model::{block, global, interop, target, types, Identifier};
+    use llvm_model::{block, global, id, interop, target, types};
+
+    interop::llvm_sys::init::all_targets();
+    assert!(interop::llvm_sys::init::native_target());
 
     // Gathers information about the target machine, unsafe as it calls LLVM C API functions.
     let host_target = unsafe {
-        llvm_sys::target::LLVM_InitializeAllTargets();
-        llvm_sys::target::LLVM_InitializeAllTargetInfos();
-        llvm_sys::target::LLVM_InitializeNativeTarget();
-
         interop::llvm_sys::target::Target::host_machine_target(
             target::CodeGenerationOptimization::Default,
             target::RelocationMode::Default,
@@ -16,14 +15,13 @@ fn main() {
         .unwrap()
     };
 
-    let mut builder =
-        interop::llvm_sys::ModuleBuilder::new(Identifier::try_from("hello").unwrap(), &host_target);
+    let mut builder = interop::llvm_sys::ModuleBuilder::new(id!("hello"), &host_target);
 
     {
         let module = builder.module();
 
         let main = global::Function::new(
-            Identifier::try_from("main").unwrap(),
+            id!("main"),
             types::Function::new(types::Return::Void, Vec::new()),
         );
 
@@ -36,10 +34,10 @@ fn main() {
         println!("{}", module);
     }
 
+    let context = interop::llvm_sys::Context::new();
+
     println!(
         "{}",
-        unsafe { builder.into_message(llvm_sys::core::LLVMGetGlobalContext()) }
-            .unwrap()
-            .to_identifier()
+        builder.into_message(&context).unwrap().to_identifier()
     );
 }
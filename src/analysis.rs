@@ -0,0 +1,283 @@
+//! Analyses of the control flow and values of functions, which can be used to implement checks and optimizations.
+//!
+//! Blocks are referred to by their index in [`Function::basic_blocks`], where the first block is the entry block.
+
+use crate::block::{BasicBlock, Instruction};
+use crate::global::Function;
+use crate::value::{Register, Value};
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// The control flow graph of a function, describing how control flow is transferred between its basic blocks.
+#[derive(Clone, Debug)]
+pub struct ControlFlowGraph {
+    blocks: Vec<Rc<BasicBlock>>,
+    indices: HashMap<*const BasicBlock, usize>,
+    successors: Vec<Vec<usize>>,
+    predecessors: Vec<Vec<usize>>,
+}
+
+impl ControlFlowGraph {
+    /// Builds the control flow graph for the current basic blocks of a function.
+    pub fn new(function: &Function) -> Self {
+        let blocks = function.basic_blocks().to_vec();
+        let indices = blocks
+            .iter()
+            .enumerate()
+            .map(|(index, block)| (Rc::as_ptr(block), index))
+            .collect::<HashMap<_, _>>();
+
+        let successors = blocks
+            .iter()
+            .map(|block| {
+                block
+                    .successors()
+                    .iter()
+                    .filter_map(|successor| indices.get(&Rc::as_ptr(successor)).copied())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let mut predecessors = vec![Vec::new(); blocks.len()];
+        for (index, block_successors) in successors.iter().enumerate() {
+            for &successor in block_successors.iter() {
+                predecessors[successor].push(index);
+            }
+        }
+
+        Self {
+            blocks,
+            indices,
+            successors,
+            predecessors,
+        }
+    }
+
+    /// Gets the basic blocks of the function.
+    pub fn blocks(&self) -> &[Rc<BasicBlock>] {
+        &self.blocks
+    }
+
+    /// Gets the index of a block, or `None` if it is not in the function.
+    pub fn index_of(&self, block: &BasicBlock) -> Option<usize> {
+        self.indices.get(&(block as *const BasicBlock)).copied()
+    }
+
+    /// Gets the indices of the blocks that control flow can be transferred to from the specified block.
+    pub fn successors(&self, block: usize) -> &[usize] {
+        &self.successors[block]
+    }
+
+    /// Gets the indices of the blocks that can transfer control flow to the specified block.
+    pub fn predecessors(&self, block: usize) -> &[usize] {
+        &self.predecessors[block]
+    }
+
+    /// Gets the indices of the blocks that are reachable from the entry block in reverse postorder, where each block comes
+    /// before its successors, except for the targets of back edges.
+    pub fn reverse_postorder(&self) -> Vec<usize> {
+        let mut order = Vec::with_capacity(self.blocks.len());
+        if self.blocks.is_empty() {
+            return order;
+        }
+
+        let mut visited = vec![false; self.blocks.len()];
+        let mut stack = vec![(0usize, 0usize)];
+        visited[0] = true;
+
+        while let Some((block, next_successor)) = stack.last_mut() {
+            match self.successors[*block].get(*next_successor) {
+                Some(&successor) => {
+                    *next_successor += 1;
+                    if !visited[successor] {
+                        visited[successor] = true;
+                        stack.push((successor, 0));
+                    }
+                }
+                None => {
+                    order.push(*block);
+                    stack.pop();
+                }
+            }
+        }
+
+        order.reverse();
+        order
+    }
+}
+
+/// The dominator tree of a function, where a block dominates another if every path from the entry block to the other
+/// block passes through it.
+///
+/// Computed with the algorithm described in [A Simple, Fast Dominance Algorithm](https://www.cs.rice.edu/~keith/EMBED/dom.pdf)
+/// by Cooper, Harvey, and Kennedy.
+#[derive(Clone, Debug)]
+pub struct DominatorTree {
+    immediate_dominators: Vec<Option<usize>>,
+}
+
+impl DominatorTree {
+    /// Computes the dominator tree from a control flow graph.
+    pub fn new(graph: &ControlFlowGraph) -> Self {
+        let order = graph.reverse_postorder();
+        let mut order_indices = vec![usize::MAX; graph.blocks().len()];
+        for (position, &block) in order.iter().enumerate() {
+            order_indices[block] = position;
+        }
+
+        let mut immediate_dominators = vec![None; graph.blocks().len()];
+        if let Some(&entry) = order.first() {
+            immediate_dominators[entry] = Some(entry);
+        }
+
+        let intersect = |immediate_dominators: &[Option<usize>], mut x: usize, mut y: usize| {
+            while x != y {
+                while order_indices[x] > order_indices[y] {
+                    x = immediate_dominators[x].expect("processed block should have a dominator");
+                }
+                while order_indices[y] > order_indices[x] {
+                    y = immediate_dominators[y].expect("processed block should have a dominator");
+                }
+            }
+            x
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for &block in order.iter().skip(1) {
+                let mut dominator = None;
+                for &predecessor in graph.predecessors(block) {
+                    if immediate_dominators[predecessor].is_some() {
+                        dominator = Some(match dominator {
+                            None => predecessor,
+                            Some(current) => intersect(&immediate_dominators, predecessor, current),
+                        });
+                    }
+                }
+
+                if dominator.is_some() && immediate_dominators[block] != dominator {
+                    immediate_dominators[block] = dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        Self {
+            immediate_dominators,
+        }
+    }
+
+    /// Returns `true` if the block is reachable from the entry block.
+    pub fn is_reachable(&self, block: usize) -> bool {
+        self.immediate_dominators[block].is_some()
+    }
+
+    /// Gets the immediate dominator of a block, or `None` for the entry block and for unreachable blocks.
+    pub fn immediate_dominator(&self, block: usize) -> Option<usize> {
+        self.immediate_dominators[block].filter(|&dominator| dominator != block)
+    }
+
+    /// Returns `true` if `dominator` dominates `block`. Every block dominates itself, and unreachable blocks are only
+    /// dominated by themselves.
+    pub fn dominates(&self, dominator: usize, block: usize) -> bool {
+        if dominator == block {
+            return true;
+        }
+
+        let mut current = block;
+        while let Some(parent) = self.immediate_dominator(current) {
+            if parent == dominator {
+                return true;
+            }
+            current = parent;
+        }
+
+        false
+    }
+
+    /// Gets the blocks whose immediate dominator is the specified block.
+    pub fn children(&self, block: usize) -> Vec<usize> {
+        (0..self.immediate_dominators.len())
+            .filter(|&child| self.immediate_dominator(child) == Some(block))
+            .collect()
+    }
+}
+
+/// The position of an instruction within a function.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Location {
+    /// The index of the block containing the instruction.
+    pub block: usize,
+    /// The index of the instruction within the block.
+    pub instruction: usize,
+}
+
+/// Maps each register in a function to the instruction that defines it and to the instructions that use it.
+#[derive(Clone, Debug, Default)]
+pub struct UseDefChains {
+    definitions: HashMap<Register, Location>,
+    uses: HashMap<Register, Vec<Location>>,
+}
+
+impl UseDefChains {
+    /// Finds the definitions and uses of every register in the blocks of a control flow graph.
+    pub fn new(graph: &ControlFlowGraph) -> Self {
+        let mut chains = Self::default();
+
+        for (block_index, block) in graph.blocks().iter().enumerate() {
+            for (instruction_index, instruction) in block.instructions().iter().enumerate() {
+                let location = Location {
+                    block: block_index,
+                    instruction: instruction_index,
+                };
+
+                if let Some(result) = instruction.result() {
+                    chains.definitions.insert(result.clone(), location);
+                }
+
+                for operand in instruction.operands() {
+                    if let Value::Register(register) = operand {
+                        chains
+                            .uses
+                            .entry(register.clone())
+                            .or_default()
+                            .push(location);
+                    }
+                }
+            }
+        }
+
+        chains
+    }
+
+    /// Gets the location of the instruction that defines a register, or `None` if it is not defined in the function.
+    pub fn definition(&self, register: &Register) -> Option<Location> {
+        self.definitions.get(register).copied()
+    }
+
+    /// Gets the locations of the instructions that use a register.
+    pub fn uses(&self, register: &Register) -> &[Location] {
+        self.uses
+            .get(register)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over all registers defined in the function, along with the locations of their definitions.
+    pub fn definitions(&self) -> impl Iterator<Item = (&Register, Location)> + '_ {
+        self.definitions
+            .iter()
+            .map(|(register, location)| (register, *location))
+    }
+}
+
+/// Returns `true` if the instruction at the specified location is a `phi` instruction.
+pub fn is_phi(graph: &ControlFlowGraph, location: Location) -> bool {
+    matches!(
+        graph.blocks()[location.block]
+            .instructions()
+            .get(location.instruction),
+        Some(Instruction::Phi { .. })
+    )
+}
@@ -2,13 +2,31 @@
 //!
 //! See [the LLVM instruction set reference here](https://llvm.org/docs/LangRef.html#instruction-reference).
 
-use crate::value::Value;
+use crate::global;
+use crate::metadata;
+use crate::module::Module;
+use crate::target::layout::Alignment;
+use crate::types;
+use crate::value::{self, Register, Value};
+use crate::{Id, Identifier, Symbol};
 use std::cell::{Cell, RefCell};
 use std::fmt::{Display, Formatter, Write as _};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
+/// Writes the label of a block, which is assigned when it is appended to a function.
+///
+/// Unnamed blocks are numbered when the function containing them is printed. Blocks that are not yet part of a function
+/// are referred to by the name they were created with, and only unnamed blocks are labeled by their address.
 fn block_name(block: &BasicBlock, f: &mut Formatter) -> std::fmt::Result {
-    write!(f, "B{:X}", block as *const BasicBlock as usize)
+    if let Some(number) = crate::numbering::block_number(block) {
+        return write!(f, "{}", number);
+    }
+
+    match (block.label.borrow().as_ref(), block.name()) {
+        (Some(label), _) => Display::fmt(label, f),
+        (None, Some(name)) => Display::fmt(name, f),
+        (None, None) => write!(f, "B{:X}", block as *const BasicBlock as usize),
+    }
 }
 
 struct BlockLabel<'b>(&'b BasicBlock);
@@ -20,9 +38,1070 @@ impl Display for BlockLabel<'_> {
     }
 }
 
+/// Error used when an instruction cannot be added to a basic block.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// An instruction was added after the terminator instruction of a block.
+    #[error("{0}")]
+    AlreadyTerminated(String),
+    /// An operand was not of the expected type.
+    #[error("{0}")]
+    InvalidOperand(String),
+    /// An instruction was placed at an invalid position within a block.
+    #[error("{0}")]
+    InvalidPosition(String),
+    /// A feature that is not yet supported was used.
+    #[error("{0}")]
+    Unsupported(String),
+    /// An [`InstructionBuilder`] was created for a block that is already used by another builder.
+    #[error("{0}")]
+    BuilderExists(String),
+}
+
+/// Panics with the message of an error, used by the methods that do not return a [`Result`].
+fn or_panic<T>(result: Result<T, Error>) -> T {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
+
+/// Indicates whether a `call` instruction is a tail call.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TailCallKind {
+    /// No tail call marker.
+    None,
+    /// A hint that the call may be optimized into a tail call (`tail`).
+    Tail,
+    /// Indicates that the call must be optimized into a tail call (`musttail`).
+    MustTail,
+    /// Indicates that the call must not be optimized into a tail call (`notail`).
+    NoTail,
+}
+
+crate::enum_default!(TailCallKind, None);
+
+impl Display for TailCallKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::None => "",
+            Self::Tail => "tail ",
+            Self::MustTail => "musttail ",
+            Self::NoTail => "notail ",
+        })
+    }
+}
+
+/// The function that is called by a `call` instruction.
+#[derive(Clone, Debug)]
+pub enum Callee {
+    /// A function that is called directly.
+    Function {
+        /// The function that is called, weakly referenced since functions may be recursive.
+        function: Weak<global::Function>,
+        /// The name of the function that is called.
+        name: Symbol,
+    },
+    /// A pointer to the function that is called, such as a function pointer loaded from memory.
+    Pointer(Value),
+    /// Inline assembly that is executed in place of a function.
+    InlineAsm(Rc<value::InlineAsm>),
+}
+
+impl Callee {
+    pub(crate) fn function(function: &Rc<global::Function>) -> Self {
+        Self::Function {
+            function: Rc::downgrade(function),
+            name: function.symbol().clone(),
+        }
+    }
+}
+
+impl Display for Callee {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::Function { name, .. } => write!(f, "@{}", name.as_id()),
+            Self::Pointer(pointer) => Display::fmt(&pointer.untyped(), f),
+            Self::InlineAsm(assembly) => Display::fmt(assembly, f),
+        }
+    }
+}
+
+/// A reference to the target of a branch or to an incoming block of a `phi` instruction.
+///
+/// Blocks are weakly referenced, since branches in loops would otherwise result in reference cycles.
+#[derive(Clone, Debug)]
+pub struct BlockReference(Weak<BasicBlock>);
+
+impl BlockReference {
+    pub(crate) fn new(block: &Rc<BasicBlock>) -> Self {
+        Self(Rc::downgrade(block))
+    }
+
+    /// Gets the referenced block, or `None` if it was dropped.
+    pub fn block(&self) -> Option<Rc<BasicBlock>> {
+        self.0.upgrade()
+    }
+
+    /// Gets a pointer to the referenced block, used to identify it.
+    pub(crate) fn as_ptr(&self) -> *const BasicBlock {
+        self.0.as_ptr()
+    }
+}
+
+impl Display for BlockReference {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_char('%')?;
+        match self.0.upgrade() {
+            Some(block) => block_name(&block, f),
+            None => write!(f, "B{:X}", self.as_ptr() as usize),
+        }
+    }
+}
+
+/// Binary operations on integers or vectors of integers.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntegerBinaryOperation {
+    /// Integer addition (`add`).
+    Add,
+    /// Integer subtraction (`sub`).
+    Sub,
+    /// Integer multiplication (`mul`).
+    Mul,
+    /// Unsigned integer division (`udiv`).
+    UDiv,
+    /// Signed integer division (`sdiv`).
+    SDiv,
+    /// Remainder of unsigned integer division (`urem`).
+    URem,
+    /// Remainder of signed integer division (`srem`).
+    SRem,
+    /// Shift left (`shl`).
+    Shl,
+    /// Logical shift right, which fills the most significant bits with zeroes (`lshr`).
+    LShr,
+    /// Arithmetic shift right, which fills the most significant bits with the sign bit (`ashr`).
+    AShr,
+    /// Bitwise AND (`and`).
+    And,
+    /// Bitwise OR (`or`).
+    Or,
+    /// Bitwise exclusive OR (`xor`).
+    Xor,
+}
+
+impl Display for IntegerBinaryOperation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::Mul => "mul",
+            Self::UDiv => "udiv",
+            Self::SDiv => "sdiv",
+            Self::URem => "urem",
+            Self::SRem => "srem",
+            Self::Shl => "shl",
+            Self::LShr => "lshr",
+            Self::AShr => "ashr",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+        })
+    }
+}
+
+/// Binary operations on floating-point values or vectors of floating-point values.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloatBinaryOperation {
+    /// Floating-point addition (`fadd`).
+    Add,
+    /// Floating-point subtraction (`fsub`).
+    Sub,
+    /// Floating-point multiplication (`fmul`).
+    Mul,
+    /// Floating-point division (`fdiv`).
+    Div,
+    /// Remainder of floating-point division (`frem`).
+    Rem,
+}
+
+impl Display for FloatBinaryOperation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Add => "fadd",
+            Self::Sub => "fsub",
+            Self::Mul => "fmul",
+            Self::Div => "fdiv",
+            Self::Rem => "frem",
+        })
+    }
+}
+
+/// Flags that allow optimizations of floating-point operations that would otherwise be unsafe.
+///
+/// See [the latest LLVM documentation on fast-math flags here](https://llvm.org/docs/LangRef.html#fast-math-flags).
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FastMathFlags(u8);
+
+impl FastMathFlags {
+    /// No flags, requiring operations to follow IEEE-754 semantics.
+    pub const NONE: Self = Self(0);
+    /// Allows optimizations to assume that the operands and result are not NaN (`nnan`).
+    pub const NO_NANS: Self = Self(1);
+    /// Allows optimizations to assume that the operands and result are not positive or negative infinity (`ninf`).
+    pub const NO_INFS: Self = Self(1 << 1);
+    /// Allows optimizations to treat the sign of a zero operand or result as insignificant (`nsz`).
+    pub const NO_SIGNED_ZEROS: Self = Self(1 << 2);
+    /// Allows the reciprocal of an operand to be used rather than performing a division (`arcp`).
+    pub const ALLOW_RECIPROCAL: Self = Self(1 << 3);
+    /// Allows floating-point contraction, such as fusing a multiplication and an addition (`contract`).
+    pub const ALLOW_CONTRACT: Self = Self(1 << 4);
+    /// Allows approximations of functions such as `sin` and `sqrt` to be used (`afn`).
+    pub const APPROXIMATE_FUNCTIONS: Self = Self(1 << 5);
+    /// Allows operations to be reassociated, which may change the result (`reassoc`).
+    pub const ALLOW_REASSOCIATION: Self = Self(1 << 6);
+    /// All other flags (`fast`).
+    pub const FAST: Self = Self((1 << 7) - 1);
+
+    const KEYWORDS: [(Self, &'static str); 7] = [
+        (Self::ALLOW_REASSOCIATION, "reassoc"),
+        (Self::NO_NANS, "nnan"),
+        (Self::NO_INFS, "ninf"),
+        (Self::NO_SIGNED_ZEROS, "nsz"),
+        (Self::ALLOW_RECIPROCAL, "arcp"),
+        (Self::ALLOW_CONTRACT, "contract"),
+        (Self::APPROXIMATE_FUNCTIONS, "afn"),
+    ];
+
+    /// Returns `true` if all of the specified flags are set.
+    pub fn contains(self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+
+    /// Returns `true` if no flags are set.
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl std::ops::BitOr for FastMathFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl std::ops::BitOrAssign for FastMathFlags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0
+    }
+}
+
+impl std::ops::BitAnd for FastMathFlags {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+/// Prints each flag followed by a space, so that nothing is printed when no flags are set.
+impl Display for FastMathFlags {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        if self.contains(Self::FAST) {
+            return f.write_str("fast ");
+        }
+
+        for (flag, keyword) in Self::KEYWORDS {
+            if self.contains(flag) {
+                write!(f, "{} ", keyword)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The condition used to compare integers, pointers, or vectors of either in an `icmp` instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IcmpPredicate {
+    /// `eq`
+    Equal,
+    /// `ne`
+    NotEqual,
+    /// `ugt`
+    UnsignedGreaterThan,
+    /// `uge`
+    UnsignedGreaterOrEqual,
+    /// `ult`
+    UnsignedLessThan,
+    /// `ule`
+    UnsignedLessOrEqual,
+    /// `sgt`
+    SignedGreaterThan,
+    /// `sge`
+    SignedGreaterOrEqual,
+    /// `slt`
+    SignedLessThan,
+    /// `sle`
+    SignedLessOrEqual,
+}
+
+impl Display for IcmpPredicate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::UnsignedGreaterThan => "ugt",
+            Self::UnsignedGreaterOrEqual => "uge",
+            Self::UnsignedLessThan => "ult",
+            Self::UnsignedLessOrEqual => "ule",
+            Self::SignedGreaterThan => "sgt",
+            Self::SignedGreaterOrEqual => "sge",
+            Self::SignedLessThan => "slt",
+            Self::SignedLessOrEqual => "sle",
+        })
+    }
+}
+
+/// The condition used to compare floating-point values or vectors of floating-point values in an `fcmp` instruction.
+///
+/// Ordered comparisons are false if either operand is a NaN, while unordered comparisons are true if either operand is a
+/// NaN.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FcmpPredicate {
+    /// `false`, which always returns false.
+    False,
+    /// `oeq`
+    OrderedEqual,
+    /// `ogt`
+    OrderedGreaterThan,
+    /// `oge`
+    OrderedGreaterOrEqual,
+    /// `olt`
+    OrderedLessThan,
+    /// `ole`
+    OrderedLessOrEqual,
+    /// `one`
+    OrderedNotEqual,
+    /// `ord`, which returns true if neither operand is a NaN.
+    Ordered,
+    /// `ueq`
+    UnorderedEqual,
+    /// `ugt`
+    UnorderedGreaterThan,
+    /// `uge`
+    UnorderedGreaterOrEqual,
+    /// `ult`
+    UnorderedLessThan,
+    /// `ule`
+    UnorderedLessOrEqual,
+    /// `une`
+    UnorderedNotEqual,
+    /// `uno`, which returns true if either operand is a NaN.
+    Unordered,
+    /// `true`, which always returns true.
+    True,
+}
+
+impl Display for FcmpPredicate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::False => "false",
+            Self::OrderedEqual => "oeq",
+            Self::OrderedGreaterThan => "ogt",
+            Self::OrderedGreaterOrEqual => "oge",
+            Self::OrderedLessThan => "olt",
+            Self::OrderedLessOrEqual => "ole",
+            Self::OrderedNotEqual => "one",
+            Self::Ordered => "ord",
+            Self::UnorderedEqual => "ueq",
+            Self::UnorderedGreaterThan => "ugt",
+            Self::UnorderedGreaterOrEqual => "uge",
+            Self::UnorderedLessThan => "ult",
+            Self::UnorderedLessOrEqual => "ule",
+            Self::UnorderedNotEqual => "une",
+            Self::Unordered => "uno",
+            Self::True => "true",
+        })
+    }
+}
+
+/// Operations that convert integers and floating-point values to other types.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConversionOperation {
+    /// Truncates an integer to a smaller integer type (`trunc`).
+    Trunc,
+    /// Extends an integer to a larger integer type, filling the most significant bits with zeroes (`zext`).
+    ZExt,
+    /// Extends an integer to a larger integer type, filling the most significant bits with the sign bit (`sext`).
+    SExt,
+    /// Truncates a floating-point value to a smaller floating-point type (`fptrunc`).
+    FPTrunc,
+    /// Extends a floating-point value to a larger floating-point type (`fpext`).
+    FPExt,
+    /// Converts a floating-point value to an unsigned integer (`fptoui`).
+    FPToUI,
+    /// Converts a floating-point value to a signed integer (`fptosi`).
+    FPToSI,
+    /// Converts an unsigned integer to a floating-point value (`uitofp`).
+    UIToFP,
+    /// Converts a signed integer to a floating-point value (`sitofp`).
+    SIToFP,
+}
+
+impl Display for ConversionOperation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Trunc => "trunc",
+            Self::ZExt => "zext",
+            Self::SExt => "sext",
+            Self::FPTrunc => "fptrunc",
+            Self::FPExt => "fpext",
+            Self::FPToUI => "fptoui",
+            Self::FPToSI => "fptosi",
+            Self::UIToFP => "uitofp",
+            Self::SIToFP => "sitofp",
+        })
+    }
+}
+
+/// Specifies the constraints on the ordering of atomic memory operations.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtomicOrdering {
+    /// The operation is atomic, but no ordering is guaranteed (`unordered`). Only valid for `load` and `store`.
+    Unordered,
+    /// Operations on the same address have a single total order (`monotonic`).
+    Monotonic,
+    /// Synchronizes with `release` operations on the same address (`acquire`).
+    Acquire,
+    /// Synchronizes with `acquire` operations on the same address (`release`).
+    Release,
+    /// Acts as both an `acquire` and a `release` operation (`acq_rel`).
+    AcquireRelease,
+    /// Acts as an `acq_rel` operation, with a single total order for all `seq_cst` operations (`seq_cst`).
+    SequentiallyConsistent,
+}
+
+impl AtomicOrdering {
+    /// Returns `true` if this ordering includes the guarantees of an `acquire` operation.
+    pub fn is_acquire(self) -> bool {
+        matches!(
+            self,
+            Self::Acquire | Self::AcquireRelease | Self::SequentiallyConsistent
+        )
+    }
+
+    /// Returns `true` if this ordering includes the guarantees of a `release` operation.
+    pub fn is_release(self) -> bool {
+        matches!(
+            self,
+            Self::Release | Self::AcquireRelease | Self::SequentiallyConsistent
+        )
+    }
+}
+
+impl Display for AtomicOrdering {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Unordered => "unordered",
+            Self::Monotonic => "monotonic",
+            Self::Acquire => "acquire",
+            Self::Release => "release",
+            Self::AcquireRelease => "acq_rel",
+            Self::SequentiallyConsistent => "seq_cst",
+        })
+    }
+}
+
+/// The set of threads that an atomic memory operation synchronizes with.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SyncScope {
+    /// The operation synchronizes with all threads in the system.
+    System,
+    /// The operation only synchronizes with the current thread, such as when handling signals (`singlethread`).
+    SingleThread,
+    /// A target-specific synchronization scope.
+    Named(Identifier),
+}
+
+crate::enum_default!(SyncScope, System);
+
+impl Display for SyncScope {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::System => Ok(()),
+            Self::SingleThread => f.write_str("syncscope(\"singlethread\") "),
+            Self::Named(name) => write!(f, "syncscope(\"{}\") ", name),
+        }
+    }
+}
+
+/// The synchronization scope and ordering of an atomic memory operation.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Atomic {
+    /// The threads that the operation synchronizes with.
+    pub scope: SyncScope,
+    /// The ordering constraints of the operation.
+    pub ordering: AtomicOrdering,
+}
+
+impl Atomic {
+    /// Creates an atomic operation with the specified ordering that synchronizes with all threads.
+    pub fn new(ordering: AtomicOrdering) -> Self {
+        Self {
+            scope: SyncScope::System,
+            ordering,
+        }
+    }
+}
+
+impl Display for Atomic {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{}{}", self.scope, self.ordering)
+    }
+}
+
+/// Operations performed by an `atomicrmw` instruction.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AtomicRmwOperation {
+    /// Replaces the value in memory (`xchg`).
+    Xchg,
+    /// Integer addition (`add`).
+    Add,
+    /// Integer subtraction (`sub`).
+    Sub,
+    /// Bitwise AND (`and`).
+    And,
+    /// Bitwise NAND (`nand`).
+    Nand,
+    /// Bitwise OR (`or`).
+    Or,
+    /// Bitwise XOR (`xor`).
+    Xor,
+    /// Signed maximum (`max`).
+    Max,
+    /// Signed minimum (`min`).
+    Min,
+    /// Unsigned maximum (`umax`).
+    UMax,
+    /// Unsigned minimum (`umin`).
+    UMin,
+    /// Floating-point addition (`fadd`).
+    FAdd,
+    /// Floating-point subtraction (`fsub`).
+    FSub,
+}
+
+impl Display for AtomicRmwOperation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Xchg => "xchg",
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::And => "and",
+            Self::Nand => "nand",
+            Self::Or => "or",
+            Self::Xor => "xor",
+            Self::Max => "max",
+            Self::Min => "min",
+            Self::UMax => "umax",
+            Self::UMin => "umin",
+            Self::FAdd => "fadd",
+            Self::FSub => "fsub",
+        })
+    }
+}
+
+/// An instruction within a basic block.
+///
+/// Instructions are created with the methods of [`BasicBlock`], and can be inspected with [`BasicBlock::instructions`].
 #[derive(Debug)]
-pub(crate) enum Instruction {
+#[non_exhaustive]
+pub enum Instruction {
+    /// A `ret` instruction, which returns control flow and optionally a value back to the calling function.
     Ret(Option<Value>),
+    /// An unconditional `br` instruction.
+    Br(BlockReference),
+    /// A conditional `br` instruction.
+    CondBr {
+        /// A boolean value indicating which block control flow is transferred to.
+        condition: Value,
+        /// The block that control flow is transferred to if the condition is `true`.
+        true_target: BlockReference,
+        /// The block that control flow is transferred to if the condition is `false`.
+        false_target: BlockReference,
+    },
+    /// A `switch` instruction.
+    Switch {
+        /// The integer value that is compared against the case values.
+        value: Value,
+        /// The block that control flow is transferred to if none of the case values match.
+        default_target: BlockReference,
+        /// The case values, along with the blocks that control flow is transferred to.
+        cases: Vec<(Value, BlockReference)>,
+    },
+    /// An `indirectbr` instruction, which transfers control flow to the block at an address.
+    IndirectBr {
+        /// The address of the block that control flow is transferred to, usually obtained from a `blockaddress` constant.
+        address: Value,
+        /// The blocks that the address can refer to.
+        destinations: Vec<BlockReference>,
+    },
+    /// A `callbr` instruction, which calls a function that can transfer control flow to one of the indirect targets
+    /// instead of returning normally, used to model `asm goto` constructs.
+    CallBr {
+        /// The calling convention used to call the function.
+        calling_convention: global::CallingConvention,
+        /// The function that is called.
+        callee: Callee,
+        /// The signature of the function that is called.
+        signature: Rc<types::Function>,
+        /// The arguments passed to the function.
+        arguments: Vec<Value>,
+        /// The register containing the return value, or `None` if the function returns `void`.
+        result: Option<Register>,
+        /// The block that control flow is transferred to when the callee returns normally.
+        fallthrough_target: BlockReference,
+        /// The blocks that the callee can transfer control flow to instead of returning.
+        indirect_targets: Vec<BlockReference>,
+    },
+    /// A `phi` instruction.
+    Phi {
+        /// The values that are selected, along with the blocks that control flow came from.
+        incoming: Vec<(Value, BlockReference)>,
+        /// The register containing the selected value.
+        result: Register,
+    },
+    /// A `call` instruction.
+    Call {
+        /// Indicates whether the call is a tail call.
+        tail_call: TailCallKind,
+        /// The calling convention used to call the function.
+        calling_convention: global::CallingConvention,
+        /// The function that is called.
+        callee: Callee,
+        /// The signature of the function that is called.
+        signature: Rc<types::Function>,
+        /// The arguments passed to the function.
+        arguments: Vec<Value>,
+        /// The alignments that the caller guarantees for each of the pointer arguments, printed as `align` attributes.
+        /// Contains one entry for each argument.
+        argument_alignments: Vec<Option<Alignment>>,
+        /// The register containing the return value, or `None` if the function returns `void`.
+        result: Option<Register>,
+    },
+    /// An instruction performing a binary operation on integers.
+    IntegerBinary {
+        /// The operation that is performed.
+        operation: IntegerBinaryOperation,
+        /// The first operand.
+        x: Value,
+        /// The second operand.
+        y: Value,
+        /// The register containing the result.
+        result: Register,
+    },
+    /// An instruction performing a binary operation on floating-point values.
+    FloatBinary {
+        /// The operation that is performed.
+        operation: FloatBinaryOperation,
+        /// Flags allowing optimizations of the operation.
+        flags: FastMathFlags,
+        /// The first operand.
+        x: Value,
+        /// The second operand.
+        y: Value,
+        /// The register containing the result.
+        result: Register,
+    },
+    /// An `fneg` instruction, which negates a floating-point value.
+    FNeg {
+        /// Flags allowing optimizations of the operation.
+        flags: FastMathFlags,
+        /// The value that is negated.
+        operand: Value,
+        /// The register containing the negated value.
+        result: Register,
+    },
+    /// An `icmp` instruction.
+    ICmp {
+        /// The condition used to compare the operands.
+        predicate: IcmpPredicate,
+        /// The first operand.
+        x: Value,
+        /// The second operand.
+        y: Value,
+        /// The register containing the boolean result.
+        result: Register,
+    },
+    /// An `fcmp` instruction.
+    FCmp {
+        /// The condition used to compare the operands.
+        predicate: FcmpPredicate,
+        /// The first operand.
+        x: Value,
+        /// The second operand.
+        y: Value,
+        /// The register containing the boolean result.
+        result: Register,
+    },
+    /// An instruction that converts a value to another type.
+    Conversion {
+        /// The conversion that is performed.
+        operation: ConversionOperation,
+        /// The value that is converted.
+        operand: Value,
+        /// The register containing the converted value.
+        result: Register,
+    },
+    /// A `va_arg` instruction, which retrieves the next argument from a variable argument list.
+    VaArg {
+        /// A pointer to the variable argument list, as initialized by `llvm.va_start`.
+        list: Value,
+        /// The register containing the retrieved argument.
+        result: Register,
+    },
+    /// An `alloca` instruction.
+    Alloca {
+        /// The type of the values that are allocated.
+        allocated_type: Rc<types::FirstClass>,
+        /// The number of values that are allocated, or `None` if a single value is allocated.
+        count: Option<Value>,
+        /// The alignment of the allocated memory.
+        alignment: Option<Alignment>,
+        /// The register containing a pointer to the allocated memory.
+        result: Register,
+    },
+    /// A `load` instruction.
+    Load {
+        /// A pointer to the value that is read.
+        pointer: Value,
+        /// The alignment of the value that is read.
+        alignment: Option<Alignment>,
+        /// Indicates whether the load is `volatile`.
+        volatile: bool,
+        /// The ordering and synchronization scope of the load, or `None` if it is not atomic.
+        atomic: Option<Atomic>,
+        /// The register containing the value that was read.
+        result: Register,
+    },
+    /// A `store` instruction.
+    Store {
+        /// The value that is written.
+        value: Value,
+        /// A pointer to the location that the value is written to.
+        pointer: Value,
+        /// The alignment of the value that is written.
+        alignment: Option<Alignment>,
+        /// Indicates whether the store is `volatile`.
+        volatile: bool,
+        /// The ordering and synchronization scope of the store, or `None` if it is not atomic.
+        atomic: Option<Atomic>,
+    },
+    /// An `atomicrmw` instruction, which atomically modifies a value in memory.
+    AtomicRmw {
+        /// The operation that is performed.
+        operation: AtomicRmwOperation,
+        /// Indicates whether the operation is `volatile`.
+        volatile: bool,
+        /// A pointer to the value that is modified.
+        pointer: Value,
+        /// The operand of the operation.
+        value: Value,
+        /// The ordering and synchronization scope of the operation.
+        atomic: Atomic,
+        /// The register containing the value that was in memory before it was modified.
+        result: Register,
+    },
+    /// A `cmpxchg` instruction, which atomically replaces a value in memory if it is equal to an expected value.
+    CmpXchg {
+        /// Indicates whether the comparison may spuriously fail (`weak`).
+        weak: bool,
+        /// Indicates whether the operation is `volatile`.
+        volatile: bool,
+        /// A pointer to the value that is compared.
+        pointer: Value,
+        /// The value that the value in memory is compared with.
+        expected: Value,
+        /// The value that is written if the comparison succeeds.
+        replacement: Value,
+        /// The ordering and synchronization scope of the operation if the comparison succeeds.
+        success: Atomic,
+        /// The ordering of the operation if the comparison fails.
+        failure_ordering: AtomicOrdering,
+        /// The register containing a structure of the value that was in memory, and a boolean indicating whether the
+        /// comparison succeeded.
+        result: Register,
+    },
+    /// A `fence` instruction, which introduces ordering constraints between memory operations.
+    Fence(Atomic),
+    /// A `getelementptr` instruction.
+    GetElementPtr {
+        /// Indicates whether the address calculation is `inbounds`.
+        inbounds: bool,
+        /// The type of the value pointed to by the pointer operand, which is indexed by the first index.
+        element_type: Rc<types::FirstClass>,
+        /// The pointer that the address is calculated from.
+        pointer: Value,
+        /// The indices used to select the element.
+        indices: Vec<Value>,
+        /// The register containing the address of the element.
+        result: Register,
+    },
+}
+
+impl Instruction {
+    /// Returns `true` if this instruction is a terminator instruction, which must be the last instruction in a block.
+    pub fn is_terminator(&self) -> bool {
+        matches!(
+            self,
+            Self::Ret(_)
+                | Self::Br(_)
+                | Self::CondBr { .. }
+                | Self::Switch { .. }
+                | Self::IndirectBr { .. }
+                | Self::CallBr { .. }
+        )
+    }
+
+    /// Gets the blocks that this instruction can transfer control flow to, which is empty for instructions that are not
+    /// terminators or for `ret` instructions.
+    pub fn targets(&self) -> Vec<&BlockReference> {
+        match self {
+            Self::Br(target) => vec![target],
+            Self::CondBr {
+                true_target,
+                false_target,
+                ..
+            } => vec![true_target, false_target],
+            Self::Switch {
+                default_target,
+                cases,
+                ..
+            } => std::iter::once(default_target)
+                .chain(cases.iter().map(|(_, target)| target))
+                .collect(),
+            Self::IndirectBr { destinations, .. } => destinations.iter().collect(),
+            Self::CallBr {
+                fallthrough_target,
+                indirect_targets,
+                ..
+            } => std::iter::once(fallthrough_target)
+                .chain(indirect_targets.iter())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Gets the values used as operands by this instruction, including the incoming values of a `phi` instruction.
+    pub fn operands(&self) -> Vec<&Value> {
+        match self {
+            Self::Ret(value) => value.iter().collect(),
+            Self::Br(_) => Vec::new(),
+            Self::CondBr { condition, .. } => vec![condition],
+            Self::Switch { value, cases, .. } => std::iter::once(value)
+                .chain(cases.iter().map(|(case_value, _)| case_value))
+                .collect(),
+            Self::IndirectBr { address, .. } => vec![address],
+            Self::Phi { incoming, .. } => incoming.iter().map(|(value, _)| value).collect(),
+            Self::Call {
+                callee, arguments, ..
+            }
+            | Self::CallBr {
+                callee, arguments, ..
+            } => match callee {
+                Callee::Pointer(pointer) => {
+                    std::iter::once(pointer).chain(arguments.iter()).collect()
+                }
+                Callee::Function { .. } | Callee::InlineAsm(_) => arguments.iter().collect(),
+            },
+            Self::IntegerBinary { x, y, .. }
+            | Self::FloatBinary { x, y, .. }
+            | Self::ICmp { x, y, .. }
+            | Self::FCmp { x, y, .. } => vec![x, y],
+            Self::FNeg { operand, .. } | Self::Conversion { operand, .. } => vec![operand],
+            Self::VaArg { list, .. } => vec![list],
+            Self::Alloca { count, .. } => count.iter().collect(),
+            Self::Load { pointer, .. } => vec![pointer],
+            Self::Store { value, pointer, .. } => vec![value, pointer],
+            Self::AtomicRmw { pointer, value, .. } => vec![pointer, value],
+            Self::CmpXchg {
+                pointer,
+                expected,
+                replacement,
+                ..
+            } => vec![pointer, expected, replacement],
+            Self::Fence(_) => Vec::new(),
+            Self::GetElementPtr {
+                pointer, indices, ..
+            } => std::iter::once(pointer).chain(indices.iter()).collect(),
+        }
+    }
+
+    /// Gets mutable references to the values used as operands by this instruction, in the same order as
+    /// [`operands`](Instruction::operands).
+    pub(crate) fn operands_mut(&mut self) -> Vec<&mut Value> {
+        match self {
+            Self::Ret(value) => value.iter_mut().collect(),
+            Self::Br(_) => Vec::new(),
+            Self::CondBr { condition, .. } => vec![condition],
+            Self::Switch { value, cases, .. } => std::iter::once(value)
+                .chain(cases.iter_mut().map(|(case_value, _)| case_value))
+                .collect(),
+            Self::IndirectBr { address, .. } => vec![address],
+            Self::Phi { incoming, .. } => incoming.iter_mut().map(|(value, _)| value).collect(),
+            Self::Call {
+                callee, arguments, ..
+            }
+            | Self::CallBr {
+                callee, arguments, ..
+            } => match callee {
+                Callee::Pointer(pointer) => std::iter::once(pointer)
+                    .chain(arguments.iter_mut())
+                    .collect(),
+                Callee::Function { .. } | Callee::InlineAsm(_) => arguments.iter_mut().collect(),
+            },
+            Self::IntegerBinary { x, y, .. }
+            | Self::FloatBinary { x, y, .. }
+            | Self::ICmp { x, y, .. }
+            | Self::FCmp { x, y, .. } => vec![x, y],
+            Self::FNeg { operand, .. } | Self::Conversion { operand, .. } => vec![operand],
+            Self::VaArg { list, .. } => vec![list],
+            Self::Alloca { count, .. } => count.iter_mut().collect(),
+            Self::Load { pointer, .. } => vec![pointer],
+            Self::Store { value, pointer, .. } => vec![value, pointer],
+            Self::AtomicRmw { pointer, value, .. } => vec![pointer, value],
+            Self::CmpXchg {
+                pointer,
+                expected,
+                replacement,
+                ..
+            } => vec![pointer, expected, replacement],
+            Self::Fence(_) => Vec::new(),
+            Self::GetElementPtr {
+                pointer, indices, ..
+            } => std::iter::once(pointer).chain(indices.iter_mut()).collect(),
+        }
+    }
+
+    /// Gets the register containing the result of this instruction, if it produces one.
+    pub fn result(&self) -> Option<&Register> {
+        match self {
+            Self::Phi { result, .. }
+            | Self::IntegerBinary { result, .. }
+            | Self::FloatBinary { result, .. }
+            | Self::FNeg { result, .. }
+            | Self::ICmp { result, .. }
+            | Self::FCmp { result, .. }
+            | Self::Conversion { result, .. }
+            | Self::VaArg { result, .. }
+            | Self::Alloca { result, .. }
+            | Self::Load { result, .. }
+            | Self::AtomicRmw { result, .. }
+            | Self::CmpXchg { result, .. }
+            | Self::GetElementPtr { result, .. } => Some(result),
+            Self::Call { result, .. } | Self::CallBr { result, .. } => result.as_ref(),
+            Self::Ret(_)
+            | Self::Br(_)
+            | Self::CondBr { .. }
+            | Self::Switch { .. }
+            | Self::IndirectBr { .. }
+            | Self::Store { .. }
+            | Self::Fence(_) => None,
+        }
+    }
+}
+
+/// Prints the alignment of a memory operation, if one is specified.
+struct AlignmentSuffix(Option<Alignment>);
+
+impl Display for AlignmentSuffix {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.0 {
+            Some(alignment) => write!(f, ", align {}", alignment),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Prints the synchronization scope and ordering of a memory operation, if it is atomic.
+struct AtomicSuffix<'a>(Option<&'a Atomic>);
+
+impl Display for AtomicSuffix<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.0 {
+            Some(atomic) => write!(f, " {}", atomic),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Prints the signature, callee, and arguments of a `call` or `callbr` instruction.
+struct CallTarget<'a> {
+    callee: &'a Callee,
+    signature: &'a types::Function,
+    arguments: &'a [Value],
+    argument_alignments: &'a [Option<Alignment>],
+}
+
+impl Display for CallTarget<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        // Calls to variadic functions must specify the full function type.
+        if self.signature.is_variadic() {
+            Display::fmt(self.signature, f)?;
+        } else {
+            Display::fmt(self.signature.return_type(), f)?;
+        }
+
+        write!(f, " {}(", self.callee)?;
+
+        for (index, argument) in self.arguments.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+
+            match self.argument_alignments.get(index) {
+                Some(Some(alignment)) => write!(
+                    f,
+                    "{} align {} {}",
+                    argument.value_type(),
+                    alignment,
+                    argument.untyped()
+                )?,
+                _ => Display::fmt(argument, f)?,
+            }
+        }
+
+        f.write_char(')')
+    }
+}
+
+/// Prints the `atomic` keyword for memory operations that are atomic.
+fn atomic_keyword(atomic: bool) -> &'static str {
+    if atomic {
+        "atomic "
+    } else {
+        ""
+    }
+}
+
+/// Prints the `volatile` keyword for memory operations that are volatile.
+fn volatile_keyword(volatile: bool) -> &'static str {
+    if volatile {
+        "volatile "
+    } else {
+        ""
+    }
 }
 
 impl Display for Instruction {
@@ -35,59 +1114,3172 @@ impl Display for Instruction {
                     None => f.write_str("void"),
                 }
             }
+            Self::Br(target) => write!(f, "br label {}", target),
+            Self::CondBr {
+                condition,
+                true_target,
+                false_target,
+            } => write!(
+                f,
+                "br {}, label {}, label {}",
+                condition, true_target, false_target
+            ),
+            Self::Switch {
+                value,
+                default_target,
+                cases,
+            } => {
+                write!(f, "switch {}, label {} [", value, default_target)?;
+                for (case_value, target) in cases.iter() {
+                    write!(f, " {}, label {}", case_value, target)?;
+                }
+                f.write_str(" ]")
+            }
+            Self::IndirectBr {
+                address,
+                destinations,
+            } => {
+                write!(f, "indirectbr {}, [", address)?;
+                for (index, destination) in destinations.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, " label {}", destination)?;
+                }
+                f.write_str(" ]")
+            }
+            Self::Phi { incoming, result } => {
+                write!(f, "{} = phi {}", result, result.value_type())?;
+                for (index, (value, block)) in incoming.iter().enumerate() {
+                    if index > 0 {
+                        f.write_char(',')?;
+                    }
+                    write!(f, " [ {}, {} ]", value.untyped(), block)?;
+                }
+                Ok(())
+            }
+            Self::Call {
+                tail_call,
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                argument_alignments,
+                result,
+            } => {
+                if let Some(result) = result {
+                    write!(f, "{} = ", result)?;
+                }
+
+                write!(
+                    f,
+                    "{}call {} {}",
+                    tail_call,
+                    calling_convention,
+                    CallTarget {
+                        callee,
+                        signature,
+                        arguments,
+                        argument_alignments,
+                    }
+                )
+            }
+            Self::CallBr {
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                result,
+                fallthrough_target,
+                indirect_targets,
+            } => {
+                if let Some(result) = result {
+                    write!(f, "{} = ", result)?;
+                }
+
+                write!(
+                    f,
+                    "callbr {} {} to label {} [",
+                    calling_convention,
+                    CallTarget {
+                        callee,
+                        signature,
+                        arguments,
+                        argument_alignments: &[],
+                    },
+                    fallthrough_target
+                )?;
+
+                for (index, target) in indirect_targets.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "label {}", target)?;
+                }
+
+                f.write_char(']')
+            }
+            Self::IntegerBinary {
+                operation,
+                x,
+                y,
+                result,
+            } => write!(f, "{} = {} {}, {}", result, operation, x, y.untyped()),
+            Self::FloatBinary {
+                operation,
+                flags,
+                x,
+                y,
+                result,
+            } => write!(
+                f,
+                "{} = {} {}{}, {}",
+                result,
+                operation,
+                flags,
+                x,
+                y.untyped()
+            ),
+            Self::FNeg {
+                flags,
+                operand,
+                result,
+            } => write!(f, "{} = fneg {}{}", result, flags, operand),
+            Self::ICmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => write!(f, "{} = icmp {} {}, {}", result, predicate, x, y.untyped()),
+            Self::FCmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => write!(f, "{} = fcmp {} {}, {}", result, predicate, x, y.untyped()),
+            Self::Conversion {
+                operation,
+                operand,
+                result,
+            } => write!(
+                f,
+                "{} = {} {} to {}",
+                result,
+                operation,
+                operand,
+                result.value_type()
+            ),
+            Self::VaArg { list, result } => {
+                write!(f, "{} = va_arg {}, {}", result, list, result.value_type())
+            }
+            Self::Alloca {
+                allocated_type,
+                count,
+                alignment,
+                result,
+            } => {
+                write!(f, "{} = alloca {}", result, allocated_type)?;
+                if let Some(element_count) = count {
+                    write!(f, ", {}", element_count)?;
+                }
+                Display::fmt(&AlignmentSuffix(*alignment), f)
+            }
+            Self::Load {
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+                result,
+            } => write!(
+                f,
+                "{} = load {}{}{}, {}{}{}",
+                result,
+                atomic_keyword(atomic.is_some()),
+                volatile_keyword(*volatile),
+                result.value_type(),
+                pointer,
+                AtomicSuffix(atomic.as_ref()),
+                AlignmentSuffix(*alignment)
+            ),
+            Self::Store {
+                value,
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+            } => write!(
+                f,
+                "store {}{}{}, {}{}{}",
+                atomic_keyword(atomic.is_some()),
+                volatile_keyword(*volatile),
+                value,
+                pointer,
+                AtomicSuffix(atomic.as_ref()),
+                AlignmentSuffix(*alignment)
+            ),
+            Self::AtomicRmw {
+                operation,
+                volatile,
+                pointer,
+                value,
+                atomic,
+                result,
+            } => write!(
+                f,
+                "{} = atomicrmw {}{} {}, {} {}",
+                result,
+                volatile_keyword(*volatile),
+                operation,
+                pointer,
+                value,
+                atomic
+            ),
+            Self::CmpXchg {
+                weak,
+                volatile,
+                pointer,
+                expected,
+                replacement,
+                success,
+                failure_ordering,
+                result,
+            } => write!(
+                f,
+                "{} = cmpxchg {}{}{}, {}, {} {} {}",
+                result,
+                if *weak { "weak " } else { "" },
+                volatile_keyword(*volatile),
+                pointer,
+                expected,
+                replacement,
+                success,
+                failure_ordering
+            ),
+            Self::Fence(atomic) => write!(f, "fence {}", atomic),
+            Self::GetElementPtr {
+                inbounds,
+                element_type,
+                pointer,
+                indices,
+                result,
+            } => {
+                write!(f, "{} = getelementptr ", result)?;
+                if *inbounds {
+                    f.write_str("inbounds ")?;
+                }
+                write!(f, "{}, {}", element_type, pointer)?;
+                for index in indices.iter() {
+                    write!(f, ", {}", index)?;
+                }
+                Ok(())
+            }
         }
     }
 }
 
-/// An LLVM basic block contains the instructions that make up function definitions.
-#[derive(Debug)]
-pub struct BasicBlock {
-    //name: Identifier,
-    instructions: RefCell<Vec<Instruction>>,
-    terminated: Cell<bool>,
-}
+/// Gets the type of the operands of a binary operation or comparison.
+///
+/// Returns an error if the operands are not of the same type, or if the type of the elements of the operands is not
+/// accepted by the `is_valid` predicate.
+fn binary_operand_type<O: Display, V: FnOnce(&types::FirstClass) -> bool>(
+    operation: O,
+    x: &Value,
+    y: &Value,
+    is_valid: V,
+) -> Result<Rc<types::FirstClass>, Error> {
+    let operand_type = x.value_type();
+    let other_type = y.value_type();
+    if operand_type != other_type {
+        return Err(Error::InvalidOperand(format!(
+            "expected operands of {} to be of the same type, but got {} and {}",
+            operation, operand_type, other_type
+        )));
+    }
 
-impl BasicBlock {
-    /// Creates an empty basic block containing no instructions.
-    pub fn new() -> Rc<Self> {
-        Rc::new(Self {
-            instructions: RefCell::default(),
-            terminated: Cell::new(false),
-        })
+    if !is_valid(operand_type.scalar_type()) {
+        return Err(Error::InvalidOperand(format!(
+            "{} is not a valid operand type for {}",
+            operand_type, operation
+        )));
     }
 
-    fn append_instruction(&self, instruction: Instruction) {
-        if self.terminated.get() {
-            panic!(
-                "attempt to append instruction {}, but block {} already ends with a terminator instruction",
-                instruction,
-                BlockLabel(self),
-            );
-        } else {
-            self.instructions.borrow_mut().push(instruction)
+    Ok(operand_type)
+}
+
+/// Gets the type of the value pointed to by a pointer operand, along with the address space of the pointer.
+///
+/// Returns an error if the operand is not a pointer, or is a function pointer.
+fn pointee_type<O: Display>(
+    operation: O,
+    pointer: &Value,
+) -> Result<(Rc<types::FirstClass>, types::AddressSpace), Error> {
+    match pointer.value_type().as_ref() {
+        types::FirstClass::Single(types::SingleValue::Pointer(pointer_type)) => {
+            match pointer_type.pointee_type() {
+                Some(pointee_type) => Ok((pointee_type.clone(), pointer_type.address_space())),
+                None => Err(Error::InvalidOperand(format!(
+                    "expected pointer to object for {}, but got function pointer {}",
+                    operation, pointer_type
+                ))),
+            }
         }
+        operand_type => Err(Error::InvalidOperand(format!(
+            "expected pointer operand for {}, but got {}",
+            operation, operand_type
+        ))),
     }
+}
 
-    /// Appends an `ret` instruction, which returns control flow back to the calling function.
-    pub fn ret(&self, value: Option<Value>) {
-        self.append_instruction(Instruction::Ret(value));
-        self.terminated.set(true);
+/// Gets the type of the element that is selected by the indices of a `getelementptr` instruction, excluding the first index
+/// which indexes the pointer operand itself.
+///
+/// Returns an error if a type that is not an aggregate or vector is indexed, or if a structure is indexed by a value that
+/// is not an integer constant.
+fn indexed_type(
+    element_type: Rc<types::FirstClass>,
+    indices: &[Value],
+) -> Result<Rc<types::FirstClass>, Error> {
+    indices
+        .iter()
+        .try_fold(element_type, |current_type, index| {
+            match current_type.as_ref() {
+                types::FirstClass::Aggregate(types::Aggregate::Array(array)) => {
+                    Ok(array.element_type().clone())
+                }
+                types::FirstClass::Single(types::SingleValue::Vector(vector)) => {
+                    Ok(vector.element_type().clone())
+                }
+                types::FirstClass::Aggregate(types::Aggregate::Struct(structure)) => match index {
+                    Value::Integer(member_index)
+                        if member_index.integer_type() == types::IntegerSize::SIZE_32 =>
+                    {
+                        usize::try_from(member_index.words()[0])
+                            .ok()
+                            .and_then(|member_index| structure.member_types().get(member_index))
+                            .cloned()
+                            .ok_or_else(|| {
+                                Error::InvalidOperand(format!(
+                                    "invalid member index for structure {}",
+                                    structure
+                                ))
+                            })
+                    }
+                    _ => Err(Error::InvalidOperand(format!(
+                        "structure {} must be indexed by an i32 constant",
+                        structure
+                    ))),
+                },
+                _ => Err(Error::InvalidOperand(format!(
+                    "cannot index into type {}",
+                    current_type
+                ))),
+            }
+        })
+}
+
+/// Gets the type of the result of a comparison, which is a boolean or a vector of booleans.
+fn comparison_result_type(operand_type: &types::FirstClass) -> Rc<types::FirstClass> {
+    let boolean_type = Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+        types::IntegerSize::MIN,
+    )));
+
+    match operand_type {
+        types::FirstClass::Single(types::SingleValue::Vector(vector)) => {
+            Rc::new(types::FirstClass::Single(types::SingleValue::Vector(
+                vector.with_element_type(boolean_type),
+            )))
+        }
+        _ => boolean_type,
     }
+}
 
-    #[cfg(feature = "_internal_deconstructors")]
-    pub(crate) fn take_instructions(&self) -> Vec<Instruction> {
-        // iter_instructions
-        self.instructions.take()
+/// Gets the type pointed to by the pointer operand of an atomic `load` or `store`, which must be an integer, floating-point,
+/// or pointer type.
+fn atomic_operand_type<O: Display>(
+    operation: O,
+    pointer: &Value,
+) -> Result<Rc<types::FirstClass>, Error> {
+    let (operand_type, _) = pointee_type(&operation, pointer)?;
+    match operand_type.as_ref() {
+        types::FirstClass::Single(
+            types::SingleValue::Integer(_)
+            | types::SingleValue::Float(_)
+            | types::SingleValue::Pointer(_),
+        ) => Ok(operand_type),
+        _ => Err(Error::InvalidOperand(format!(
+            "{} is not a valid operand type for {}",
+            operand_type, operation
+        ))),
     }
 }
 
-impl Display for BasicBlock {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        block_name(self, f)?;
-        writeln!(f, ":")?;
-        for instruction in self.instructions.borrow().iter() {
-            writeln!(f, "  {}", instruction)?;
+/// Checks that the arguments of a `call` or `callbr` instruction match the parameter types of the signature, returning
+/// a register for the return value if the signature does not return `void`.
+fn call_result(
+    callee: &Callee,
+    signature: &types::Function,
+    arguments: &[Value],
+) -> Result<Option<Register>, Error> {
+    let parameter_types = signature.parameter_types();
+
+    if signature.is_variadic() {
+        if arguments.len() < parameter_types.len() {
+            return Err(Error::InvalidOperand(format!(
+                "expected at least {} arguments for call to {}, but got {}",
+                parameter_types.len(),
+                callee,
+                arguments.len()
+            )));
+        }
+    } else if arguments.len() != parameter_types.len() {
+        return Err(Error::InvalidOperand(format!(
+            "expected {} arguments for call to {}, but got {}",
+            parameter_types.len(),
+            callee,
+            arguments.len()
+        )));
+    }
+
+    for (argument, parameter_type) in arguments.iter().zip(parameter_types) {
+        if &argument.value_type() != parameter_type {
+            return Err(Error::InvalidOperand(format!(
+                "expected argument of type {} for call to {}, but got {}",
+                parameter_type, callee, argument
+            )));
+        }
+    }
+
+    Ok(match signature.return_type() {
+        types::Return::Void => None,
+        types::Return::FirstClass(return_type) => Some(Register::new(return_type.clone())),
+    })
+}
+
+/// Checks that the callee of an indirect call is a pointer, and that it points to a function of the correct type if it is
+/// a function pointer.
+fn check_indirect_callee(callee: &Value, signature: &types::Function) -> Result<(), Error> {
+    match callee.value_type().as_ref() {
+        types::FirstClass::Single(types::SingleValue::Pointer(pointer_type)) => {
+            match pointer_type.function_signature() {
+                Some(callee_signature) if callee_signature.as_ref() != signature => {
+                    Err(Error::InvalidOperand(format!(
+                        "expected pointer to function of type {} for callee, but got {}",
+                        signature, callee
+                    )))
+                }
+                _ => Ok(()),
+            }
+        }
+        _ => Err(Error::InvalidOperand(format!(
+            "expected pointer value for callee, but got {}",
+            callee
+        ))),
+    }
+}
+
+/// Gets the part of the name of an overloaded memory intrinsic corresponding to a pointer to `i8`, such as `p0i8`.
+fn byte_pointer_suffix(intrinsic: &str, pointer: &Value) -> Result<String, Error> {
+    match pointer.value_type().as_ref() {
+        types::FirstClass::Single(types::SingleValue::Pointer(pointer_type))
+            if pointer_type.pointee_type()
+                == Some(&Rc::new(types::FirstClass::Single(
+                    types::SingleValue::Integer(types::IntegerSize::SIZE_8),
+                ))) =>
+        {
+            Ok(format!("p{}i8", pointer_type.address_space().0))
+        }
+        _ => Err(Error::InvalidOperand(format!(
+            "expected pointer to i8 for {}, but got {}",
+            intrinsic, pointer
+        ))),
+    }
+}
+
+/// Gets the part of the name of an overloaded memory intrinsic corresponding to the integer type of the length.
+fn length_suffix(intrinsic: &str, length: &Value) -> Result<String, Error> {
+    match length.value_type().as_ref() {
+        types::FirstClass::Single(types::SingleValue::Integer(size)) => {
+            Ok(format!("i{}", size.bits()))
+        }
+        _ => Err(Error::InvalidOperand(format!(
+            "expected integer length for {}, but got {}",
+            intrinsic, length
+        ))),
+    }
+}
+
+/// Gets the `i1` constant indicating whether a memory intrinsic is volatile.
+fn volatile_flag(volatile: bool) -> Value {
+    Value::Integer(value::Integer::from_u64(
+        types::IntegerSize::MIN,
+        u64::from(volatile),
+    ))
+}
+
+/// Gets the size of a floating-point type, in bits.
+fn float_bits(float: &types::Float) -> u32 {
+    match float {
+        types::Float::Half => 16,
+        types::Float::Float => 32,
+        types::Float::Double => 64,
+    }
+}
+
+fn is_integer_type(operand_type: &types::FirstClass) -> bool {
+    matches!(
+        operand_type,
+        types::FirstClass::Single(types::SingleValue::Integer(_))
+    )
+}
+
+fn is_float_type(operand_type: &types::FirstClass) -> bool {
+    matches!(
+        operand_type,
+        types::FirstClass::Single(types::SingleValue::Float(_))
+    )
+}
+
+/// An LLVM basic block contains the instructions that make up function definitions.
+#[derive(Debug)]
+pub struct BasicBlock {
+    /// A reference to this block, given to the registers defined by its instructions.
+    this: Weak<BasicBlock>,
+    /// The function that this block was appended to.
+    function: RefCell<Weak<global::Function>>,
+    name: Option<Identifier>,
+    /// The unique label of this block within the function that contains it.
+    label: RefCell<Option<Identifier>>,
+    instructions: RefCell<Vec<Instruction>>,
+    /// Metadata attached to instructions, along with the index of the instruction that each is attached to.
+    metadata: RefCell<Vec<(usize, Identifier, Rc<metadata::Node>)>>,
+    terminated: Cell<bool>,
+    /// The insertion point of the [`InstructionBuilder`] for this block, which is adjusted when instructions are inserted
+    /// or removed.
+    builder: RefCell<Weak<Cell<Option<usize>>>>,
+    /// Set while instructions are being inserted by an [`InstructionBuilder`], rather than appended.
+    building: Cell<bool>,
+}
+
+impl BasicBlock {
+    /// Creates an empty basic block containing no instructions.
+    pub fn new() -> Rc<Self> {
+        Self::with_optional_name(None)
+    }
+
+    /// Creates an empty basic block with the specified name. If another block in the same function has the same name, a
+    /// suffix is added to make the label of this block unique.
+    pub fn with_name(name: Identifier) -> Rc<Self> {
+        Self::with_optional_name(Some(name))
+    }
+
+    fn with_optional_name(name: Option<Identifier>) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            this: this.clone(),
+            function: RefCell::default(),
+            name,
+            label: RefCell::default(),
+            instructions: RefCell::default(),
+            metadata: RefCell::default(),
+            terminated: Cell::new(false),
+            builder: RefCell::default(),
+            building: Cell::new(false),
+        })
+    }
+
+    /// Gets the name that was specified when this block was created.
+    pub fn name(&self) -> Option<&Id> {
+        self.name.as_ref().map(Identifier::as_id)
+    }
+
+    /// Gets the label used to refer to this block, which is unique within the function containing this block, or `None`
+    /// if this block was not yet appended to a function.
+    pub fn label(&self) -> Option<Identifier> {
+        self.label.borrow().clone()
+    }
+
+    /// Gets the function that this block was appended to, or `None` if it was not yet appended to a function or the
+    /// function was dropped.
+    pub fn function(&self) -> Option<Rc<global::Function>> {
+        self.function.borrow().upgrade()
+    }
+
+    pub(crate) fn set_function(&self, function: Weak<global::Function>) {
+        *self.function.borrow_mut() = function;
+    }
+
+    /// Returns an error if a value is a register defined in a function other than the one containing this block.
+    fn check_register_function(&self, value: &Value) -> Result<(), Error> {
+        if let (Value::Register(register), Some(function)) = (value, self.function()) {
+            match register.function() {
+                Some(other) if !Rc::ptr_eq(&function, &other) => {
+                    return Err(Error::InvalidOperand(format!(
+                        "{} is defined in @{}, and cannot be used in @{}",
+                        register,
+                        other.name(),
+                        function.name()
+                    )))
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the operands of an instruction that is added to this block, and records this block as the definition of its
+    /// result.
+    fn define_instruction(&self, instruction: &Instruction) -> Result<(), Error> {
+        for operand in instruction.operands() {
+            self.check_register_function(operand)?;
+        }
+
+        if let Some(result) = instruction.result() {
+            result.set_block(self.this.clone());
+        }
+
+        Ok(())
+    }
+
+    /// Assigns the label of this block when it is appended to a function.
+    ///
+    /// # Panics
+    /// Panics if the block was already appended to a function.
+    pub(crate) fn set_label(&self, label: Identifier) {
+        let mut current = self.label.borrow_mut();
+        if let Some(existing) = current.as_ref() {
+            panic!("block %{} was already appended to a function", existing);
+        }
+        *current = Some(label);
+    }
+
+    /// Gets the insertion point of the builder for this block, if one exists.
+    fn builder_insertion_point(&self) -> Option<Rc<Cell<Option<usize>>>> {
+        self.builder.borrow().upgrade()
+    }
+
+    /// Gets the index that instructions are inserted at by an [`InstructionBuilder`], or `None` if instructions are
+    /// appended.
+    fn insertion_point(&self) -> Option<usize> {
+        if self.building.get() {
+            self.builder_insertion_point().and_then(|point| point.get())
+        } else {
+            None
+        }
+    }
+
+    fn try_append_instruction(&self, instruction: Instruction) -> Result<(), Error> {
+        if let Some(index) = self.insertion_point() {
+            self.try_insert_instruction(index, instruction)
+        } else if self.terminated.get() {
+            Err(Error::AlreadyTerminated(format!(
+                "attempt to append instruction {}, but block {} already ends with a terminator instruction",
+                instruction,
+                BlockLabel(self),
+            )))
+        } else {
+            self.define_instruction(&instruction)?;
+            self.instructions.borrow_mut().push(instruction);
+            Ok(())
+        }
+    }
+
+    fn try_append_terminator(&self, instruction: Instruction) -> Result<(), Error> {
+        self.try_append_instruction(instruction)?;
+        self.terminated.set(true);
+        Ok(())
+    }
+
+    /// Appends an `ret` instruction, which returns control flow back to the calling function.
+    ///
+    /// # Panics
+    /// Panics if this block already ends with a terminator instruction.
+    pub fn ret(&self, value: Option<Value>) {
+        or_panic(self.try_ret(value))
+    }
+
+    /// Attempts to append a `ret` instruction, returning an error instead of panicking.
+    pub fn try_ret(&self, value: Option<Value>) -> Result<(), Error> {
+        self.try_append_terminator(Instruction::Ret(value))
+    }
+
+    /// Appends an unconditional `br` instruction, which transfers control flow to the specified block.
+    ///
+    /// # Panics
+    /// Panics if this block already ends with a terminator instruction.
+    pub fn br(&self, target: &Rc<BasicBlock>) {
+        or_panic(self.try_br(target))
+    }
+
+    /// Attempts to append an unconditional `br` instruction, returning an error instead of panicking.
+    pub fn try_br(&self, target: &Rc<BasicBlock>) -> Result<(), Error> {
+        self.try_append_terminator(Instruction::Br(BlockReference::new(target)))
+    }
+
+    /// Appends a conditional `br` instruction, which transfers control flow to `true_target` if the condition is `true`, and
+    /// to `false_target` otherwise.
+    ///
+    /// # Panics
+    /// Panics if the condition is not a boolean (`i1`).
+    pub fn br_if(
+        &self,
+        condition: Value,
+        true_target: &Rc<BasicBlock>,
+        false_target: &Rc<BasicBlock>,
+    ) {
+        or_panic(self.try_br_if(condition, true_target, false_target))
+    }
+
+    /// Attempts to append a conditional `br` instruction, returning an error instead of panicking.
+    pub fn try_br_if(
+        &self,
+        condition: Value,
+        true_target: &Rc<BasicBlock>,
+        false_target: &Rc<BasicBlock>,
+    ) -> Result<(), Error> {
+        match condition.value_type().as_ref() {
+            types::FirstClass::Single(types::SingleValue::Integer(types::IntegerSize::MIN)) => {}
+            _ => {
+                return Err(Error::InvalidOperand(format!(
+                    "expected boolean condition for br, but got {}",
+                    condition
+                )))
+            }
+        }
+
+        self.try_append_terminator(Instruction::CondBr {
+            condition,
+            true_target: BlockReference::new(true_target),
+            false_target: BlockReference::new(false_target),
+        })
+    }
+
+    /// Appends a `switch` instruction, which transfers control flow to the block corresponding to the matching case value,
+    /// or to the default block if no case value matches.
+    ///
+    /// # Panics
+    /// Panics if the value is not an integer, or if a case value is not of the same type as the value.
+    pub fn switch(
+        &self,
+        value: Value,
+        default_target: &Rc<BasicBlock>,
+        cases: Vec<(value::Integer, &Rc<BasicBlock>)>,
+    ) {
+        or_panic(self.try_switch(value, default_target, cases))
+    }
+
+    /// Attempts to append a `switch` instruction, returning an error instead of panicking.
+    pub fn try_switch(
+        &self,
+        value: Value,
+        default_target: &Rc<BasicBlock>,
+        cases: Vec<(value::Integer, &Rc<BasicBlock>)>,
+    ) -> Result<(), Error> {
+        let value_type = match value.value_type().as_ref() {
+            types::FirstClass::Single(types::SingleValue::Integer(size)) => *size,
+            _ => {
+                return Err(Error::InvalidOperand(format!(
+                    "expected integer value for switch, but got {}",
+                    value
+                )))
+            }
+        };
+
+        let cases = cases
+            .into_iter()
+            .map(|(case_value, target)| {
+                if case_value.integer_type() != value_type {
+                    return Err(Error::InvalidOperand(format!(
+                        "expected case value of type i{}, but got {}",
+                        value_type,
+                        Value::from(case_value)
+                    )));
+                }
+
+                Ok((Value::from(case_value), BlockReference::new(target)))
+            })
+            .collect::<Result<_, _>>()?;
+
+        self.try_append_terminator(Instruction::Switch {
+            value,
+            default_target: BlockReference::new(default_target),
+            cases,
+        })
+    }
+
+    /// Appends an `indirectbr` instruction, which transfers control flow to the block at the specified address, which must
+    /// be one of the possible destinations.
+    ///
+    /// # Panics
+    /// Panics if the address is not a pointer.
+    pub fn indirectbr(&self, address: Value, destinations: Vec<&Rc<BasicBlock>>) {
+        or_panic(self.try_indirectbr(address, destinations))
+    }
+
+    /// Attempts to append an `indirectbr` instruction, returning an error instead of panicking.
+    pub fn try_indirectbr(
+        &self,
+        address: Value,
+        destinations: Vec<&Rc<BasicBlock>>,
+    ) -> Result<(), Error> {
+        if !matches!(
+            address.value_type().as_ref(),
+            types::FirstClass::Single(types::SingleValue::Pointer(_))
+        ) {
+            return Err(Error::InvalidOperand(format!(
+                "expected pointer value for indirectbr, but got {}",
+                address
+            )));
+        }
+
+        self.try_append_terminator(Instruction::IndirectBr {
+            address,
+            destinations: destinations.into_iter().map(BlockReference::new).collect(),
+        })
+    }
+
+    /// Appends a `phi` instruction, which selects a value depending on the block that control flow came from, returning a
+    /// register containing the selected value.
+    ///
+    /// Incoming values can be added later with [`BasicBlock::add_phi_incoming`], allowing values defined in blocks that are
+    /// created later to be used, such as in loops.
+    ///
+    /// # Panics
+    /// Panics if the block contains any instructions that are not `phi` instructions, or if an incoming value is not of the
+    /// specified type.
+    pub fn phi(
+        &self,
+        value_type: Rc<types::FirstClass>,
+        incoming: Vec<(Value, &Rc<BasicBlock>)>,
+    ) -> Register {
+        or_panic(self.try_phi(value_type, incoming))
+    }
+
+    /// Attempts to append a `phi` instruction, returning an error instead of panicking.
+    pub fn try_phi(
+        &self,
+        value_type: Rc<types::FirstClass>,
+        incoming: Vec<(Value, &Rc<BasicBlock>)>,
+    ) -> Result<Register, Error> {
+        {
+            let instructions = self.instructions.borrow();
+            if let Some(instruction) = instructions
+                [..self.insertion_point().unwrap_or(instructions.len())]
+                .iter()
+                .find(|instruction| !matches!(instruction, Instruction::Phi { .. }))
+            {
+                return Err(Error::InvalidPosition(format!(
+                    "phi instructions must be at the start of a block, but block {} contains {}",
+                    BlockLabel(self),
+                    instruction
+                )));
+            }
+        }
+
+        if let Some((value, _)) = incoming
+            .iter()
+            .find(|(value, _)| value.value_type() != value_type)
+        {
+            return Err(Error::InvalidOperand(format!(
+                "expected incoming value of type {} for phi, but got {}",
+                value_type, value
+            )));
+        }
+
+        let result = Register::new(value_type);
+        self.try_append_instruction(Instruction::Phi {
+            incoming: incoming
+                .into_iter()
+                .map(|(value, block)| (value, BlockReference::new(block)))
+                .collect(),
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Inserts a `phi` instruction with no incoming values after the existing `phi` instructions at the start of this block,
+    /// allowing phis to be added to blocks that already contain other instructions.
+    pub(crate) fn insert_phi(&self, value_type: Rc<types::FirstClass>) -> Register {
+        let index = self
+            .instructions
+            .borrow()
+            .iter()
+            .take_while(|instruction| matches!(instruction, Instruction::Phi { .. }))
+            .count();
+
+        let result = Register::new(value_type);
+        self.insert_instruction(
+            index,
+            Instruction::Phi {
+                incoming: Vec::new(),
+                result: result.clone(),
+            },
+        );
+        result
+    }
+
+    /// Adds an incoming value to the `phi` instruction in this block whose result is stored in the specified register.
+    ///
+    /// # Panics
+    /// Panics if this block does not contain a `phi` instruction whose result is the register, or if the value is not of the
+    /// same type as the result.
+    pub fn add_phi_incoming(&self, phi: &Register, value: Value, block: &Rc<BasicBlock>) {
+        or_panic(self.try_add_phi_incoming(phi, value, block))
+    }
+
+    /// Attempts to add an incoming value to a `phi` instruction, returning an error instead of panicking.
+    pub fn try_add_phi_incoming(
+        &self,
+        phi: &Register,
+        value: Value,
+        block: &Rc<BasicBlock>,
+    ) -> Result<(), Error> {
+        if &value.value_type() != phi.value_type() {
+            return Err(Error::InvalidOperand(format!(
+                "expected incoming value of type {} for phi, but got {}",
+                phi.value_type(),
+                value
+            )));
+        }
+
+        self.check_register_function(&value)?;
+
+        match self
+            .instructions
+            .borrow_mut()
+            .iter_mut()
+            .find_map(|instruction| match instruction {
+                Instruction::Phi { incoming, result } if result == phi => Some(incoming),
+                _ => None,
+            }) {
+            Some(incoming) => {
+                incoming.push((value, BlockReference::new(block)));
+                Ok(())
+            }
+            None => Err(Error::InvalidOperand(format!(
+                "block {} does not contain a phi instruction defining {}",
+                BlockLabel(self),
+                phi
+            ))),
+        }
+    }
+
+    /// Appends a `call` instruction, which calls the specified function using the function's calling convention, returning
+    /// a register containing the return value if the function does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the arguments do not match the parameter types of the function.
+    pub fn call(&self, callee: &Rc<global::Function>, arguments: Vec<Value>) -> Option<Register> {
+        or_panic(self.try_call(callee, arguments))
+    }
+
+    /// Attempts to append a `call` instruction, returning an error instead of panicking.
+    pub fn try_call(
+        &self,
+        callee: &Rc<global::Function>,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Register>, Error> {
+        self.try_call_with(
+            callee,
+            arguments,
+            callee.get_calling_convention(),
+            TailCallKind::None,
+        )
+    }
+
+    /// Appends a `call` instruction with the specified calling convention and tail call kind, returning a register
+    /// containing the return value if the function does not return `void`.
+    ///
+    /// The constraints on [`TailCallKind::MustTail`] calls, such as being immediately followed by a `ret` instruction,
+    /// are not checked here, and are instead reported by [`verify_function`](crate::verify::verify_function).
+    ///
+    /// # Panics
+    /// Panics if the arguments do not match the parameter types of the function.
+    pub fn call_with(
+        &self,
+        callee: &Rc<global::Function>,
+        arguments: Vec<Value>,
+        calling_convention: global::CallingConvention,
+        tail_call: TailCallKind,
+    ) -> Option<Register> {
+        or_panic(self.try_call_with(callee, arguments, calling_convention, tail_call))
+    }
+
+    /// Attempts to append a `call` instruction with the specified calling convention and tail call kind, returning an error
+    /// instead of panicking.
+    pub fn try_call_with(
+        &self,
+        callee: &Rc<global::Function>,
+        arguments: Vec<Value>,
+        calling_convention: global::CallingConvention,
+        tail_call: TailCallKind,
+    ) -> Result<Option<Register>, Error> {
+        self.try_append_call(
+            Callee::function(callee),
+            callee.signature().clone(),
+            arguments,
+            Vec::new(),
+            calling_convention,
+            tail_call,
+        )
+    }
+
+    /// Appends a `call` instruction that calls the function at the address contained in a pointer, using the C calling
+    /// convention, returning a register containing the return value if the signature does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the callee is not a pointer, is a pointer to a function of a different type, or if the arguments do not
+    /// match the parameter types of the signature.
+    pub fn call_indirect(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+    ) -> Option<Register> {
+        or_panic(self.try_call_indirect(callee, signature, arguments))
+    }
+
+    /// Attempts to append a `call` instruction that calls a function pointer, returning an error instead of panicking.
+    pub fn try_call_indirect(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Register>, Error> {
+        self.try_call_indirect_with(
+            callee,
+            signature,
+            arguments,
+            global::CallingConvention::default(),
+            TailCallKind::None,
+        )
+    }
+
+    /// Appends a `call` instruction that calls a function pointer with the specified calling convention and tail call kind,
+    /// returning a register containing the return value if the signature does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the callee is not a pointer, is a pointer to a function of a different type, or if the arguments do not
+    /// match the parameter types of the signature.
+    pub fn call_indirect_with(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        calling_convention: global::CallingConvention,
+        tail_call: TailCallKind,
+    ) -> Option<Register> {
+        or_panic(self.try_call_indirect_with(
+            callee,
+            signature,
+            arguments,
+            calling_convention,
+            tail_call,
+        ))
+    }
+
+    /// Attempts to append a `call` instruction that calls a function pointer with the specified calling convention and tail
+    /// call kind, returning an error instead of panicking.
+    pub fn try_call_indirect_with(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        calling_convention: global::CallingConvention,
+        tail_call: TailCallKind,
+    ) -> Result<Option<Register>, Error> {
+        check_indirect_callee(&callee, &signature)?;
+        self.try_append_call(
+            Callee::Pointer(callee),
+            signature,
+            arguments,
+            Vec::new(),
+            calling_convention,
+            tail_call,
+        )
+    }
+
+    /// Appends a `call` instruction that executes inline assembly, using the C calling convention, returning a register
+    /// containing the output of the assembly if its signature does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the arguments do not match the parameter types of the signature of the assembly.
+    pub fn call_asm(
+        &self,
+        assembly: &Rc<value::InlineAsm>,
+        arguments: Vec<Value>,
+    ) -> Option<Register> {
+        or_panic(self.try_call_asm(assembly, arguments))
+    }
+
+    /// Attempts to append a `call` instruction that executes inline assembly, returning an error instead of panicking.
+    pub fn try_call_asm(
+        &self,
+        assembly: &Rc<value::InlineAsm>,
+        arguments: Vec<Value>,
+    ) -> Result<Option<Register>, Error> {
+        self.try_append_call(
+            Callee::InlineAsm(assembly.clone()),
+            assembly.signature().clone(),
+            arguments,
+            Vec::new(),
+            global::CallingConvention::default(),
+            TailCallKind::None,
+        )
+    }
+
+    /// Appends a `call` instruction, where arguments without a corresponding alignment are not known to be aligned.
+    fn try_append_call(
+        &self,
+        callee: Callee,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        mut argument_alignments: Vec<Option<Alignment>>,
+        calling_convention: global::CallingConvention,
+        tail_call: TailCallKind,
+    ) -> Result<Option<Register>, Error> {
+        let result = call_result(&callee, &signature, &arguments)?;
+        argument_alignments.resize(arguments.len(), None);
+        self.try_append_instruction(Instruction::Call {
+            tail_call,
+            calling_convention,
+            callee,
+            signature,
+            arguments,
+            argument_alignments,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `callbr` instruction, which calls the specified function using the function's calling convention, then
+    /// transfers control flow to the fallthrough target or to one of the indirect targets, returning a register containing
+    /// the return value if the function does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the arguments do not match the parameter types of the function, or if this block already ends with a
+    /// terminator instruction.
+    pub fn callbr(
+        &self,
+        callee: &Rc<global::Function>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Option<Register> {
+        or_panic(self.try_callbr(callee, arguments, fallthrough_target, indirect_targets))
+    }
+
+    /// Attempts to append a `callbr` instruction, returning an error instead of panicking.
+    pub fn try_callbr(
+        &self,
+        callee: &Rc<global::Function>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Result<Option<Register>, Error> {
+        self.try_append_callbr(
+            Callee::function(callee),
+            callee.signature().clone(),
+            arguments,
+            callee.get_calling_convention(),
+            fallthrough_target,
+            indirect_targets,
+        )
+    }
+
+    /// Appends a `callbr` instruction that calls the function at the address contained in a pointer, using the C calling
+    /// convention, returning a register containing the return value if the signature does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the callee is not a pointer or is a pointer to a function of a different type, if the arguments do not
+    /// match the parameter types of the signature, or if this block already ends with a terminator instruction.
+    pub fn callbr_indirect(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Option<Register> {
+        or_panic(self.try_callbr_indirect(
+            callee,
+            signature,
+            arguments,
+            fallthrough_target,
+            indirect_targets,
+        ))
+    }
+
+    /// Attempts to append a `callbr` instruction that calls a function pointer, returning an error instead of panicking.
+    pub fn try_callbr_indirect(
+        &self,
+        callee: Value,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Result<Option<Register>, Error> {
+        check_indirect_callee(&callee, &signature)?;
+
+        self.try_append_callbr(
+            Callee::Pointer(callee),
+            signature,
+            arguments,
+            global::CallingConvention::default(),
+            fallthrough_target,
+            indirect_targets,
+        )
+    }
+
+    /// Appends a `callbr` instruction that executes inline assembly, which can transfer control flow to one of the
+    /// indirect targets as in an `asm goto` statement, returning a register containing the output of the assembly if its
+    /// signature does not return `void`.
+    ///
+    /// # Panics
+    /// Panics if the arguments do not match the parameter types of the signature of the assembly, or if this block
+    /// already ends with a terminator instruction.
+    pub fn callbr_asm(
+        &self,
+        assembly: &Rc<value::InlineAsm>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Option<Register> {
+        or_panic(self.try_callbr_asm(assembly, arguments, fallthrough_target, indirect_targets))
+    }
+
+    /// Attempts to append a `callbr` instruction that executes inline assembly, returning an error instead of panicking.
+    pub fn try_callbr_asm(
+        &self,
+        assembly: &Rc<value::InlineAsm>,
+        arguments: Vec<Value>,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Result<Option<Register>, Error> {
+        self.try_append_callbr(
+            Callee::InlineAsm(assembly.clone()),
+            assembly.signature().clone(),
+            arguments,
+            global::CallingConvention::default(),
+            fallthrough_target,
+            indirect_targets,
+        )
+    }
+
+    /// Appends a call to the `llvm.memcpy` intrinsic, which copies the specified number of bytes from the source to the
+    /// destination, declaring the intrinsic in the module if it was not already declared. The source and destination must
+    /// not overlap.
+    ///
+    /// The intrinsic is overloaded on the types of its operands, so the declaration is named after them, such as
+    /// `llvm.memcpy.p0i8.p0i8.i64`. The known alignments of the destination and source are specified with `align`
+    /// attributes on the arguments of the call.
+    ///
+    /// # Panics
+    /// Panics if the destination or source are not pointers to `i8`, or if the length is not an integer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn memcpy(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        source: Value,
+        source_alignment: Option<Alignment>,
+        length: Value,
+        volatile: bool,
+    ) {
+        or_panic(self.try_memcpy(
+            module,
+            destination,
+            destination_alignment,
+            source,
+            source_alignment,
+            length,
+            volatile,
+        ))
+    }
+
+    /// Attempts to append a call to the `llvm.memcpy` intrinsic, returning an error instead of panicking.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_memcpy(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        source: Value,
+        source_alignment: Option<Alignment>,
+        length: Value,
+        volatile: bool,
+    ) -> Result<(), Error> {
+        self.try_append_memory_transfer(
+            module,
+            "llvm.memcpy",
+            (destination, destination_alignment),
+            (source, source_alignment),
+            length,
+            volatile,
+        )
+    }
+
+    /// Appends a call to the `llvm.memmove` intrinsic, which copies the specified number of bytes from the source to the
+    /// destination, declaring the intrinsic in the module if it was not already declared. Unlike
+    /// [`memcpy`](BasicBlock::memcpy), the source and destination may overlap.
+    ///
+    /// # Panics
+    /// Panics if the destination or source are not pointers to `i8`, or if the length is not an integer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn memmove(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        source: Value,
+        source_alignment: Option<Alignment>,
+        length: Value,
+        volatile: bool,
+    ) {
+        or_panic(self.try_memmove(
+            module,
+            destination,
+            destination_alignment,
+            source,
+            source_alignment,
+            length,
+            volatile,
+        ))
+    }
+
+    /// Attempts to append a call to the `llvm.memmove` intrinsic, returning an error instead of panicking.
+    #[allow(clippy::too_many_arguments)]
+    pub fn try_memmove(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        source: Value,
+        source_alignment: Option<Alignment>,
+        length: Value,
+        volatile: bool,
+    ) -> Result<(), Error> {
+        self.try_append_memory_transfer(
+            module,
+            "llvm.memmove",
+            (destination, destination_alignment),
+            (source, source_alignment),
+            length,
+            volatile,
+        )
+    }
+
+    fn try_append_memory_transfer(
+        &self,
+        module: &mut Module,
+        intrinsic: &str,
+        (destination, destination_alignment): (Value, Option<Alignment>),
+        (source, source_alignment): (Value, Option<Alignment>),
+        length: Value,
+        volatile: bool,
+    ) -> Result<(), Error> {
+        let name = format!(
+            "{}.{}.{}.{}",
+            intrinsic,
+            byte_pointer_suffix(intrinsic, &destination)?,
+            byte_pointer_suffix(intrinsic, &source)?,
+            length_suffix(intrinsic, &length)?
+        );
+
+        let signature = types::Function::new(
+            types::Return::Void,
+            vec![
+                destination.value_type(),
+                source.value_type(),
+                length.value_type(),
+                Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+                    types::IntegerSize::MIN,
+                ))),
+            ],
+        );
+
+        let function = module.get_or_declare_intrinsic(Identifier::sanitized(&name), signature);
+        self.try_append_call(
+            Callee::function(&function),
+            function.signature().clone(),
+            vec![destination, source, length, volatile_flag(volatile)],
+            vec![destination_alignment, source_alignment],
+            function.get_calling_convention(),
+            TailCallKind::None,
+        )?;
+        Ok(())
+    }
+
+    /// Appends a call to the `llvm.memset` intrinsic, which fills the specified number of bytes at the destination with
+    /// an `i8` value, declaring the intrinsic in the module if it was not already declared.
+    ///
+    /// The declaration is named after the types of the operands, such as `llvm.memset.p0i8.i64`. The known alignment of
+    /// the destination is specified with an `align` attribute on the argument of the call.
+    ///
+    /// # Panics
+    /// Panics if the destination is not a pointer to `i8`, if the value is not an `i8`, or if the length is not an
+    /// integer.
+    pub fn memset(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        value: Value,
+        length: Value,
+        volatile: bool,
+    ) {
+        or_panic(self.try_memset(
+            module,
+            destination,
+            destination_alignment,
+            value,
+            length,
+            volatile,
+        ))
+    }
+
+    /// Attempts to append a call to the `llvm.memset` intrinsic, returning an error instead of panicking.
+    pub fn try_memset(
+        &self,
+        module: &mut Module,
+        destination: Value,
+        destination_alignment: Option<Alignment>,
+        value: Value,
+        length: Value,
+        volatile: bool,
+    ) -> Result<(), Error> {
+        let byte_type = Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+            types::IntegerSize::SIZE_8,
+        )));
+        if value.value_type() != byte_type {
+            return Err(Error::InvalidOperand(format!(
+                "expected i8 value for llvm.memset, but got {}",
+                value
+            )));
+        }
+
+        let name = format!(
+            "llvm.memset.{}.{}",
+            byte_pointer_suffix("llvm.memset", &destination)?,
+            length_suffix("llvm.memset", &length)?
+        );
+
+        let signature = types::Function::new(
+            types::Return::Void,
+            vec![
+                destination.value_type(),
+                byte_type,
+                length.value_type(),
+                Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+                    types::IntegerSize::MIN,
+                ))),
+            ],
+        );
+
+        let function = module.get_or_declare_intrinsic(Identifier::sanitized(&name), signature);
+        self.try_append_call(
+            Callee::function(&function),
+            function.signature().clone(),
+            vec![destination, value, length, volatile_flag(volatile)],
+            vec![destination_alignment],
+            function.get_calling_convention(),
+            TailCallKind::None,
+        )?;
+        Ok(())
+    }
+
+    fn try_append_callbr(
+        &self,
+        callee: Callee,
+        signature: Rc<types::Function>,
+        arguments: Vec<Value>,
+        calling_convention: global::CallingConvention,
+        fallthrough_target: &Rc<BasicBlock>,
+        indirect_targets: Vec<&Rc<BasicBlock>>,
+    ) -> Result<Option<Register>, Error> {
+        let result = call_result(&callee, &signature, &arguments)?;
+        self.try_append_terminator(Instruction::CallBr {
+            calling_convention,
+            callee,
+            signature,
+            arguments,
+            result: result.clone(),
+            fallthrough_target: BlockReference::new(fallthrough_target),
+            indirect_targets: indirect_targets
+                .into_iter()
+                .map(BlockReference::new)
+                .collect(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an instruction performing a binary operation on integers, returning the register containing the result.
+    ///
+    /// # Panics
+    /// Panics if the operands are not of the same type, or if the operands are not integers or vectors of integers.
+    pub fn integer_binary(
+        &self,
+        operation: IntegerBinaryOperation,
+        x: Value,
+        y: Value,
+    ) -> Register {
+        or_panic(self.try_integer_binary(operation, x, y))
+    }
+
+    /// Attempts to append an instruction performing a binary operation on integers, returning an error instead of
+    /// panicking.
+    pub fn try_integer_binary(
+        &self,
+        operation: IntegerBinaryOperation,
+        x: Value,
+        y: Value,
+    ) -> Result<Register, Error> {
+        let result = Register::new(binary_operand_type(operation, &x, &y, is_integer_type)?);
+        self.try_append_instruction(Instruction::IntegerBinary {
+            operation,
+            x,
+            y,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an instruction performing a binary operation on floating-point values, returning the register containing the
+    /// result.
+    ///
+    /// # Panics
+    /// Panics if the operands are not of the same type, or if the operands are not floating-point values or vectors of
+    /// floating-point values.
+    pub fn float_binary(&self, operation: FloatBinaryOperation, x: Value, y: Value) -> Register {
+        or_panic(self.try_float_binary(operation, x, y))
+    }
+
+    /// Attempts to append an instruction performing a binary operation on floating-point values, returning an error
+    /// instead of panicking.
+    pub fn try_float_binary(
+        &self,
+        operation: FloatBinaryOperation,
+        x: Value,
+        y: Value,
+    ) -> Result<Register, Error> {
+        self.try_float_binary_with(operation, FastMathFlags::NONE, x, y)
+    }
+
+    /// Appends an instruction performing a binary operation on floating-point values with the specified fast-math flags,
+    /// returning the register containing the result.
+    ///
+    /// # Panics
+    /// See [`BasicBlock::float_binary`].
+    pub fn float_binary_with(
+        &self,
+        operation: FloatBinaryOperation,
+        flags: FastMathFlags,
+        x: Value,
+        y: Value,
+    ) -> Register {
+        or_panic(self.try_float_binary_with(operation, flags, x, y))
+    }
+
+    /// Attempts to append an instruction performing a binary operation on floating-point values with the specified
+    /// fast-math flags, returning an error instead of panicking.
+    pub fn try_float_binary_with(
+        &self,
+        operation: FloatBinaryOperation,
+        flags: FastMathFlags,
+        x: Value,
+        y: Value,
+    ) -> Result<Register, Error> {
+        let result = Register::new(binary_operand_type(operation, &x, &y, is_float_type)?);
+        self.try_append_instruction(Instruction::FloatBinary {
+            operation,
+            flags,
+            x,
+            y,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an `fneg` instruction, which negates a floating-point value, returning the register containing the result.
+    ///
+    /// # Panics
+    /// Panics if the operand is not a floating-point value or a vector of floating-point values.
+    pub fn fneg(&self, operand: Value) -> Register {
+        self.fneg_with(FastMathFlags::NONE, operand)
+    }
+
+    /// Attempts to append an `fneg` instruction, returning an error instead of panicking.
+    pub fn try_fneg(&self, operand: Value) -> Result<Register, Error> {
+        self.try_fneg_with(FastMathFlags::NONE, operand)
+    }
+
+    /// Appends an `fneg` instruction with the specified fast-math flags.
+    ///
+    /// # Panics
+    /// See [`BasicBlock::fneg`].
+    pub fn fneg_with(&self, flags: FastMathFlags, operand: Value) -> Register {
+        or_panic(self.try_fneg_with(flags, operand))
+    }
+
+    /// Attempts to append an `fneg` instruction with the specified fast-math flags, returning an error instead of
+    /// panicking.
+    pub fn try_fneg_with(&self, flags: FastMathFlags, operand: Value) -> Result<Register, Error> {
+        let operand_type = operand.value_type();
+        if !is_float_type(operand_type.scalar_type()) {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid operand type for fneg",
+                operand_type
+            )));
+        }
+
+        let result = Register::new(operand_type);
+        self.try_append_instruction(Instruction::FNeg {
+            flags,
+            operand,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an `alloca` instruction, which allocates memory on the stack for one or more values of the specified type,
+    /// returning a register containing a pointer to the allocated memory.
+    ///
+    /// # Panics
+    /// Panics if the number of values to allocate is not an integer, or if the type is `token` or `metadata`.
+    pub fn alloca(
+        &self,
+        allocated_type: Rc<types::FirstClass>,
+        count: Option<Value>,
+        alignment: Option<Alignment>,
+    ) -> Register {
+        or_panic(self.try_alloca(allocated_type, count, alignment))
+    }
+
+    /// Attempts to append an `alloca` instruction, returning an error instead of panicking.
+    pub fn try_alloca(
+        &self,
+        allocated_type: Rc<types::FirstClass>,
+        count: Option<Value>,
+        alignment: Option<Alignment>,
+    ) -> Result<Register, Error> {
+        if matches!(
+            allocated_type.as_ref(),
+            types::FirstClass::Token | types::FirstClass::Metadata
+        ) {
+            return Err(Error::InvalidOperand(format!(
+                "cannot allocate memory for values of type {}",
+                allocated_type
+            )));
+        }
+
+        if let Some(element_count) = &count {
+            if !is_integer_type(&element_count.value_type()) {
+                return Err(Error::InvalidOperand(format!(
+                    "expected integer element count for alloca, but got {}",
+                    element_count
+                )));
+            }
+        }
+
+        let result = Register::new(Rc::new(types::FirstClass::Single(
+            types::SingleValue::Pointer(types::Pointer::new(allocated_type.clone())),
+        )));
+
+        self.try_append_instruction(Instruction::Alloca {
+            allocated_type,
+            count,
+            alignment,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `load` instruction, which reads a value from memory, returning a register containing the value.
+    ///
+    /// # Panics
+    /// Panics if the operand is not a pointer.
+    pub fn load(&self, pointer: Value, alignment: Option<Alignment>, volatile: bool) -> Register {
+        or_panic(self.try_load(pointer, alignment, volatile))
+    }
+
+    /// Attempts to append a `load` instruction, returning an error instead of panicking.
+    pub fn try_load(
+        &self,
+        pointer: Value,
+        alignment: Option<Alignment>,
+        volatile: bool,
+    ) -> Result<Register, Error> {
+        let result = Register::new(pointee_type("load", &pointer)?.0);
+        self.try_append_instruction(Instruction::Load {
+            pointer,
+            alignment,
+            volatile,
+            atomic: None,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `store` instruction, which writes a value to memory.
+    ///
+    /// # Panics
+    /// Panics if the pointer operand is not a pointer to a value of the same type as the value to store.
+    pub fn store(
+        &self,
+        value: Value,
+        pointer: Value,
+        alignment: Option<Alignment>,
+        volatile: bool,
+    ) {
+        or_panic(self.try_store(value, pointer, alignment, volatile))
+    }
+
+    /// Attempts to append a `store` instruction, returning an error instead of panicking.
+    pub fn try_store(
+        &self,
+        value: Value,
+        pointer: Value,
+        alignment: Option<Alignment>,
+        volatile: bool,
+    ) -> Result<(), Error> {
+        let (stored_type, _) = pointee_type("store", &pointer)?;
+        if stored_type != value.value_type() {
+            return Err(Error::InvalidOperand(format!(
+                "cannot store {} into {}",
+                value, pointer
+            )));
+        }
+
+        self.try_append_instruction(Instruction::Store {
+            value,
+            pointer,
+            alignment,
+            volatile,
+            atomic: None,
+        })
+    }
+
+    /// Appends an atomic `load` instruction, which reads a value from memory, returning a register containing the value.
+    ///
+    /// # Panics
+    /// Panics if the operand is not a pointer to an integer, floating-point, or pointer value, or if the ordering is
+    /// `release` or `acq_rel`.
+    pub fn atomic_load(
+        &self,
+        pointer: Value,
+        alignment: Alignment,
+        volatile: bool,
+        atomic: Atomic,
+    ) -> Register {
+        or_panic(self.try_atomic_load(pointer, alignment, volatile, atomic))
+    }
+
+    /// Attempts to append an atomic `load` instruction, returning an error instead of panicking.
+    pub fn try_atomic_load(
+        &self,
+        pointer: Value,
+        alignment: Alignment,
+        volatile: bool,
+        atomic: Atomic,
+    ) -> Result<Register, Error> {
+        if atomic.ordering.is_release() && atomic.ordering != AtomicOrdering::SequentiallyConsistent
+        {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid ordering for an atomic load",
+                atomic.ordering
+            )));
+        }
+
+        let loaded_type = atomic_operand_type("atomic load", &pointer)?;
+        let result = Register::new(loaded_type);
+        self.try_append_instruction(Instruction::Load {
+            pointer,
+            alignment: Some(alignment),
+            volatile,
+            atomic: Some(atomic),
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an atomic `store` instruction, which writes a value to memory.
+    ///
+    /// # Panics
+    /// Panics if the pointer operand is not a pointer to a value of the same type as the value to store, if the value is not
+    /// an integer, floating-point, or pointer value, or if the ordering is `acquire` or `acq_rel`.
+    pub fn atomic_store(
+        &self,
+        value: Value,
+        pointer: Value,
+        alignment: Alignment,
+        volatile: bool,
+        atomic: Atomic,
+    ) {
+        or_panic(self.try_atomic_store(value, pointer, alignment, volatile, atomic))
+    }
+
+    /// Attempts to append an atomic `store` instruction, returning an error instead of panicking.
+    pub fn try_atomic_store(
+        &self,
+        value: Value,
+        pointer: Value,
+        alignment: Alignment,
+        volatile: bool,
+        atomic: Atomic,
+    ) -> Result<(), Error> {
+        if atomic.ordering.is_acquire() && atomic.ordering != AtomicOrdering::SequentiallyConsistent
+        {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid ordering for an atomic store",
+                atomic.ordering
+            )));
+        }
+
+        if atomic_operand_type("atomic store", &pointer)? != value.value_type() {
+            return Err(Error::InvalidOperand(format!(
+                "cannot store {} into {}",
+                value, pointer
+            )));
+        }
+
+        self.try_append_instruction(Instruction::Store {
+            value,
+            pointer,
+            alignment: Some(alignment),
+            volatile,
+            atomic: Some(atomic),
+        })
+    }
+
+    /// Appends an `atomicrmw` instruction, which atomically modifies the value in memory, returning a register containing
+    /// the value that was in memory before it was modified.
+    ///
+    /// # Panics
+    /// Panics if the pointer operand is not a pointer to a value of the same type as the operand, if the operand is not of
+    /// a type supported by the operation, or if the ordering is `unordered`.
+    pub fn atomicrmw(
+        &self,
+        operation: AtomicRmwOperation,
+        pointer: Value,
+        value: Value,
+        volatile: bool,
+        atomic: Atomic,
+    ) -> Register {
+        or_panic(self.try_atomicrmw(operation, pointer, value, volatile, atomic))
+    }
+
+    /// Attempts to append an `atomicrmw` instruction, returning an error instead of panicking.
+    pub fn try_atomicrmw(
+        &self,
+        operation: AtomicRmwOperation,
+        pointer: Value,
+        value: Value,
+        volatile: bool,
+        atomic: Atomic,
+    ) -> Result<Register, Error> {
+        if atomic.ordering == AtomicOrdering::Unordered {
+            return Err(Error::InvalidOperand(String::from(
+                "unordered is not a valid ordering for atomicrmw",
+            )));
+        }
+
+        let operand_type = value.value_type();
+        if pointee_type("atomicrmw", &pointer)?.0 != operand_type {
+            return Err(Error::InvalidOperand(format!(
+                "expected operand of atomicrmw to be of the type pointed to by {}, but got {}",
+                pointer, value
+            )));
+        }
+
+        let is_valid = match operation {
+            AtomicRmwOperation::Xchg => {
+                is_integer_type(&operand_type) || is_float_type(&operand_type)
+            }
+            AtomicRmwOperation::FAdd | AtomicRmwOperation::FSub => is_float_type(&operand_type),
+            _ => is_integer_type(&operand_type),
+        };
+
+        if !is_valid {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid operand type for atomicrmw {}",
+                operand_type, operation
+            )));
+        }
+
+        let result = Register::new(operand_type);
+        self.try_append_instruction(Instruction::AtomicRmw {
+            operation,
+            volatile,
+            pointer,
+            value,
+            atomic,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `cmpxchg` instruction, which atomically replaces the value in memory if it is equal to an expected value.
+    /// Returns a register containing a structure of the value that was in memory, and a boolean indicating whether the
+    /// value was replaced.
+    ///
+    /// # Panics
+    /// Panics if the pointer operand is not a pointer to an integer or pointer value of the same type as the other
+    /// operands, if either ordering is `unordered`, or if the failure ordering is `release` or `acq_rel`.
+    pub fn cmpxchg(
+        &self,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        volatile: bool,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+    ) -> Register {
+        or_panic(self.try_cmpxchg(
+            pointer,
+            expected,
+            replacement,
+            volatile,
+            success,
+            failure_ordering,
+        ))
+    }
+
+    /// Attempts to append a `cmpxchg` instruction, returning an error instead of panicking.
+    pub fn try_cmpxchg(
+        &self,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        volatile: bool,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+    ) -> Result<Register, Error> {
+        self.try_append_cmpxchg(
+            false,
+            pointer,
+            expected,
+            replacement,
+            volatile,
+            success,
+            failure_ordering,
+        )
+    }
+
+    /// Appends a `weak` `cmpxchg` instruction, which is allowed to fail even if the value in memory is equal to the
+    /// expected value, see [`BasicBlock::cmpxchg`].
+    ///
+    /// # Panics
+    /// Panics if the operands or orderings are not valid, see [`BasicBlock::cmpxchg`].
+    pub fn cmpxchg_weak(
+        &self,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        volatile: bool,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+    ) -> Register {
+        or_panic(self.try_cmpxchg_weak(
+            pointer,
+            expected,
+            replacement,
+            volatile,
+            success,
+            failure_ordering,
+        ))
+    }
+
+    /// Attempts to append a `weak` `cmpxchg` instruction, returning an error instead of panicking.
+    pub fn try_cmpxchg_weak(
+        &self,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        volatile: bool,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+    ) -> Result<Register, Error> {
+        self.try_append_cmpxchg(
+            true,
+            pointer,
+            expected,
+            replacement,
+            volatile,
+            success,
+            failure_ordering,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn try_append_cmpxchg(
+        &self,
+        weak: bool,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        volatile: bool,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+    ) -> Result<Register, Error> {
+        if success.ordering == AtomicOrdering::Unordered
+            || matches!(
+                failure_ordering,
+                AtomicOrdering::Unordered
+                    | AtomicOrdering::Release
+                    | AtomicOrdering::AcquireRelease
+            )
+        {
+            return Err(Error::InvalidOperand(format!(
+                "{} and {} are not valid orderings for cmpxchg",
+                success.ordering, failure_ordering
+            )));
+        }
+
+        let operand_type = pointee_type("cmpxchg", &pointer)?.0;
+        if !matches!(
+            operand_type.as_ref(),
+            types::FirstClass::Single(
+                types::SingleValue::Integer(_) | types::SingleValue::Pointer(_)
+            )
+        ) {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid operand type for cmpxchg",
+                operand_type
+            )));
+        }
+
+        for operand in [&expected, &replacement] {
+            if operand.value_type() != operand_type {
+                return Err(Error::InvalidOperand(format!(
+                    "expected operand of cmpxchg to be of type {}, but got {}",
+                    operand_type, operand
+                )));
+            }
+        }
+
+        let result = Register::new(Rc::new(types::FirstClass::Aggregate(
+            types::Aggregate::Struct(types::Struct::new(
+                vec![
+                    operand_type,
+                    Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+                        types::IntegerSize::MIN,
+                    ))),
+                ],
+                false,
+            )),
+        )));
+
+        self.try_append_instruction(Instruction::CmpXchg {
+            weak,
+            volatile,
+            pointer,
+            expected,
+            replacement,
+            success,
+            failure_ordering,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `fence` instruction, which introduces ordering constraints between memory operations.
+    ///
+    /// # Panics
+    /// Panics if the ordering is `unordered` or `monotonic`.
+    pub fn fence(&self, atomic: Atomic) {
+        or_panic(self.try_fence(atomic))
+    }
+
+    /// Attempts to append a `fence` instruction, returning an error instead of panicking.
+    pub fn try_fence(&self, atomic: Atomic) -> Result<(), Error> {
+        if matches!(
+            atomic.ordering,
+            AtomicOrdering::Unordered | AtomicOrdering::Monotonic
+        ) {
+            return Err(Error::InvalidOperand(format!(
+                "{} is not a valid ordering for fence",
+                atomic.ordering
+            )));
+        }
+
+        self.try_append_instruction(Instruction::Fence(atomic))
+    }
+
+    /// Appends a `getelementptr` instruction, which calculates the address of an element in an aggregate or vector,
+    /// returning a register containing a pointer to the element.
+    ///
+    /// The first index is used to index the pointer operand, with the remaining indices used to select elements. If
+    /// `inbounds` is `true`, the result is a poison value if the calculated address is out of bounds of the object pointed to
+    /// by the pointer operand.
+    ///
+    /// # Panics
+    /// Panics if the operand is not a pointer, if the indices are not integers, or if the indices do not select an element.
+    pub fn getelementptr(&self, pointer: Value, indices: Vec<Value>, inbounds: bool) -> Register {
+        or_panic(self.try_getelementptr(pointer, indices, inbounds))
+    }
+
+    /// Attempts to append a `getelementptr` instruction, returning an error instead of panicking.
+    pub fn try_getelementptr(
+        &self,
+        pointer: Value,
+        indices: Vec<Value>,
+        inbounds: bool,
+    ) -> Result<Register, Error> {
+        let (element_type, _) = pointee_type("getelementptr", &pointer)?;
+        self.try_getelementptr_with_type(element_type, pointer, indices, inbounds)
+    }
+
+    /// Appends a `getelementptr` instruction with an explicit source element type, which is the type indexed by the first
+    /// index, returning a register containing a pointer to the element.
+    ///
+    /// # Panics
+    /// Panics if the operand is not a pointer to the source element type, if the indices are not integers, or if the
+    /// indices do not select an element, see [`BasicBlock::getelementptr`].
+    pub fn getelementptr_with_type(
+        &self,
+        element_type: Rc<types::FirstClass>,
+        pointer: Value,
+        indices: Vec<Value>,
+        inbounds: bool,
+    ) -> Register {
+        or_panic(self.try_getelementptr_with_type(element_type, pointer, indices, inbounds))
+    }
+
+    /// Attempts to append a `getelementptr` instruction with an explicit source element type, returning an error instead of
+    /// panicking.
+    pub fn try_getelementptr_with_type(
+        &self,
+        element_type: Rc<types::FirstClass>,
+        pointer: Value,
+        indices: Vec<Value>,
+        inbounds: bool,
+    ) -> Result<Register, Error> {
+        if let Some(index) = indices
+            .iter()
+            .find(|index| !is_integer_type(index.value_type().scalar_type()))
+        {
+            return Err(Error::InvalidOperand(format!(
+                "expected integer index for getelementptr, but got {}",
+                index
+            )));
+        }
+
+        let (pointee_type, address_space) = pointee_type("getelementptr", &pointer)?;
+        if pointee_type != element_type {
+            return Err(Error::InvalidOperand(format!(
+                "expected pointer to {} for getelementptr, but got {}",
+                element_type, pointer
+            )));
+        }
+
+        let result = Register::new(Rc::new(types::FirstClass::Single(
+            types::SingleValue::Pointer(types::Pointer::in_address_space(
+                indexed_type(element_type.clone(), indices.get(1..).unwrap_or_default())?,
+                address_space,
+            )),
+        )));
+
+        self.try_append_instruction(Instruction::GetElementPtr {
+            inbounds,
+            element_type,
+            pointer,
+            indices,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an `icmp` instruction, which compares two integers or pointers and returns a register containing a boolean
+    /// or a vector of booleans.
+    ///
+    /// # Panics
+    /// Panics if the operands are not of the same type, if the operands are not integers, pointers, or vectors of either,
+    /// or if this block already ends with a terminator instruction.
+    pub fn icmp(&self, predicate: IcmpPredicate, x: Value, y: Value) -> Register {
+        or_panic(self.try_icmp(predicate, x, y))
+    }
+
+    /// Attempts to append an `icmp` instruction, returning an error instead of panicking.
+    pub fn try_icmp(
+        &self,
+        predicate: IcmpPredicate,
+        x: Value,
+        y: Value,
+    ) -> Result<Register, Error> {
+        let operand_type = binary_operand_type("icmp", &x, &y, |operand_type| {
+            matches!(
+                operand_type,
+                types::FirstClass::Single(
+                    types::SingleValue::Integer(_) | types::SingleValue::Pointer(_)
+                )
+            )
+        })?;
+        let result = Register::new(comparison_result_type(&operand_type));
+        self.try_append_instruction(Instruction::ICmp {
+            predicate,
+            x,
+            y,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an `fcmp` instruction, which compares two floating-point values and returns a register containing a boolean
+    /// or a vector of booleans.
+    ///
+    /// # Panics
+    /// Panics if the operands are not of the same type, or if the operands are not floating-point values or vectors of
+    /// floating-point values.
+    pub fn fcmp(&self, predicate: FcmpPredicate, x: Value, y: Value) -> Register {
+        or_panic(self.try_fcmp(predicate, x, y))
+    }
+
+    /// Attempts to append an `fcmp` instruction, returning an error instead of panicking.
+    pub fn try_fcmp(
+        &self,
+        predicate: FcmpPredicate,
+        x: Value,
+        y: Value,
+    ) -> Result<Register, Error> {
+        let operand_type = binary_operand_type("fcmp", &x, &y, is_float_type)?;
+        let result = Register::new(comparison_result_type(&operand_type));
+        self.try_append_instruction(Instruction::FCmp {
+            predicate,
+            x,
+            y,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends an instruction that converts an integer or floating-point value, or a vector of either, to another type,
+    /// returning a register containing the converted value.
+    ///
+    /// # Panics
+    /// Panics if the operand or result type are not valid for the conversion, such as when a `trunc` would result in a
+    /// larger integer type, or if only one of the types is a vector or the vectors have different lengths.
+    pub fn conversion(
+        &self,
+        operation: ConversionOperation,
+        operand: Value,
+        result_type: Rc<types::FirstClass>,
+    ) -> Register {
+        or_panic(self.try_conversion(operation, operand, result_type))
+    }
+
+    /// Attempts to append a conversion instruction, returning an error instead of panicking.
+    pub fn try_conversion(
+        &self,
+        operation: ConversionOperation,
+        operand: Value,
+        result_type: Rc<types::FirstClass>,
+    ) -> Result<Register, Error> {
+        let operand_type = operand.value_type();
+        let vector_length = |conversion_type: &types::FirstClass| match conversion_type {
+            types::FirstClass::Single(types::SingleValue::Vector(vector)) => Some(vector.count()),
+            _ => None,
+        };
+
+        let is_valid = vector_length(&operand_type) == vector_length(&result_type)
+            && match (operand_type.scalar_type(), result_type.scalar_type()) {
+                (
+                    types::FirstClass::Single(types::SingleValue::Integer(from)),
+                    types::FirstClass::Single(types::SingleValue::Integer(to)),
+                ) => match operation {
+                    ConversionOperation::Trunc => from.bits() > to.bits(),
+                    ConversionOperation::ZExt | ConversionOperation::SExt => {
+                        from.bits() < to.bits()
+                    }
+                    _ => false,
+                },
+                (
+                    types::FirstClass::Single(types::SingleValue::Float(from)),
+                    types::FirstClass::Single(types::SingleValue::Float(to)),
+                ) => match operation {
+                    ConversionOperation::FPTrunc => float_bits(from) > float_bits(to),
+                    ConversionOperation::FPExt => float_bits(from) < float_bits(to),
+                    _ => false,
+                },
+                (
+                    types::FirstClass::Single(types::SingleValue::Float(_)),
+                    types::FirstClass::Single(types::SingleValue::Integer(_)),
+                ) => matches!(
+                    operation,
+                    ConversionOperation::FPToUI | ConversionOperation::FPToSI
+                ),
+                (
+                    types::FirstClass::Single(types::SingleValue::Integer(_)),
+                    types::FirstClass::Single(types::SingleValue::Float(_)),
+                ) => matches!(
+                    operation,
+                    ConversionOperation::UIToFP | ConversionOperation::SIToFP
+                ),
+                _ => false,
+            };
+
+        if !is_valid {
+            return Err(Error::InvalidOperand(format!(
+                "cannot {} {} to {}",
+                operation, operand, result_type
+            )));
+        }
+
+        let result = Register::new(result_type);
+        self.try_append_instruction(Instruction::Conversion {
+            operation,
+            operand,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Appends a `va_arg` instruction that retrieves the next argument of the specified type from a variable argument
+    /// list, returning the register containing the argument.
+    ///
+    /// # Panics
+    /// Panics if the argument list is not a pointer.
+    pub fn va_arg(&self, list: Value, value_type: Rc<types::FirstClass>) -> Register {
+        or_panic(self.try_va_arg(list, value_type))
+    }
+
+    /// Attempts to append a `va_arg` instruction, returning an error instead of panicking.
+    pub fn try_va_arg(
+        &self,
+        list: Value,
+        value_type: Rc<types::FirstClass>,
+    ) -> Result<Register, Error> {
+        if !matches!(
+            list.value_type().as_ref(),
+            types::FirstClass::Single(types::SingleValue::Pointer(_))
+        ) {
+            return Err(Error::InvalidOperand(format!(
+                "expected pointer to variable argument list, but got {}",
+                list
+            )));
+        }
+
+        let result = Register::new(value_type);
+        self.try_append_instruction(Instruction::VaArg {
+            list,
+            result: result.clone(),
+        })?;
+        Ok(result)
+    }
+
+    /// Attaches metadata of the specified kind, such as `range`, to the last instruction in this block.
+    ///
+    /// # Panics
+    /// Panics if the block does not contain any instructions, or if the kind is `dbg`, since debug locations are not yet
+    /// supported.
+    pub fn attach_metadata(&self, kind: Identifier, node: Rc<metadata::Node>) {
+        or_panic(self.try_attach_metadata(kind, node))
+    }
+
+    /// Attempts to attach metadata to the last instruction in this block, returning an error instead of panicking.
+    pub fn try_attach_metadata(
+        &self,
+        kind: Identifier,
+        node: Rc<metadata::Node>,
+    ) -> Result<(), Error> {
+        if kind.as_str() == "dbg" {
+            return Err(Error::Unsupported(String::from(
+                "debug location metadata is not supported",
+            )));
+        }
+
+        let end = self
+            .insertion_point()
+            .unwrap_or_else(|| self.instructions.borrow().len());
+
+        match end.checked_sub(1) {
+            Some(index) => {
+                self.metadata.borrow_mut().push((index, kind, node));
+                Ok(())
+            }
+            None => Err(Error::InvalidPosition(format!(
+                "cannot attach metadata !{}, since block {} does not contain any instructions",
+                kind,
+                BlockLabel(self)
+            ))),
+        }
+    }
+
+    /// Inserts an instruction at the specified index, shifting all instructions after it.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds, if a terminator instruction is not inserted at the end of an unterminated
+    /// block, or if an instruction is inserted after the terminator of this block.
+    pub fn insert_instruction(&self, index: usize, instruction: Instruction) {
+        or_panic(self.try_insert_instruction(index, instruction))
+    }
+
+    /// Attempts to insert an instruction at the specified index, returning an error instead of panicking.
+    pub fn try_insert_instruction(
+        &self,
+        index: usize,
+        instruction: Instruction,
+    ) -> Result<(), Error> {
+        let mut instructions = self.instructions.borrow_mut();
+        let length = instructions.len();
+
+        if index > length {
+            return Err(Error::InvalidPosition(format!(
+                "insertion index {} is out of bounds for block {} containing {} instructions",
+                index,
+                BlockLabel(self),
+                length
+            )));
+        } else if self.terminated.get() && index == length {
+            return Err(Error::AlreadyTerminated(format!(
+                "attempt to insert instruction {}, but block {} already ends with a terminator instruction",
+                instruction,
+                BlockLabel(self),
+            )));
+        } else if instruction.is_terminator() && (self.terminated.get() || index != length) {
+            return Err(Error::InvalidPosition(format!(
+                "terminator instruction {} must be inserted at the end of block {}",
+                instruction,
+                BlockLabel(self),
+            )));
+        }
+
+        self.define_instruction(&instruction)?;
+
+        if instruction.is_terminator() {
+            self.terminated.set(true);
+        }
+
+        instructions.insert(index, instruction);
+
+        if let Some(point) = self.builder_insertion_point() {
+            match point.get() {
+                Some(insertion_point) if index <= insertion_point => {
+                    point.set(Some(insertion_point + 1))
+                }
+                _ => (),
+            }
+        }
+
+        for (attached_index, _, _) in self.metadata.borrow_mut().iter_mut() {
+            if *attached_index >= index {
+                *attached_index += 1;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Removes the instruction at the specified index, along with any metadata attached to it.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn remove_instruction(&self, index: usize) -> Instruction {
+        or_panic(self.try_remove_instruction(index))
+    }
+
+    /// Attempts to remove the instruction at the specified index, returning an error instead of panicking.
+    pub fn try_remove_instruction(&self, index: usize) -> Result<Instruction, Error> {
+        let mut instructions = self.instructions.borrow_mut();
+
+        if index >= instructions.len() {
+            return Err(Error::InvalidPosition(format!(
+                "removal index {} is out of bounds for block {} containing {} instructions",
+                index,
+                BlockLabel(self),
+                instructions.len()
+            )));
+        }
+
+        let removed = instructions.remove(index);
+
+        if let Some(point) = self.builder_insertion_point() {
+            match point.get() {
+                Some(insertion_point) if index < insertion_point => {
+                    point.set(Some(insertion_point - 1))
+                }
+                _ => (),
+            }
+        }
+
+        if removed.is_terminator() {
+            self.terminated.set(false);
+        }
+
+        let mut metadata = self.metadata.borrow_mut();
+        metadata.retain(|(attached_index, _, _)| *attached_index != index);
+        for (attached_index, _, _) in metadata.iter_mut() {
+            if *attached_index > index {
+                *attached_index -= 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Replaces the instruction at the specified index, returning the original instruction. Any metadata attached to the
+    /// original instruction is removed.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds, or if a terminator instruction would no longer be the last instruction in
+    /// this block.
+    pub fn replace_instruction(&self, index: usize, instruction: Instruction) -> Instruction {
+        or_panic(self.try_replace_instruction(index, instruction))
+    }
+
+    /// Attempts to replace the instruction at the specified index, returning an error instead of panicking.
+    pub fn try_replace_instruction(
+        &self,
+        index: usize,
+        instruction: Instruction,
+    ) -> Result<Instruction, Error> {
+        let mut instructions = self.instructions.borrow_mut();
+        let length = instructions.len();
+
+        if index >= length {
+            return Err(Error::InvalidPosition(format!(
+                "replacement index {} is out of bounds for block {} containing {} instructions",
+                index,
+                BlockLabel(self),
+                length
+            )));
+        } else if instruction.is_terminator() && index != length - 1 {
+            return Err(Error::InvalidPosition(format!(
+                "terminator instruction {} must be the last instruction in block {}",
+                instruction,
+                BlockLabel(self),
+            )));
+        }
+
+        self.define_instruction(&instruction)?;
+
+        if index == length - 1 {
+            self.terminated.set(instruction.is_terminator());
+        }
+
+        self.metadata
+            .borrow_mut()
+            .retain(|(attached_index, _, _)| *attached_index != index);
+
+        Ok(std::mem::replace(&mut instructions[index], instruction))
+    }
+
+    /// Moves the instructions in the specified range, along with any metadata attached to them, into another block,
+    /// inserting them before the instruction at the specified index in the destination.
+    ///
+    /// # Panics
+    /// Panics if the range or index are out of bounds, if the destination is this block, if the moved instructions use
+    /// registers defined in another function, or if a terminator instruction would not be the last instruction in the
+    /// destination.
+    pub fn splice_instructions(
+        &self,
+        range: std::ops::Range<usize>,
+        destination: &BasicBlock,
+        index: usize,
+    ) {
+        or_panic(self.try_splice_instructions(range, destination, index))
+    }
+
+    /// Attempts to move a range of instructions into another block, returning an error instead of panicking.
+    pub fn try_splice_instructions(
+        &self,
+        range: std::ops::Range<usize>,
+        destination: &BasicBlock,
+        index: usize,
+    ) -> Result<(), Error> {
+        if std::ptr::eq(self, destination) {
+            return Err(Error::InvalidPosition(format!(
+                "cannot splice instructions of block {} into itself",
+                BlockLabel(self)
+            )));
+        }
+
+        let mut instructions = self.instructions.borrow_mut();
+        let mut destination_instructions = destination.instructions.borrow_mut();
+        let length = instructions.len();
+        let destination_length = destination_instructions.len();
+
+        if range.start > range.end || range.end > length {
+            return Err(Error::InvalidPosition(format!(
+                "range {:?} is out of bounds for block {} containing {} instructions",
+                range,
+                BlockLabel(self),
+                length
+            )));
+        } else if index > destination_length {
+            return Err(Error::InvalidPosition(format!(
+                "insertion index {} is out of bounds for block {} containing {} instructions",
+                index,
+                BlockLabel(destination),
+                destination_length
+            )));
+        }
+
+        let moved = &instructions[range.clone()];
+        let moves_terminator = moved.last().is_some_and(Instruction::is_terminator);
+        if !moved.is_empty()
+            && (destination.terminated.get() && index == destination_length
+                || moves_terminator
+                    && (destination.terminated.get() || index != destination_length))
+        {
+            return Err(Error::InvalidPosition(format!(
+                "instructions from block {} cannot be inserted at index {} of block {}, since a terminator would not be the last instruction",
+                BlockLabel(self),
+                index,
+                BlockLabel(destination)
+            )));
+        }
+
+        for instruction in moved.iter() {
+            for operand in instruction.operands() {
+                destination.check_register_function(operand)?;
+            }
+        }
+
+        let count = range.end - range.start;
+        let spliced = instructions.drain(range.clone()).collect::<Vec<_>>();
+        for instruction in spliced.iter() {
+            if let Some(result) = instruction.result() {
+                result.set_block(destination.this.clone());
+            }
+        }
+        destination_instructions.splice(index..index, spliced);
+
+        if moves_terminator {
+            self.terminated.set(false);
+            destination.terminated.set(true);
+        }
+
+        if let Some(point) = self.builder_insertion_point() {
+            match point.get() {
+                Some(insertion_point) if insertion_point > range.start => point.set(Some(
+                    insertion_point - (insertion_point.min(range.end) - range.start),
+                )),
+                _ => (),
+            }
+        }
+
+        if let Some(point) = destination.builder_insertion_point() {
+            match point.get() {
+                Some(insertion_point) if insertion_point >= index => {
+                    point.set(Some(insertion_point + count))
+                }
+                _ => (),
+            }
+        }
+
+        let mut metadata = self.metadata.borrow_mut();
+        let mut destination_metadata = destination.metadata.borrow_mut();
+        for (attached_index, _, _) in destination_metadata.iter_mut() {
+            if *attached_index >= index {
+                *attached_index += count;
+            }
+        }
+
+        metadata.retain(|(attached_index, kind, node)| {
+            if range.contains(attached_index) {
+                destination_metadata.push((
+                    *attached_index - range.start + index,
+                    kind.clone(),
+                    node.clone(),
+                ));
+                false
+            } else {
+                true
+            }
+        });
+
+        for (attached_index, _, _) in metadata.iter_mut() {
+            if *attached_index >= range.end {
+                *attached_index -= count;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Gets the instructions in this block.
+    ///
+    /// # Panics
+    /// Panics if instructions are appended to this block while the returned reference is in use.
+    pub fn instructions(&self) -> std::cell::Ref<'_, [Instruction]> {
+        std::cell::Ref::map(self.instructions.borrow(), Vec::as_slice)
+    }
+
+    /// Gets mutable access to the instructions in this block, which can be used to modify instructions without changing
+    /// their kind or the number of instructions in the block.
+    pub(crate) fn instructions_mut(&self) -> std::cell::RefMut<'_, [Instruction]> {
+        std::cell::RefMut::map(self.instructions.borrow_mut(), Vec::as_mut_slice)
+    }
+
+    /// Gets the blocks that control flow can be transferred to from the terminator of this block, in the order that they
+    /// appear in the terminator.
+    ///
+    /// Blocks that appear multiple times in the terminator, such as a `switch` with several cases targeting the same block,
+    /// are only included once. Use [`successor_edges`](BasicBlock::successor_edges) to get one entry for each edge instead.
+    /// Blocks that were dropped are excluded.
+    pub fn successors(&self) -> Vec<Rc<BasicBlock>> {
+        let mut successors = Vec::<Rc<BasicBlock>>::new();
+        if let Some(terminator) = self.terminator() {
+            for block in terminator
+                .targets()
+                .into_iter()
+                .filter_map(BlockReference::block)
+            {
+                if !successors
+                    .iter()
+                    .any(|existing| Rc::ptr_eq(existing, &block))
+                {
+                    successors.push(block);
+                }
+            }
+        }
+        successors
+    }
+
+    /// Gets the blocks that control flow can be transferred to from the terminator of this block, with one entry for each
+    /// edge in the control flow graph.
+    ///
+    /// Unlike [`successors`](BasicBlock::successors), a block is included each time it appears in the terminator, such as
+    /// both targets of `br i1 %c, label %x, label %x`, which matches the number of incoming values that a `phi` instruction
+    /// in the target block needs for this block. Blocks that were dropped are excluded.
+    pub fn successor_edges(&self) -> Vec<Rc<BasicBlock>> {
+        match self.terminator() {
+            Some(terminator) => terminator
+                .targets()
+                .into_iter()
+                .filter_map(BlockReference::block)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Gets the terminator instruction at the end of this block, or `None` if the block is not yet terminated.
+    pub fn terminator(&self) -> Option<std::cell::Ref<'_, Instruction>> {
+        if self.terminated.get() {
+            Some(std::cell::Ref::map(
+                self.instructions.borrow(),
+                |instructions| {
+                    instructions
+                        .last()
+                        .expect("terminated block should contain a terminator instruction")
+                },
+            ))
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn metadata(&self) -> std::cell::Ref<'_, [(usize, Identifier, Rc<metadata::Node>)]> {
+        std::cell::Ref::map(self.metadata.borrow(), Vec::as_slice)
+    }
+}
+
+/// Inserts instructions at a position within a basic block, rather than only at the end.
+///
+/// The builder keeps track of its own insertion point, which is moved after each inserted instruction, and is adjusted when
+/// instructions are inserted into or removed from its block elsewhere. Only the instructions built within
+/// [`InstructionBuilder::build`] are inserted at the insertion point, other calls to the instruction building methods of
+/// the block still append instructions to its end.
+///
+/// # Example
+/// Instructions can be inserted before the terminator of a block with `InstructionBuilder::before(&block, index)`, where
+/// `index` is the index of the terminator, and then calling `builder.build(|block| block.add(x, y))`.
+#[derive(Debug)]
+pub struct InstructionBuilder {
+    block: Rc<BasicBlock>,
+    /// The index that instructions are inserted at, or `None` if instructions are appended.
+    insertion_point: Rc<Cell<Option<usize>>>,
+}
+
+impl InstructionBuilder {
+    fn try_new(block: &Rc<BasicBlock>, insertion_point: Option<usize>) -> Result<Self, Error> {
+        if block.builder_insertion_point().is_some() {
+            return Err(Error::BuilderExists(format!(
+                "block {} is already used by another instruction builder",
+                BlockLabel(block)
+            )));
+        }
+
+        let builder = Self {
+            block: block.clone(),
+            insertion_point: Rc::default(),
+        };
+        builder.try_set_insertion_point(insertion_point)?;
+        *block.builder.borrow_mut() = Rc::downgrade(&builder.insertion_point);
+        Ok(builder)
+    }
+
+    /// Creates a builder that appends instructions to the end of a block.
+    ///
+    /// # Panics
+    /// Panics if another builder for the block exists.
+    pub fn at_end(block: &Rc<BasicBlock>) -> Self {
+        or_panic(Self::try_at_end(block))
+    }
+
+    /// Attempts to create a builder that appends instructions to the end of a block, returning an error instead of
+    /// panicking.
+    pub fn try_at_end(block: &Rc<BasicBlock>) -> Result<Self, Error> {
+        Self::try_new(block, None)
+    }
+
+    /// Creates a builder that inserts instructions at the start of a block, before any existing instructions.
+    ///
+    /// # Panics
+    /// Panics if another builder for the block exists.
+    pub fn at_start(block: &Rc<BasicBlock>) -> Self {
+        or_panic(Self::try_at_start(block))
+    }
+
+    /// Attempts to create a builder that inserts instructions at the start of a block, returning an error instead of
+    /// panicking.
+    pub fn try_at_start(block: &Rc<BasicBlock>) -> Result<Self, Error> {
+        Self::try_new(block, Some(0))
+    }
+
+    /// Creates a builder that inserts instructions before the instruction at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds, or if another builder for the block exists.
+    pub fn before(block: &Rc<BasicBlock>, index: usize) -> Self {
+        or_panic(Self::try_before(block, index))
+    }
+
+    /// Attempts to create a builder that inserts instructions before the instruction at the specified index, returning an
+    /// error instead of panicking.
+    pub fn try_before(block: &Rc<BasicBlock>, index: usize) -> Result<Self, Error> {
+        Self::try_new(block, Some(index))
+    }
+
+    /// Creates a builder that inserts instructions after the instruction at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds, or if another builder for the block exists.
+    pub fn after(block: &Rc<BasicBlock>, index: usize) -> Self {
+        or_panic(Self::try_after(block, index))
+    }
+
+    /// Attempts to create a builder that inserts instructions after the instruction at the specified index, returning an
+    /// error instead of panicking.
+    pub fn try_after(block: &Rc<BasicBlock>, index: usize) -> Result<Self, Error> {
+        Self::try_new(block, Some(index + 1))
+    }
+
+    /// Creates a builder that inserts instructions before the instruction that defines the specified register.
+    ///
+    /// # Panics
+    /// Panics if the register contains a parameter, if the instruction defining the register is not contained in a block,
+    /// or if another builder for the block exists.
+    pub fn before_definition(register: &Register) -> Self {
+        or_panic(Self::try_before_definition(register))
+    }
+
+    /// Attempts to create a builder that inserts instructions before the instruction that defines the specified register,
+    /// returning an error instead of panicking.
+    pub fn try_before_definition(register: &Register) -> Result<Self, Error> {
+        let (block, index) = Self::try_definition(register)?;
+        Self::try_new(&block, Some(index))
+    }
+
+    /// Creates a builder that inserts instructions after the instruction that defines the specified register, such as to
+    /// insert a conversion of its value.
+    ///
+    /// # Panics
+    /// Panics if the register contains a parameter, if the instruction defining the register is not contained in a block,
+    /// or if another builder for the block exists.
+    pub fn after_definition(register: &Register) -> Self {
+        or_panic(Self::try_after_definition(register))
+    }
+
+    /// Attempts to create a builder that inserts instructions after the instruction that defines the specified register,
+    /// returning an error instead of panicking.
+    pub fn try_after_definition(register: &Register) -> Result<Self, Error> {
+        let (block, index) = Self::try_definition(register)?;
+        Self::try_new(&block, Some(index + 1))
+    }
+
+    fn try_definition(register: &Register) -> Result<(Rc<BasicBlock>, usize), Error> {
+        match register.definition() {
+            Some(definition) => Ok(definition),
+            None if register.parameter_index().is_some() => Err(Error::InvalidOperand(format!(
+                "{} is a parameter, and is not defined by an instruction",
+                register
+            ))),
+            None => Err(Error::InvalidOperand(format!(
+                "the instruction defining {} is not contained in a block",
+                register
+            ))),
+        }
+    }
+
+    fn try_set_insertion_point(&self, insertion_point: Option<usize>) -> Result<(), Error> {
+        let length = self.block.instructions.borrow().len();
+        match insertion_point {
+            Some(index) if index > length => {
+                return Err(Error::InvalidPosition(format!(
+                    "insertion point {} is out of bounds for block {} containing {} instructions",
+                    index,
+                    BlockLabel(&self.block),
+                    length
+                )))
+            }
+            Some(index) if index < length => self.insertion_point.set(Some(index)),
+            _ => self.insertion_point.set(None),
+        }
+        Ok(())
+    }
+
+    /// Gets the block that instructions are inserted into.
+    pub fn block(&self) -> &Rc<BasicBlock> {
+        &self.block
+    }
+
+    /// Gets the index that the next instruction is inserted at.
+    pub fn insertion_index(&self) -> usize {
+        self.insertion_point
+            .get()
+            .unwrap_or_else(|| self.block.instructions.borrow().len())
+    }
+
+    /// Calls a closure with the block of this builder, inserting the instructions built by its instruction building
+    /// methods at the insertion point.
+    pub fn build<T>(&mut self, build: impl FnOnce(&BasicBlock) -> T) -> T {
+        /// Causes instructions to be appended again, even if the closure panics.
+        struct Restore<'b>(&'b BasicBlock);
+
+        impl Drop for Restore<'_> {
+            fn drop(&mut self) {
+                self.0.building.set(false);
+            }
+        }
+
+        self.block.building.set(true);
+        let restore = Restore(&self.block);
+        build(restore.0)
+    }
+
+    /// Moves the insertion point to the start of the block.
+    pub fn position_at_start(&mut self) {
+        or_panic(self.try_set_insertion_point(Some(0)))
+    }
+
+    /// Moves the insertion point to the end of the block.
+    pub fn position_at_end(&mut self) {
+        or_panic(self.try_set_insertion_point(None))
+    }
+
+    /// Moves the insertion point before the instruction at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn position_before(&mut self, index: usize) {
+        or_panic(self.try_position_before(index))
+    }
+
+    /// Attempts to move the insertion point before the instruction at the specified index, returning an error instead of
+    /// panicking.
+    pub fn try_position_before(&mut self, index: usize) -> Result<(), Error> {
+        self.try_set_insertion_point(Some(index))
+    }
+
+    /// Moves the insertion point after the instruction at the specified index.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn position_after(&mut self, index: usize) {
+        or_panic(self.try_position_after(index))
+    }
+
+    /// Attempts to move the insertion point after the instruction at the specified index, returning an error instead of
+    /// panicking.
+    pub fn try_position_after(&mut self, index: usize) -> Result<(), Error> {
+        self.try_set_insertion_point(Some(index + 1))
+    }
+}
+
+macro_rules! binary_operations {
+    ($($name: ident, $builder: ident, $operation_type: ident::$operation: ident, $description: literal;)*) => {
+        impl BasicBlock {
+            $(
+                #[doc = $description]
+                ///
+                /// # Panics
+                /// See the documentation for the corresponding general binary operation method, whose `try_` variant can be
+                /// used to handle errors without panicking.
+                pub fn $name(&self, x: Value, y: Value) -> Register {
+                    self.$builder($operation_type::$operation, x, y)
+                }
+            )*
+        }
+    };
+}
+
+binary_operations! {
+    add, integer_binary, IntegerBinaryOperation::Add, "Appends an `add` instruction, which adds two integers.";
+    sub, integer_binary, IntegerBinaryOperation::Sub, "Appends a `sub` instruction, which subtracts two integers.";
+    mul, integer_binary, IntegerBinaryOperation::Mul, "Appends a `mul` instruction, which multiplies two integers.";
+    udiv, integer_binary, IntegerBinaryOperation::UDiv, "Appends a `udiv` instruction, which divides two unsigned integers.";
+    sdiv, integer_binary, IntegerBinaryOperation::SDiv, "Appends an `sdiv` instruction, which divides two signed integers.";
+    urem, integer_binary, IntegerBinaryOperation::URem, "Appends a `urem` instruction, which computes the remainder of unsigned integer division.";
+    srem, integer_binary, IntegerBinaryOperation::SRem, "Appends an `srem` instruction, which computes the remainder of signed integer division.";
+    shl, integer_binary, IntegerBinaryOperation::Shl, "Appends a `shl` instruction, which shifts an integer to the left.";
+    lshr, integer_binary, IntegerBinaryOperation::LShr, "Appends an `lshr` instruction, which performs a logical shift to the right.";
+    ashr, integer_binary, IntegerBinaryOperation::AShr, "Appends an `ashr` instruction, which performs an arithmetic shift to the right.";
+    and, integer_binary, IntegerBinaryOperation::And, "Appends an `and` instruction, which computes the bitwise AND of two integers.";
+    or, integer_binary, IntegerBinaryOperation::Or, "Appends an `or` instruction, which computes the bitwise OR of two integers.";
+    xor, integer_binary, IntegerBinaryOperation::Xor, "Appends a `xor` instruction, which computes the bitwise exclusive OR of two integers.";
+    fadd, float_binary, FloatBinaryOperation::Add, "Appends an `fadd` instruction, which adds two floating-point values.";
+    fsub, float_binary, FloatBinaryOperation::Sub, "Appends an `fsub` instruction, which subtracts two floating-point values.";
+    fmul, float_binary, FloatBinaryOperation::Mul, "Appends an `fmul` instruction, which multiplies two floating-point values.";
+    fdiv, float_binary, FloatBinaryOperation::Div, "Appends an `fdiv` instruction, which divides two floating-point values.";
+    frem, float_binary, FloatBinaryOperation::Rem, "Appends an `frem` instruction, which computes the remainder of floating-point division.";
+}
+
+impl Display for BasicBlock {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        block_name(self, f)?;
+        writeln!(f, ":")?;
+        let metadata = self.metadata.borrow();
+        for (index, instruction) in self.instructions.borrow().iter().enumerate() {
+            write!(f, "  {}", instruction)?;
+            for (_, kind, node) in metadata.iter().filter(|(i, _, _)| *i == index) {
+                write!(f, ", !{} {}", kind, node)?;
+            }
+            writeln!(f)?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constant(value: f64) -> Value {
+        Value::Float(value::Float::Double(value))
+    }
+
+    fn result_of(instruction: &Instruction) -> Register {
+        instruction
+            .result()
+            .cloned()
+            .expect("instruction should define a register")
+    }
+
+    #[test]
+    fn memory_intrinsic_arguments_have_alignment_attributes() {
+        let target = crate::target::Target::new(
+            crate::target::Machine::with_defaults(
+                crate::target::Triple::default(),
+                Identifier::sanitized("generic"),
+                crate::target::FeatureSet::new(),
+            ),
+            crate::target::Layout::default(),
+        );
+        let mut module = Module::new(Identifier::sanitized("intrinsics"), &target);
+        let byte_pointer = Value::Null(types::Pointer::new(Rc::new(types::FirstClass::Single(
+            types::SingleValue::Integer(types::IntegerSize::I8),
+        ))));
+        let length = Value::Integer(value::Integer::from_u64(types::IntegerSize::I64, 16));
+        let block = BasicBlock::new();
+        block.memcpy(
+            &mut module,
+            byte_pointer.clone(),
+            Alignment::new(8),
+            byte_pointer.clone(),
+            None,
+            length.clone(),
+            false,
+        );
+        block.memset(
+            &mut module,
+            byte_pointer,
+            Alignment::new(4),
+            Value::Integer(value::Integer::from_u64(types::IntegerSize::I8, 0)),
+            length,
+            false,
+        );
+
+        let instructions = block.instructions();
+        assert_eq!(
+            instructions[0].to_string(),
+            "call ccc void @llvm.memcpy.p0i8.p0i8.i64(i8* align 8 null, i8* null, i64 16, i1 false)"
+        );
+        assert_eq!(
+            instructions[1].to_string(),
+            "call ccc void @llvm.memset.p0i8.i64(i8* align 4 null, i8 0, i64 16, i1 false)"
+        );
+    }
+
+    #[test]
+    fn builder_inserts_only_instructions_built_through_it() {
+        let block = BasicBlock::new();
+        let first = block.fadd(constant(1.0), constant(2.0));
+
+        let mut builder = InstructionBuilder::at_start(&block);
+        let appended = block.fmul(constant(1.0), constant(2.0));
+        let inserted = builder.build(|block| block.fsub(constant(1.0), constant(2.0)));
+
+        let instructions = block.instructions();
+        assert_eq!(result_of(&instructions[0]), inserted);
+        assert_eq!(result_of(&instructions[1]), first);
+        assert_eq!(result_of(&instructions[2]), appended);
+        assert_eq!(builder.insertion_index(), 1);
+    }
+
+    #[test]
+    fn builder_stops_inserting_after_closure_panics() {
+        let block = BasicBlock::new();
+        block.fadd(constant(1.0), constant(2.0));
+
+        let mut builder = InstructionBuilder::at_start(&block);
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            builder.build(|block| {
+                block.fsub(constant(1.0), constant(2.0));
+                panic!("closure panicked");
+            })
+        }));
+        assert!(panicked.is_err());
+
+        let appended = block.fmul(constant(1.0), constant(2.0));
+        assert_eq!(result_of(&block.instructions()[2]), appended);
+        assert_eq!(builder.insertion_index(), 1);
+    }
+
+    #[test]
+    fn builder_insertion_point_is_adjusted_by_other_insertions() {
+        let block = BasicBlock::new();
+        block.fadd(constant(1.0), constant(2.0));
+        block.ret(Some(constant(3.0)));
+
+        let mut builder = InstructionBuilder::before(&block, 1);
+        block.remove_instruction(0);
+        assert_eq!(builder.insertion_index(), 0);
+
+        let inserted = builder.build(|block| block.fadd(constant(1.0), constant(2.0)));
+        assert_eq!(result_of(&block.instructions()[0]), inserted);
+        assert_eq!(builder.insertion_index(), 1);
+        assert!(block.instructions()[1].is_terminator());
+    }
+
+    #[test]
+    fn second_builder_for_block_is_rejected() {
+        let block = BasicBlock::new();
+        let builder = InstructionBuilder::at_end(&block);
+        assert!(matches!(
+            InstructionBuilder::try_at_end(&block),
+            Err(Error::BuilderExists(_))
+        ));
+        drop(builder);
+        assert!(InstructionBuilder::try_at_end(&block).is_ok());
+    }
+
+    #[test]
+    fn builder_out_of_bounds_is_rejected() {
+        let block = BasicBlock::new();
+        assert!(matches!(
+            InstructionBuilder::try_before(&block, 1),
+            Err(Error::InvalidPosition(_))
+        ));
+        assert!(InstructionBuilder::try_at_end(&block).is_ok());
+    }
+}
@@ -0,0 +1,2324 @@
+//! Copies of global values that can be sent between threads, allowing functions to be built in parallel.
+//!
+//! The models used by this crate are reference counted without synchronization, so they cannot be sent to other threads.
+//! Instead, each worker thread builds its global values as usual, and copies them into a [`Fragment`]. Fragments can be
+//! sent to the thread that owns the [`Module`], and then merged into it with [`Module::merge`].
+//!
+//! Calls to functions and the addresses of global values are resolved by name when a fragment is merged, so functions
+//! built on different threads can call each other as long as each thread declares the functions that it calls.
+//!
+//! When the `serde` feature is enabled, fragments and [`Snapshot`]s of entire modules can also be serialized, allowing IR
+//! to be cached or sent to other processes.
+
+use crate::block::{
+    self, Atomic, AtomicOrdering, AtomicRmwOperation, BasicBlock, ConversionOperation,
+    FastMathFlags, FcmpPredicate, FloatBinaryOperation, IcmpPredicate, IntegerBinaryOperation,
+    TailCallKind,
+};
+use crate::global::{
+    self, CallingConvention, ComdatSelectionKind, Linkage, ThreadLocalMode, UnnamedAddress,
+    Visibility,
+};
+use crate::identifier::{Id, Identifier};
+use crate::metadata;
+use crate::module::Module;
+use crate::target::layout::{AddressSpace, Alignment};
+use crate::target::Target;
+use crate::types;
+use crate::value;
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+/// Error used when a fragment cannot be merged into a module.
+///
+/// Modules are not modified when an error is returned.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// A global value is defined both in the module and in the fragment, or more than once in the fragment.
+    #[error("@{0} is defined more than once")]
+    DuplicateDefinition(Identifier),
+    /// Global values with the same name are of different kinds or types, such as a function and a global variable, or a
+    /// call that does not match the signature of the function.
+    #[error("uses of @{0} do not have the same type")]
+    TypeMismatch(Identifier),
+    /// The address of an indirect function is used, but the indirect function is not defined in the module or in the
+    /// fragment.
+    #[error("@{0} is used as an indirect function, but is not defined")]
+    UndefinedIFunc(Identifier),
+    /// The address of a global value is used, or an indirect function refers to its resolver, but the global value is
+    /// not declared in the module or in the fragment.
+    #[error("@{0} is used, but is not declared")]
+    UndeclaredGlobal(Identifier),
+    /// A copied function refers to a register or block that it does not contain, or a register is used in the initializer
+    /// of a global variable or in a metadata node.
+    #[error("@{0} refers to a register or block that it does not contain")]
+    InvalidReference(Identifier),
+    /// The address of a block is used, but the function is not defined in the module or in the fragment, or does not
+    /// contain the block, or the block is the entry block.
+    #[error(
+        "the address of block {1} in @{0} is used, but the function does not define that block"
+    )]
+    UndefinedBlock(Identifier, usize),
+}
+
+/// Error used when a global value cannot be copied into a fragment.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CopyError {
+    /// An instruction uses a register that is not defined in the function containing the instruction.
+    #[error("register {0} is not defined in the function that uses it")]
+    UndefinedRegister(String),
+    /// An instruction refers to a block that is not contained in the function containing the instruction.
+    #[error("block {0} is not contained in the function that refers to it")]
+    UndefinedBlock(String),
+    /// The address of a block in a function is taken, but the function was dropped, or the block was removed from the
+    /// function or became its entry block.
+    #[error("the address of a block in @{0} refers to a block that is not in the function, or to its entry block")]
+    InvalidBlockAddress(Identifier),
+}
+
+/// Panics with the message of an error, used by the methods that do not return a [`Result`].
+fn or_panic<T>(result: Result<T, CopyError>) -> T {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Type {
+    Integer(types::IntegerSize),
+    Float(types::Float),
+    Pointer(Box<Pointee>, AddressSpace),
+    Vector(Box<Type>, u32),
+    Array(Box<Type>, u32),
+    Struct(Vec<Type>, bool),
+    Token,
+    Metadata,
+}
+
+impl Type {
+    fn new(first_class: &types::FirstClass) -> Self {
+        match first_class {
+            types::FirstClass::Single(types::SingleValue::Integer(size)) => Self::Integer(*size),
+            types::FirstClass::Single(types::SingleValue::Float(float)) => {
+                Self::Float(float.clone())
+            }
+            types::FirstClass::Single(types::SingleValue::Pointer(pointer)) => Self::Pointer(
+                Box::new(Pointee::new(pointer.pointee())),
+                pointer.address_space(),
+            ),
+            types::FirstClass::Single(types::SingleValue::Vector(vector)) => {
+                Self::Vector(Box::new(Self::new(vector.element_type())), vector.count())
+            }
+            types::FirstClass::Aggregate(types::Aggregate::Array(array)) => {
+                Self::Array(Box::new(Self::new(array.element_type())), array.count())
+            }
+            types::FirstClass::Aggregate(types::Aggregate::Struct(structure)) => Self::Struct(
+                structure
+                    .member_types()
+                    .iter()
+                    .map(|member| Self::new(member))
+                    .collect(),
+                structure.is_packed(),
+            ),
+            types::FirstClass::Token => Self::Token,
+            types::FirstClass::Metadata => Self::Metadata,
+        }
+    }
+
+    fn attach(&self) -> Rc<types::FirstClass> {
+        Rc::new(match self {
+            Self::Integer(size) => types::FirstClass::Single(types::SingleValue::Integer(*size)),
+            Self::Float(float) => {
+                types::FirstClass::Single(types::SingleValue::Float(float.clone()))
+            }
+            Self::Pointer(pointee, address_space) => {
+                types::FirstClass::Single(types::SingleValue::Pointer(
+                    types::Pointer::in_address_space(pointee.attach(), *address_space),
+                ))
+            }
+            Self::Vector(element_type, count) => {
+                types::FirstClass::Single(types::SingleValue::Vector(types::Vector::new(
+                    element_type.attach(),
+                    NonZeroU32::new(*count).expect("vector types should contain elements"),
+                )))
+            }
+            Self::Array(element_type, count) => types::FirstClass::Aggregate(
+                types::Aggregate::Array(types::Array::new(element_type.attach(), *count)),
+            ),
+            Self::Struct(member_types, packed) => {
+                types::FirstClass::Aggregate(types::Aggregate::Struct(types::Struct::new(
+                    member_types.iter().map(Self::attach).collect::<Vec<_>>(),
+                    *packed,
+                )))
+            }
+            Self::Token => types::FirstClass::Token,
+            Self::Metadata => types::FirstClass::Metadata,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Signature {
+    return_type: Option<Type>,
+    parameter_types: Vec<Type>,
+    variadic: bool,
+}
+
+impl Signature {
+    fn new(signature: &types::Function) -> Self {
+        Self {
+            return_type: match signature.return_type() {
+                types::Return::Void => None,
+                types::Return::FirstClass(return_type) => Some(Type::new(return_type)),
+            },
+            parameter_types: signature
+                .parameter_types()
+                .iter()
+                .map(|parameter| Type::new(parameter))
+                .collect(),
+            variadic: signature.is_variadic(),
+        }
+    }
+
+    fn attach(&self) -> Rc<types::Function> {
+        let return_type = match &self.return_type {
+            None => types::Return::Void,
+            Some(return_type) => types::Return::FirstClass(return_type.attach()),
+        };
+        let parameter_types = self
+            .parameter_types
+            .iter()
+            .map(Type::attach)
+            .collect::<Vec<_>>();
+
+        Rc::new(if self.variadic {
+            types::Function::new_variadic(return_type, parameter_types)
+        } else {
+            types::Function::new(return_type, parameter_types)
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Pointee {
+    FirstClass(Type),
+    Function(Signature),
+}
+
+impl Pointee {
+    fn new(pointee: &types::Pointee) -> Self {
+        match pointee {
+            types::Pointee::FirstClass(pointee_type) => Self::FirstClass(Type::new(pointee_type)),
+            types::Pointee::Function(signature) => Self::Function(Signature::new(signature)),
+        }
+    }
+
+    fn attach(&self) -> types::Pointee {
+        match self {
+            Self::FirstClass(pointee_type) => types::Pointee::FirstClass(pointee_type.attach()),
+            Self::Function(signature) => types::Pointee::Function(signature.attach()),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Value {
+    Integer(value::Integer),
+    Float(value::Float),
+    Null(Pointee, AddressSpace),
+    Aggregate(Type, Vec<Value>),
+    Vector(Type, Vec<Value>),
+    /// The name of a function or global variable whose address is taken.
+    GlobalAddress(Identifier),
+    /// The name of a function and the index of the block in that function whose address is taken, which is resolved
+    /// after the blocks of every copied function are appended.
+    BlockAddress(Identifier, usize),
+    /// The index of a register defined in the same function.
+    Register(usize),
+}
+
+impl Value {
+    /// Copies a value, where `indices` maps the registers and blocks of the function containing the value to their indices.
+    fn new(value: &value::Value, indices: &FunctionIndices) -> Result<Self, CopyError> {
+        Ok(match value {
+            value::Value::Integer(integer) => Self::Integer(integer.clone()),
+            value::Value::Float(float) => Self::Float(*float),
+            value::Value::Null(pointer) => {
+                Self::Null(Pointee::new(pointer.pointee()), pointer.address_space())
+            }
+            value::Value::Aggregate(aggregate) => Self::Aggregate(
+                Type::new(&types::FirstClass::Aggregate(
+                    aggregate.aggregate_type().clone(),
+                )),
+                indices.values(aggregate.elements())?,
+            ),
+            value::Value::Vector(vector) => Self::Vector(
+                Type::new(&types::FirstClass::Single(types::SingleValue::Vector(
+                    vector.vector_type().clone(),
+                ))),
+                indices.values(vector.elements())?,
+            ),
+            value::Value::GlobalAddress(address) => {
+                Self::GlobalAddress(Identifier::from(address.symbol().as_id()))
+            }
+            value::Value::BlockAddress(address) => {
+                let name = Identifier::from(address.function_name().as_id());
+                let index = address.function().and_then(|function| {
+                    function
+                        .basic_blocks()
+                        .iter()
+                        .position(|block| Rc::as_ptr(block) == address.block().as_ptr())
+                });
+
+                match index {
+                    Some(index) if index > 0 => Self::BlockAddress(name, index),
+                    _ => return Err(CopyError::InvalidBlockAddress(name)),
+                }
+            }
+            value::Value::Register(register) => Self::Register(indices.register(register)?),
+        })
+    }
+
+    /// Recreates a value, where `registers` contains the registers of the function containing the value, and `globals`
+    /// contains the global values of the module.
+    ///
+    /// The blocks of every function defined by the fragment must already be appended, since block addresses refer to them
+    /// by index.
+    fn attach(
+        &self,
+        registers: &[value::Register],
+        globals: &HashMap<String, global::Value>,
+    ) -> value::Value {
+        match self {
+            Self::Integer(integer) => value::Value::Integer(integer.clone()),
+            Self::Float(float) => value::Value::Float(*float),
+            Self::Null(pointee, address_space) => value::Value::Null(
+                types::Pointer::in_address_space(pointee.attach(), *address_space),
+            ),
+            Self::Aggregate(aggregate_type, elements) => match &*aggregate_type.attach() {
+                types::FirstClass::Aggregate(aggregate_type) => {
+                    value::Value::Aggregate(value::Aggregate::new(
+                        aggregate_type.clone(),
+                        elements
+                            .iter()
+                            .map(|element| element.attach(registers, globals))
+                            .collect(),
+                    ))
+                }
+                _ => unreachable!("aggregate constants should have an aggregate type"),
+            },
+            Self::Vector(vector_type, elements) => match &*vector_type.attach() {
+                types::FirstClass::Single(types::SingleValue::Vector(vector_type)) => {
+                    value::Value::Vector(value::Vector::new(
+                        vector_type.clone(),
+                        elements
+                            .iter()
+                            .map(|element| element.attach(registers, globals))
+                            .collect(),
+                    ))
+                }
+                _ => unreachable!("vector constants should have a vector type"),
+            },
+            Self::GlobalAddress(name) => {
+                value::Value::GlobalAddress(value::GlobalAddress::new(&globals[name.as_str()]))
+            }
+            Self::BlockAddress(name, index) => match &globals[name.as_str()] {
+                global::Value::Function(function) => {
+                    let block = function.basic_blocks()[*index].clone();
+                    value::Value::BlockAddress(value::BlockAddress::new(function, &block))
+                }
+                global::Value::Variable(_) | global::Value::IFunc(_) => {
+                    unreachable!("validation should detect type mismatches")
+                }
+            },
+            Self::Register(index) => value::Value::Register(registers[*index].clone()),
+        }
+    }
+}
+
+/// The operands of a copied metadata node.
+type Node = Vec<Option<Metadata>>;
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Metadata {
+    String(Identifier),
+    Value(Value),
+    Node(Node),
+}
+
+impl Metadata {
+    fn new_node(node: &metadata::Node) -> Result<Node, CopyError> {
+        node.operands()
+            .iter()
+            .map(|operand| {
+                operand
+                    .as_ref()
+                    .map(|operand| {
+                        Ok(match operand {
+                            metadata::Metadata::String(string) => Self::String(string.clone()),
+                            metadata::Metadata::Value(value) => {
+                                Self::Value(Value::new(value, &FunctionIndices::default())?)
+                            }
+                            metadata::Metadata::Node(node) => Self::Node(Self::new_node(node)?),
+                        })
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+
+    fn attach_node(
+        operands: &[Option<Self>],
+        globals: &HashMap<String, global::Value>,
+    ) -> Rc<metadata::Node> {
+        metadata::Node::new(
+            operands
+                .iter()
+                .map(|operand| {
+                    operand.as_ref().map(|operand| match operand {
+                        Self::String(string) => metadata::Metadata::String(string.clone()),
+                        Self::Value(value) => metadata::Metadata::Value(value.attach(&[], globals)),
+                        Self::Node(node) => {
+                            metadata::Metadata::Node(Self::attach_node(node, globals))
+                        }
+                    })
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/// A copy of inline assembly, whose signature is the signature of the call that uses it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct InlineAsm {
+    assembly: Identifier,
+    constraints: Identifier,
+    side_effects: bool,
+    align_stack: bool,
+    dialect: value::AsmDialect,
+}
+
+/// A copy of the function called by a `call` instruction, where functions are referred to by name.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Callee {
+    Function(Identifier),
+    Pointer(Value),
+    InlineAsm(InlineAsm),
+}
+
+/// A copy of an instruction, where blocks are referred to by their index in the function.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum Instruction {
+    Ret(Option<Value>),
+    Br(usize),
+    CondBr {
+        condition: Value,
+        true_target: usize,
+        false_target: usize,
+    },
+    Switch {
+        value: Value,
+        default_target: usize,
+        cases: Vec<(Value, usize)>,
+    },
+    IndirectBr {
+        address: Value,
+        destinations: Vec<usize>,
+    },
+    Phi {
+        incoming: Vec<(Value, usize)>,
+        result: usize,
+    },
+    Call {
+        tail_call: TailCallKind,
+        calling_convention: CallingConvention,
+        callee: Callee,
+        signature: Signature,
+        arguments: Vec<Value>,
+        argument_alignments: Vec<Option<Alignment>>,
+        result: Option<usize>,
+    },
+    CallBr {
+        calling_convention: CallingConvention,
+        callee: Callee,
+        signature: Signature,
+        arguments: Vec<Value>,
+        result: Option<usize>,
+        fallthrough_target: usize,
+        indirect_targets: Vec<usize>,
+    },
+    IntegerBinary {
+        operation: IntegerBinaryOperation,
+        x: Value,
+        y: Value,
+        result: usize,
+    },
+    FloatBinary {
+        operation: FloatBinaryOperation,
+        flags: FastMathFlags,
+        x: Value,
+        y: Value,
+        result: usize,
+    },
+    FNeg {
+        flags: FastMathFlags,
+        operand: Value,
+        result: usize,
+    },
+    Conversion {
+        operation: ConversionOperation,
+        operand: Value,
+        result: usize,
+    },
+    VaArg {
+        list: Value,
+        result: usize,
+    },
+    ICmp {
+        predicate: IcmpPredicate,
+        x: Value,
+        y: Value,
+        result: usize,
+    },
+    FCmp {
+        predicate: FcmpPredicate,
+        x: Value,
+        y: Value,
+        result: usize,
+    },
+    Alloca {
+        allocated_type: Type,
+        count: Option<Value>,
+        alignment: Option<Alignment>,
+        result: usize,
+    },
+    Load {
+        pointer: Value,
+        alignment: Option<Alignment>,
+        volatile: bool,
+        atomic: Option<Atomic>,
+        result: usize,
+    },
+    Store {
+        value: Value,
+        pointer: Value,
+        alignment: Option<Alignment>,
+        volatile: bool,
+        atomic: Option<Atomic>,
+    },
+    AtomicRmw {
+        operation: AtomicRmwOperation,
+        volatile: bool,
+        pointer: Value,
+        value: Value,
+        atomic: Atomic,
+        result: usize,
+    },
+    CmpXchg {
+        weak: bool,
+        volatile: bool,
+        pointer: Value,
+        expected: Value,
+        replacement: Value,
+        success: Atomic,
+        failure_ordering: AtomicOrdering,
+        result: usize,
+    },
+    Fence(Atomic),
+    GetElementPtr {
+        inbounds: bool,
+        element_type: Type,
+        pointer: Value,
+        indices: Vec<Value>,
+        result: usize,
+    },
+}
+
+/// Used to look up the indices of the registers and blocks of the function being copied.
+#[derive(Default)]
+struct FunctionIndices {
+    registers: HashMap<value::Register, usize>,
+    blocks: HashMap<*const BasicBlock, usize>,
+}
+
+impl FunctionIndices {
+    fn value(&self, value: &value::Value) -> Result<Value, CopyError> {
+        Value::new(value, self)
+    }
+
+    fn values(&self, values: &[value::Value]) -> Result<Vec<Value>, CopyError> {
+        values.iter().map(|value| self.value(value)).collect()
+    }
+
+    fn register(&self, register: &value::Register) -> Result<usize, CopyError> {
+        match self.registers.get(register) {
+            Some(index) => Ok(*index),
+            None => Err(CopyError::UndefinedRegister(register.to_string())),
+        }
+    }
+
+    fn callee(&self, callee: &block::Callee) -> Result<Callee, CopyError> {
+        Ok(match callee {
+            block::Callee::Function { name, .. } => {
+                Callee::Function(Identifier::from(name.as_id()))
+            }
+            block::Callee::Pointer(pointer) => Callee::Pointer(self.value(pointer)?),
+            block::Callee::InlineAsm(assembly) => Callee::InlineAsm(InlineAsm {
+                assembly: assembly.assembly().clone(),
+                constraints: assembly.constraints().clone(),
+                side_effects: assembly.has_side_effects(),
+                align_stack: assembly.is_align_stack(),
+                dialect: assembly.dialect(),
+            }),
+        })
+    }
+
+    fn block(&self, block: &block::BlockReference) -> Result<usize, CopyError> {
+        match self.blocks.get(&block.as_ptr()) {
+            Some(index) => Ok(*index),
+            None => Err(CopyError::UndefinedBlock(block.to_string())),
+        }
+    }
+}
+
+impl Instruction {
+    fn new(instruction: &block::Instruction, indices: &FunctionIndices) -> Result<Self, CopyError> {
+        Ok(match instruction {
+            block::Instruction::Ret(value) => Self::Ret(
+                value
+                    .as_ref()
+                    .map(|value| indices.value(value))
+                    .transpose()?,
+            ),
+            block::Instruction::Br(target) => Self::Br(indices.block(target)?),
+            block::Instruction::CondBr {
+                condition,
+                true_target,
+                false_target,
+            } => Self::CondBr {
+                condition: indices.value(condition)?,
+                true_target: indices.block(true_target)?,
+                false_target: indices.block(false_target)?,
+            },
+            block::Instruction::Switch {
+                value,
+                default_target,
+                cases,
+            } => Self::Switch {
+                value: indices.value(value)?,
+                default_target: indices.block(default_target)?,
+                cases: cases
+                    .iter()
+                    .map(|(case_value, target)| {
+                        Ok((indices.value(case_value)?, indices.block(target)?))
+                    })
+                    .collect::<Result<_, _>>()?,
+            },
+            block::Instruction::IndirectBr {
+                address,
+                destinations,
+            } => Self::IndirectBr {
+                address: indices.value(address)?,
+                destinations: destinations
+                    .iter()
+                    .map(|destination| indices.block(destination))
+                    .collect::<Result<_, _>>()?,
+            },
+            block::Instruction::Phi { incoming, result } => Self::Phi {
+                incoming: incoming
+                    .iter()
+                    .map(|(value, block)| Ok((indices.value(value)?, indices.block(block)?)))
+                    .collect::<Result<_, _>>()?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::Call {
+                tail_call,
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                argument_alignments,
+                result,
+            } => Self::Call {
+                tail_call: *tail_call,
+                calling_convention: *calling_convention,
+                callee: indices.callee(callee)?,
+                signature: Signature::new(signature),
+                arguments: indices.values(arguments)?,
+                argument_alignments: argument_alignments.clone(),
+                result: result
+                    .as_ref()
+                    .map(|result| indices.register(result))
+                    .transpose()?,
+            },
+            block::Instruction::CallBr {
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                result,
+                fallthrough_target,
+                indirect_targets,
+            } => Self::CallBr {
+                calling_convention: *calling_convention,
+                callee: indices.callee(callee)?,
+                signature: Signature::new(signature),
+                arguments: indices.values(arguments)?,
+                result: result
+                    .as_ref()
+                    .map(|result| indices.register(result))
+                    .transpose()?,
+                fallthrough_target: indices.block(fallthrough_target)?,
+                indirect_targets: indirect_targets
+                    .iter()
+                    .map(|target| indices.block(target))
+                    .collect::<Result<_, _>>()?,
+            },
+            block::Instruction::IntegerBinary {
+                operation,
+                x,
+                y,
+                result,
+            } => Self::IntegerBinary {
+                operation: *operation,
+                x: indices.value(x)?,
+                y: indices.value(y)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::FloatBinary {
+                operation,
+                flags,
+                x,
+                y,
+                result,
+            } => Self::FloatBinary {
+                operation: *operation,
+                flags: *flags,
+                x: indices.value(x)?,
+                y: indices.value(y)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::FNeg {
+                flags,
+                operand,
+                result,
+            } => Self::FNeg {
+                flags: *flags,
+                operand: indices.value(operand)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::Conversion {
+                operation,
+                operand,
+                result,
+            } => Self::Conversion {
+                operation: *operation,
+                operand: indices.value(operand)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::VaArg { list, result } => Self::VaArg {
+                list: indices.value(list)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::ICmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => Self::ICmp {
+                predicate: *predicate,
+                x: indices.value(x)?,
+                y: indices.value(y)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::FCmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => Self::FCmp {
+                predicate: *predicate,
+                x: indices.value(x)?,
+                y: indices.value(y)?,
+                result: indices.register(result)?,
+            },
+            block::Instruction::Alloca {
+                allocated_type,
+                count,
+                alignment,
+                result,
+            } => Self::Alloca {
+                allocated_type: Type::new(allocated_type),
+                count: count
+                    .as_ref()
+                    .map(|count| indices.value(count))
+                    .transpose()?,
+                alignment: *alignment,
+                result: indices.register(result)?,
+            },
+            block::Instruction::Load {
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+                result,
+            } => Self::Load {
+                pointer: indices.value(pointer)?,
+                alignment: *alignment,
+                volatile: *volatile,
+                atomic: atomic.clone(),
+                result: indices.register(result)?,
+            },
+            block::Instruction::Store {
+                value,
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+            } => Self::Store {
+                value: indices.value(value)?,
+                pointer: indices.value(pointer)?,
+                alignment: *alignment,
+                volatile: *volatile,
+                atomic: atomic.clone(),
+            },
+            block::Instruction::AtomicRmw {
+                operation,
+                volatile,
+                pointer,
+                value,
+                atomic,
+                result,
+            } => Self::AtomicRmw {
+                operation: *operation,
+                volatile: *volatile,
+                pointer: indices.value(pointer)?,
+                value: indices.value(value)?,
+                atomic: atomic.clone(),
+                result: indices.register(result)?,
+            },
+            block::Instruction::CmpXchg {
+                weak,
+                volatile,
+                pointer,
+                expected,
+                replacement,
+                success,
+                failure_ordering,
+                result,
+            } => Self::CmpXchg {
+                weak: *weak,
+                volatile: *volatile,
+                pointer: indices.value(pointer)?,
+                expected: indices.value(expected)?,
+                replacement: indices.value(replacement)?,
+                success: success.clone(),
+                failure_ordering: *failure_ordering,
+                result: indices.register(result)?,
+            },
+            block::Instruction::Fence(atomic) => Self::Fence(atomic.clone()),
+            block::Instruction::GetElementPtr {
+                inbounds,
+                element_type,
+                pointer,
+                indices: element_indices,
+                result,
+            } => Self::GetElementPtr {
+                inbounds: *inbounds,
+                element_type: Type::new(element_type),
+                pointer: indices.value(pointer)?,
+                indices: indices.values(element_indices)?,
+                result: indices.register(result)?,
+            },
+        })
+    }
+
+    /// Gets the values used by this instruction, including the pointers called by indirect calls.
+    fn operands(&self) -> Vec<&Value> {
+        match self {
+            Self::Ret(value) => value.iter().collect(),
+            Self::Br(_) | Self::Fence(_) => Vec::new(),
+            Self::CondBr { condition, .. } => vec![condition],
+            Self::Switch { value, cases, .. } => std::iter::once(value)
+                .chain(cases.iter().map(|(case_value, _)| case_value))
+                .collect(),
+            Self::IndirectBr { address, .. } => vec![address],
+            Self::Phi { incoming, .. } => incoming.iter().map(|(value, _)| value).collect(),
+            Self::Call {
+                callee, arguments, ..
+            }
+            | Self::CallBr {
+                callee, arguments, ..
+            } => {
+                let pointer = match callee {
+                    Callee::Pointer(pointer) => Some(pointer),
+                    Callee::Function(_) | Callee::InlineAsm(_) => None,
+                };
+                pointer.into_iter().chain(arguments.iter()).collect()
+            }
+            Self::IntegerBinary { x, y, .. }
+            | Self::FloatBinary { x, y, .. }
+            | Self::ICmp { x, y, .. }
+            | Self::FCmp { x, y, .. } => vec![x, y],
+            Self::FNeg { operand, .. } | Self::Conversion { operand, .. } => vec![operand],
+            Self::VaArg { list, .. } => vec![list],
+            Self::Alloca { count, .. } => count.iter().collect(),
+            Self::Load { pointer, .. } => vec![pointer],
+            Self::Store { value, pointer, .. } | Self::AtomicRmw { pointer, value, .. } => {
+                vec![value, pointer]
+            }
+            Self::CmpXchg {
+                pointer,
+                expected,
+                replacement,
+                ..
+            } => vec![pointer, expected, replacement],
+            Self::GetElementPtr {
+                pointer, indices, ..
+            } => std::iter::once(pointer).chain(indices.iter()).collect(),
+        }
+    }
+
+    /// Gets the indices of the blocks that this instruction refers to.
+    fn targets(&self) -> Vec<usize> {
+        match self {
+            Self::Br(target) => vec![*target],
+            Self::CondBr {
+                true_target,
+                false_target,
+                ..
+            } => vec![*true_target, *false_target],
+            Self::Switch {
+                default_target,
+                cases,
+                ..
+            } => std::iter::once(*default_target)
+                .chain(cases.iter().map(|(_, target)| *target))
+                .collect(),
+            Self::IndirectBr { destinations, .. } => destinations.clone(),
+            Self::Phi { incoming, .. } => incoming.iter().map(|(_, block)| *block).collect(),
+            Self::CallBr {
+                fallthrough_target,
+                indirect_targets,
+                ..
+            } => std::iter::once(*fallthrough_target)
+                .chain(indirect_targets.iter().copied())
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Gets the index of the register defined by this instruction.
+    fn result(&self) -> Option<usize> {
+        match self {
+            Self::Call { result, .. } | Self::CallBr { result, .. } => *result,
+            Self::Phi { result, .. }
+            | Self::IntegerBinary { result, .. }
+            | Self::FloatBinary { result, .. }
+            | Self::FNeg { result, .. }
+            | Self::Conversion { result, .. }
+            | Self::VaArg { result, .. }
+            | Self::ICmp { result, .. }
+            | Self::FCmp { result, .. }
+            | Self::Alloca { result, .. }
+            | Self::Load { result, .. }
+            | Self::AtomicRmw { result, .. }
+            | Self::CmpXchg { result, .. }
+            | Self::GetElementPtr { result, .. } => Some(*result),
+            Self::Ret(_)
+            | Self::Br(_)
+            | Self::CondBr { .. }
+            | Self::Switch { .. }
+            | Self::IndirectBr { .. }
+            | Self::Store { .. }
+            | Self::Fence(_) => None,
+        }
+    }
+}
+
+/// State used to recreate the instructions of a function within a module.
+struct Attacher<'a> {
+    registers: &'a [value::Register],
+    blocks: &'a [Rc<BasicBlock>],
+    globals: &'a HashMap<String, global::Value>,
+}
+
+impl Attacher<'_> {
+    fn value(&self, value: &Value) -> value::Value {
+        value.attach(self.registers, self.globals)
+    }
+
+    fn values(&self, values: &[Value]) -> Vec<value::Value> {
+        values.iter().map(|value| self.value(value)).collect()
+    }
+
+    fn block(&self, index: usize) -> block::BlockReference {
+        block::BlockReference::new(&self.blocks[index])
+    }
+
+    fn register(&self, index: usize) -> value::Register {
+        self.registers[index].clone()
+    }
+
+    /// Gets the callee of a call, along with its signature, which for direct calls is the signature of the function in the
+    /// module.
+    fn callee(
+        &self,
+        callee: &Callee,
+        signature: &Signature,
+    ) -> (block::Callee, Rc<types::Function>) {
+        match callee {
+            Callee::Function(name) => {
+                let function = match &self.globals[name.as_str()] {
+                    global::Value::Function(function) => function,
+                    global::Value::Variable(_) | global::Value::IFunc(_) => {
+                        unreachable!("validation should detect type mismatches")
+                    }
+                };
+                (
+                    block::Callee::function(function),
+                    function.signature().clone(),
+                )
+            }
+            Callee::Pointer(pointer) => (
+                block::Callee::Pointer(self.value(pointer)),
+                signature.attach(),
+            ),
+            Callee::InlineAsm(assembly) => {
+                let signature = signature.attach();
+                let mut attached = value::InlineAsm::new(
+                    signature.clone(),
+                    assembly.assembly.clone(),
+                    assembly.constraints.clone(),
+                )
+                .with_dialect(assembly.dialect);
+
+                if assembly.side_effects {
+                    attached = attached.with_side_effects();
+                }
+                if assembly.align_stack {
+                    attached = attached.with_align_stack();
+                }
+
+                (block::Callee::InlineAsm(Rc::new(attached)), signature)
+            }
+        }
+    }
+
+    fn instruction(&self, instruction: &Instruction) -> block::Instruction {
+        match instruction {
+            Instruction::Ret(value) => {
+                block::Instruction::Ret(value.as_ref().map(|value| self.value(value)))
+            }
+            Instruction::Br(target) => block::Instruction::Br(self.block(*target)),
+            Instruction::CondBr {
+                condition,
+                true_target,
+                false_target,
+            } => block::Instruction::CondBr {
+                condition: self.value(condition),
+                true_target: self.block(*true_target),
+                false_target: self.block(*false_target),
+            },
+            Instruction::Switch {
+                value,
+                default_target,
+                cases,
+            } => block::Instruction::Switch {
+                value: self.value(value),
+                default_target: self.block(*default_target),
+                cases: cases
+                    .iter()
+                    .map(|(case_value, target)| (self.value(case_value), self.block(*target)))
+                    .collect(),
+            },
+            Instruction::IndirectBr {
+                address,
+                destinations,
+            } => block::Instruction::IndirectBr {
+                address: self.value(address),
+                destinations: destinations
+                    .iter()
+                    .map(|destination| self.block(*destination))
+                    .collect(),
+            },
+            Instruction::Phi { incoming, result } => block::Instruction::Phi {
+                incoming: incoming
+                    .iter()
+                    .map(|(value, block)| (self.value(value), self.block(*block)))
+                    .collect(),
+                result: self.register(*result),
+            },
+            Instruction::Call {
+                tail_call,
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                argument_alignments,
+                result,
+            } => {
+                let (callee, signature) = self.callee(callee, signature);
+                let mut argument_alignments = argument_alignments.clone();
+                argument_alignments.resize(arguments.len(), None);
+                block::Instruction::Call {
+                    tail_call: *tail_call,
+                    calling_convention: *calling_convention,
+                    callee,
+                    signature,
+                    arguments: self.values(arguments),
+                    argument_alignments,
+                    result: result.map(|result| self.register(result)),
+                }
+            }
+            Instruction::CallBr {
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                result,
+                fallthrough_target,
+                indirect_targets,
+            } => {
+                let (callee, signature) = self.callee(callee, signature);
+                block::Instruction::CallBr {
+                    calling_convention: *calling_convention,
+                    callee,
+                    signature,
+                    arguments: self.values(arguments),
+                    result: result.map(|result| self.register(result)),
+                    fallthrough_target: self.block(*fallthrough_target),
+                    indirect_targets: indirect_targets
+                        .iter()
+                        .map(|target| self.block(*target))
+                        .collect(),
+                }
+            }
+            Instruction::IntegerBinary {
+                operation,
+                x,
+                y,
+                result,
+            } => block::Instruction::IntegerBinary {
+                operation: *operation,
+                x: self.value(x),
+                y: self.value(y),
+                result: self.register(*result),
+            },
+            Instruction::FloatBinary {
+                operation,
+                flags,
+                x,
+                y,
+                result,
+            } => block::Instruction::FloatBinary {
+                operation: *operation,
+                flags: *flags,
+                x: self.value(x),
+                y: self.value(y),
+                result: self.register(*result),
+            },
+            Instruction::FNeg {
+                flags,
+                operand,
+                result,
+            } => block::Instruction::FNeg {
+                flags: *flags,
+                operand: self.value(operand),
+                result: self.register(*result),
+            },
+            Instruction::Conversion {
+                operation,
+                operand,
+                result,
+            } => block::Instruction::Conversion {
+                operation: *operation,
+                operand: self.value(operand),
+                result: self.register(*result),
+            },
+            Instruction::VaArg { list, result } => block::Instruction::VaArg {
+                list: self.value(list),
+                result: self.register(*result),
+            },
+            Instruction::ICmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => block::Instruction::ICmp {
+                predicate: *predicate,
+                x: self.value(x),
+                y: self.value(y),
+                result: self.register(*result),
+            },
+            Instruction::FCmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => block::Instruction::FCmp {
+                predicate: *predicate,
+                x: self.value(x),
+                y: self.value(y),
+                result: self.register(*result),
+            },
+            Instruction::Alloca {
+                allocated_type,
+                count,
+                alignment,
+                result,
+            } => block::Instruction::Alloca {
+                allocated_type: allocated_type.attach(),
+                count: count.as_ref().map(|count| self.value(count)),
+                alignment: *alignment,
+                result: self.register(*result),
+            },
+            Instruction::Load {
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+                result,
+            } => block::Instruction::Load {
+                pointer: self.value(pointer),
+                alignment: *alignment,
+                volatile: *volatile,
+                atomic: atomic.clone(),
+                result: self.register(*result),
+            },
+            Instruction::Store {
+                value,
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+            } => block::Instruction::Store {
+                value: self.value(value),
+                pointer: self.value(pointer),
+                alignment: *alignment,
+                volatile: *volatile,
+                atomic: atomic.clone(),
+            },
+            Instruction::AtomicRmw {
+                operation,
+                volatile,
+                pointer,
+                value,
+                atomic,
+                result,
+            } => block::Instruction::AtomicRmw {
+                operation: *operation,
+                volatile: *volatile,
+                pointer: self.value(pointer),
+                value: self.value(value),
+                atomic: atomic.clone(),
+                result: self.register(*result),
+            },
+            Instruction::CmpXchg {
+                weak,
+                volatile,
+                pointer,
+                expected,
+                replacement,
+                success,
+                failure_ordering,
+                result,
+            } => block::Instruction::CmpXchg {
+                weak: *weak,
+                volatile: *volatile,
+                pointer: self.value(pointer),
+                expected: self.value(expected),
+                replacement: self.value(replacement),
+                success: success.clone(),
+                failure_ordering: *failure_ordering,
+                result: self.register(*result),
+            },
+            Instruction::Fence(atomic) => block::Instruction::Fence(atomic.clone()),
+            Instruction::GetElementPtr {
+                inbounds,
+                element_type,
+                pointer,
+                indices,
+                result,
+            } => block::Instruction::GetElementPtr {
+                inbounds: *inbounds,
+                element_type: element_type.attach(),
+                pointer: self.value(pointer),
+                indices: self.values(indices),
+                result: self.register(*result),
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Block {
+    name: Option<Identifier>,
+    /// The instructions of the block, along with the metadata attached to each instruction.
+    instructions: Vec<(Instruction, Vec<(Identifier, Node)>)>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Comdat {
+    name: Identifier,
+    selection_kind: ComdatSelectionKind,
+}
+
+impl Comdat {
+    fn new(comdat: &global::Comdat) -> Self {
+        Self {
+            name: Identifier::from(comdat.name()),
+            selection_kind: comdat.selection_kind(),
+        }
+    }
+
+    fn attach(&self) -> global::Comdat {
+        global::Comdat::new(self.name.clone(), self.selection_kind)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Function {
+    name: Identifier,
+    signature: Signature,
+    linkage: Linkage,
+    visibility: Visibility,
+    dso_local: bool,
+    unnamed_address: Option<UnnamedAddress>,
+    calling_convention: CallingConvention,
+    section: Option<Identifier>,
+    comdat: Option<Comdat>,
+    alignment: Option<Alignment>,
+    parameter_names: Vec<Option<Identifier>>,
+    /// The types and names of the registers defined by the instructions of the function, which are indexed after the
+    /// parameters.
+    registers: Vec<(Type, Option<Identifier>)>,
+    blocks: Vec<Block>,
+}
+
+impl Function {
+    fn new(function: &global::Function) -> Result<Self, CopyError> {
+        let blocks = function.basic_blocks();
+        let parameters = function.parameters();
+        let mut indices = FunctionIndices {
+            registers: parameters
+                .iter()
+                .enumerate()
+                .map(|(index, parameter)| (parameter.clone(), index))
+                .collect(),
+            blocks: blocks
+                .iter()
+                .enumerate()
+                .map(|(index, block)| (Rc::as_ptr(block), index))
+                .collect(),
+        };
+
+        let mut registers = Vec::new();
+        for block in blocks.iter() {
+            for instruction in block.instructions().iter() {
+                if let Some(result) = instruction.result() {
+                    indices
+                        .registers
+                        .insert(result.clone(), parameters.len() + registers.len());
+                    registers.push((Type::new(result.value_type()), result.name()));
+                }
+            }
+        }
+
+        let blocks = blocks
+            .iter()
+            .map(|block| {
+                let metadata = block.metadata();
+                Ok(Block {
+                    name: block.name().map(Identifier::from),
+                    instructions: block
+                        .instructions()
+                        .iter()
+                        .enumerate()
+                        .map(|(index, instruction)| {
+                            let attached = metadata
+                                .iter()
+                                .filter(|(attached_index, _, _)| *attached_index == index)
+                                .map(|(_, kind, node)| {
+                                    Ok((kind.clone(), Metadata::new_node(node)?))
+                                })
+                                .collect::<Result<_, _>>()?;
+                            Ok((Instruction::new(instruction, &indices)?, attached))
+                        })
+                        .collect::<Result<_, _>>()?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            name: Identifier::from(function.name()),
+            signature: Signature::new(function.signature()),
+            linkage: function.get_linkage(),
+            visibility: function.get_visibility(),
+            dso_local: function.get_dso_local(),
+            unnamed_address: function.get_unnamed_address(),
+            calling_convention: function.get_calling_convention(),
+            section: function.get_section(),
+            comdat: function.get_comdat().as_ref().map(Comdat::new),
+            alignment: function.get_alignment(),
+            parameter_names: parameters.iter().map(value::Register::name).collect(),
+            registers,
+            blocks,
+        })
+    }
+
+    fn is_definition(&self) -> bool {
+        !self.blocks.is_empty()
+    }
+
+    fn set_properties(&self, function: &global::Function) {
+        function.set_linkage(self.linkage);
+        function.set_visibility(self.visibility);
+        function.set_dso_local(self.dso_local);
+        function.set_unnamed_address(self.unnamed_address);
+        function.set_calling_convention(self.calling_convention);
+        function.set_section(self.section.clone());
+        function.set_comdat(self.comdat.as_ref().map(Comdat::attach));
+        function.set_alignment(self.alignment);
+        function.set_parameter_names(self.parameter_names.iter().cloned());
+    }
+
+    /// Appends empty blocks to a function in a module, which are filled by [`Function::attach_instructions`] once the
+    /// blocks of every copied function are appended.
+    fn append_blocks(&self, function: &global::Function) {
+        for block in self.blocks.iter() {
+            function.append_basic_block(match &block.name {
+                Some(name) => BasicBlock::with_name(name.clone()),
+                None => BasicBlock::new(),
+            });
+        }
+    }
+
+    /// Inserts copies of the instructions of this function into the blocks appended by [`Function::append_blocks`].
+    fn attach_instructions(
+        &self,
+        function: &global::Function,
+        globals: &HashMap<String, global::Value>,
+    ) {
+        let registers = function
+            .parameters()
+            .iter()
+            .cloned()
+            .chain(self.registers.iter().map(|(register_type, name)| {
+                let register = value::Register::new(register_type.attach());
+                register.set_name(name.clone());
+                register
+            }))
+            .collect::<Vec<_>>();
+
+        let blocks = function.basic_blocks().to_vec();
+        let attacher = Attacher {
+            registers: &registers,
+            blocks: &blocks,
+            globals,
+        };
+
+        for (block, copied) in blocks.iter().zip(self.blocks.iter()) {
+            for (index, (instruction, metadata)) in copied.instructions.iter().enumerate() {
+                block.insert_instruction(index, attacher.instruction(instruction));
+                for (kind, node) in metadata.iter() {
+                    block.attach_metadata(kind.clone(), Metadata::attach_node(node, globals));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Variable {
+    name: Identifier,
+    value_type: Type,
+    constant: bool,
+    linkage: Linkage,
+    visibility: Visibility,
+    dso_local: bool,
+    unnamed_address: Option<UnnamedAddress>,
+    initializer: Option<Value>,
+    section: Option<Identifier>,
+    comdat: Option<Comdat>,
+    alignment: Option<Alignment>,
+    thread_local: Option<ThreadLocalMode>,
+    externally_initialized: bool,
+}
+
+impl Variable {
+    fn new(variable: &global::Variable) -> Result<Self, CopyError> {
+        Ok(Self {
+            name: Identifier::from(variable.name()),
+            value_type: Type::new(variable.value_type()),
+            constant: variable.is_constant(),
+            linkage: variable.get_linkage(),
+            visibility: variable.get_visibility(),
+            dso_local: variable.get_dso_local(),
+            unnamed_address: variable.get_unnamed_address(),
+            initializer: variable
+                .get_initializer()
+                .map(|initializer| Value::new(&initializer, &FunctionIndices::default()))
+                .transpose()?,
+            section: variable.get_section(),
+            comdat: variable.get_comdat().as_ref().map(Comdat::new),
+            alignment: variable.get_alignment(),
+            thread_local: variable.get_thread_local_mode(),
+            externally_initialized: variable.get_externally_initialized(),
+        })
+    }
+
+    fn set_properties(
+        &self,
+        variable: &global::Variable,
+        globals: &HashMap<String, global::Value>,
+    ) {
+        variable.set_linkage(self.linkage);
+        variable.set_visibility(self.visibility);
+        variable.set_dso_local(self.dso_local);
+        variable.set_unnamed_address(self.unnamed_address);
+        variable.set_initializer(
+            self.initializer
+                .as_ref()
+                .map(|initializer| initializer.attach(&[], globals)),
+        );
+        variable.set_section(self.section.clone());
+        variable.set_comdat(self.comdat.as_ref().map(Comdat::attach));
+        variable.set_alignment(self.alignment);
+        variable.set_thread_local_mode(self.thread_local);
+        variable.set_externally_initialized(self.externally_initialized);
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct IFunc {
+    name: Identifier,
+    signature: Signature,
+    resolver: Identifier,
+    linkage: Linkage,
+    visibility: Visibility,
+}
+
+impl IFunc {
+    fn new(ifunc: &global::IFunc) -> Self {
+        Self {
+            name: Identifier::from(ifunc.name()),
+            signature: Signature::new(ifunc.signature()),
+            resolver: Identifier::from(ifunc.resolver().name()),
+            linkage: ifunc.get_linkage(),
+            visibility: ifunc.get_visibility(),
+        }
+    }
+
+    fn attach(&self, module: &mut Module, resolver: Rc<global::Function>) -> Rc<global::IFunc> {
+        let ifunc = global::IFunc::new(
+            module.intern(self.name.as_id()),
+            self.signature.attach(),
+            resolver,
+        );
+        ifunc.set_linkage(self.linkage);
+        ifunc.set_visibility(self.visibility);
+        ifunc
+    }
+}
+
+/// The kind of a global value whose address is used, needed to declare it if it does not exist.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+enum GlobalKind {
+    Function(Signature),
+    /// A global variable of the specified type, and whether it is constant.
+    Variable(Type, bool),
+    /// An indirect function with the specified signature, which cannot be declared and must be defined.
+    IFunc(Signature),
+}
+
+/// Used to check that the global values, registers, and blocks referred to by copied values exist.
+struct References<'a> {
+    /// The names of the global values that are declared in the module or in the fragment.
+    globals: HashSet<&'a str>,
+    /// The number of blocks in each function that is defined in the module or in the fragment.
+    block_counts: HashMap<&'a str, usize>,
+}
+
+impl References<'_> {
+    /// Checks a value used by `owner`, where `registers` is the number of registers that the value can use.
+    fn check_value(
+        &self,
+        value: &Value,
+        owner: &Identifier,
+        registers: usize,
+    ) -> Result<(), MergeError> {
+        match value {
+            Value::Integer(_) | Value::Float(_) | Value::Null(_, _) => Ok(()),
+            Value::Aggregate(_, elements) | Value::Vector(_, elements) => elements
+                .iter()
+                .try_for_each(|element| self.check_value(element, owner, registers)),
+            Value::GlobalAddress(name) if self.globals.contains(name.as_str()) => Ok(()),
+            Value::GlobalAddress(name) => Err(MergeError::UndeclaredGlobal(name.clone())),
+            Value::BlockAddress(name, index) => match self.block_counts.get(name.as_str()) {
+                Some(count) if *index > 0 && index < count => Ok(()),
+                _ => Err(MergeError::UndefinedBlock(name.clone(), *index)),
+            },
+            Value::Register(index) if *index < registers => Ok(()),
+            Value::Register(_) => Err(MergeError::InvalidReference(owner.clone())),
+        }
+    }
+
+    /// Checks the operands of a metadata node attached to an instruction in `owner`, which cannot use registers.
+    fn check_node(&self, node: &[Option<Metadata>], owner: &Identifier) -> Result<(), MergeError> {
+        node.iter().flatten().try_for_each(|operand| match operand {
+            Metadata::String(_) => Ok(()),
+            Metadata::Value(value) => self.check_value(value, owner, 0),
+            Metadata::Node(node) => self.check_node(node, owner),
+        })
+    }
+
+    fn check_function(&self, function: &Function) -> Result<(), MergeError> {
+        let registers = function.signature.parameter_types.len() + function.registers.len();
+        let invalid = || MergeError::InvalidReference(function.name.clone());
+
+        for block in function.blocks.iter() {
+            for (instruction, metadata) in block.instructions.iter() {
+                for operand in instruction.operands() {
+                    self.check_value(operand, &function.name, registers)?;
+                }
+
+                if instruction
+                    .targets()
+                    .into_iter()
+                    .any(|target| target >= function.blocks.len())
+                {
+                    return Err(invalid());
+                }
+
+                if matches!(instruction.result(), Some(result) if result >= registers) {
+                    return Err(invalid());
+                }
+
+                for (_, node) in metadata.iter() {
+                    self.check_node(node, &function.name)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A collection of copied functions, global variables, and indirect functions, which can be sent to other threads and merged into a module.
+///
+/// Copies of functions include their basic blocks, instructions, and attached metadata. Calls and global addresses refer
+/// to global values by name, and metadata nodes that are shared by several instructions are copied separately for each
+/// instruction.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fragment {
+    functions: Vec<Function>,
+    variables: Vec<Variable>,
+    ifuncs: Vec<IFunc>,
+    /// The functions and global variables whose addresses are used by the copied global values, which are declared if
+    /// they do not exist when the fragment is merged.
+    globals: Vec<(Identifier, GlobalKind)>,
+}
+
+// Fragments are used to send global values to other threads.
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Fragment>();
+};
+
+impl Fragment {
+    /// Creates an empty fragment.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a copy of a function to this fragment.
+    ///
+    /// # Panics
+    /// Panics if the function cannot be copied, see [`Fragment::try_add_function`].
+    pub fn add_function(&mut self, function: &global::Function) {
+        or_panic(self.try_add_function(function))
+    }
+
+    /// Adds a copy of a function to this fragment, returning an error if an instruction in the function uses a register or
+    /// refers to a block that is not contained in the function, or takes the address of a block that was removed.
+    ///
+    /// The fragment is not modified when an error is returned.
+    pub fn try_add_function(&mut self, function: &global::Function) -> Result<(), CopyError> {
+        let copy = Function::new(function)?;
+
+        for block in function.basic_blocks().iter() {
+            for instruction in block.instructions().iter() {
+                for operand in instruction.operands() {
+                    self.add_global_addresses(operand);
+                }
+            }
+
+            for (_, _, node) in block.metadata().iter() {
+                self.add_node_global_addresses(node);
+            }
+        }
+
+        self.functions.push(copy);
+        Ok(())
+    }
+
+    /// Adds a copy of a global variable to this fragment.
+    ///
+    /// # Panics
+    /// Panics if the global variable cannot be copied, see [`Fragment::try_add_variable`].
+    pub fn add_variable(&mut self, variable: &global::Variable) {
+        or_panic(self.try_add_variable(variable))
+    }
+
+    /// Adds a copy of a global variable to this fragment, returning an error if its initializer uses a register or takes
+    /// the address of a block that was removed.
+    ///
+    /// Block addresses in the initializer refer to the function containing the block by name, so the function must be
+    /// defined in the module or in the fragment when the fragment is merged.
+    ///
+    /// The fragment is not modified when an error is returned.
+    pub fn try_add_variable(&mut self, variable: &global::Variable) -> Result<(), CopyError> {
+        let copy = Variable::new(variable)?;
+
+        if let Some(initializer) = variable.get_initializer() {
+            self.add_global_addresses(&initializer);
+        }
+
+        self.variables.push(copy);
+        Ok(())
+    }
+
+    /// Adds a copy of an indirect function to this fragment, which refers to its resolver by name.
+    pub fn add_ifunc(&mut self, ifunc: &global::IFunc) {
+        let resolver = ifunc.resolver();
+        self.globals.push((
+            Identifier::from(resolver.name()),
+            GlobalKind::Function(Signature::new(resolver.signature())),
+        ));
+        self.ifuncs.push(IFunc::new(ifunc))
+    }
+
+    /// Records the functions and global variables whose addresses are used in the operands of a metadata node.
+    fn add_node_global_addresses(&mut self, node: &metadata::Node) {
+        for operand in node.operands().iter().flatten() {
+            match operand {
+                metadata::Metadata::Value(value) => self.add_global_addresses(value),
+                metadata::Metadata::Node(node) => self.add_node_global_addresses(node),
+                metadata::Metadata::String(_) => (),
+            }
+        }
+    }
+
+    /// Records the functions and global variables whose addresses are used in a value.
+    fn add_global_addresses(&mut self, value: &value::Value) {
+        match value {
+            value::Value::GlobalAddress(address) => {
+                let kind = match (address.function_signature(), address.variable_type()) {
+                    (Some(signature), _) if address.is_ifunc() => {
+                        GlobalKind::IFunc(Signature::new(signature))
+                    }
+                    (Some(signature), _) => GlobalKind::Function(Signature::new(signature)),
+                    (None, Some((value_type, constant))) => {
+                        GlobalKind::Variable(Type::new(value_type), constant)
+                    }
+                    (None, None) => {
+                        unreachable!("global address should refer to a function or variable")
+                    }
+                };
+
+                self.globals
+                    .push((Identifier::from(address.symbol().as_id()), kind));
+            }
+            value::Value::BlockAddress(address) => {
+                if let Some(function) = address.function() {
+                    self.globals.push((
+                        Identifier::from(address.function_name().as_id()),
+                        GlobalKind::Function(Signature::new(function.signature())),
+                    ));
+                }
+            }
+            value::Value::Aggregate(aggregate) => {
+                for element in aggregate.elements() {
+                    self.add_global_addresses(element);
+                }
+            }
+            value::Value::Vector(vector) => {
+                for element in vector.elements() {
+                    self.add_global_addresses(element);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Adds a copy of a function, global variable, or indirect function to this fragment.
+    ///
+    /// # Panics
+    /// Panics if the global value cannot be copied, see [`Fragment::try_add_global_value`].
+    pub fn add_global_value(&mut self, value: &global::Value) {
+        or_panic(self.try_add_global_value(value))
+    }
+
+    /// Adds a copy of a function, global variable, or indirect function to this fragment, returning an error if it cannot
+    /// be copied, see [`Fragment::try_add_function`] and [`Fragment::try_add_variable`].
+    pub fn try_add_global_value(&mut self, value: &global::Value) -> Result<(), CopyError> {
+        match value {
+            global::Value::Function(function) => self.try_add_function(function),
+            global::Value::Variable(variable) => self.try_add_variable(variable),
+            global::Value::IFunc(ifunc) => {
+                self.add_ifunc(ifunc);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns `true` if this fragment does not contain any global values.
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.variables.is_empty() && self.ifuncs.is_empty()
+    }
+
+    /// Checks that this fragment can be merged into a module, before the module is modified.
+    fn validate(&self, existing: &HashMap<String, global::Value>) -> Result<(), MergeError> {
+        #[derive(PartialEq)]
+        enum Kind {
+            Function(Signature),
+            Variable(Type),
+            IFunc(Signature),
+        }
+
+        // The kind of each global value, and whether it is defined.
+        let mut expected = HashMap::<String, (Kind, bool)>::new();
+        let mut check = |name: &str, kind: Kind, defined: bool| match expected.get_mut(name) {
+            Some((expected_kind, _)) if *expected_kind != kind => {
+                Err(MergeError::TypeMismatch(Identifier::sanitized(name)))
+            }
+            Some((_, true)) if defined => {
+                Err(MergeError::DuplicateDefinition(Identifier::sanitized(name)))
+            }
+            Some((_, expected_defined)) => {
+                *expected_defined |= defined;
+                Ok(())
+            }
+            None => {
+                expected.insert(name.to_string(), (kind, defined));
+                Ok(())
+            }
+        };
+
+        for (name, value) in existing.iter() {
+            match value {
+                global::Value::Function(function) => check(
+                    name,
+                    Kind::Function(Signature::new(function.signature())),
+                    !function.basic_blocks().is_empty(),
+                )?,
+                global::Value::Variable(variable) => check(
+                    name,
+                    Kind::Variable(Type::new(variable.value_type())),
+                    variable.get_initializer().is_some(),
+                )?,
+                global::Value::IFunc(ifunc) => {
+                    check(name, Kind::IFunc(Signature::new(ifunc.signature())), true)?
+                }
+            }
+        }
+
+        for function in self.functions.iter() {
+            check(
+                function.name.as_str(),
+                Kind::Function(function.signature.clone()),
+                function.is_definition(),
+            )?;
+        }
+
+        for variable in self.variables.iter() {
+            check(
+                variable.name.as_str(),
+                Kind::Variable(variable.value_type.clone()),
+                variable.initializer.is_some(),
+            )?;
+        }
+
+        for ifunc in self.ifuncs.iter() {
+            check(
+                ifunc.name.as_str(),
+                Kind::IFunc(ifunc.signature.clone()),
+                true,
+            )?;
+        }
+
+        for (name, kind) in self.globals.iter() {
+            let kind = match kind {
+                GlobalKind::Function(signature) => Kind::Function(signature.clone()),
+                GlobalKind::Variable(value_type, _) => Kind::Variable(value_type.clone()),
+                GlobalKind::IFunc(signature) => Kind::IFunc(signature.clone()),
+            };
+
+            check(name.as_str(), kind, false)?;
+        }
+
+        for function in self.functions.iter() {
+            for block in function.blocks.iter() {
+                for (instruction, _) in block.instructions.iter() {
+                    if let Instruction::Call {
+                        callee: Callee::Function(callee),
+                        signature,
+                        ..
+                    }
+                    | Instruction::CallBr {
+                        callee: Callee::Function(callee),
+                        signature,
+                        ..
+                    } = instruction
+                    {
+                        check(callee.as_str(), Kind::Function(signature.clone()), false)?;
+                    }
+                }
+            }
+        }
+
+        // Indirect functions cannot be declared, so any that are used must be defined.
+        for (name, kind) in self.globals.iter() {
+            if let (GlobalKind::IFunc(_), Some((_, false))) = (kind, expected.get(name.as_str())) {
+                return Err(MergeError::UndefinedIFunc(name.clone()));
+            }
+        }
+
+        for ifunc in self.ifuncs.iter() {
+            match expected.get(ifunc.resolver.as_str()) {
+                Some((Kind::Function(_), _)) => (),
+                Some(_) => return Err(MergeError::TypeMismatch(ifunc.resolver.clone())),
+                None => return Err(MergeError::UndeclaredGlobal(ifunc.resolver.clone())),
+            }
+        }
+
+        // Registers, blocks, and the names of global values are checked last, since data that was not created from a
+        // module may refer to any of them.
+        let mut block_counts = HashMap::new();
+        for (name, value) in existing.iter() {
+            if let global::Value::Function(function) = value {
+                block_counts.insert(name.as_str(), function.basic_blocks().len());
+            }
+        }
+
+        for function in self
+            .functions
+            .iter()
+            .filter(|function| function.is_definition())
+        {
+            block_counts.insert(function.name.as_str(), function.blocks.len());
+        }
+
+        let references = References {
+            globals: expected.keys().map(String::as_str).collect(),
+            block_counts,
+        };
+
+        for function in self.functions.iter() {
+            references.check_function(function)?;
+        }
+
+        for variable in self.variables.iter() {
+            if let Some(initializer) = &variable.initializer {
+                references.check_value(initializer, &variable.name, 0)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds the global values in this fragment to a module, reusing existing declarations.
+    pub(crate) fn merge_into(self, module: &mut Module) -> Result<(), MergeError> {
+        let existing = module
+            .global_values()
+            .iter()
+            .map(|value| (value.symbol().as_id().as_str().to_string(), value.clone()))
+            .collect::<HashMap<_, _>>();
+
+        self.validate(&existing)?;
+
+        let mut globals = existing.clone();
+
+        let mut initialized = Vec::new();
+        for variable in self.variables.iter() {
+            match existing.get(variable.name.as_str()) {
+                Some(global::Value::Variable(existing)) => {
+                    if variable.initializer.is_some() {
+                        initialized.push((variable, existing.clone()));
+                    }
+                }
+                Some(global::Value::Function(_) | global::Value::IFunc(_)) => {
+                    unreachable!("validation should detect type mismatches")
+                }
+                None => {
+                    let copy = declare_variable(
+                        module,
+                        &mut globals,
+                        &variable.name,
+                        &variable.value_type,
+                        variable.constant,
+                    );
+                    initialized.push((variable, copy));
+                }
+            }
+        }
+
+        let mut definitions = Vec::new();
+        for function in self.functions.iter() {
+            let is_new = !existing.contains_key(function.name.as_str());
+            let declaration =
+                get_or_declare_function(module, &mut globals, &function.name, &function.signature);
+            if is_new || function.is_definition() {
+                function.set_properties(&declaration);
+            }
+            if function.is_definition() {
+                definitions.push((function, declaration));
+            }
+        }
+
+        for (name, kind) in self.globals.iter() {
+            match kind {
+                GlobalKind::Function(signature) => {
+                    get_or_declare_function(module, &mut globals, name, signature);
+                }
+                GlobalKind::Variable(value_type, constant) => {
+                    if !globals.contains_key(name.as_str()) {
+                        declare_variable(module, &mut globals, name, value_type, *constant);
+                    }
+                }
+                // Validation ensures that indirect functions are defined in the module or in the fragment.
+                GlobalKind::IFunc(_) => (),
+            }
+        }
+
+        for (function, _) in definitions.iter() {
+            for block in function.blocks.iter() {
+                for (instruction, _) in block.instructions.iter() {
+                    if let Instruction::Call {
+                        callee: Callee::Function(callee),
+                        signature,
+                        ..
+                    }
+                    | Instruction::CallBr {
+                        callee: Callee::Function(callee),
+                        signature,
+                        ..
+                    } = instruction
+                    {
+                        get_or_declare_function(module, &mut globals, callee, signature);
+                    }
+                }
+            }
+        }
+
+        // Indirect functions are added after their resolvers are declared.
+        for ifunc in self.ifuncs.iter() {
+            let resolver = match &globals[ifunc.resolver.as_str()] {
+                global::Value::Function(resolver) => resolver.clone(),
+                global::Value::Variable(_) | global::Value::IFunc(_) => {
+                    unreachable!("validation should detect type mismatches")
+                }
+            };
+
+            let copy = ifunc.attach(module, resolver);
+            module.add_global_value(copy.clone());
+            globals.insert(ifunc.name.to_string(), global::Value::IFunc(copy));
+        }
+
+        // Initializers and instructions are attached after every global value is declared and the blocks of every
+        // function are appended, since they may use the addresses of any global value or block.
+        for (function, declaration) in definitions.iter() {
+            function.append_blocks(declaration);
+        }
+
+        for (variable, copy) in initialized {
+            variable.set_properties(&copy, &globals);
+        }
+
+        for (function, declaration) in definitions {
+            function.attach_instructions(&declaration, &globals);
+        }
+
+        Ok(())
+    }
+}
+
+/// A copy of an entire module, including its name and target.
+///
+/// Since modules borrow their target, a module is recreated from a snapshot with [`Snapshot::to_module`], which borrows
+/// the target stored in the snapshot.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    name: Identifier,
+    target: Target,
+    opaque_pointers: bool,
+    fragment: Fragment,
+}
+
+// Snapshots are used to send modules to other threads.
+const _: fn() = || {
+    fn assert_send<T: Send>() {}
+    assert_send::<Snapshot>();
+};
+
+impl Snapshot {
+    /// Copies the name, target, and global values of a module.
+    ///
+    /// # Panics
+    /// Panics if a global value in the module cannot be copied, see [`Snapshot::try_new`].
+    pub fn new(module: &Module) -> Self {
+        or_panic(Self::try_new(module))
+    }
+
+    /// Copies the name, target, and global values of a module, returning an error if a global value cannot be copied, see
+    /// [`Fragment::try_add_global_value`].
+    pub fn try_new(module: &Module) -> Result<Self, CopyError> {
+        let mut fragment = Fragment::new();
+        for value in module.global_values() {
+            fragment.try_add_global_value(value)?;
+        }
+
+        Ok(Self {
+            name: Identifier::from(module.name()),
+            target: module.target().clone(),
+            opaque_pointers: module.get_opaque_pointers(),
+            fragment,
+        })
+    }
+
+    /// Gets the name of the copied module.
+    pub fn name(&self) -> &Id {
+        self.name.as_id()
+    }
+
+    /// Gets the target of the copied module.
+    pub fn target(&self) -> &Target {
+        &self.target
+    }
+
+    /// Creates a module containing copies of the global values in this snapshot, targeting the target stored in this
+    /// snapshot.
+    ///
+    /// Global variables are added to the module before functions, which are added before indirect functions, so the
+    /// order of global values may differ from the original module.
+    ///
+    /// # Errors
+    /// Returns an error if the copied global values conflict with each other, or refer to global values, registers, or
+    /// blocks that do not exist, which can only occur if the snapshot was not created from a module, such as when it was
+    /// deserialized from modified data.
+    pub fn to_module(&self) -> Result<Module<'_>, MergeError> {
+        let mut module = Module::new(self.name.clone(), &self.target);
+        module.set_opaque_pointers(self.opaque_pointers);
+        module.merge(self.fragment.clone())?;
+        Ok(module)
+    }
+}
+
+/// Gets the function with the specified name, declaring it in the module if it does not exist.
+fn get_or_declare_function(
+    module: &mut Module,
+    globals: &mut HashMap<String, global::Value>,
+    name: &Identifier,
+    signature: &Signature,
+) -> Rc<global::Function> {
+    let global = globals.entry(name.to_string()).or_insert_with(|| {
+        let function = global::Function::new(module.intern(name.as_id()), signature.attach());
+        module.add_global_value(function.clone());
+        global::Value::Function(function)
+    });
+
+    match global {
+        global::Value::Function(function) => function.clone(),
+        global::Value::Variable(_) | global::Value::IFunc(_) => {
+            unreachable!("validation should detect type mismatches")
+        }
+    }
+}
+
+/// Declares a global variable without an initializer in the module.
+fn declare_variable(
+    module: &mut Module,
+    globals: &mut HashMap<String, global::Value>,
+    name: &Identifier,
+    value_type: &Type,
+    constant: bool,
+) -> Rc<global::Variable> {
+    let variable =
+        global::Variable::new(module.intern(name.as_id()), value_type.attach(), constant);
+    module.add_global_value(variable.clone());
+    globals.insert(name.to_string(), global::Value::Variable(variable.clone()));
+    variable
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::{FeatureSet, Layout, Machine, Triple};
+
+    fn target() -> Target {
+        Target::new(
+            Machine::with_defaults(
+                Triple::default(),
+                Identifier::sanitized("generic"),
+                FeatureSet::new(),
+            ),
+            Layout::default(),
+        )
+    }
+
+    /// Creates a function `@f` containing an unnamed entry block that branches to another unnamed block, returning the
+    /// address of the second block.
+    fn function_with_address() -> (Rc<global::Function>, value::Value) {
+        let function = global::Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::Void, Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        let target = BasicBlock::new();
+        entry.br(&target);
+        target.ret(None);
+        function.append_basic_block(entry);
+        function.append_basic_block(target.clone());
+        let address = value::Value::BlockAddress(value::BlockAddress::new(&function, &target));
+        (function, address)
+    }
+
+    #[test]
+    fn block_address_in_global_initializer_round_trips() {
+        let target = target();
+        let mut module = Module::new(Identifier::sanitized("m"), &target);
+        let (function, address) = function_with_address();
+        let table = global::Variable::new(crate::id!("table"), address.value_type(), true);
+        table.set_initializer(Some(address));
+        module.add_global_value(table);
+        module.add_global_value(function);
+
+        let snapshot = Snapshot::new(&module);
+        let copy = snapshot.to_module().unwrap();
+        assert_eq!(copy.to_string(), module.to_string());
+    }
+
+    /// Creates a function `@g` that returns the address of a block in `@f`.
+    fn function_using_address() -> (Rc<global::Function>, Rc<global::Function>) {
+        let (function, address) = function_with_address();
+        let other = global::Function::new(
+            crate::id!("g"),
+            types::Function::new(types::Return::FirstClass(address.value_type()), Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        entry.ret(Some(address));
+        other.append_basic_block(entry);
+        (function, other)
+    }
+
+    #[test]
+    fn block_address_in_other_function_round_trips() {
+        let target = target();
+        let mut module = Module::new(Identifier::sanitized("m"), &target);
+        let (function, other) = function_using_address();
+        module.add_global_value(other);
+        module.add_global_value(function);
+
+        let snapshot = Snapshot::new(&module);
+        let copy = snapshot.to_module().unwrap();
+        assert_eq!(copy.to_string(), module.to_string());
+        assert!(copy.to_string().contains("blockaddress(@f, %1)"));
+    }
+
+    #[test]
+    fn address_of_removed_block_is_error() {
+        let (function, address) = function_with_address();
+        let table = global::Variable::new(crate::id!("table"), address.value_type(), true);
+        table.set_initializer(Some(address));
+        let mut is_entry = true;
+        function.retain_basic_blocks(|_| std::mem::take(&mut is_entry));
+
+        let mut fragment = Fragment::new();
+        assert!(matches!(
+            fragment.try_add_variable(&table),
+            Err(CopyError::InvalidBlockAddress(_))
+        ));
+        assert!(fragment.is_empty());
+    }
+
+    #[test]
+    fn address_of_block_in_undefined_function_is_error() {
+        let target = target();
+        let mut module = Module::new(Identifier::sanitized("m"), &target);
+        let (_function, other) = function_using_address();
+        let mut fragment = Fragment::new();
+        fragment.add_function(&other);
+
+        assert!(matches!(
+            module.merge(fragment),
+            Err(MergeError::UndefinedBlock(name, 1)) if name.as_str() == "f"
+        ));
+        assert!(module.global_values().is_empty());
+    }
+
+    #[test]
+    fn invalid_references_in_snapshot_are_errors() {
+        let target = target();
+        let mut module = Module::new(Identifier::sanitized("m"), &target);
+        let (function, other) = function_using_address();
+        let address = value::Value::GlobalAddress(value::GlobalAddress::new(
+            &global::Value::Function(function.clone()),
+        ));
+        let table = global::Variable::new(crate::id!("table"), address.value_type(), true);
+        table.set_initializer(Some(address));
+        module.add_global_value(table);
+        module.add_global_value(other);
+        module.add_global_value(function);
+        let snapshot = Snapshot::new(&module);
+
+        let mut copy = snapshot.clone();
+        copy.fragment.variables[0].initializer =
+            Some(Value::GlobalAddress(Identifier::sanitized("h")));
+        assert!(matches!(
+            copy.to_module(),
+            Err(MergeError::UndeclaredGlobal(_))
+        ));
+
+        let mut copy = snapshot.clone();
+        copy.fragment.variables[0].initializer = Some(Value::Register(0));
+        assert!(matches!(
+            copy.to_module(),
+            Err(MergeError::InvalidReference(_))
+        ));
+
+        let mut copy = snapshot.clone();
+        copy.fragment.functions[0].blocks[0].instructions[0].0 = Instruction::Br(1);
+        assert!(matches!(
+            copy.to_module(),
+            Err(MergeError::InvalidReference(_))
+        ));
+
+        let mut copy = snapshot.clone();
+        copy.fragment.functions[0].blocks[0].instructions[0].0 =
+            Instruction::Ret(Some(Value::BlockAddress(Identifier::sanitized("f"), 2)));
+        assert!(matches!(
+            copy.to_module(),
+            Err(MergeError::UndefinedBlock(_, 2))
+        ));
+
+        assert!(snapshot.to_module().is_ok());
+    }
+}
@@ -0,0 +1,266 @@
+//! Contains the [`llvm_ir!`](crate::llvm_ir) macro, used to build functions with a syntax resembling LLVM's textual
+//! format.
+
+/// Creates a [`global::Function`](crate::global::Function) from a definition written in a syntax resembling LLVM's textual
+/// format, expanding into calls to the [`BasicBlock`](crate::BasicBlock) instruction building methods.
+///
+/// The contents of each basic block are enclosed in braces, and each instruction ends with a semicolon. Registers defined
+/// by instructions are bound to local variables with the same name, keep their names when the function is printed, and
+/// can be used by instructions in later blocks. The incoming values of `phi` instructions are added once every block has
+/// been built, so they can also refer to registers defined later, such as in the body of a loop.
+/// Constants and other values are written as Rust expressions enclosed in braces, and types can also be Rust expressions
+/// evaluating to an `Rc<types::FirstClass>`. The function itself is bound to a variable with the same name, allowing
+/// recursive calls.
+///
+/// ```
+/// use llvm_model::{llvm_ir, types, value};
+///
+/// let max = llvm_ir! {
+///     define i32 @max(i32 %a, i32 %b) {
+///         entry: {
+///             %greater = icmp sgt i32 %a, %b;
+///             br i1 %greater, label %left, label %right;
+///         }
+///         left: { ret i32 %a; }
+///         right: { ret i32 %b; }
+///     }
+/// };
+///
+/// assert_eq!(max.basic_blocks().len(), 3);
+/// assert!(max.to_string().contains("icmp sgt i32 %a, %b"));
+///
+/// let answer = llvm_ir! {
+///     define i32 @answer() {
+///         entry: { ret i32 {value::Integer::from_u64(types::IntegerSize::I32, 42)}; }
+///     }
+/// };
+///
+/// assert!(answer.to_string().contains("ret i32 42"));
+///
+/// let zero = value::Integer::from_u64(types::IntegerSize::I32, 0);
+/// let one = value::Integer::from_u64(types::IntegerSize::I32, 1);
+/// let count = llvm_ir! {
+///     define i32 @count(i32 %n) {
+///         entry: { br label %header; }
+///         header: {
+///             %i = phi i32 [{zero.clone()}, %entry], [%next, %header];
+///             %next = add i32 %i, {one.clone()};
+///             %done = icmp eq i32 %next, %n;
+///             br i1 %done, label %exit, label %header;
+///         }
+///         exit: { ret i32 %next; }
+///     }
+/// };
+///
+/// assert!(count.to_string().contains("%i = phi i32 [ 0, %entry ], [ %next, %header ]"));
+/// ```
+///
+/// The supported instructions are `ret`, `br`, `phi`, `icmp`, `call`, `fneg`, and the binary operations such as `add` and
+/// `mul`. Calls can be marked with `tail`, `musttail`, or `notail`. Named parameters are bound to local variables in the
+/// same way as registers, and keep their names when the function is printed.
+///
+/// # Panics
+/// Panics if any of the instruction building methods panic, such as when operands are of the wrong type.
+#[macro_export]
+macro_rules! llvm_ir {
+    (define $return_type: tt @ $name: ident ( $($parameter_type: tt $(% $parameter_name: ident)?),* $(,)? ) {
+        $($label: ident : { $($body: tt)* })*
+    }) => {{
+        let $name = $crate::global::Function::new(
+            $crate::Identifier::sanitized(stringify!($name)),
+            $crate::types::Function::new(
+                $crate::llvm_ir!(@return $return_type),
+                std::vec![$($crate::llvm_ir!(@type $parameter_type)),*],
+            ),
+        );
+
+        let mut parameters = $name.parameters().iter();
+        $(
+            let parameter = parameters.next().expect("parameter should be in signature");
+            $(
+                parameter.set_name(Some($crate::Identifier::sanitized(stringify!($parameter_name))));
+                let $parameter_name = parameter.clone();
+            )?
+        )*
+
+        $(
+            let $label = $crate::BasicBlock::with_name($crate::Identifier::sanitized(stringify!($label)));
+            $name.append_basic_block($label.clone());
+        )*
+
+        $($crate::llvm_ir!(@instructions $label; $($body)*);)*
+        $($crate::llvm_ir!(@incoming $label; $($body)*);)*
+
+        $name
+    }};
+
+    (@return void) => {
+        $crate::types::Return::Void
+    };
+    (@return $return_type: tt) => {
+        $crate::types::Return::FirstClass($crate::llvm_ir!(@type $return_type))
+    };
+
+    (@type i1) => { $crate::llvm_ir!(@integer MIN) };
+    (@type i8) => { $crate::llvm_ir!(@integer SIZE_8) };
+    (@type i16) => { $crate::llvm_ir!(@integer SIZE_16) };
+    (@type i32) => { $crate::llvm_ir!(@integer SIZE_32) };
+    (@type i64) => { $crate::llvm_ir!(@integer SIZE_64) };
+    (@type half) => { $crate::llvm_ir!(@float Half) };
+    (@type float) => { $crate::llvm_ir!(@float Float) };
+    (@type double) => { $crate::llvm_ir!(@float Double) };
+    (@type { $type_expression: expr }) => {
+        $type_expression
+    };
+
+    (@integer $size: ident) => {
+        std::rc::Rc::new($crate::types::FirstClass::Single($crate::types::SingleValue::Integer(
+            $crate::types::IntegerSize::$size,
+        )))
+    };
+    (@float $float: ident) => {
+        std::rc::Rc::new($crate::types::FirstClass::Single($crate::types::SingleValue::Float(
+            $crate::types::Float::$float,
+        )))
+    };
+
+    (@value % $register: ident) => {
+        $crate::value::Value::from($register.clone())
+    };
+    (@value { $value: expr }) => {
+        $crate::value::Value::from($value)
+    };
+
+    (@icmp eq) => { $crate::block::IcmpPredicate::Equal };
+    (@icmp ne) => { $crate::block::IcmpPredicate::NotEqual };
+    (@icmp ugt) => { $crate::block::IcmpPredicate::UnsignedGreaterThan };
+    (@icmp uge) => { $crate::block::IcmpPredicate::UnsignedGreaterOrEqual };
+    (@icmp ult) => { $crate::block::IcmpPredicate::UnsignedLessThan };
+    (@icmp ule) => { $crate::block::IcmpPredicate::UnsignedLessOrEqual };
+    (@icmp sgt) => { $crate::block::IcmpPredicate::SignedGreaterThan };
+    (@icmp sge) => { $crate::block::IcmpPredicate::SignedGreaterOrEqual };
+    (@icmp slt) => { $crate::block::IcmpPredicate::SignedLessThan };
+    (@icmp sle) => { $crate::block::IcmpPredicate::SignedLessOrEqual };
+
+    (@name $register: ident) => {
+        $register.set_name(Some($crate::Identifier::sanitized(stringify!($register))));
+    };
+
+    (@tail tail) => { $crate::block::TailCallKind::Tail };
+    (@tail musttail) => { $crate::block::TailCallKind::MustTail };
+    (@tail notail) => { $crate::block::TailCallKind::NoTail };
+
+    (@incoming $block: ident;
+        % $result: ident = phi $result_type: tt $([ $value: tt $($value_name: ident)?, % $incoming: ident ]),*; $($rest: tt)*
+    ) => {
+        $($block.add_phi_incoming(&$result, $crate::llvm_ir!(@value $value $($value_name)?), &$incoming);)*
+        $crate::llvm_ir!(@incoming $block; $($rest)*);
+    };
+    (@incoming $block: ident; $($rest: tt)*) => {};
+
+    (@instructions $block: ident;) => {};
+    (@instructions $block: ident; ret void; $($rest: tt)*) => {
+        $block.ret(None);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident; ret $value_type: tt $value: tt $($value_name: ident)?; $($rest: tt)*) => {
+        $block.ret(Some($crate::llvm_ir!(@value $value $($value_name)?)));
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident; br label % $target: ident; $($rest: tt)*) => {
+        $block.br(&$target);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        br $condition_type: tt $condition: tt $($condition_name: ident)?,
+        label % $true_target: ident, label % $false_target: ident; $($rest: tt)*
+    ) => {
+        $block.br_if(
+            $crate::llvm_ir!(@value $condition $($condition_name)?),
+            &$true_target,
+            &$false_target,
+        );
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = phi $result_type: tt $([ $value: tt $($value_name: ident)?, % $incoming: ident ]),*; $($rest: tt)*
+    ) => {
+        let $result = $block.phi($crate::llvm_ir!(@type $result_type), std::vec::Vec::new());
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = icmp $predicate: ident $operand_type: tt
+        $x: tt $($x_name: ident)?, $y: tt $($y_name: ident)?; $($rest: tt)*
+    ) => {
+        let $result = $block.icmp(
+            $crate::llvm_ir!(@icmp $predicate),
+            $crate::llvm_ir!(@value $x $($x_name)?),
+            $crate::llvm_ir!(@value $y $($y_name)?),
+        );
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = call $return_type: tt @ $callee: ident
+        ( $($argument_type: tt $argument: tt $($argument_name: ident)?),* ); $($rest: tt)*
+    ) => {
+        let $result = $block
+            .call(&$callee, std::vec![$($crate::llvm_ir!(@value $argument $($argument_name)?)),*])
+            .expect("callee should not return void");
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        call $return_type: tt @ $callee: ident
+        ( $($argument_type: tt $argument: tt $($argument_name: ident)?),* ); $($rest: tt)*
+    ) => {
+        $block.call(&$callee, std::vec![$($crate::llvm_ir!(@value $argument $($argument_name)?)),*]);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = $tail_call: ident call $return_type: tt @ $callee: ident
+        ( $($argument_type: tt $argument: tt $($argument_name: ident)?),* ); $($rest: tt)*
+    ) => {
+        let $result = $block
+            .call_with(
+                &$callee,
+                std::vec![$($crate::llvm_ir!(@value $argument $($argument_name)?)),*],
+                $callee.get_calling_convention(),
+                $crate::llvm_ir!(@tail $tail_call),
+            )
+            .expect("callee should not return void");
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        $tail_call: ident call $return_type: tt @ $callee: ident
+        ( $($argument_type: tt $argument: tt $($argument_name: ident)?),* ); $($rest: tt)*
+    ) => {
+        $block.call_with(
+            &$callee,
+            std::vec![$($crate::llvm_ir!(@value $argument $($argument_name)?)),*],
+            $callee.get_calling_convention(),
+            $crate::llvm_ir!(@tail $tail_call),
+        );
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = fneg $operand_type: tt $operand: tt $($operand_name: ident)?; $($rest: tt)*
+    ) => {
+        let $result = $block.fneg($crate::llvm_ir!(@value $operand $($operand_name)?));
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+    (@instructions $block: ident;
+        % $result: ident = $operation: ident $operand_type: tt
+        $x: tt $($x_name: ident)?, $y: tt $($y_name: ident)?; $($rest: tt)*
+    ) => {
+        let $result = $block.$operation(
+            $crate::llvm_ir!(@value $x $($x_name)?),
+            $crate::llvm_ir!(@value $y $($y_name)?),
+        );
+        $crate::llvm_ir!(@name $result);
+        $crate::llvm_ir!(@instructions $block; $($rest)*);
+    };
+}
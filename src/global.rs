@@ -1,15 +1,22 @@
-//! Modules consist of global values, which are global variables or function definitions.
+//! Modules consist of global values, which are global variables, function definitions, or indirect functions.
 
 use crate::block::BasicBlock;
+use crate::identifier::Quoted;
+use crate::target::layout::Alignment;
 use crate::types;
-use crate::{Id, Identifier};
+use crate::value;
+use crate::{Id, Identifier, Symbol};
 use std::cell::RefCell;
+use std::collections::HashSet;
 use std::fmt::{Debug, Display, Formatter, Write as _};
-use std::rc::Rc;
+use std::rc::{Rc, Weak};
 
-// TODO: Split linkage types into those that are valid for global variables, functions, and both.
 /// Describes how global variables or functions are linked.
+///
+/// Some linkage types are only valid for certain kinds of global values, which is checked when the linkage is set, see
+/// [`LinkageError`].
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Linkage {
     /// Accessible only to the current module, and renames any symbols "as necessary to avoid collisions".
     Private,
@@ -55,10 +62,57 @@ impl Display for Linkage {
     }
 }
 
+/// Error used when a global value is given a linkage type that it can never have, such as a function with `common`
+/// linkage.
+///
+/// Linkage types that depend on whether a global value is a definition or a declaration are instead checked by the
+/// [`verify`](crate::verify) module.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("@{global} cannot have {linkage} linkage, since {reason}")]
+pub struct LinkageError {
+    global: Identifier,
+    linkage: Linkage,
+    reason: &'static str,
+}
+
+impl LinkageError {
+    fn check(global: &Id, linkage: Linkage, reason: Option<&'static str>) -> Result<(), Self> {
+        match reason {
+            Some(reason) => Err(Self {
+                global: global.to_owned(),
+                linkage,
+                reason,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Gets the name of the global value.
+    pub fn global(&self) -> &Id {
+        self.global.as_id()
+    }
+
+    /// Gets the linkage type that is not allowed.
+    pub fn linkage(&self) -> Linkage {
+        self.linkage
+    }
+
+    /// Explains why the linkage type is not allowed.
+    pub fn reason(&self) -> &'static str {
+        self.reason
+    }
+}
+
+/// Panics with the message of an error, used by the methods that do not return a [`Result`].
+fn or_panic<T>(result: Result<T, LinkageError>) -> T {
+    result.unwrap_or_else(|error| panic!("{}", error))
+}
+
 /// Well-known calling conventions used by functions.
 ///
 /// See [the latest LLVM documentation on calling conventions here](https://llvm.org/docs/LangRef.html#callingconv).
 #[derive(Copy, Clone, Debug, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[non_exhaustive]
 pub enum CallingConvention {
     /// The target platform's C calling conventions.
@@ -140,19 +194,193 @@ impl Display for CallingConvention {
     }
 }
 
+/// Describes whether a global variable or function is visible to other modules when linked into a shared library or
+/// executable.
+///
+/// See [the latest LLVM documentation on visibility styles here](https://llvm.org/docs/LangRef.html#visibility-styles).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Visibility {
+    /// The global is visible to other modules, and "may be overridden by another module".
+    Default,
+    /// The global is not placed in the dynamic symbol table, "so no other module (executable or shared library) can
+    /// reference it directly".
+    Hidden,
+    /// The global is visible to other modules, but "cannot be overridden by another module".
+    Protected,
+}
+
+crate::enum_default!(Visibility, Default);
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Default => "default",
+            Self::Hidden => "hidden",
+            Self::Protected => "protected",
+        })
+    }
+}
+
+/// Indicates that the address of a global variable or function is not significant, allowing it to be merged with other
+/// globals that have the same contents.
+///
+/// See [the latest LLVM documentation on global variables here](https://llvm.org/docs/LangRef.html#global-variables).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnnamedAddress {
+    /// The address is not significant within the module (`local_unnamed_addr`), but may be significant to other modules.
+    Local,
+    /// The address is not significant at all (`unnamed_addr`).
+    Global,
+}
+
+impl Display for UnnamedAddress {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Local => "local_unnamed_addr",
+            Self::Global => "unnamed_addr",
+        })
+    }
+}
+
+/// Indicates how the linker chooses between sections in different object files that belong to comdats with the same name.
+///
+/// See [the latest LLVM documentation on comdats here](https://llvm.org/docs/LangRef.html#comdats).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComdatSelectionKind {
+    /// "The linker may choose any COMDAT key, the choice is arbitrary".
+    Any,
+    /// "The linker may choose any COMDAT key but the sections must contain the same data".
+    ExactMatch,
+    /// "The linker will choose the section containing the largest COMDAT key".
+    Largest,
+    /// "No deduplication is performed".
+    NoDeduplicate,
+    /// "The linker may choose any COMDAT key but the sections must contain the same amount of data".
+    SameSize,
+}
+
+crate::enum_default!(ComdatSelectionKind, Any);
+
+impl Display for ComdatSelectionKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Any => "any",
+            Self::ExactMatch => "exactmatch",
+            Self::Largest => "largest",
+            Self::NoDeduplicate => "nodeduplicate",
+            Self::SameSize => "samesize",
+        })
+    }
+}
+
+/// A comdat, which groups global values that are kept or discarded together by the linker.
+///
+/// Global values in a module that refer to comdats with the same name should use the same selection kind.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Comdat {
+    name: Symbol,
+    selection_kind: ComdatSelectionKind,
+}
+
+impl Comdat {
+    /// Creates a comdat with the specified name and selection kind.
+    pub fn new(name: impl Into<Symbol>, selection_kind: ComdatSelectionKind) -> Self {
+        Self {
+            name: name.into(),
+            selection_kind,
+        }
+    }
+
+    /// Gets the name of this comdat.
+    pub fn name(&self) -> &Id {
+        self.name.as_id()
+    }
+
+    /// Gets the symbol containing the name of this comdat.
+    pub fn symbol(&self) -> &Symbol {
+        &self.name
+    }
+
+    /// Gets a value indicating how the linker chooses between comdats with the same name.
+    pub fn selection_kind(&self) -> ComdatSelectionKind {
+        self.selection_kind
+    }
+}
+
+impl Display for Comdat {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "${} = comdat {}", self.name(), self.selection_kind)
+    }
+}
+
+/// Writes the section, comdat, and alignment of a global value, with each preceded by the separator.
+fn write_placement(
+    f: &mut Formatter,
+    separator: &str,
+    section: Option<&Identifier>,
+    comdat: Option<&Comdat>,
+    alignment: Option<Alignment>,
+) -> std::fmt::Result {
+    if let Some(section) = section {
+        write!(f, "{}section {}", separator, Quoted(section.as_id()))?;
+    }
+    if let Some(comdat) = comdat {
+        write!(f, "{}comdat(${})", separator, comdat.name())?;
+    }
+    if let Some(alignment) = alignment {
+        write!(f, "{}align {}", separator, alignment)?;
+    }
+    Ok(())
+}
+
 #[derive(Default)]
 struct FunctionInformation {
     linkage: Linkage,
+    visibility: Visibility,
+    dso_local: bool,
+    unnamed_address: Option<UnnamedAddress>,
     calling_convention: CallingConvention,
+    section: Option<Identifier>,
+    comdat: Option<Comdat>,
+    alignment: Option<Alignment>,
     basic_blocks: Vec<Rc<BasicBlock>>,
+    block_labels: HashSet<String>,
+}
+
+/// An error indicating that a basic block in a function is malformed.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BlockError {
+    /// A block that does not end with a terminator instruction.
+    #[error("block %{label} does not end with a terminator instruction")]
+    Unterminated {
+        /// The label of the block.
+        label: Identifier,
+    },
+    /// A block containing instructions after its terminator instruction, which can never be executed.
+    #[error("block %{label} contains {count} unreachable instruction(s) after the terminator at index {terminator_index}")]
+    UnreachableInstructions {
+        /// The label of the block.
+        label: Identifier,
+        /// The index of the first terminator instruction in the block.
+        terminator_index: usize,
+        /// The number of instructions after the terminator instruction.
+        count: usize,
+    },
 }
 
 /// A function definition or declaration.
 ///
 /// See [the latest LLVM documentation on functions here](https://llvm.org/docs/LangRef.html#functions).
 pub struct Function {
-    name: Identifier,
+    /// A reference to this function, given to the blocks that are appended to it.
+    this: Weak<Function>,
+    name: Symbol,
     signature: Rc<types::Function>,
+    parameters: Box<[value::Register]>,
     information: RefCell<FunctionInformation>,
     // TODO: Move Copy fields here, since it is faster and UnsafeCell/Cell has no memory space overhead.
     //calling_convention: Cell<CallingConvention>,
@@ -160,11 +388,21 @@ pub struct Function {
 }
 
 impl Function {
-    /// Creates a new function.
-    pub fn new(name: Identifier, signature: impl Into<Rc<types::Function>>) -> Rc<Self> {
-        Rc::new(Self {
-            name,
-            signature: signature.into(),
+    /// Creates a new function, with a register for each of the parameters in its signature.
+    pub fn new(name: impl Into<Symbol>, signature: impl Into<Rc<types::Function>>) -> Rc<Self> {
+        let signature = signature.into();
+        Rc::new_cyclic(|this| Self {
+            this: this.clone(),
+            name: name.into(),
+            parameters: signature
+                .parameter_types()
+                .iter()
+                .enumerate()
+                .map(|(index, parameter_type)| {
+                    value::Register::parameter(parameter_type.clone(), this.clone(), index)
+                })
+                .collect(),
+            signature,
             information: RefCell::default(),
         })
     }
@@ -174,19 +412,107 @@ impl Function {
         self.name.as_id()
     }
 
+    /// Gets the symbol containing the name of this function.
+    pub fn symbol(&self) -> &Symbol {
+        &self.name
+    }
+
     /// Gets the signature of this function.
     pub fn signature(&self) -> &Rc<types::Function> {
         &self.signature
     }
 
+    /// Gets the registers containing the values of the parameters of this function, which can be used as operands by the
+    /// instructions in its basic blocks.
+    ///
+    /// Parameters are printed as numbers unless they are given a name with [`Register::set_name`](value::Register::set_name),
+    /// in which case they are printed as `%name`.
+    pub fn parameters(&self) -> &[value::Register] {
+        &self.parameters
+    }
+
+    /// Sets the names of the parameters of this function, where a `None` leaves a parameter unnamed.
+    ///
+    /// # Panics
+    /// Panics if more names are provided than there are parameters.
+    pub fn set_parameter_names<N: IntoIterator<Item = Option<Identifier>>>(&self, names: N) {
+        let mut parameters = self.parameters.iter();
+        for name in names {
+            match parameters.next() {
+                Some(parameter) => parameter.set_name(name),
+                None => panic!(
+                    "@{} only has {} parameter(s)",
+                    self.name(),
+                    self.parameters.len()
+                ),
+            }
+        }
+    }
+
     /// Gets the linkage type for this function.
     pub fn get_linkage(&self) -> Linkage {
         self.information.borrow().linkage
     }
 
     /// Sets the linkage type for this function.
+    ///
+    /// # Panics
+    /// Panics if the linkage type is not allowed for functions, see [`Function::try_set_linkage`].
     pub fn set_linkage(&self, linkage: Linkage) {
+        or_panic(self.try_set_linkage(linkage))
+    }
+
+    /// Sets the linkage type for this function, returning an error if functions cannot have `common` or `appending`
+    /// linkage.
+    pub fn try_set_linkage(&self, linkage: Linkage) -> Result<(), LinkageError> {
+        LinkageError::check(
+            self.name(),
+            linkage,
+            match linkage {
+                Linkage::Common => Some("functions may not have common linkage"),
+                Linkage::Appending => {
+                    Some("appending linkage is only allowed on global variables of array type")
+                }
+                _ => None,
+            },
+        )?;
+
         self.information.borrow_mut().linkage = linkage;
+        Ok(())
+    }
+
+    /// Gets the visibility of this function.
+    pub fn get_visibility(&self) -> Visibility {
+        self.information.borrow().visibility
+    }
+
+    /// Sets the visibility of this function.
+    ///
+    /// Functions with `private` or `internal` linkage must have the default visibility, which is checked by
+    /// [`verify_function`](crate::verify::verify_function).
+    pub fn set_visibility(&self, visibility: Visibility) {
+        self.information.borrow_mut().visibility = visibility;
+    }
+
+    /// Gets a value indicating whether this function is known to resolve to a definition in the same linkage unit
+    /// (`dso_local`).
+    pub fn get_dso_local(&self) -> bool {
+        self.information.borrow().dso_local
+    }
+
+    /// Sets a value indicating whether this function is known to resolve to a definition in the same linkage unit.
+    pub fn set_dso_local(&self, dso_local: bool) {
+        self.information.borrow_mut().dso_local = dso_local;
+    }
+
+    /// Gets a value indicating whether the address of this function is significant.
+    pub fn get_unnamed_address(&self) -> Option<UnnamedAddress> {
+        self.information.borrow().unnamed_address
+    }
+
+    /// Sets a value indicating whether the address of this function is significant.
+    pub fn set_unnamed_address(&self, unnamed_address: Option<UnnamedAddress>) {
+        self.information.borrow_mut().unnamed_address = unnamed_address;
     }
 
     /// Gets the calling convention of this function.
@@ -199,15 +525,219 @@ impl Function {
         self.information.borrow_mut().calling_convention = calling_convention;
     }
 
-    /// Appends a basic block.
+    /// Gets the name of the section that this function is placed in, or `None` if the default section is used.
+    pub fn get_section(&self) -> Option<Identifier> {
+        self.information.borrow().section.clone()
+    }
+
+    /// Sets the name of the section that this function is placed in, such as `.init`.
+    pub fn set_section(&self, section: Option<Identifier>) {
+        self.information.borrow_mut().section = section;
+    }
+
+    /// Gets the comdat that this function belongs to.
+    pub fn get_comdat(&self) -> Option<Comdat> {
+        self.information.borrow().comdat.clone()
+    }
+
+    /// Sets the comdat that this function belongs to.
+    pub fn set_comdat(&self, comdat: Option<Comdat>) {
+        self.information.borrow_mut().comdat = comdat;
+    }
+
+    /// Gets the alignment of the code of this function.
+    pub fn get_alignment(&self) -> Option<Alignment> {
+        self.information.borrow().alignment
+    }
+
+    /// Sets the alignment of the code of this function.
+    pub fn set_alignment(&self, alignment: Option<Alignment>) {
+        self.information.borrow_mut().alignment = alignment;
+    }
+
+    /// Appends a basic block, assigning it a label that is unique within this function.
+    ///
+    /// Blocks without a name are labeled based on their position within the function.
+    ///
+    /// # Panics
+    /// Panics if the block was already appended to a function, or if the block uses registers defined in another
+    /// function.
     pub fn append_basic_block(&self, basic_block: Rc<BasicBlock>) {
-        self.information.borrow_mut().basic_blocks.push(basic_block)
+        for instruction in basic_block.instructions().iter() {
+            for operand in instruction.operands() {
+                if let value::Value::Register(register) = operand {
+                    match register.function() {
+                        Some(other) if !std::ptr::eq(Rc::as_ptr(&other), self) => panic!(
+                            "{} is defined in @{}, and cannot be used in @{}",
+                            register,
+                            other.name(),
+                            self.name()
+                        ),
+                        _ => (),
+                    }
+                }
+            }
+        }
+
+        let mut information = self.information.borrow_mut();
+        let base_label = match basic_block.name() {
+            Some(name) => name.to_string(),
+            None => format!("B{}", information.basic_blocks.len()),
+        };
+
+        let mut label = base_label.clone();
+        let mut suffix = 0usize;
+        while information.block_labels.contains(&label) {
+            suffix += 1;
+            label = format!("{}.{}", base_label, suffix);
+        }
+
+        basic_block.set_label(unsafe {
+            // Safety: Block names are identifiers, and the suffixes do not contain null bytes.
+            Identifier::new_unchecked(label.clone())
+        });
+
+        basic_block.set_function(self.this.clone());
+        information.block_labels.insert(label);
+        information.basic_blocks.push(basic_block)
+    }
+
+    /// Gets the basic blocks of this function, in the order that they were appended.
+    pub fn basic_blocks(&self) -> std::cell::Ref<'_, [Rc<BasicBlock>]> {
+        std::cell::Ref::map(self.information.borrow(), |information| {
+            information.basic_blocks.as_slice()
+        })
+    }
+
+    /// Removes the basic blocks for which the predicate returns `false`, freeing their labels for use by other blocks.
+    pub(crate) fn retain_basic_blocks<P: FnMut(&Rc<BasicBlock>) -> bool>(&self, mut predicate: P) {
+        let mut information = self.information.borrow_mut();
+        let FunctionInformation {
+            basic_blocks,
+            block_labels,
+            ..
+        } = &mut *information;
+
+        basic_blocks.retain(|block| {
+            let retained = predicate(block);
+            if !retained {
+                if let Some(label) = block.label() {
+                    block_labels.remove(label.as_str());
+                }
+            }
+            retained
+        });
+    }
+
+    /// Checks that every basic block in this function ends with a single terminator instruction, returning all of the
+    /// malformed blocks.
+    pub fn validate_blocks(&self) -> Result<(), Vec<BlockError>> {
+        let mut errors = Vec::new();
+
+        for block in self.basic_blocks().iter() {
+            let label = block
+                .label()
+                .expect("blocks in a function should have a label");
+            let instructions = block.instructions();
+
+            match instructions
+                .iter()
+                .position(|instruction| instruction.is_terminator())
+            {
+                Some(terminator_index) if terminator_index + 1 < instructions.len() => {
+                    errors.push(BlockError::UnreachableInstructions {
+                        label,
+                        terminator_index,
+                        count: instructions.len() - terminator_index - 1,
+                    })
+                }
+                Some(_) => (),
+                None => errors.push(BlockError::Unterminated { label }),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Gets the predecessors of each basic block in this function, which are the blocks whose terminators can transfer
+    /// control flow to it. The predecessors of a block are at the same index as the block in
+    /// [`basic_blocks`](Function::basic_blocks).
+    ///
+    /// Each predecessor is included once, even if its terminator has several edges to the same block. Use
+    /// [`predecessor_edges_of`](Function::predecessor_edges_of) to get one entry for each edge instead.
+    pub fn predecessors(&self) -> Vec<Vec<Rc<BasicBlock>>> {
+        let basic_blocks = self.basic_blocks();
+        let mut predecessors = vec![Vec::new(); basic_blocks.len()];
+
+        for block in basic_blocks.iter() {
+            for successor in block.successors() {
+                if let Some(index) = basic_blocks
+                    .iter()
+                    .position(|existing| Rc::ptr_eq(existing, &successor))
+                {
+                    predecessors[index].push(block.clone());
+                }
+            }
+        }
+
+        predecessors
+    }
+
+    /// Gets the predecessors of the specified block in this function, with each predecessor included once.
+    ///
+    /// # Panics
+    /// Panics if the block is not contained in this function.
+    pub fn predecessors_of(&self, block: &Rc<BasicBlock>) -> Vec<Rc<BasicBlock>> {
+        self.check_contains_block(block);
+        self.basic_blocks()
+            .iter()
+            .filter(|predecessor| {
+                predecessor
+                    .successors()
+                    .iter()
+                    .any(|successor| Rc::ptr_eq(successor, block))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Gets the predecessors of the specified block in this function, with one entry for each edge in the control flow
+    /// graph.
+    ///
+    /// A predecessor whose terminator targets the block several times, such as a `switch` with several cases targeting the
+    /// block, is included once for each target. A `phi` instruction in the block needs one incoming value for each entry.
+    ///
+    /// # Panics
+    /// Panics if the block is not contained in this function.
+    pub fn predecessor_edges_of(&self, block: &Rc<BasicBlock>) -> Vec<Rc<BasicBlock>> {
+        self.check_contains_block(block);
+        let mut predecessors = Vec::new();
+        for predecessor in self.basic_blocks().iter() {
+            for successor in predecessor.successor_edges() {
+                if Rc::ptr_eq(&successor, block) {
+                    predecessors.push(predecessor.clone());
+                }
+            }
+        }
+        predecessors
     }
 
-    #[cfg(feature = "_internal_deconstructors")]
-    pub(crate) fn take_basic_blocks(&self) -> Vec<Rc<BasicBlock>> {
-        //iter_basic_blocks
-        std::mem::take(&mut self.information.borrow_mut().basic_blocks)
+    fn check_contains_block(&self, block: &Rc<BasicBlock>) {
+        if !self
+            .basic_blocks()
+            .iter()
+            .any(|existing| Rc::ptr_eq(existing, block))
+        {
+            panic!(
+                "block {:?} is not contained in function {}",
+                block.label(),
+                self.name()
+            );
+        }
     }
 }
 
@@ -216,8 +746,15 @@ impl Debug for Function {
         f.debug_struct("Function")
             .field("name", &self.name)
             .field("signature", &self.signature)
+            .field("parameters", &self.parameters)
             .field("linkage", &self.get_linkage())
+            .field("visibility", &self.get_visibility())
+            .field("dso_local", &self.get_dso_local())
+            .field("unnamed_address", &self.get_unnamed_address())
             .field("calling_convention", &self.get_calling_convention())
+            .field("section", &self.get_section())
+            .field("comdat", &self.get_comdat())
+            .field("alignment", &self.get_alignment())
             .field("basic_blocks", &self.information.borrow().basic_blocks)
             .finish()
     }
@@ -225,53 +762,582 @@ impl Debug for Function {
 
 impl Display for Function {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "define {}", self.get_linkage())?;
-        //rtpreemt
-        //visibility
-        //dllst
-        write!(f, " {}", self.get_calling_convention())?;
-        //unnamed_addr
-        write!(f, " {}", self.signature.return_type())?;
-        //attribute of return type
-        write!(f, " @{} (", self.name())?;
-        for (index, parameter_type) in self.signature().parameter_types().iter().enumerate() {
-            if index > 0 {
-                f.write_str(", ")?;
+        // Parameters are numbered along with the registers and blocks, so the numbering also applies to the signature.
+        crate::numbering::Numbering::new(self).scope(|| {
+            // Functions without a body are declarations, whose parameters are not named.
+            let is_declaration = self.information.borrow().basic_blocks.is_empty();
+            f.write_str(if is_declaration { "declare" } else { "define" })?;
+            write!(f, " {}", self.get_linkage())?;
+            if self.get_dso_local() {
+                f.write_str(" dso_local")?;
             }
+            let visibility = self.get_visibility();
+            if visibility != Visibility::Default {
+                write!(f, " {}", visibility)?;
+            }
+            //dllst
+            write!(f, " {}", self.get_calling_convention())?;
+            write!(f, " {}", self.signature.return_type())?;
+            //attribute of return type
+            write!(f, " @{} (", self.name())?;
+            for (index, parameter) in self.parameters.iter().enumerate() {
+                if index > 0 {
+                    f.write_str(", ")?;
+                }
 
-            // parameter attributes
-            Display::fmt(&parameter_type, f)?;
-        }
-        f.write_char(')')?;
-        // other things
-
-        let basic_blocks = &self.information.borrow().basic_blocks;
-        if !basic_blocks.is_empty() {
-            writeln!(f, " {{")?;
-            for block in basic_blocks.iter() {
-                writeln!(f, "{}", block)?;
+                // parameter attributes
+                Display::fmt(parameter.value_type(), f)?;
+                if !is_declaration {
+                    write!(f, " {}", parameter)?;
+                }
+            }
+            if self.signature.is_variadic() {
+                if !self.signature.parameter_types().is_empty() {
+                    f.write_str(", ")?;
+                }
+                f.write_str("...")?;
+            }
+            f.write_char(')')?;
+            if let Some(unnamed_address) = self.get_unnamed_address() {
+                write!(f, " {}", unnamed_address)?;
+            }
+            // other things
+
+            {
+                let information = self.information.borrow();
+                write_placement(
+                    f,
+                    " ",
+                    information.section.as_ref(),
+                    information.comdat.as_ref(),
+                    information.alignment,
+                )?;
+            }
+
+            let basic_blocks = &self.information.borrow().basic_blocks;
+            if !basic_blocks.is_empty() {
+                writeln!(f, " {{")?;
+                for block in basic_blocks.iter() {
+                    writeln!(f, "{}", block)?;
+                }
+                writeln!(f, "}}")?;
+            }
+
+            Ok(())
+        })
+    }
+}
+
+/// The thread-local storage model used by a global variable.
+///
+/// See [the latest LLVM documentation on thread local storage models here](https://llvm.org/docs/LangRef.html#tls-model).
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThreadLocalMode {
+    /// The default model, which is used when a more specific model is not known.
+    GeneralDynamic,
+    /// "For variables that are only used within the current shared library".
+    LocalDynamic,
+    /// "For variables in modules that will not be loaded dynamically".
+    InitialExec,
+    /// "For variables defined in the executable and only used within it".
+    LocalExec,
+}
+
+crate::enum_default!(ThreadLocalMode, GeneralDynamic);
+
+impl Display for ThreadLocalMode {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::GeneralDynamic => "thread_local",
+            Self::LocalDynamic => "thread_local(localdynamic)",
+            Self::InitialExec => "thread_local(initialexec)",
+            Self::LocalExec => "thread_local(localexec)",
+        })
+    }
+}
+
+#[derive(Default)]
+struct VariableInformation {
+    linkage: Linkage,
+    visibility: Visibility,
+    dso_local: bool,
+    unnamed_address: Option<UnnamedAddress>,
+    initializer: Option<value::Value>,
+    section: Option<Identifier>,
+    comdat: Option<Comdat>,
+    alignment: Option<Alignment>,
+    thread_local: Option<ThreadLocalMode>,
+    externally_initialized: bool,
+}
+
+/// A global variable definition or declaration.
+///
+/// See [the latest LLVM documentation on global variables here](https://llvm.org/docs/LangRef.html#global-variables).
+pub struct Variable {
+    name: Symbol,
+    value_type: Rc<types::FirstClass>,
+    constant: bool,
+    information: RefCell<VariableInformation>,
+}
+
+impl Variable {
+    /// Creates a new global variable containing a value of the specified type. If `constant` is `true`, the contents of
+    /// the variable are never modified.
+    pub fn new(
+        name: impl Into<Symbol>,
+        value_type: Rc<types::FirstClass>,
+        constant: bool,
+    ) -> Rc<Self> {
+        Rc::new(Self {
+            name: name.into(),
+            value_type,
+            constant,
+            information: RefCell::default(),
+        })
+    }
+
+    /// Gets the name of this global variable.
+    pub fn name(&self) -> &Id {
+        self.name.as_id()
+    }
+
+    /// Gets the symbol containing the name of this global variable.
+    pub fn symbol(&self) -> &Symbol {
+        &self.name
+    }
+
+    /// Gets the type of the value stored in this global variable.
+    pub fn value_type(&self) -> &Rc<types::FirstClass> {
+        &self.value_type
+    }
+
+    /// Gets a value indicating whether the contents of this global variable are never modified.
+    pub fn is_constant(&self) -> bool {
+        self.constant
+    }
+
+    /// Gets the linkage type for this global variable.
+    pub fn get_linkage(&self) -> Linkage {
+        self.information.borrow().linkage
+    }
+
+    /// Sets the linkage type for this global variable.
+    ///
+    /// # Panics
+    /// Panics if the linkage type is not allowed for this global variable, see [`Variable::try_set_linkage`].
+    pub fn set_linkage(&self, linkage: Linkage) {
+        or_panic(self.try_set_linkage(linkage))
+    }
+
+    /// Sets the linkage type for this global variable, returning an error if the linkage is `common` and the global
+    /// variable is constant, or if the linkage is `appending` and the global variable is not an array.
+    pub fn try_set_linkage(&self, linkage: Linkage) -> Result<(), LinkageError> {
+        LinkageError::check(
+            self.name(),
+            linkage,
+            match linkage {
+                Linkage::Common if self.constant => {
+                    Some("global variables with common linkage cannot be constant")
+                }
+                Linkage::Appending
+                    if !matches!(
+                        self.value_type.as_ref(),
+                        types::FirstClass::Aggregate(types::Aggregate::Array(_))
+                    ) =>
+                {
+                    Some("appending linkage is only allowed on global variables of array type")
+                }
+                _ => None,
+            },
+        )?;
+
+        self.information.borrow_mut().linkage = linkage;
+        Ok(())
+    }
+
+    /// Gets the visibility of this global variable.
+    pub fn get_visibility(&self) -> Visibility {
+        self.information.borrow().visibility
+    }
+
+    /// Sets the visibility of this global variable.
+    ///
+    /// Global variables with `private` or `internal` linkage must have the default visibility, which is checked by
+    /// [`verify_variable`](crate::verify::verify_variable).
+    pub fn set_visibility(&self, visibility: Visibility) {
+        self.information.borrow_mut().visibility = visibility;
+    }
+
+    /// Gets a value indicating whether this global variable is known to resolve to a definition in the same linkage unit
+    /// (`dso_local`).
+    pub fn get_dso_local(&self) -> bool {
+        self.information.borrow().dso_local
+    }
+
+    /// Sets a value indicating whether this global variable is known to resolve to a definition in the same linkage unit.
+    pub fn set_dso_local(&self, dso_local: bool) {
+        self.information.borrow_mut().dso_local = dso_local;
+    }
+
+    /// Gets a value indicating whether the address of this global variable is significant.
+    pub fn get_unnamed_address(&self) -> Option<UnnamedAddress> {
+        self.information.borrow().unnamed_address
+    }
+
+    /// Sets a value indicating whether the address of this global variable is significant, allowing constants with the
+    /// same contents to be merged if it is not.
+    pub fn set_unnamed_address(&self, unnamed_address: Option<UnnamedAddress>) {
+        self.information.borrow_mut().unnamed_address = unnamed_address;
+    }
+
+    /// Gets the initial value of this global variable, or `None` if this global variable is a declaration.
+    pub fn get_initializer(&self) -> Option<value::Value> {
+        self.information.borrow().initializer.clone()
+    }
+
+    /// Sets the initial value of this global variable.
+    ///
+    /// # Panics
+    /// Panics if the initial value is not a constant, or if it is not of the same type as the global variable.
+    pub fn set_initializer(&self, initializer: Option<value::Value>) {
+        if let Some(value) = &initializer {
+            if !value.is_constant() {
+                panic!(
+                    "expected constant initializer for @{}, but got {}",
+                    self.name(),
+                    value
+                );
+            } else if &value.value_type() != self.value_type() {
+                panic!(
+                    "expected initializer of type {} for @{}, but got {}",
+                    self.value_type(),
+                    self.name(),
+                    value
+                );
             }
-            writeln!(f, "}}")?;
         }
 
+        self.information.borrow_mut().initializer = initializer;
+    }
+
+    /// Gets the name of the section that this global variable is placed in, or `None` if the default section is used.
+    pub fn get_section(&self) -> Option<Identifier> {
+        self.information.borrow().section.clone()
+    }
+
+    /// Sets the name of the section that this global variable is placed in.
+    pub fn set_section(&self, section: Option<Identifier>) {
+        self.information.borrow_mut().section = section;
+    }
+
+    /// Gets the comdat that this global variable belongs to.
+    pub fn get_comdat(&self) -> Option<Comdat> {
+        self.information.borrow().comdat.clone()
+    }
+
+    /// Sets the comdat that this global variable belongs to.
+    pub fn set_comdat(&self, comdat: Option<Comdat>) {
+        self.information.borrow_mut().comdat = comdat;
+    }
+
+    /// Gets the alignment of this global variable.
+    pub fn get_alignment(&self) -> Option<Alignment> {
+        self.information.borrow().alignment
+    }
+
+    /// Sets the alignment of this global variable.
+    pub fn set_alignment(&self, alignment: Option<Alignment>) {
+        self.information.borrow_mut().alignment = alignment;
+    }
+
+    /// Gets the thread-local storage model of this global variable, or `None` if it is not thread local.
+    pub fn get_thread_local_mode(&self) -> Option<ThreadLocalMode> {
+        self.information.borrow().thread_local
+    }
+
+    /// Sets the thread-local storage model used by this global variable.
+    pub fn set_thread_local_mode(&self, mode: Option<ThreadLocalMode>) {
+        self.information.borrow_mut().thread_local = mode;
+    }
+
+    /// Gets a value indicating whether this global variable may be initialized outside of the program
+    /// (`externally_initialized`).
+    pub fn get_externally_initialized(&self) -> bool {
+        self.information.borrow().externally_initialized
+    }
+
+    /// Sets a value indicating whether this global variable may be initialized or modified outside of the program, such
+    /// as by a loader or a JIT compiler, which prevents its initializer from being used in optimizations.
+    pub fn set_externally_initialized(&self, externally_initialized: bool) {
+        self.information.borrow_mut().externally_initialized = externally_initialized;
+    }
+}
+
+impl Debug for Variable {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Variable")
+            .field("name", &self.name)
+            .field("value_type", &self.value_type)
+            .field("constant", &self.constant)
+            .field("linkage", &self.get_linkage())
+            .field("visibility", &self.get_visibility())
+            .field("dso_local", &self.get_dso_local())
+            .field("unnamed_address", &self.get_unnamed_address())
+            .field("initializer", &self.get_initializer())
+            .field("section", &self.get_section())
+            .field("comdat", &self.get_comdat())
+            .field("alignment", &self.get_alignment())
+            .field("thread_local", &self.get_thread_local_mode())
+            .field("externally_initialized", &self.get_externally_initialized())
+            .finish()
+    }
+}
+
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let information = self.information.borrow();
+        write!(f, "@{} =", self.name())?;
+        // External linkage is implied for definitions, and LLVM only accepts the keyword on declarations.
+        if information.linkage != Linkage::External || information.initializer.is_none() {
+            write!(f, " {}", information.linkage)?;
+        }
+        if information.dso_local {
+            f.write_str(" dso_local")?;
+        }
+        if information.visibility != Visibility::Default {
+            write!(f, " {}", information.visibility)?;
+        }
+        if let Some(mode) = information.thread_local {
+            write!(f, " {}", mode)?;
+        }
+        if let Some(unnamed_address) = information.unnamed_address {
+            write!(f, " {}", unnamed_address)?;
+        }
+        if information.externally_initialized {
+            f.write_str(" externally_initialized")?;
+        }
+
+        f.write_str(if self.constant {
+            " constant "
+        } else {
+            " global "
+        })?;
+        match &information.initializer {
+            Some(initializer) => Display::fmt(initializer, f)?,
+            None => Display::fmt(&self.value_type, f)?,
+        }
+
+        write_placement(
+            f,
+            ", ",
+            information.section.as_ref(),
+            information.comdat.as_ref(),
+            information.alignment,
+        )
+    }
+}
+
+#[derive(Default)]
+struct IFuncInformation {
+    linkage: Linkage,
+    visibility: Visibility,
+}
+
+/// An indirect function (`ifunc`), whose address is determined when the program is loaded by calling a resolver function,
+/// allowing an implementation to be selected based on the features of the CPU.
+///
+/// See [the latest LLVM documentation on indirect functions here](https://llvm.org/docs/LangRef.html#ifuncs).
+pub struct IFunc {
+    name: Symbol,
+    signature: Rc<types::Function>,
+    resolver: RefCell<Rc<Function>>,
+    information: RefCell<IFuncInformation>,
+}
+
+impl IFunc {
+    /// Creates an indirect function with the specified signature, whose address is returned by the resolver function.
+    ///
+    /// # Panics
+    /// Panics if the resolver does not return a pointer.
+    pub fn new(
+        name: impl Into<Symbol>,
+        signature: impl Into<Rc<types::Function>>,
+        resolver: Rc<Function>,
+    ) -> Rc<Self> {
+        let name = name.into();
+        match resolver.signature().return_type() {
+            types::Return::FirstClass(return_type)
+                if matches!(
+                    return_type.as_ref(),
+                    types::FirstClass::Single(types::SingleValue::Pointer(_))
+                ) => {}
+            return_type => panic!(
+                "expected resolver @{} of @{} to return a pointer, but got {}",
+                resolver.name(),
+                name.as_id(),
+                return_type
+            ),
+        }
+
+        Rc::new(Self {
+            name,
+            signature: signature.into(),
+            resolver: RefCell::new(resolver),
+            information: RefCell::default(),
+        })
+    }
+
+    /// Gets the name of this indirect function.
+    pub fn name(&self) -> &Id {
+        self.name.as_id()
+    }
+
+    /// Gets the symbol containing the name of this indirect function.
+    pub fn symbol(&self) -> &Symbol {
+        &self.name
+    }
+
+    /// Gets the signature of this indirect function, which is used when it is called.
+    pub fn signature(&self) -> &Rc<types::Function> {
+        &self.signature
+    }
+
+    /// Gets the function that is called to determine the address of this indirect function.
+    pub fn resolver(&self) -> Rc<Function> {
+        self.resolver.borrow().clone()
+    }
+
+    /// Replaces the resolver with a function of the same signature.
+    pub(crate) fn set_resolver(&self, resolver: Rc<Function>) {
+        *self.resolver.borrow_mut() = resolver;
+    }
+
+    /// Gets the linkage type for this indirect function.
+    pub fn get_linkage(&self) -> Linkage {
+        self.information.borrow().linkage
+    }
+
+    /// Sets the linkage type for this indirect function.
+    ///
+    /// # Panics
+    /// Panics if the linkage type is not allowed for indirect functions, see [`IFunc::try_set_linkage`].
+    pub fn set_linkage(&self, linkage: Linkage) {
+        or_panic(self.try_set_linkage(linkage))
+    }
+
+    /// Sets the linkage type for this indirect function, returning an error if the linkage is `available_externally`,
+    /// `common`, `appending`, or `extern_weak`, since indirect functions are always definitions.
+    pub fn try_set_linkage(&self, linkage: Linkage) -> Result<(), LinkageError> {
+        LinkageError::check(
+            self.name(),
+            linkage,
+            match linkage {
+                Linkage::AvailableExternally
+                | Linkage::Common
+                | Linkage::Appending
+                | Linkage::ExternWeak => {
+                    Some("indirect functions can only have private, internal, linkonce, weak, or external linkage")
+                }
+                _ => None,
+            },
+        )?;
+
+        self.information.borrow_mut().linkage = linkage;
         Ok(())
     }
+
+    /// Gets the visibility of this indirect function.
+    pub fn get_visibility(&self) -> Visibility {
+        self.information.borrow().visibility
+    }
+
+    /// Sets the visibility of this indirect function.
+    pub fn set_visibility(&self, visibility: Visibility) {
+        self.information.borrow_mut().visibility = visibility;
+    }
+}
+
+impl Debug for IFunc {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("IFunc")
+            .field("name", &self.name)
+            .field("signature", &self.signature)
+            .field("resolver", self.resolver.borrow().symbol())
+            .field("linkage", &self.get_linkage())
+            .field("visibility", &self.get_visibility())
+            .finish()
+    }
 }
 
-/// A global value in a module, either a global variable or a function definition.
-#[derive(Debug)]
+impl Display for IFunc {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let information = self.information.borrow();
+        write!(f, "@{} = {}", self.name(), information.linkage)?;
+        if information.visibility != Visibility::Default {
+            write!(f, " {}", information.visibility)?;
+        }
+        let resolver = self.resolver.borrow();
+        write!(
+            f,
+            " ifunc {}, {} @{}",
+            self.signature,
+            types::Pointer::new(resolver.signature().clone()),
+            resolver.name()
+        )
+    }
+}
+
+/// A global value in a module, either a global variable, a function definition, or an indirect function.
+#[derive(Clone, Debug)]
 pub enum Value {
-    //Variable(Variable),
+    /// A global variable.
+    Variable(Rc<Variable>),
     /// A function definition.
     Function(Rc<Function>),
+    /// An indirect function.
+    IFunc(Rc<IFunc>),
+}
+
+impl Value {
+    /// Gets the symbol of this global value.
+    pub fn symbol(&self) -> &Symbol {
+        match self {
+            Self::Variable(variable) => variable.symbol(),
+            Self::Function(function) => function.symbol(),
+            Self::IFunc(ifunc) => ifunc.symbol(),
+        }
+    }
+
+    /// Gets the comdat that this global value belongs to.
+    pub fn get_comdat(&self) -> Option<Comdat> {
+        match self {
+            Self::Variable(variable) => variable.get_comdat(),
+            Self::Function(function) => function.get_comdat(),
+            Self::IFunc(_) => None,
+        }
+    }
+
+    /// Gets a pointer to the global value, used to identify it.
+    pub(crate) fn as_ptr(&self) -> *const () {
+        match self {
+            Self::Variable(variable) => Rc::as_ptr(variable).cast(),
+            Self::Function(function) => Rc::as_ptr(function).cast(),
+            Self::IFunc(ifunc) => Rc::as_ptr(ifunc).cast(),
+        }
+    }
 }
 
+crate::enum_case_from!(Value, Variable, Rc<Variable>);
 crate::enum_case_from!(Value, Function, Rc<Function>);
+crate::enum_case_from!(Value, IFunc, Rc<IFunc>);
 
 impl Display for Value {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
+            Self::Variable(variable) => Display::fmt(&variable, f),
             Self::Function(function) => Display::fmt(&function, f),
+            Self::IFunc(ifunc) => Display::fmt(&ifunc, f),
         }
     }
 }
@@ -2,10 +2,14 @@
 //! LLVM uses null-terminated strings, so `null` bytes are not allowed in names.
 
 use std::borrow::{Borrow, ToOwned};
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::convert::AsRef;
-use std::ffi::CString;
-use std::fmt::{Debug, Display, Formatter};
+use std::ffi::{CStr, CString};
+use std::fmt::{Debug, Display, Formatter, Write as _};
+use std::hash::{Hash, Hasher};
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
 // TODO: Should identifiers contain only valid ASCII?
 
@@ -16,6 +20,36 @@ pub struct Error {
     byte_index: usize,
 }
 
+/// Checks that a string does not contain any `null` bytes, used by [`id!`](crate::id) to validate identifiers at compile
+/// time.
+///
+/// # Panics
+/// Panics if the string contains a `null` byte.
+#[doc(hidden)]
+pub const fn validate_literal(identifier: &str) -> &str {
+    let bytes = identifier.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == 0 {
+            panic!("identifier literal contains null byte");
+        }
+        index += 1;
+    }
+    identifier
+}
+
+/// Creates an [`Identifier`] from a string literal, checking that it does not contain any `null` bytes at compile time.
+#[macro_export]
+macro_rules! id {
+    ($identifier: literal) => {{
+        const IDENTIFIER: &str = $crate::identifier::validate_literal($identifier);
+        unsafe {
+            // Safety: The literal is checked for null bytes at compile time.
+            $crate::Identifier::new_unchecked(std::string::String::from(IDENTIFIER))
+        }
+    }};
+}
+
 /// A borrowed identifier string.
 #[repr(transparent)]
 pub struct Id(str);
@@ -104,6 +138,24 @@ impl Display for Id {
     }
 }
 
+/// Prints an identifier as a quoted string, such as a metadata string or inline assembly, escaping any quotes,
+/// backslashes, and non-printable characters.
+pub(crate) struct Quoted<'a>(pub(crate) &'a Id);
+
+impl Display for Quoted<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_char('"')?;
+        for byte in self.0.as_bytes() {
+            match byte {
+                b'"' | b'\\' => write!(f, "\\{:02X}", byte)?,
+                b' '..=b'~' => f.write_char(char::from(*byte))?,
+                _ => write!(f, "\\{:02X}", byte)?,
+            }
+        }
+        f.write_char('"')
+    }
+}
+
 /// An owned identifier string.
 #[derive(Clone, Default)]
 #[repr(transparent)]
@@ -118,6 +170,11 @@ impl Identifier {
         Self(identifier)
     }
 
+    /// Creates a new owned identifier string, removing any `null` bytes.
+    pub fn sanitized(identifier: &str) -> Self {
+        Self(identifier.replace('\0', ""))
+    }
+
     /// Borrows the contents of this identifier string.
     #[allow(clippy::needless_lifetimes)]
     pub fn as_id<'a>(&'a self) -> &'a Id {
@@ -152,6 +209,22 @@ impl TryFrom<&str> for Identifier {
     }
 }
 
+impl TryFrom<&String> for Identifier {
+    type Error = Error;
+
+    fn try_from(identifier: &String) -> Result<Self, Self::Error> {
+        Self::try_from(identifier.as_str())
+    }
+}
+
+impl std::str::FromStr for Identifier {
+    type Err = Error;
+
+    fn from_str(identifier: &str) -> Result<Self, Self::Err> {
+        Self::try_from(identifier)
+    }
+}
+
 impl From<&Id> for Identifier {
     fn from(identifier: &Id) -> Self {
         unsafe {
@@ -204,3 +277,131 @@ impl Display for Identifier {
         <Id as Display>::fmt(self.as_ref(), f)
     }
 }
+
+struct SymbolContents(CString);
+
+/// A reference-counted identifier, which allows names to be shared without copying them.
+///
+/// Symbols obtained from the same [`SymbolTable`] share their contents, allowing them to be compared by reference.
+#[derive(Clone)]
+pub struct Symbol(Rc<SymbolContents>);
+
+impl Symbol {
+    /// Creates a new symbol that is not stored in a symbol table.
+    pub fn new(identifier: &Id) -> Self {
+        Self(Rc::new(SymbolContents(identifier.to_c_string())))
+    }
+
+    /// Borrows the contents of this symbol.
+    pub fn as_id(&self) -> &Id {
+        unsafe {
+            // Safety: The C string was created from an identifier, so it contains valid UTF-8 and no interior null bytes.
+            Id::new_unchecked(std::str::from_utf8_unchecked(self.0 .0.as_bytes()))
+        }
+    }
+
+    /// Borrows the contents of this symbol as a C-compatible string, without copying it.
+    pub fn as_c_str(&self) -> &CStr {
+        &self.0 .0
+    }
+
+    /// Returns `true` if both symbols share the same contents, which is always the case for equal symbols obtained from
+    /// the same symbol table.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl From<&Id> for Symbol {
+    fn from(identifier: &Id) -> Self {
+        Self::new(identifier)
+    }
+}
+
+impl From<Identifier> for Symbol {
+    fn from(identifier: Identifier) -> Self {
+        Self(Rc::new(SymbolContents(identifier.into_c_string())))
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || self.as_id().as_str() == other.as_id().as_str()
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_id().as_str().hash(state)
+    }
+}
+
+impl Deref for Symbol {
+    type Target = Id;
+
+    fn deref(&self) -> &Id {
+        self.as_id()
+    }
+}
+
+impl AsRef<Id> for Symbol {
+    fn as_ref(&self) -> &Id {
+        self.as_id()
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        self.as_id().as_str()
+    }
+}
+
+impl Debug for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Debug::fmt(self.as_id(), f)
+    }
+}
+
+impl Display for Symbol {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(self.as_id(), f)
+    }
+}
+
+/// Stores symbols so that each name is only stored once.
+#[derive(Debug, Default)]
+pub struct SymbolTable {
+    symbols: RefCell<HashSet<Symbol>>,
+}
+
+impl SymbolTable {
+    /// Creates an empty symbol table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the symbol with the specified name, adding it to the table if it does not already exist.
+    pub fn intern(&self, name: &Id) -> Symbol {
+        let mut symbols = self.symbols.borrow_mut();
+        match symbols.get(name.as_str()) {
+            Some(existing) => existing.clone(),
+            None => {
+                let symbol = Symbol::new(name);
+                symbols.insert(symbol.clone());
+                symbol
+            }
+        }
+    }
+
+    /// Gets the number of symbols in this table.
+    pub fn len(&self) -> usize {
+        self.symbols.borrow().len()
+    }
+
+    /// Returns `true` if this table does not contain any symbols.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
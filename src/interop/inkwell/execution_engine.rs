@@ -0,0 +1,60 @@
+//! Contains code to compile and execute modules in-process using inkwell's execution engines.
+//!
+//! The native target must be initialized before an execution engine is created, such as by calling
+//! [`inkwell::targets::Target::initialize_native`].
+
+use crate::identifier::Id;
+use crate::interop::inkwell::module::{BuildError, Builder};
+use inkwell::execution_engine::{
+    ExecutionEngine, FunctionLookupError, JitFunction, UnsafeFunctionPointer,
+};
+
+/// Error used when a module could not be compiled or a function could not be found.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum JitError {
+    /// The module could not be lowered into an `inkwell` module.
+    Build(BuildError),
+    /// An LLVM message describing why the execution engine could not be created.
+    Creation(inkwell::support::LLVMString),
+    /// The function could not be found in the compiled module.
+    Lookup(FunctionLookupError),
+}
+
+crate::enum_case_from!(JitError, Build, BuildError);
+crate::enum_case_from!(JitError, Creation, inkwell::support::LLVMString);
+crate::enum_case_from!(JitError, Lookup, FunctionLookupError);
+
+impl Builder<'_> {
+    /// Lowers the contents of this module and creates an execution engine that compiles it for the host machine, using
+    /// the optimization level of the module's target machine.
+    pub fn create_jit_execution_engine<'ctx>(
+        &self,
+        context: &'ctx inkwell::context::Context,
+    ) -> Result<ExecutionEngine<'ctx>, JitError> {
+        let optimization_level = self
+            .target()
+            .machine()
+            .machine()
+            .code_generation_optimization_level();
+
+        Ok(self
+            .build(context)?
+            .create_jit_execution_engine(optimization_level.into())?)
+    }
+
+    /// Lowers and compiles the contents of this module, returning the function with the specified name.
+    ///
+    /// # Safety
+    /// Callers must ensure that `F` is a function pointer type (such as `unsafe extern "C" fn(i32) -> i32`) matching the
+    /// signature and calling convention of the function.
+    pub unsafe fn get_jit_function<'ctx, F: UnsafeFunctionPointer>(
+        &self,
+        context: &'ctx inkwell::context::Context,
+        name: &Id,
+    ) -> Result<JitFunction<'ctx, F>, JitError> {
+        Ok(self
+            .create_jit_execution_engine(context)?
+            .get_function(name.as_str())?)
+    }
+}
@@ -1,6 +1,11 @@
 //! Contains implementations of traits for interoperation with [`inkwell`](https://crates.io/crates/inkwell).
 
+pub mod execution_engine;
+pub mod module;
 pub mod target;
+pub mod types;
+
+pub use module::Builder as ModuleBuilder;
 
 /// Common result type used in `inkwell` functions, indicating an error with an LLVM allocated message.
 pub type InkwellResult<T> = Result<T, inkwell::support::LLVMString>;
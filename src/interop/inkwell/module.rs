@@ -0,0 +1,117 @@
+//! Contains code for lowering `llvm-model` modules into `inkwell` modules.
+
+use crate::interop::inkwell::target::InvalidMachineError;
+use crate::interop::llvm_sys;
+use crate::Identifier;
+use inkwell::targets::{FileType, TargetMachine};
+
+/// Error used when an attempt to convert a module into an `inkwell` module fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The module could not be lowered into an LLVM module.
+    Lowering(llvm_sys::module::BuildError),
+    /// The lowered module could not be loaded into the `inkwell` context.
+    Loading(inkwell::support::LLVMString),
+    /// The inkwell target machine used to emit code could not be created.
+    InvalidMachine(InvalidMachineError),
+    /// An LLVM message describing why code could not be emitted for the module.
+    Emission(inkwell::support::LLVMString),
+}
+
+crate::enum_case_from!(BuildError, Lowering, llvm_sys::module::BuildError);
+crate::enum_case_from!(BuildError, Loading, inkwell::support::LLVMString);
+crate::enum_case_from!(BuildError, InvalidMachine, InvalidMachineError);
+
+/// Contains a [`llvm-model::Module`] that is lowered into an `inkwell` module.
+#[derive(Debug)]
+pub struct Builder<'t> {
+    builder: llvm_sys::ModuleBuilder<'t>,
+}
+
+impl<'t> Builder<'t> {
+    /// Creates a module with the specified name and target.
+    pub fn new(name: Identifier, target: &'t llvm_sys::target::Target) -> Self {
+        Self {
+            builder: llvm_sys::ModuleBuilder::new(name, target),
+        }
+    }
+
+    /// The target machine and layout of the module.
+    pub fn target(&self) -> &'t llvm_sys::target::Target {
+        self.builder.target()
+    }
+
+    /// Used to mutate the contents of the module.
+    pub fn module(&mut self) -> &mut crate::Module<'t> {
+        self.builder.module()
+    }
+
+    /// Gets a value indicating whether the module is checked by the LLVM verifier after it is built.
+    pub fn get_verify(&self) -> bool {
+        self.builder.get_verify()
+    }
+
+    /// Sets whether the module is checked by the LLVM verifier after it is built.
+    pub fn set_verify(&mut self, verify: bool) {
+        self.builder.set_verify(verify)
+    }
+
+    /// Lowers the contents of this module into an `inkwell` module owned by the specified context.
+    pub fn build<'ctx>(
+        &self,
+        context: &'ctx inkwell::context::Context,
+    ) -> Result<inkwell::module::Module<'ctx>, BuildError> {
+        // The module is lowered in a separate context, since inkwell does not allow modules to be created from
+        // references, and is then transferred to the inkwell context as bitcode.
+        let lowering_context = llvm_sys::Context::new();
+        let bitcode = self
+            .builder
+            .build(&lowering_context)?
+            .write_bitcode_to_buffer();
+
+        // The name of the buffer is used as the name of the module.
+        let buffer = inkwell::memory_buffer::MemoryBuffer::create_from_memory_range_copy(
+            &bitcode,
+            self.builder.name().as_str(),
+        );
+
+        Ok(inkwell::module::Module::parse_bitcode_from_buffer(
+            &buffer, context,
+        )?)
+    }
+
+    fn emit_target_code<T, F>(&self, emit: F) -> Result<T, BuildError>
+    where
+        F: FnOnce(
+            &TargetMachine,
+            &inkwell::module::Module,
+        ) -> Result<T, inkwell::support::LLVMString>,
+    {
+        let target_machine = TargetMachine::try_from(self.target().machine().machine())?;
+        let context = inkwell::context::Context::create();
+        let module = self.build(&context)?;
+        emit(&target_machine, &module).map_err(BuildError::Emission)
+    }
+
+    /// Emits assembly code or an object file for the module's target machine into a memory buffer.
+    pub fn emit_target_code_to_buffer(
+        &self,
+        file_type: FileType,
+    ) -> Result<inkwell::memory_buffer::MemoryBuffer, BuildError> {
+        self.emit_target_code(|target_machine, module| {
+            target_machine.write_to_memory_buffer(module, file_type)
+        })
+    }
+
+    /// Emits assembly code or an object file for the module's target machine into a file.
+    pub fn emit_target_code_to_file(
+        &self,
+        file_type: FileType,
+        path: &std::path::Path,
+    ) -> Result<(), BuildError> {
+        self.emit_target_code(|target_machine, module| {
+            target_machine.write_to_file(module, file_type, path)
+        })
+    }
+}
@@ -1,9 +1,18 @@
 //! Contains code for converting from `llvm-model`'s representation of LLVM targets to `inkwell`'s representations.
 
+use crate::identifier::{Id, Identifier};
 use crate::interop::inkwell::InkwellResult;
-use crate::target::Triple;
+use crate::target::{
+    self, layout, CodeGenerationOptimization, CodeModel, FeatureSet, Layout, Machine,
+    RelocationMode, Triple,
+};
 
-use inkwell::targets::{Target as InkwellTarget, TargetTriple as InkwellTargetTriple};
+use inkwell::targets::{
+    CodeModel as InkwellCodeModel, RelocMode as InkwellRelocMode, Target as InkwellTarget,
+    TargetData as InkwellTargetData, TargetMachine as InkwellTargetMachine,
+    TargetTriple as InkwellTargetTriple,
+};
+use inkwell::OptimizationLevel as InkwellOptimizationLevel;
 
 impl From<&'_ Triple> for InkwellTargetTriple {
     fn from(triple: &Triple) -> Self {
@@ -11,6 +20,18 @@ impl From<&'_ Triple> for InkwellTargetTriple {
     }
 }
 
+impl TryFrom<&'_ InkwellTargetTriple> for Triple {
+    type Error = std::str::Utf8Error;
+
+    /// Parses an inkwell target triple, returning an error if the triple string is not valid UTF-8.
+    fn try_from(triple: &InkwellTargetTriple) -> Result<Self, Self::Error> {
+        let triple_string = triple.as_str().to_str()?;
+        Ok(Self::from(
+            Id::new(triple_string).expect("triple string should not contain null bytes"),
+        ))
+    }
+}
+
 impl Triple {
     /// Retrieves an inkwell Target structure corresponding to this target triple.
     ///
@@ -21,3 +42,157 @@ impl Triple {
         InkwellTarget::from_triple(&self.into())
     }
 }
+
+impl From<&'_ Layout> for InkwellTargetData {
+    fn from(layout: &Layout) -> Self {
+        Self::create(&layout.to_string())
+    }
+}
+
+impl TryFrom<&'_ InkwellTargetData> for Layout {
+    type Error = layout::ParseError;
+
+    fn try_from(target_data: &InkwellTargetData) -> Result<Self, Self::Error> {
+        let layout_string = target_data.get_data_layout();
+        let layout_string = layout_string.as_str().to_string_lossy();
+        Self::try_from(
+            Id::new(&layout_string).expect("layout string should not contain null bytes"),
+        )
+    }
+}
+
+impl From<CodeGenerationOptimization> for InkwellOptimizationLevel {
+    fn from(level: CodeGenerationOptimization) -> Self {
+        match level {
+            CodeGenerationOptimization::None => Self::None,
+            CodeGenerationOptimization::Less => Self::Less,
+            CodeGenerationOptimization::Default => Self::Default,
+            CodeGenerationOptimization::Aggressive => Self::Aggressive,
+        }
+    }
+}
+
+/// Error used when an attempt to create an inkwell target machine fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InvalidMachineError {
+    /// An LLVM message describing why the target triple is invalid.
+    Message(inkwell::support::LLVMString),
+    /// The relocation mode is not supported by inkwell.
+    UnsupportedRelocationMode(RelocationMode),
+    /// The code model is not supported by inkwell.
+    UnsupportedCodeModel(CodeModel),
+    /// LLVM could not create the target machine.
+    Unknown,
+}
+
+crate::enum_case_from!(InvalidMachineError, Message, inkwell::support::LLVMString);
+
+impl TryFrom<&'_ Machine> for InkwellTargetMachine {
+    type Error = InvalidMachineError;
+
+    fn try_from(machine: &Machine) -> Result<Self, Self::Error> {
+        let relocation_mode = match machine.relocation_mode() {
+            RelocationMode::Default => InkwellRelocMode::Default,
+            RelocationMode::Static => InkwellRelocMode::Static,
+            RelocationMode::PIC => InkwellRelocMode::PIC,
+            RelocationMode::DynamicNoPIC => InkwellRelocMode::DynamicNoPic,
+            unsupported => return Err(InvalidMachineError::UnsupportedRelocationMode(unsupported)),
+        };
+
+        let code_model = match machine.code_model() {
+            CodeModel::Default => InkwellCodeModel::Default,
+            CodeModel::JITDefault => InkwellCodeModel::JITDefault,
+            CodeModel::Small => InkwellCodeModel::Small,
+            CodeModel::Kernel => InkwellCodeModel::Kernel,
+            CodeModel::Medium => InkwellCodeModel::Medium,
+            CodeModel::Large => InkwellCodeModel::Large,
+            unsupported => return Err(InvalidMachineError::UnsupportedCodeModel(unsupported)),
+        };
+
+        let triple = InkwellTargetTriple::from(machine.target_triple());
+
+        InkwellTarget::from_triple(&triple)?
+            .create_target_machine(
+                &triple,
+                machine.cpu_name().as_str(),
+                machine.features().to_feature_string().as_id().as_str(),
+                machine.code_generation_optimization_level().into(),
+                relocation_mode,
+                code_model,
+            )
+            .ok_or(InvalidMachineError::Unknown)
+    }
+}
+
+/// Copies the contents of an LLVM string into an identifier, replacing any invalid UTF-8 sequences.
+fn llvm_string_to_identifier(string: &inkwell::support::LLVMString) -> Identifier {
+    Identifier::try_from(string.to_string_lossy().into_owned())
+        .expect("LLVM strings should not contain null bytes")
+}
+
+/// Error used when creation of a target fails.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InvalidTargetError {
+    /// Used when the inkwell target machine could not be created.
+    InvalidMachine(InvalidMachineError),
+    /// Used when a target's data layout could not be parsed.
+    InvalidLayout(layout::ParseError),
+}
+
+crate::enum_case_from!(InvalidTargetError, InvalidMachine, InvalidMachineError);
+crate::enum_case_from!(InvalidTargetError, InvalidLayout, layout::ParseError);
+
+/// An inkwell target machine along with its description.
+#[derive(Debug)]
+pub struct Target {
+    machine: InkwellTargetMachine,
+    target: target::Target,
+}
+
+impl Target {
+    /// Gets the inkwell target machine.
+    pub fn machine(&self) -> &InkwellTargetMachine {
+        &self.machine
+    }
+
+    /// Gets the target.
+    pub fn target(&self) -> &target::Target {
+        &self.target
+    }
+
+    /// Gets a target for the host machine.
+    ///
+    /// # Caution
+    /// Before calling, ensure that the native target has been initialized, such as by calling
+    /// [`inkwell::targets::Target::initialize_native()`], otherwise an error may be returned.
+    pub fn host_machine_target(
+        optimization_level: CodeGenerationOptimization,
+        relocation_mode: RelocationMode,
+        code_model: CodeModel,
+    ) -> Result<Self, InvalidTargetError> {
+        let host_triple = InkwellTargetMachine::get_default_triple();
+        let host_triple = Identifier::try_from(host_triple.as_str().to_string_lossy().into_owned())
+            .expect("target triple should not contain null bytes");
+
+        let features = llvm_string_to_identifier(&InkwellTargetMachine::get_host_cpu_features());
+
+        let machine = Machine::new(
+            Triple::from(host_triple),
+            llvm_string_to_identifier(&InkwellTargetMachine::get_host_cpu_name()),
+            FeatureSet::try_from(features.as_id()).expect("host CPU features should be valid"),
+            optimization_level,
+            relocation_mode,
+            code_model,
+        );
+
+        let inkwell_machine = InkwellTargetMachine::try_from(&machine)?;
+        let layout = Layout::try_from(&inkwell_machine.get_target_data())?;
+
+        Ok(Self {
+            machine: inkwell_machine,
+            target: target::Target::new(machine, layout),
+        })
+    }
+}
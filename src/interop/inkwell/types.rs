@@ -0,0 +1,102 @@
+//! Contains code for converting from `llvm-model`'s representation of types to `inkwell`'s representations.
+
+use crate::types::{Aggregate, FirstClass, Float, Function, Pointee, Return, SingleValue};
+
+use inkwell::context::Context;
+use inkwell::types::{BasicMetadataTypeEnum, BasicType, BasicTypeEnum, FunctionType};
+use inkwell::AddressSpace;
+
+impl FirstClass {
+    /// Creates the inkwell type corresponding to this type in the specified context.
+    ///
+    /// # Panics
+    /// Panics if this type is or contains a pointer in an address space that is not supported by inkwell, a vector of
+    /// elements that are not integers, floating-point values, or pointers, or a `token` or `metadata` type, which inkwell
+    /// cannot represent as a basic type.
+    pub fn to_inkwell_type<'ctx>(&self, context: &'ctx Context) -> BasicTypeEnum<'ctx> {
+        match self {
+            Self::Single(SingleValue::Integer(size)) => {
+                context.custom_width_int_type(size.bits()).into()
+            }
+            Self::Single(SingleValue::Float(Float::Half)) => context.f16_type().into(),
+            Self::Single(SingleValue::Float(Float::Float)) => context.f32_type().into(),
+            Self::Single(SingleValue::Float(Float::Double)) => context.f64_type().into(),
+            Self::Single(SingleValue::Pointer(pointer)) => {
+                let address_space = AddressSpace::try_from(pointer.address_space().0)
+                    .unwrap_or_else(|_| {
+                        panic!(
+                            "address space {} is not supported by inkwell",
+                            pointer.address_space()
+                        )
+                    });
+
+                match pointer.pointee() {
+                    Pointee::FirstClass(pointee_type) => pointee_type
+                        .to_inkwell_type(context)
+                        .ptr_type(address_space),
+                    Pointee::Function(signature) => {
+                        signature.to_inkwell_type(context).ptr_type(address_space)
+                    }
+                }
+                .into()
+            }
+            Self::Single(SingleValue::Vector(vector)) => match vector
+                .element_type()
+                .to_inkwell_type(context)
+            {
+                BasicTypeEnum::IntType(element_type) => element_type.vec_type(vector.count()),
+                BasicTypeEnum::FloatType(element_type) => element_type.vec_type(vector.count()),
+                BasicTypeEnum::PointerType(element_type) => element_type.vec_type(vector.count()),
+                _ => panic!(
+                    "{} is not a valid vector element type",
+                    vector.element_type()
+                ),
+            }
+            .into(),
+            Self::Aggregate(Aggregate::Array(array)) => array
+                .element_type()
+                .to_inkwell_type(context)
+                .array_type(array.count())
+                .into(),
+            Self::Aggregate(Aggregate::Struct(structure)) => {
+                let member_types = structure
+                    .member_types()
+                    .iter()
+                    .map(|member_type| member_type.to_inkwell_type(context))
+                    .collect::<Vec<_>>();
+
+                context
+                    .struct_type(&member_types, structure.is_packed())
+                    .into()
+            }
+            Self::Token | Self::Metadata => {
+                panic!("{} types are not supported by inkwell", self)
+            }
+        }
+    }
+}
+
+impl Function {
+    /// Creates the inkwell function type corresponding to this function type in the specified context.
+    ///
+    /// # Panics
+    /// See [`FirstClass::to_inkwell_type`].
+    pub fn to_inkwell_type<'ctx>(&self, context: &'ctx Context) -> FunctionType<'ctx> {
+        let parameter_types = self
+            .parameter_types()
+            .iter()
+            .map(|parameter_type| {
+                BasicMetadataTypeEnum::from(parameter_type.to_inkwell_type(context))
+            })
+            .collect::<Vec<_>>();
+
+        match self.return_type() {
+            Return::Void => context
+                .void_type()
+                .fn_type(&parameter_types, self.is_variadic()),
+            Return::FirstClass(return_type) => return_type
+                .to_inkwell_type(context)
+                .fn_type(&parameter_types, self.is_variadic()),
+        }
+    }
+}
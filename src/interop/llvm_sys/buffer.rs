@@ -1,6 +1,21 @@
 //! Contains code for interacting with LLVM memory buffers.
 
+use crate::identifier::{Id, Identifier};
+use crate::interop::llvm_sys as interop;
 use llvm_sys::prelude::LLVMMemoryBufferRef;
+use std::path::{Path, PathBuf};
+
+/// Error used when a file could not be read into a memory buffer.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ReadFileError {
+    /// The path was not valid Unicode or contained `null` bytes, and could not be passed to LLVM.
+    InvalidPath(PathBuf),
+    /// An LLVM message describing why the file could not be read.
+    Message(interop::Message),
+}
+
+crate::enum_case_from!(ReadFileError, Message, interop::Message);
 
 /// A wrapper around an LLVM memory buffer.
 #[repr(transparent)]
@@ -27,6 +42,69 @@ impl MemoryBuffer {
         self.buffer.as_ptr()
     }
 
+    /// Returns the underlying memory buffer, transferring ownership of it to the caller.
+    ///
+    /// # Safety
+    /// Callers are responsible for disposing the returned memory buffer, which is usually done by passing it to an LLVM
+    /// function that takes ownership of it.
+    pub unsafe fn into_reference(self) -> LLVMMemoryBufferRef {
+        let buffer = self.reference();
+        std::mem::forget(self);
+        buffer
+    }
+
+    fn copy_with_c_name(memory: &[u8], name: &std::ffi::CStr) -> Self {
+        unsafe {
+            let buffer: *const u8 = memory.as_ptr();
+            Self::from_reference_unchecked(
+                llvm_sys::core::LLVMCreateMemoryBufferWithMemoryRangeCopy(
+                    buffer as *const i8,
+                    memory.len(),
+                    name.as_ptr(),
+                ),
+            )
+        }
+    }
+
+    /// Creates a memory buffer containing a copy of the specified memory, with a name that is used to refer to the buffer
+    /// in diagnostics.
+    pub fn copy_with_name(memory: &[u8], name: &Id) -> Self {
+        Self::copy_with_c_name(memory, &name.to_c_string())
+    }
+
+    /// Reads the contents of a file into a memory buffer.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ReadFileError> {
+        let path = path.as_ref();
+        let path_string = path
+            .to_str()
+            .and_then(|path_string| Identifier::try_from(path_string).ok())
+            .ok_or_else(|| ReadFileError::InvalidPath(path.to_path_buf()))?
+            .into_c_string();
+
+        let mut buffer: LLVMMemoryBufferRef = std::ptr::null_mut();
+        let mut message: *mut std::os::raw::c_char = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: The error message is wrapped so that it is disposed.
+            let failed = llvm_sys::core::LLVMCreateMemoryBufferWithContentsOfFile(
+                path_string.as_ptr(),
+                &mut buffer as *mut _,
+                &mut message as *mut _,
+            );
+
+            if failed != 0 {
+                Err(ReadFileError::Message(interop::Message::from_ptr(message)))
+            } else {
+                Ok(Self::from_reference_unchecked(buffer))
+            }
+        }
+    }
+
+    /// Writes the contents of this buffer to a file, replacing its contents if it already exists.
+    pub fn write_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.as_slice())
+    }
+
     /// Gets the length of this buffer.
     pub fn len(&self) -> usize {
         unsafe {
@@ -83,20 +161,69 @@ impl std::borrow::Borrow<[u8]> for MemoryBuffer {
 
 impl From<&[u8]> for MemoryBuffer {
     fn from(memory: &[u8]) -> Self {
+        // Assuming here that buffer name can be empty.
+        Self::copy_with_c_name(memory, &std::ffi::CString::default())
+    }
+}
+
+/// A wrapper around an LLVM memory buffer that refers to memory without copying it, which cannot outlive the memory.
+#[repr(transparent)]
+pub struct BorrowedMemoryBuffer<'a> {
+    buffer: MemoryBuffer,
+    memory: std::marker::PhantomData<&'a [u8]>,
+}
+
+impl<'a> BorrowedMemoryBuffer<'a> {
+    /// Creates a memory buffer over the specified memory without copying it.
+    pub fn new(memory: &'a [u8]) -> Self {
+        Self::with_c_name(memory, &std::ffi::CString::default())
+    }
+
+    /// Creates a memory buffer over the specified memory without copying it, with a name that is used to refer to the
+    /// buffer in diagnostics.
+    pub fn with_name(memory: &'a [u8], name: &Id) -> Self {
+        Self::with_c_name(memory, &name.to_c_string())
+    }
+
+    fn with_c_name(memory: &'a [u8], name: &std::ffi::CStr) -> Self {
         unsafe {
+            // Safety: The buffer cannot outlive the memory, and LLVM does not dispose of memory that it does not own.
             let buffer: *const u8 = memory.as_ptr();
-            Self::from_reference_unchecked(
-                llvm_sys::core::LLVMCreateMemoryBufferWithMemoryRangeCopy(
-                    buffer as *const i8,
-                    memory.len(),
-                    // Assuming here that buffer name can be empty.
-                    std::ffi::CString::default().as_ptr(),
+            Self {
+                buffer: MemoryBuffer::from_reference_unchecked(
+                    llvm_sys::core::LLVMCreateMemoryBufferWithMemoryRange(
+                        buffer as *const i8,
+                        memory.len(),
+                        name.as_ptr(),
+                        0,
+                    ),
                 ),
-            )
+                memory: std::marker::PhantomData,
+            }
         }
     }
 }
 
+impl std::ops::Deref for BorrowedMemoryBuffer<'_> {
+    type Target = MemoryBuffer;
+
+    fn deref(&self) -> &MemoryBuffer {
+        &self.buffer
+    }
+}
+
+impl<'a> From<&'a [u8]> for BorrowedMemoryBuffer<'a> {
+    fn from(memory: &'a [u8]) -> Self {
+        Self::new(memory)
+    }
+}
+
+impl std::fmt::Debug for BorrowedMemoryBuffer<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.buffer, f)
+    }
+}
+
 impl std::fmt::Debug for MemoryBuffer {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         std::fmt::Debug::fmt(self.as_slice(), f)
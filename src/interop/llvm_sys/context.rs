@@ -0,0 +1,53 @@
+//! Contains code for managing LLVM contexts.
+
+use llvm_sys::prelude::LLVMContextRef;
+
+/// A wrapper over an LLVM context, which owns the types and constants used by modules.
+///
+/// The context is disposed when the wrapper is dropped.
+#[repr(transparent)]
+pub struct Context {
+    context: std::ptr::NonNull<llvm_sys::LLVMContext>,
+}
+
+impl Context {
+    /// Creates a new LLVM context.
+    pub fn new() -> Self {
+        unsafe {
+            // Safety: The newly created context is disposed when the wrapper is dropped.
+            Self {
+                context: std::ptr::NonNull::new(llvm_sys::core::LLVMContextCreate())
+                    .expect("context should not be null"),
+            }
+        }
+    }
+
+    /// Returns the underlying context.
+    ///
+    /// # Safety
+    /// Callers must ensure that the wrapper outlives any use of the returned pointer.
+    pub unsafe fn reference(&self) -> LLVMContextRef {
+        self.context.as_ptr()
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Context").field(&self.context).finish()
+    }
+}
+
+impl std::ops::Drop for Context {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: The context was created when the wrapper was created.
+            llvm_sys::core::LLVMContextDispose(self.reference())
+        }
+    }
+}
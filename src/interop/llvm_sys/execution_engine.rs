@@ -0,0 +1,228 @@
+//! Contains code to execute modules in-process using LLVM's MCJIT execution engine, for use with versions of LLVM where
+//! the ORC JIT is not available.
+//!
+//! The native target must be initialized before an execution engine is created, such as by calling
+//! [`interop::init::native_target`].
+
+use crate::identifier::Id;
+use crate::interop::llvm_sys as interop;
+use crate::types;
+use llvm_sys::execution_engine::{LLVMExecutionEngineRef, LLVMGenericValueRef};
+use std::os::raw::c_void;
+
+/// Gets a type used to indicate the kind of value stored in a generic value.
+///
+/// Generic values only use types to determine the width of integers and the kind of floating-point values, so types from
+/// the global context can be used regardless of the context of the module being executed.
+fn float_type_reference(float_type: &types::Float) -> llvm_sys::prelude::LLVMTypeRef {
+    unsafe {
+        match float_type {
+            types::Float::Float => llvm_sys::core::LLVMFloatType(),
+            types::Float::Double => llvm_sys::core::LLVMDoubleType(),
+            types::Float::Half => panic!("generic values cannot contain {} values", float_type),
+        }
+    }
+}
+
+/// A wrapper over an LLVM generic value, used to pass arguments to and return values from functions run by an execution
+/// engine.
+pub struct GenericValue {
+    value: std::ptr::NonNull<llvm_sys::execution_engine::LLVMOpaqueGenericValue>,
+}
+
+impl GenericValue {
+    /// Creates a wrapper for the specified generic value.
+    ///
+    /// # Safety
+    /// Callers must ensure that the generic value is valid and has not been disposed.
+    pub unsafe fn from_reference_unchecked(value: LLVMGenericValueRef) -> Self {
+        Self {
+            value: std::ptr::NonNull::new_unchecked(value),
+        }
+    }
+
+    /// Returns the underlying generic value.
+    ///
+    /// # Safety
+    /// Callers must ensure that the wrapper outlives any use of the returned pointer.
+    pub unsafe fn reference(&self) -> LLVMGenericValueRef {
+        self.value.as_ptr()
+    }
+
+    /// Creates a generic value containing an integer of the specified size, which is sign extended if `signed` is `true`.
+    pub fn from_integer(size: types::IntegerSize, value: u64, signed: bool) -> Self {
+        unsafe {
+            Self::from_reference_unchecked(llvm_sys::execution_engine::LLVMCreateGenericValueOfInt(
+                llvm_sys::core::LLVMIntType(size.bits()),
+                value,
+                signed.into(),
+            ))
+        }
+    }
+
+    /// Creates a generic value containing a floating-point value.
+    ///
+    /// # Panics
+    /// Panics if the type is `half`, which is not supported by generic values.
+    pub fn from_float(float_type: types::Float, value: f64) -> Self {
+        unsafe {
+            Self::from_reference_unchecked(
+                llvm_sys::execution_engine::LLVMCreateGenericValueOfFloat(
+                    float_type_reference(&float_type),
+                    value,
+                ),
+            )
+        }
+    }
+
+    /// Creates a generic value containing a pointer.
+    ///
+    /// # Safety
+    /// Callers must ensure that the pointer is valid for any functions that it is passed to.
+    pub unsafe fn from_pointer(pointer: *mut c_void) -> Self {
+        Self::from_reference_unchecked(llvm_sys::execution_engine::LLVMCreateGenericValueOfPointer(
+            pointer,
+        ))
+    }
+
+    /// Gets the width, in bits, of the integer contained in this generic value.
+    pub fn integer_width(&self) -> u32 {
+        unsafe { llvm_sys::execution_engine::LLVMGenericValueIntWidth(self.reference()) }
+    }
+
+    /// Interprets this generic value as an integer, which is sign extended if `signed` is `true`.
+    pub fn to_integer(&self, signed: bool) -> u64 {
+        unsafe {
+            llvm_sys::execution_engine::LLVMGenericValueToInt(self.reference(), signed.into())
+        }
+    }
+
+    /// Interprets this generic value as a floating-point value of the specified type.
+    ///
+    /// # Panics
+    /// Panics if the type is `half`, which is not supported by generic values.
+    pub fn to_float(&self, float_type: types::Float) -> f64 {
+        unsafe {
+            llvm_sys::execution_engine::LLVMGenericValueToFloat(
+                float_type_reference(&float_type),
+                self.reference(),
+            )
+        }
+    }
+
+    /// Interprets this generic value as a pointer.
+    pub fn to_pointer(&self) -> *mut c_void {
+        unsafe { llvm_sys::execution_engine::LLVMGenericValueToPointer(self.reference()) }
+    }
+}
+
+impl std::fmt::Debug for GenericValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("GenericValue").field(&self.value).finish()
+    }
+}
+
+impl std::ops::Drop for GenericValue {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: The generic value is assumed to be valid.
+            llvm_sys::execution_engine::LLVMDisposeGenericValue(self.reference())
+        }
+    }
+}
+
+/// A wrapper over an MCJIT execution engine, which owns the module that it executes.
+pub struct ExecutionEngine<'c> {
+    engine: std::ptr::NonNull<llvm_sys::execution_engine::LLVMOpaqueExecutionEngine>,
+    context: std::marker::PhantomData<&'c interop::Context>,
+}
+
+impl<'c> ExecutionEngine<'c> {
+    /// Creates an execution engine that compiles the specified module for the host machine.
+    pub fn new(module: interop::module::Wrapper<'c>) -> interop::Result<Self> {
+        let mut engine: LLVMExecutionEngineRef = std::ptr::null_mut();
+        let mut error: *mut std::os::raw::c_char = std::ptr::null_mut();
+
+        unsafe {
+            llvm_sys::execution_engine::LLVMLinkInMCJIT();
+
+            let failed = llvm_sys::execution_engine::LLVMCreateExecutionEngineForModule(
+                &mut engine,
+                module.reference(),
+                &mut error,
+            );
+
+            if failed != 0 {
+                // Safety: The module is still owned by the wrapper, and is disposed when it is dropped.
+                Err(interop::Message::from_ptr(error))
+            } else {
+                // Safety: Ownership of the module is transferred to the execution engine.
+                module.into_reference();
+                Ok(Self {
+                    engine: std::ptr::NonNull::new(engine).expect("engine should not be null"),
+                    context: std::marker::PhantomData,
+                })
+            }
+        }
+    }
+
+    /// Returns the underlying execution engine.
+    ///
+    /// # Safety
+    /// Callers must ensure that the wrapper outlives any use of the returned pointer.
+    pub unsafe fn reference(&self) -> LLVMExecutionEngineRef {
+        self.engine.as_ptr()
+    }
+
+    /// Calls the function with the specified name, returning `None` if no function with the name exists.
+    ///
+    /// # Safety
+    /// Callers must ensure that the arguments match the parameters of the function.
+    pub unsafe fn run_function(
+        &self,
+        name: &Id,
+        arguments: &[GenericValue],
+    ) -> Option<GenericValue> {
+        let mut function: llvm_sys::prelude::LLVMValueRef = std::ptr::null_mut();
+        let not_found = llvm_sys::execution_engine::LLVMFindFunction(
+            self.reference(),
+            name.to_c_string().as_ptr(),
+            &mut function,
+        );
+
+        if not_found != 0 {
+            return None;
+        }
+
+        let mut arguments = arguments
+            .iter()
+            .map(|argument| argument.reference())
+            .collect::<Vec<_>>();
+
+        Some(GenericValue::from_reference_unchecked(
+            llvm_sys::execution_engine::LLVMRunFunction(
+                self.reference(),
+                function,
+                arguments.len().try_into().expect("too many arguments"),
+                arguments.as_mut_ptr(),
+            ),
+        ))
+    }
+}
+
+impl std::fmt::Debug for ExecutionEngine<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("ExecutionEngine")
+            .field(&self.engine)
+            .finish()
+    }
+}
+
+impl std::ops::Drop for ExecutionEngine<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: The execution engine was created when the wrapper was created, and disposes the modules it owns.
+            llvm_sys::execution_engine::LLVMDisposeExecutionEngine(self.reference())
+        }
+    }
+}
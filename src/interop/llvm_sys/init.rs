@@ -0,0 +1,42 @@
+//! Contains functions to initialize the targets used by LLVM, which can be safely called multiple times.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Once;
+
+/// Initializes all targets supported by LLVM, including their target information, target machines, assembly printers,
+/// and assembly parsers.
+///
+/// Subsequent calls to this function have no effect.
+pub fn all_targets() {
+    static INITIALIZE: Once = Once::new();
+
+    INITIALIZE.call_once(|| unsafe {
+        // Safety: Initialization is only performed once.
+        llvm_sys::target::LLVM_InitializeAllTargetInfos();
+        llvm_sys::target::LLVM_InitializeAllTargets();
+        llvm_sys::target::LLVM_InitializeAllTargetMCs();
+        llvm_sys::target::LLVM_InitializeAllAsmPrinters();
+        llvm_sys::target::LLVM_InitializeAllAsmParsers();
+    })
+}
+
+/// Initializes the target for the host machine, including its assembly printer and assembly parser, returning `false` if
+/// LLVM does not support the host machine.
+///
+/// Subsequent calls to this function have no effect, and return the same result.
+pub fn native_target() -> bool {
+    static INITIALIZE: Once = Once::new();
+    static SUCCEEDED: AtomicBool = AtomicBool::new(false);
+
+    INITIALIZE.call_once(|| unsafe {
+        // Safety: Initialization is only performed once.
+        // LLVM returns 1 to indicate that the native target is not available.
+        let succeeded = llvm_sys::target::LLVM_InitializeNativeTarget() == 0
+            && llvm_sys::target::LLVM_InitializeNativeAsmPrinter() == 0
+            && llvm_sys::target::LLVM_InitializeNativeAsmParser() == 0;
+
+        SUCCEEDED.store(succeeded, Ordering::Release);
+    });
+
+    SUCCEEDED.load(Ordering::Acquire)
+}
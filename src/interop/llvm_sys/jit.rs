@@ -0,0 +1,130 @@
+//! Contains code to compile and execute modules in-process using LLVM's ORC JIT.
+//!
+//! The native target must be initialized before a JIT is created, such as by calling
+//! [`interop::init::native_target`].
+
+use crate::identifier::Id;
+use crate::interop::llvm_sys as interop;
+use llvm_sys::orc2::lljit::LLVMOrcLLJITRef;
+
+/// A wrapper over an LLJIT instance, which compiles modules for the host machine.
+pub struct Jit {
+    jit: std::ptr::NonNull<llvm_sys::orc2::lljit::LLVMOrcOpaqueLLJIT>,
+}
+
+impl Jit {
+    /// Creates a JIT for the host machine.
+    pub fn new() -> interop::Result<Self> {
+        let mut jit: LLVMOrcLLJITRef = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: A null builder indicates that the default settings are used.
+            let error = llvm_sys::orc2::lljit::LLVMOrcCreateLLJIT(&mut jit, std::ptr::null_mut());
+            if error.is_null() {
+                Ok(Self {
+                    jit: std::ptr::NonNull::new(jit).expect("JIT should not be null"),
+                })
+            } else {
+                Err(interop::Message::from_error(error))
+            }
+        }
+    }
+
+    /// Returns the underlying LLJIT instance.
+    ///
+    /// # Safety
+    /// Callers must ensure that the wrapper outlives any use of the returned pointer.
+    pub unsafe fn reference(&self) -> LLVMOrcLLJITRef {
+        self.jit.as_ptr()
+    }
+
+    /// Lowers the contents of a module and adds it to the JIT, allowing the symbols it defines to be looked up.
+    pub fn add_module(
+        &mut self,
+        module: interop::ModuleBuilder,
+    ) -> Result<(), interop::module::BuildError> {
+        unsafe {
+            // Safety: The context is only used by the module, which keeps the context alive once it is created.
+            let context = llvm_sys::orc2::LLVMOrcCreateNewThreadSafeContext();
+            let module =
+                module.build_in(llvm_sys::orc2::LLVMOrcThreadSafeContextGetContext(context));
+            let thread_safe_module = module.map(|module| {
+                llvm_sys::orc2::LLVMOrcCreateNewThreadSafeModule(module.into_reference(), context)
+            });
+
+            llvm_sys::orc2::LLVMOrcDisposeThreadSafeContext(context);
+
+            // Safety: Ownership of the module is transferred to the JIT.
+            let error = llvm_sys::orc2::lljit::LLVMOrcLLJITAddLLVMIRModule(
+                self.reference(),
+                llvm_sys::orc2::lljit::LLVMOrcLLJITGetMainJITDylib(self.reference()),
+                thread_safe_module?,
+            );
+
+            if error.is_null() {
+                Ok(())
+            } else {
+                Err(interop::module::BuildError::Unknown(
+                    interop::Message::from_error(error),
+                ))
+            }
+        }
+    }
+
+    /// Gets the address of the symbol with the specified name.
+    pub fn lookup_address(&self, name: &Id) -> interop::Result<u64> {
+        let mut address: llvm_sys::orc2::LLVMOrcExecutorAddress = 0;
+
+        unsafe {
+            let error = llvm_sys::orc2::lljit::LLVMOrcLLJITLookup(
+                self.reference(),
+                &mut address,
+                name.to_c_string().as_ptr(),
+            );
+
+            if error.is_null() {
+                Ok(address)
+            } else {
+                Err(interop::Message::from_error(error))
+            }
+        }
+    }
+
+    /// Looks up a function with the specified name, returning a function pointer to the compiled code.
+    ///
+    /// # Safety
+    /// Callers must ensure that `F` is a function pointer type (such as `extern "C" fn(i32) -> i32`) matching the signature
+    /// and calling convention of the function, and that the function pointer is not called after the JIT is dropped.
+    ///
+    /// # Panics
+    /// Panics if `F` is not the size of a pointer.
+    pub unsafe fn lookup_function<F: Copy>(&self, name: &Id) -> interop::Result<F> {
+        assert_eq!(
+            std::mem::size_of::<F>(),
+            std::mem::size_of::<usize>(),
+            "function pointer type must be the size of a pointer"
+        );
+
+        let address = self.lookup_address(name)? as usize;
+        Ok(std::mem::transmute_copy::<usize, F>(&address))
+    }
+}
+
+impl std::fmt::Debug for Jit {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_tuple("Jit").field(&self.jit).finish()
+    }
+}
+
+impl std::ops::Drop for Jit {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: The JIT was created when the wrapper was created.
+            let error = llvm_sys::orc2::lljit::LLVMOrcDisposeLLJIT(self.reference());
+            if !error.is_null() {
+                // Errors cannot be reported while dropping, so the message is discarded.
+                drop(interop::Message::from_error(error));
+            }
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Contains code to handle LLVM error messages.
 
 use crate::identifier::Identifier;
+use std::borrow::Cow;
 use std::ffi::CStr;
 use std::fmt::{Debug, Formatter};
 use std::os::raw::c_char;
@@ -25,6 +26,23 @@ impl Message {
         }
     }
 
+    /// Creates a message containing the description of an LLVM error, consuming the error.
+    ///
+    /// # Safety
+    /// The `error` must be a valid error reference that has not already been consumed.
+    ///
+    /// # Panics
+    /// Will panic if the `error` is `null`, which indicates success.
+    pub unsafe fn from_error(error: llvm_sys::error::LLVMErrorRef) -> Self {
+        assert!(!error.is_null(), "error must not be null");
+
+        // Error messages must be disposed with a different function, so the contents are copied into a new message.
+        let error_message = llvm_sys::error::LLVMGetErrorMessage(error);
+        let message = Self::from_ptr(llvm_sys::core::LLVMCreateMessage(error_message));
+        llvm_sys::error::LLVMDisposeErrorMessage(error_message);
+        message
+    }
+
     /// Returns a pointer to the contents of the message.
     ///
     /// # Safety
@@ -41,19 +59,37 @@ impl Message {
         }
     }
 
-    /// Attempts to copy the contents of this LLVM message into an owned identifier.
+    /// Gets the contents of this LLVM message, excluding the null terminator.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.as_c_str().to_bytes()
+    }
+
+    /// Copies the contents of this LLVM message into an owned identifier, replacing any invalid UTF-8 sequences with
+    /// [`std::char::REPLACEMENT_CHARACTER`].
     pub fn to_identifier(&self) -> Identifier {
         unsafe {
             // Safety: The message is null terminated, so no interior nul bytes are possible.
-            Identifier::new_unchecked(self.to_string().expect("invalid UTF-8 in message"))
+            Identifier::new_unchecked(self.to_string_lossy().into_owned())
         }
     }
 
+    /// Interprets the contents of this LLVM message as a string, replacing any invalid UTF-8 sequences with
+    /// [`std::char::REPLACEMENT_CHARACTER`].
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        self.as_c_str().to_string_lossy()
+    }
+
     /// Copies the contents of this LLVM message into an owned string, returning an error value if the message did not contain
     /// valid UTF-8.
     pub fn to_string(&self) -> Result<String, std::str::Utf8Error> {
         Ok(self.as_c_str().to_str()?.to_string())
     }
+
+    /// Copies the contents of this LLVM message into an owned string and disposes the message, returning an error value if
+    /// the message did not contain valid UTF-8.
+    pub fn into_string(self) -> Result<String, std::str::Utf8Error> {
+        self.to_string()
+    }
 }
 
 impl std::convert::AsRef<CStr> for Message {
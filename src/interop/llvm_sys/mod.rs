@@ -5,11 +5,18 @@
 //! - [Documentation for the LLVM C API](https://llvm.org/doxygen/group__LLVMC.html)
 
 pub mod buffer;
+pub mod context;
+#[cfg(feature = "llvm_sys_jit")]
+pub mod execution_engine;
+pub mod init;
+#[cfg(feature = "llvm_sys_jit")]
+pub mod jit;
 pub mod message;
 pub mod module;
 pub mod target;
 
-pub use buffer::MemoryBuffer;
+pub use buffer::{BorrowedMemoryBuffer, MemoryBuffer};
+pub use context::Context;
 pub use message::Message;
 pub use module::Builder as ModuleBuilder;
 
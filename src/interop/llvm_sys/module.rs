@@ -3,10 +3,17 @@
 use crate::block;
 use crate::global;
 use crate::interop::llvm_sys as interop;
+use crate::metadata;
 use crate::types;
-use crate::Identifier;
+use crate::value;
+use crate::{Id, Identifier};
+use llvm_sys::prelude::{
+    LLVMBasicBlockRef, LLVMBuilderRef, LLVMContextRef, LLVMMetadataRef, LLVMModuleRef, LLVMTypeRef,
+    LLVMValueRef,
+};
 use std::collections::hash_map;
-use std::rc::Rc;
+use std::ffi::CString;
+use std::rc::{Rc, Weak};
 
 pub use llvm_sys::target_machine::LLVMCodeGenFileType as EmitType;
 
@@ -16,9 +23,1327 @@ impl From<global::Linkage> for llvm_sys::LLVMLinkage {
             global::Linkage::Private => Self::LLVMLinkerPrivateLinkage,
             global::Linkage::Internal => Self::LLVMInternalLinkage,
             global::Linkage::AvailableExternally => Self::LLVMAvailableExternallyLinkage,
+            global::Linkage::LinkOnce => Self::LLVMLinkOnceAnyLinkage,
+            global::Linkage::Weak => Self::LLVMWeakAnyLinkage,
+            global::Linkage::Common => Self::LLVMCommonLinkage,
             global::Linkage::Appending => Self::LLVMAppendingLinkage,
+            global::Linkage::ExternWeak => Self::LLVMExternalWeakLinkage,
+            global::Linkage::LinkOnceODR => Self::LLVMLinkOnceODRLinkage,
+            global::Linkage::WeakODR => Self::LLVMWeakODRLinkage,
             global::Linkage::External => Self::LLVMExternalLinkage,
-            _ => todo!("bad linkage {}", linkage),
+        }
+    }
+}
+
+impl From<global::Visibility> for llvm_sys::LLVMVisibility {
+    fn from(visibility: global::Visibility) -> Self {
+        match visibility {
+            global::Visibility::Default => Self::LLVMDefaultVisibility,
+            global::Visibility::Hidden => Self::LLVMHiddenVisibility,
+            global::Visibility::Protected => Self::LLVMProtectedVisibility,
+        }
+    }
+}
+
+impl From<global::ComdatSelectionKind> for llvm_sys::comdat::LLVMComdatSelectionKind {
+    fn from(kind: global::ComdatSelectionKind) -> Self {
+        match kind {
+            global::ComdatSelectionKind::Any => Self::LLVMAnyComdatSelectionKind,
+            global::ComdatSelectionKind::ExactMatch => Self::LLVMExactMatchComdatSelectionKind,
+            global::ComdatSelectionKind::Largest => Self::LLVMLargestComdatSelectionKind,
+            global::ComdatSelectionKind::NoDeduplicate => Self::LLVMNoDuplicatesComdatSelectionKind,
+            global::ComdatSelectionKind::SameSize => Self::LLVMSameSizeComdatSelectionKind,
+        }
+    }
+}
+
+impl From<value::AsmDialect> for llvm_sys::LLVMInlineAsmDialect {
+    fn from(dialect: value::AsmDialect) -> Self {
+        match dialect {
+            value::AsmDialect::ATT => Self::LLVMInlineAsmDialectATT,
+            value::AsmDialect::Intel => Self::LLVMInlineAsmDialectIntel,
+        }
+    }
+}
+
+impl From<block::IntegerBinaryOperation> for llvm_sys::LLVMOpcode {
+    fn from(operation: block::IntegerBinaryOperation) -> Self {
+        match operation {
+            block::IntegerBinaryOperation::Add => Self::LLVMAdd,
+            block::IntegerBinaryOperation::Sub => Self::LLVMSub,
+            block::IntegerBinaryOperation::Mul => Self::LLVMMul,
+            block::IntegerBinaryOperation::UDiv => Self::LLVMUDiv,
+            block::IntegerBinaryOperation::SDiv => Self::LLVMSDiv,
+            block::IntegerBinaryOperation::URem => Self::LLVMURem,
+            block::IntegerBinaryOperation::SRem => Self::LLVMSRem,
+            block::IntegerBinaryOperation::Shl => Self::LLVMShl,
+            block::IntegerBinaryOperation::LShr => Self::LLVMLShr,
+            block::IntegerBinaryOperation::AShr => Self::LLVMAShr,
+            block::IntegerBinaryOperation::And => Self::LLVMAnd,
+            block::IntegerBinaryOperation::Or => Self::LLVMOr,
+            block::IntegerBinaryOperation::Xor => Self::LLVMXor,
+        }
+    }
+}
+
+impl From<block::FloatBinaryOperation> for llvm_sys::LLVMOpcode {
+    fn from(operation: block::FloatBinaryOperation) -> Self {
+        match operation {
+            block::FloatBinaryOperation::Add => Self::LLVMFAdd,
+            block::FloatBinaryOperation::Sub => Self::LLVMFSub,
+            block::FloatBinaryOperation::Mul => Self::LLVMFMul,
+            block::FloatBinaryOperation::Div => Self::LLVMFDiv,
+            block::FloatBinaryOperation::Rem => Self::LLVMFRem,
+        }
+    }
+}
+
+impl From<block::ConversionOperation> for llvm_sys::LLVMOpcode {
+    fn from(operation: block::ConversionOperation) -> Self {
+        match operation {
+            block::ConversionOperation::Trunc => Self::LLVMTrunc,
+            block::ConversionOperation::ZExt => Self::LLVMZExt,
+            block::ConversionOperation::SExt => Self::LLVMSExt,
+            block::ConversionOperation::FPTrunc => Self::LLVMFPTrunc,
+            block::ConversionOperation::FPExt => Self::LLVMFPExt,
+            block::ConversionOperation::FPToUI => Self::LLVMFPToUI,
+            block::ConversionOperation::FPToSI => Self::LLVMFPToSI,
+            block::ConversionOperation::UIToFP => Self::LLVMUIToFP,
+            block::ConversionOperation::SIToFP => Self::LLVMSIToFP,
+        }
+    }
+}
+
+impl From<block::IcmpPredicate> for llvm_sys::LLVMIntPredicate {
+    fn from(predicate: block::IcmpPredicate) -> Self {
+        match predicate {
+            block::IcmpPredicate::Equal => Self::LLVMIntEQ,
+            block::IcmpPredicate::NotEqual => Self::LLVMIntNE,
+            block::IcmpPredicate::UnsignedGreaterThan => Self::LLVMIntUGT,
+            block::IcmpPredicate::UnsignedGreaterOrEqual => Self::LLVMIntUGE,
+            block::IcmpPredicate::UnsignedLessThan => Self::LLVMIntULT,
+            block::IcmpPredicate::UnsignedLessOrEqual => Self::LLVMIntULE,
+            block::IcmpPredicate::SignedGreaterThan => Self::LLVMIntSGT,
+            block::IcmpPredicate::SignedGreaterOrEqual => Self::LLVMIntSGE,
+            block::IcmpPredicate::SignedLessThan => Self::LLVMIntSLT,
+            block::IcmpPredicate::SignedLessOrEqual => Self::LLVMIntSLE,
+        }
+    }
+}
+
+impl From<block::FcmpPredicate> for llvm_sys::LLVMRealPredicate {
+    fn from(predicate: block::FcmpPredicate) -> Self {
+        match predicate {
+            block::FcmpPredicate::False => Self::LLVMRealPredicateFalse,
+            block::FcmpPredicate::OrderedEqual => Self::LLVMRealOEQ,
+            block::FcmpPredicate::OrderedGreaterThan => Self::LLVMRealOGT,
+            block::FcmpPredicate::OrderedGreaterOrEqual => Self::LLVMRealOGE,
+            block::FcmpPredicate::OrderedLessThan => Self::LLVMRealOLT,
+            block::FcmpPredicate::OrderedLessOrEqual => Self::LLVMRealOLE,
+            block::FcmpPredicate::OrderedNotEqual => Self::LLVMRealONE,
+            block::FcmpPredicate::Ordered => Self::LLVMRealORD,
+            block::FcmpPredicate::UnorderedEqual => Self::LLVMRealUEQ,
+            block::FcmpPredicate::UnorderedGreaterThan => Self::LLVMRealUGT,
+            block::FcmpPredicate::UnorderedGreaterOrEqual => Self::LLVMRealUGE,
+            block::FcmpPredicate::UnorderedLessThan => Self::LLVMRealULT,
+            block::FcmpPredicate::UnorderedLessOrEqual => Self::LLVMRealULE,
+            block::FcmpPredicate::UnorderedNotEqual => Self::LLVMRealUNE,
+            block::FcmpPredicate::Unordered => Self::LLVMRealUNO,
+            block::FcmpPredicate::True => Self::LLVMRealPredicateTrue,
+        }
+    }
+}
+
+impl From<global::ThreadLocalMode> for llvm_sys::LLVMThreadLocalMode {
+    fn from(mode: global::ThreadLocalMode) -> Self {
+        match mode {
+            global::ThreadLocalMode::GeneralDynamic => Self::LLVMGeneralDynamicTLSModel,
+            global::ThreadLocalMode::LocalDynamic => Self::LLVMLocalDynamicTLSModel,
+            global::ThreadLocalMode::InitialExec => Self::LLVMInitialExecTLSModel,
+            global::ThreadLocalMode::LocalExec => Self::LLVMLocalExecTLSModel,
+        }
+    }
+}
+
+impl From<block::AtomicOrdering> for llvm_sys::LLVMAtomicOrdering {
+    fn from(ordering: block::AtomicOrdering) -> Self {
+        match ordering {
+            block::AtomicOrdering::Unordered => Self::LLVMAtomicOrderingUnordered,
+            block::AtomicOrdering::Monotonic => Self::LLVMAtomicOrderingMonotonic,
+            block::AtomicOrdering::Acquire => Self::LLVMAtomicOrderingAcquire,
+            block::AtomicOrdering::Release => Self::LLVMAtomicOrderingRelease,
+            block::AtomicOrdering::AcquireRelease => Self::LLVMAtomicOrderingAcquireRelease,
+            block::AtomicOrdering::SequentiallyConsistent => {
+                Self::LLVMAtomicOrderingSequentiallyConsistent
+            }
+        }
+    }
+}
+
+impl From<block::AtomicRmwOperation> for llvm_sys::LLVMAtomicRMWBinOp {
+    fn from(operation: block::AtomicRmwOperation) -> Self {
+        match operation {
+            block::AtomicRmwOperation::Xchg => Self::LLVMAtomicRMWBinOpXchg,
+            block::AtomicRmwOperation::Add => Self::LLVMAtomicRMWBinOpAdd,
+            block::AtomicRmwOperation::Sub => Self::LLVMAtomicRMWBinOpSub,
+            block::AtomicRmwOperation::And => Self::LLVMAtomicRMWBinOpAnd,
+            block::AtomicRmwOperation::Nand => Self::LLVMAtomicRMWBinOpNand,
+            block::AtomicRmwOperation::Or => Self::LLVMAtomicRMWBinOpOr,
+            block::AtomicRmwOperation::Xor => Self::LLVMAtomicRMWBinOpXor,
+            block::AtomicRmwOperation::Max => Self::LLVMAtomicRMWBinOpMax,
+            block::AtomicRmwOperation::Min => Self::LLVMAtomicRMWBinOpMin,
+            block::AtomicRmwOperation::UMax => Self::LLVMAtomicRMWBinOpUMax,
+            block::AtomicRmwOperation::UMin => Self::LLVMAtomicRMWBinOpUMin,
+            block::AtomicRmwOperation::FAdd => Self::LLVMAtomicRMWBinOpFAdd,
+            block::AtomicRmwOperation::FSub => Self::LLVMAtomicRMWBinOpFSub,
+        }
+    }
+}
+
+/// Indicates whether an atomic operation only synchronizes with the current thread.
+///
+/// The C API for LLVM 13 does not allow setting other synchronization scopes.
+fn is_single_thread(scope: &block::SyncScope) -> llvm_sys::prelude::LLVMBool {
+    match scope {
+        block::SyncScope::System => 0,
+        block::SyncScope::SingleThread => 1,
+        block::SyncScope::Named(_) => {
+            panic!(
+                "atomic operations with the scope {}cannot be lowered",
+                scope
+            )
+        }
+    }
+}
+
+/// Sets the ordering and synchronization scope of an atomic `load` or `store`.
+unsafe fn set_atomic(value: LLVMValueRef, atomic: Option<&block::Atomic>) {
+    if let Some(atomic) = atomic {
+        llvm_sys::core::LLVMSetOrdering(value, atomic.ordering.into());
+        llvm_sys::core::LLVMSetAtomicSingleThread(value, is_single_thread(&atomic.scope));
+    }
+}
+
+/// Converts the bit pattern of an IEEE-754 `binary16` value into a double, since the C API only allows the creation of
+/// floating-point constants from doubles.
+fn half_to_double(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1F);
+    let fraction = f64::from(bits & 0x3FF);
+    sign * match exponent {
+        0 => fraction * 2f64.powi(-24),
+        0x1F if fraction == 0.0 => f64::INFINITY,
+        0x1F => f64::NAN,
+        _ => (fraction + 1024.0) * 2f64.powi(exponent - 25),
+    }
+}
+
+/// Creates an LLVM message with the specified contents.
+fn create_message(contents: &str) -> interop::Message {
+    let contents = CString::new(contents).expect("message should not contain nul bytes");
+    unsafe {
+        // Safety: The message is copied by LLVM.
+        interop::Message::from_ptr(llvm_sys::core::LLVMCreateMessage(contents.as_ptr()))
+    }
+}
+
+/// Calls a function that reports errors through the diagnostic handler of the context instead of returning them, returning
+/// the first error that was reported.
+///
+/// Safety: The context must be valid.
+unsafe fn capture_diagnostics<T, F: FnOnce() -> T>(
+    context: llvm_sys::prelude::LLVMContextRef,
+    action: F,
+) -> (T, Option<interop::Message>) {
+    extern "C" fn capture_diagnostic(
+        info: llvm_sys::prelude::LLVMDiagnosticInfoRef,
+        diagnostic_context: *mut std::os::raw::c_void,
+    ) {
+        unsafe {
+            // Safety: The diagnostic context is only set to point to the message while the action is called.
+            let message = &mut *(diagnostic_context as *mut Option<interop::Message>);
+            if message.is_none() {
+                *message = Some(interop::Message::from_ptr(
+                    llvm_sys::core::LLVMGetDiagInfoDescription(info),
+                ));
+            }
+        }
+    }
+
+    let previous_handler = llvm_sys::core::LLVMContextGetDiagnosticHandler(context);
+    let previous_context = llvm_sys::core::LLVMContextGetDiagnosticContext(context);
+    let mut message: Option<interop::Message> = None;
+
+    llvm_sys::core::LLVMContextSetDiagnosticHandler(
+        context,
+        Some(capture_diagnostic),
+        &mut message as *mut Option<interop::Message> as *mut std::os::raw::c_void,
+    );
+
+    let result = action();
+    llvm_sys::core::LLVMContextSetDiagnosticHandler(context, previous_handler, previous_context);
+    (result, message)
+}
+
+/// Sets the alignment of a global value or memory operation, if one is specified.
+///
+/// Safety: The value must be a valid global variable or instruction that accesses memory.
+unsafe fn set_alignment(value: LLVMValueRef, alignment: Option<crate::target::layout::Alignment>) {
+    if let Some(alignment) = alignment {
+        llvm_sys::core::LLVMSetAlignment(value, alignment.bytes().get());
+    }
+}
+
+/// Adds `align` attributes to the arguments of a call instruction whose alignments are known.
+///
+/// Safety: The value must be a valid call instruction in the context.
+unsafe fn set_argument_alignments(
+    context: LLVMContextRef,
+    call: LLVMValueRef,
+    alignments: &[Option<crate::target::layout::Alignment>],
+) {
+    const ALIGN: &str = "align";
+    let kind = llvm_sys::core::LLVMGetEnumAttributeKindForName(
+        ALIGN.as_ptr() as *const std::os::raw::c_char,
+        ALIGN.len(),
+    );
+
+    for (index, alignment) in alignments.iter().enumerate() {
+        if let Some(alignment) = alignment {
+            let attribute = llvm_sys::core::LLVMCreateEnumAttribute(
+                context,
+                kind,
+                u64::from(alignment.bytes().get()),
+            );
+
+            // Attribute indices of arguments start at 1, since 0 refers to the return value.
+            llvm_sys::core::LLVMAddCallSiteAttribute(
+                call,
+                u32::try_from(index + 1).expect("too many arguments"),
+                attribute,
+            );
+        }
+    }
+}
+
+fn unnamed_address(unnamed_address: Option<global::UnnamedAddress>) -> llvm_sys::LLVMUnnamedAddr {
+    match unnamed_address {
+        None => llvm_sys::LLVMUnnamedAddr::LLVMNoUnnamedAddr,
+        Some(global::UnnamedAddress::Local) => llvm_sys::LLVMUnnamedAddr::LLVMLocalUnnamedAddr,
+        Some(global::UnnamedAddress::Global) => llvm_sys::LLVMUnnamedAddr::LLVMGlobalUnnamedAddr,
+    }
+}
+
+/// Sets the section and comdat of a global variable or function, if they are specified.
+///
+/// # Safety
+/// Callers must ensure that the module and global value are valid.
+unsafe fn set_placement(
+    module: LLVMModuleRef,
+    value: LLVMValueRef,
+    section: Option<Identifier>,
+    comdat: Option<global::Comdat>,
+) {
+    if let Some(section) = section {
+        llvm_sys::core::LLVMSetSection(value, section.into_c_string().as_ptr());
+    }
+
+    if let Some(comdat) = comdat {
+        let comdat_reference =
+            llvm_sys::comdat::LLVMGetOrInsertComdat(module, comdat.symbol().as_c_str().as_ptr());
+        llvm_sys::comdat::LLVMSetComdatSelectionKind(
+            comdat_reference,
+            comdat.selection_kind().into(),
+        );
+        llvm_sys::comdat::LLVMSetComdat(value, comdat_reference);
+    }
+}
+
+/// Contains the state used to translate the contents of a module into calls to the LLVM C APIs.
+/// Gets the LLVM type corresponding to a type in the specified context.
+///
+/// # Safety
+/// Callers must ensure that the context is valid.
+pub(crate) unsafe fn type_reference(context: LLVMContextRef, t: &types::FirstClass) -> LLVMTypeRef {
+    match t {
+        types::FirstClass::Single(single_value_type) => match single_value_type {
+            types::SingleValue::Integer(integer_size) => {
+                llvm_sys::core::LLVMIntTypeInContext(context, integer_size.bits())
+            }
+            types::SingleValue::Float(types::Float::Half) => {
+                llvm_sys::core::LLVMHalfTypeInContext(context)
+            }
+            types::SingleValue::Float(types::Float::Float) => {
+                llvm_sys::core::LLVMFloatTypeInContext(context)
+            }
+            types::SingleValue::Float(types::Float::Double) => {
+                llvm_sys::core::LLVMDoubleTypeInContext(context)
+            }
+            types::SingleValue::Pointer(pointer) => llvm_sys::core::LLVMPointerType(
+                match pointer.pointee() {
+                    types::Pointee::FirstClass(pointee_type) => {
+                        type_reference(context, pointee_type)
+                    }
+                    types::Pointee::Function(signature) => {
+                        function_type_reference(context, signature)
+                    }
+                },
+                pointer.address_space().0,
+            ),
+            types::SingleValue::Vector(vector) => llvm_sys::core::LLVMVectorType(
+                type_reference(context, vector.element_type()),
+                vector.count(),
+            ),
+        },
+        types::FirstClass::Aggregate(types::Aggregate::Array(array)) => {
+            llvm_sys::core::LLVMArrayType(
+                type_reference(context, array.element_type()),
+                array.count(),
+            )
+        }
+        types::FirstClass::Aggregate(types::Aggregate::Struct(structure)) => {
+            let mut member_types = structure
+                .member_types()
+                .iter()
+                .map(|member_type| type_reference(context, member_type))
+                .collect::<Vec<_>>();
+
+            llvm_sys::core::LLVMStructTypeInContext(
+                context,
+                member_types.as_mut_ptr(),
+                member_types.len().try_into().expect("too many members"),
+                structure.is_packed().into(),
+            )
+        }
+        types::FirstClass::Token => llvm_sys::core::LLVMTokenTypeInContext(context),
+        types::FirstClass::Metadata => llvm_sys::core::LLVMMetadataTypeInContext(context),
+    }
+}
+
+/// Gets the LLVM function type corresponding to a function type in the specified context.
+///
+/// # Safety
+/// Callers must ensure that the context is valid.
+unsafe fn function_type_reference(
+    context: LLVMContextRef,
+    function_type: &types::Function,
+) -> LLVMTypeRef {
+    let return_type = match function_type.return_type() {
+        types::Return::Void => llvm_sys::core::LLVMVoidTypeInContext(context),
+        types::Return::FirstClass(actual_return_type) => {
+            type_reference(context, actual_return_type)
+        }
+    };
+
+    let mut parameter_types = function_type
+        .parameter_types()
+        .iter()
+        .map(|parameter_type| type_reference(context, parameter_type))
+        .collect::<Vec<_>>();
+
+    llvm_sys::core::LLVMFunctionType(
+        return_type,
+        parameter_types.as_mut_ptr(),
+        parameter_types
+            .len()
+            .try_into()
+            .expect("too many parameters"),
+        function_type.is_variadic().into(),
+    )
+}
+
+struct Lowering {
+    context: LLVMContextRef,
+    builder: LLVMBuilderRef,
+    empty_name: CString,
+    types: hash_map::HashMap<Rc<types::FirstClass>, LLVMTypeRef>,
+    function_types: hash_map::HashMap<Rc<types::Function>, LLVMTypeRef>,
+    /// Maps the registers of the function currently being lowered to their values.
+    registers: hash_map::HashMap<value::Register, LLVMValueRef>,
+    /// Maps the basic blocks of the function currently being lowered to their LLVM counterparts.
+    blocks: hash_map::HashMap<*const block::BasicBlock, LLVMBasicBlockRef>,
+    /// The `phi` instructions of the function currently being lowered, whose incoming values are added after all blocks are
+    /// lowered, since the incoming values may be defined in blocks that come later.
+    phis: Vec<(LLVMValueRef, Vec<(value::Value, block::BlockReference)>)>,
+    /// Maps the functions that were added to the module to their values.
+    ///
+    /// The weak references ensure that the address of a function is not reused after it is dropped, allowing functions
+    /// that were already lowered to be dropped while still being callable by functions that are lowered later.
+    functions: hash_map::HashMap<*const global::Function, (Weak<global::Function>, LLVMValueRef)>,
+    /// Maps the global variables that were added to the module to their values, allowing their addresses to be used.
+    variables: hash_map::HashMap<*const global::Variable, LLVMValueRef>,
+    /// Maps the indirect functions that were added to the module to their values.
+    ifuncs: hash_map::HashMap<*const global::IFunc, LLVMValueRef>,
+    /// The blocks of functions that were appended before the functions were lowered, since the addresses of the blocks
+    /// were used.
+    appended_blocks: hash_map::HashMap<*const global::Function, Vec<LLVMBasicBlockRef>>,
+}
+
+impl Lowering {
+    /// Safety: The context must be valid for the lifetime of the returned value.
+    unsafe fn new(context: LLVMContextRef) -> Self {
+        Self {
+            context,
+            // Safety: Builder is disposed when the lowering state is dropped.
+            builder: llvm_sys::core::LLVMCreateBuilderInContext(context),
+            empty_name: CString::default(),
+            types: hash_map::HashMap::new(),
+            function_types: hash_map::HashMap::new(),
+            registers: hash_map::HashMap::new(),
+            blocks: hash_map::HashMap::new(),
+            phis: Vec::new(),
+            functions: hash_map::HashMap::new(),
+            variables: hash_map::HashMap::new(),
+            ifuncs: hash_map::HashMap::new(),
+            appended_blocks: hash_map::HashMap::new(),
+        }
+    }
+
+    unsafe fn get_type(&mut self, t: &Rc<types::FirstClass>) -> LLVMTypeRef {
+        if let Some(existing) = self.types.get(t) {
+            return *existing;
+        }
+
+        let type_reference = type_reference(self.context, t);
+        self.types.insert(t.clone(), type_reference);
+        type_reference
+    }
+
+    unsafe fn get_function_type(&mut self, function_type: &Rc<types::Function>) -> LLVMTypeRef {
+        if let Some(existing) = self.function_types.get(function_type) {
+            return *existing;
+        }
+
+        let type_reference = function_type_reference(self.context, function_type);
+        self.function_types
+            .insert(function_type.clone(), type_reference);
+        type_reference
+    }
+
+    unsafe fn get_value(&mut self, value: &value::Value) -> LLVMValueRef {
+        match value {
+            value::Value::Integer(integer) => {
+                let words = integer.words();
+                llvm_sys::core::LLVMConstIntOfArbitraryPrecision(
+                    self.get_type(&value.value_type()),
+                    words.len().try_into().expect("integer value is too large"),
+                    words.as_ptr(),
+                )
+            }
+            value::Value::Float(float) => {
+                let float_value = match float {
+                    value::Float::Half(bits) => half_to_double(*bits),
+                    value::Float::Float(value) => f64::from(*value),
+                    value::Float::Double(value) => *value,
+                };
+
+                llvm_sys::core::LLVMConstReal(self.get_type(&value.value_type()), float_value)
+            }
+            value::Value::Null(_) => {
+                llvm_sys::core::LLVMConstPointerNull(self.get_type(&value.value_type()))
+            }
+            value::Value::Aggregate(aggregate) => {
+                let mut elements = aggregate
+                    .elements()
+                    .iter()
+                    .map(|element| self.get_value(element))
+                    .collect::<Vec<_>>();
+
+                let length = elements.len().try_into().expect("too many elements");
+                match aggregate.aggregate_type() {
+                    types::Aggregate::Array(array) => llvm_sys::core::LLVMConstArray(
+                        self.get_type(array.element_type()),
+                        elements.as_mut_ptr(),
+                        length,
+                    ),
+                    types::Aggregate::Struct(structure) => {
+                        llvm_sys::core::LLVMConstStructInContext(
+                            self.context,
+                            elements.as_mut_ptr(),
+                            length,
+                            structure.is_packed().into(),
+                        )
+                    }
+                }
+            }
+            value::Value::Vector(vector) => {
+                let mut elements = vector
+                    .elements()
+                    .iter()
+                    .map(|element| self.get_value(element))
+                    .collect::<Vec<_>>();
+
+                llvm_sys::core::LLVMConstVector(
+                    elements.as_mut_ptr(),
+                    elements.len().try_into().expect("too many elements"),
+                )
+            }
+            value::Value::BlockAddress(address) => self.get_block_address(address),
+            value::Value::GlobalAddress(address) => {
+                let global = if let Some(function) = address.function_ptr() {
+                    self.functions.get(&function).map(|(_, global)| *global)
+                } else if let Some(variable) = address.variable_ptr() {
+                    self.variables.get(&variable).copied()
+                } else if let Some(ifunc) = address.ifunc_ptr() {
+                    self.ifuncs.get(&ifunc).copied()
+                } else {
+                    None
+                };
+
+                match global {
+                    Some(global) => global,
+                    None => panic!(
+                        "address of @{} was used before it was added to the module",
+                        address.symbol()
+                    ),
+                }
+            }
+            value::Value::Register(register) => match self.registers.get(register) {
+                Some(register_value) => *register_value,
+                None => panic!("register {} was used before it was defined", register),
+            },
+        }
+    }
+
+    /// Gets the address of a block, which may be contained in a function other than the one currently being lowered.
+    unsafe fn get_block_address(&mut self, address: &value::BlockAddress) -> LLVMValueRef {
+        let function = match address.function() {
+            Some(function) => function,
+            None => panic!(
+                "the address of block {} was used after @{} was dropped",
+                address.block(),
+                address.function_name()
+            ),
+        };
+
+        let function_reference = match self.functions.get(&address.function_ptr()) {
+            Some((_, function_reference)) => *function_reference,
+            None => panic!(
+                "@{} must be added to the module before the addresses of its blocks are used",
+                function.name()
+            ),
+        };
+
+        let index = function
+            .basic_blocks()
+            .iter()
+            .position(|block| Rc::as_ptr(block) == address.block().as_ptr())
+            .unwrap_or_else(|| panic!("block {} is not in @{}", address.block(), function.name()));
+
+        // Blocks of functions that were not lowered yet are appended early, and reused when the function is lowered.
+        if llvm_sys::core::LLVMCountBasicBlocks(function_reference) == 0 {
+            let blocks = self.append_blocks(&function, function_reference);
+            self.appended_blocks.insert(Rc::as_ptr(&function), blocks);
+        }
+
+        let mut block_reference = llvm_sys::core::LLVMGetFirstBasicBlock(function_reference);
+        for _ in 0..index {
+            block_reference = llvm_sys::core::LLVMGetNextBasicBlock(block_reference);
+        }
+
+        llvm_sys::core::LLVMBlockAddress(function_reference, block_reference)
+    }
+
+    fn get_block(&self, block: &block::BlockReference) -> LLVMBasicBlockRef {
+        match self.blocks.get(&block.as_ptr()) {
+            Some(block_reference) => *block_reference,
+            None => panic!("block {} is not defined in the current function", block),
+        }
+    }
+
+    unsafe fn lower_instruction(&mut self, instruction: &block::Instruction) {
+        use block::Instruction as Instr;
+
+        match instruction {
+            Instr::Ret(None) => {
+                llvm_sys::core::LLVMBuildRetVoid(self.builder);
+            }
+            Instr::Ret(Some(return_value)) => {
+                let return_value = self.get_value(return_value);
+                llvm_sys::core::LLVMBuildRet(self.builder, return_value);
+            }
+            Instr::Br(target) => {
+                llvm_sys::core::LLVMBuildBr(self.builder, self.get_block(target));
+            }
+            Instr::CondBr {
+                condition,
+                true_target,
+                false_target,
+            } => {
+                let condition = self.get_value(condition);
+                llvm_sys::core::LLVMBuildCondBr(
+                    self.builder,
+                    condition,
+                    self.get_block(true_target),
+                    self.get_block(false_target),
+                );
+            }
+            Instr::Switch {
+                value,
+                default_target,
+                cases,
+            } => {
+                let value = self.get_value(value);
+                let switch = llvm_sys::core::LLVMBuildSwitch(
+                    self.builder,
+                    value,
+                    self.get_block(default_target),
+                    cases.len().try_into().expect("too many switch cases"),
+                );
+
+                for (case_value, target) in cases.iter() {
+                    let case_value = self.get_value(case_value);
+                    llvm_sys::core::LLVMAddCase(switch, case_value, self.get_block(target));
+                }
+            }
+            Instr::IndirectBr {
+                address,
+                destinations,
+            } => {
+                let address = self.get_value(address);
+                let indirect_branch = llvm_sys::core::LLVMBuildIndirectBr(
+                    self.builder,
+                    address,
+                    destinations
+                        .len()
+                        .try_into()
+                        .expect("too many indirectbr destinations"),
+                );
+
+                for destination in destinations.iter() {
+                    llvm_sys::core::LLVMAddDestination(
+                        indirect_branch,
+                        self.get_block(destination),
+                    );
+                }
+            }
+            Instr::Phi { incoming, result } => {
+                let value = llvm_sys::core::LLVMBuildPhi(
+                    self.builder,
+                    self.get_type(result.value_type()),
+                    self.empty_name.as_ptr(),
+                );
+
+                self.phis.push((value, incoming.clone()));
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::Call {
+                tail_call,
+                calling_convention,
+                callee,
+                signature,
+                arguments,
+                argument_alignments,
+                result,
+            } => {
+                let function_type = self.get_function_type(signature);
+                let callee = match callee {
+                    block::Callee::Function { function, name } => {
+                        match self.functions.get(&function.as_ptr()) {
+                            Some((_, function)) => *function,
+                            _ => panic!("callee @{} was not added to the module", name.as_id()),
+                        }
+                    }
+                    block::Callee::Pointer(pointer) => {
+                        let address_space = match pointer.value_type().as_ref() {
+                            types::FirstClass::Single(types::SingleValue::Pointer(pointer)) => {
+                                pointer.address_space()
+                            }
+                            _ => panic!("expected pointer value for callee, but got {}", pointer),
+                        };
+
+                        // Pointers to other types, such as to a function with a different signature, are converted.
+                        let pointer_type =
+                            llvm_sys::core::LLVMPointerType(function_type, address_space.0);
+                        let pointer = self.get_value(pointer);
+                        if llvm_sys::core::LLVMTypeOf(pointer) == pointer_type {
+                            pointer
+                        } else {
+                            llvm_sys::core::LLVMBuildBitCast(
+                                self.builder,
+                                pointer,
+                                pointer_type,
+                                self.empty_name.as_ptr(),
+                            )
+                        }
+                    }
+                    block::Callee::InlineAsm(assembly) => {
+                        let mut assembly_string = assembly.assembly().as_bytes().to_vec();
+                        let mut constraints = assembly.constraints().as_bytes().to_vec();
+                        llvm_sys::core::LLVMGetInlineAsm(
+                            function_type,
+                            assembly_string.as_mut_ptr() as *mut std::os::raw::c_char,
+                            assembly_string.len(),
+                            constraints.as_mut_ptr() as *mut std::os::raw::c_char,
+                            constraints.len(),
+                            assembly.has_side_effects().into(),
+                            assembly.is_align_stack().into(),
+                            assembly.dialect().into(),
+                            0,
+                        )
+                    }
+                };
+
+                let mut arguments = arguments
+                    .iter()
+                    .map(|argument| self.get_value(argument))
+                    .collect::<Vec<_>>();
+
+                let value = llvm_sys::core::LLVMBuildCall2(
+                    self.builder,
+                    function_type,
+                    callee,
+                    arguments.as_mut_ptr(),
+                    arguments.len().try_into().expect("too many arguments"),
+                    self.empty_name.as_ptr(),
+                );
+
+                llvm_sys::core::LLVMSetInstructionCallConv(value, calling_convention.value());
+                set_argument_alignments(self.context, value, argument_alignments);
+
+                // The C API for LLVM 13 only allows setting the tail marker, since LLVMSetTailCallKind was added in LLVM 18.
+                match tail_call {
+                    block::TailCallKind::None => (),
+                    block::TailCallKind::Tail => llvm_sys::core::LLVMSetTailCall(value, 1),
+                    block::TailCallKind::MustTail | block::TailCallKind::NoTail => {
+                        panic!("{}calls cannot be lowered", tail_call)
+                    }
+                }
+
+                if let Some(result) = result {
+                    self.registers.insert(result.clone(), value);
+                }
+            }
+            // The C API for LLVM 13 does not provide a way to build callbr instructions.
+            Instr::CallBr { .. } => panic!("callbr instructions cannot be lowered"),
+            Instr::IntegerBinary {
+                operation,
+                x,
+                y,
+                result,
+            } => {
+                let x = self.get_value(x);
+                let y = self.get_value(y);
+                let value = llvm_sys::core::LLVMBuildBinOp(
+                    self.builder,
+                    (*operation).into(),
+                    x,
+                    y,
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            // Fast-math flags cannot be set with the C API of LLVM 13, and are omitted since they only allow additional
+            // optimizations.
+            Instr::FloatBinary {
+                operation,
+                x,
+                y,
+                result,
+                ..
+            } => {
+                let x = self.get_value(x);
+                let y = self.get_value(y);
+                let value = llvm_sys::core::LLVMBuildBinOp(
+                    self.builder,
+                    (*operation).into(),
+                    x,
+                    y,
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::FNeg {
+                operand, result, ..
+            } => {
+                let operand = self.get_value(operand);
+                let value =
+                    llvm_sys::core::LLVMBuildFNeg(self.builder, operand, self.empty_name.as_ptr());
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::ICmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => {
+                let x = self.get_value(x);
+                let y = self.get_value(y);
+                let value = llvm_sys::core::LLVMBuildICmp(
+                    self.builder,
+                    (*predicate).into(),
+                    x,
+                    y,
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::FCmp {
+                predicate,
+                x,
+                y,
+                result,
+            } => {
+                let x = self.get_value(x);
+                let y = self.get_value(y);
+                let value = llvm_sys::core::LLVMBuildFCmp(
+                    self.builder,
+                    (*predicate).into(),
+                    x,
+                    y,
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::Conversion {
+                operation,
+                operand,
+                result,
+            } => {
+                let operand = self.get_value(operand);
+                let value = llvm_sys::core::LLVMBuildCast(
+                    self.builder,
+                    (*operation).into(),
+                    operand,
+                    self.get_type(result.value_type()),
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::VaArg { list, result } => {
+                let list = self.get_value(list);
+                let value = llvm_sys::core::LLVMBuildVAArg(
+                    self.builder,
+                    list,
+                    self.get_type(result.value_type()),
+                    self.empty_name.as_ptr(),
+                );
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::Alloca {
+                allocated_type,
+                count,
+                alignment,
+                result,
+            } => {
+                let allocated_type = self.get_type(allocated_type);
+                let value = match count {
+                    Some(element_count) => {
+                        let element_count = self.get_value(element_count);
+                        llvm_sys::core::LLVMBuildArrayAlloca(
+                            self.builder,
+                            allocated_type,
+                            element_count,
+                            self.empty_name.as_ptr(),
+                        )
+                    }
+                    None => llvm_sys::core::LLVMBuildAlloca(
+                        self.builder,
+                        allocated_type,
+                        self.empty_name.as_ptr(),
+                    ),
+                };
+
+                set_alignment(value, *alignment);
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::Load {
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+                result,
+            } => {
+                let loaded_type = self.get_type(result.value_type());
+                let pointer = self.get_value(pointer);
+                let value = llvm_sys::core::LLVMBuildLoad2(
+                    self.builder,
+                    loaded_type,
+                    pointer,
+                    self.empty_name.as_ptr(),
+                );
+
+                set_alignment(value, *alignment);
+                llvm_sys::core::LLVMSetVolatile(value, (*volatile).into());
+                set_atomic(value, atomic.as_ref());
+                self.registers.insert(result.clone(), value);
+            }
+            Instr::Store {
+                value,
+                pointer,
+                alignment,
+                volatile,
+                atomic,
+            } => {
+                let value = self.get_value(value);
+                let pointer = self.get_value(pointer);
+                let store = llvm_sys::core::LLVMBuildStore(self.builder, value, pointer);
+                set_alignment(store, *alignment);
+                llvm_sys::core::LLVMSetVolatile(store, (*volatile).into());
+                set_atomic(store, atomic.as_ref());
+            }
+            Instr::AtomicRmw {
+                operation,
+                volatile,
+                pointer,
+                value,
+                atomic,
+                result,
+            } => {
+                let pointer = self.get_value(pointer);
+                let value = self.get_value(value);
+                let modify = llvm_sys::core::LLVMBuildAtomicRMW(
+                    self.builder,
+                    (*operation).into(),
+                    pointer,
+                    value,
+                    atomic.ordering.into(),
+                    is_single_thread(&atomic.scope),
+                );
+
+                llvm_sys::core::LLVMSetVolatile(modify, (*volatile).into());
+                self.registers.insert(result.clone(), modify);
+            }
+            Instr::CmpXchg {
+                weak,
+                volatile,
+                pointer,
+                expected,
+                replacement,
+                success,
+                failure_ordering,
+                result,
+            } => {
+                let pointer = self.get_value(pointer);
+                let expected = self.get_value(expected);
+                let replacement = self.get_value(replacement);
+                let exchange = llvm_sys::core::LLVMBuildAtomicCmpXchg(
+                    self.builder,
+                    pointer,
+                    expected,
+                    replacement,
+                    success.ordering.into(),
+                    (*failure_ordering).into(),
+                    is_single_thread(&success.scope),
+                );
+
+                llvm_sys::core::LLVMSetWeak(exchange, (*weak).into());
+                llvm_sys::core::LLVMSetVolatile(exchange, (*volatile).into());
+                self.registers.insert(result.clone(), exchange);
+            }
+            Instr::Fence(atomic) => {
+                llvm_sys::core::LLVMBuildFence(
+                    self.builder,
+                    atomic.ordering.into(),
+                    is_single_thread(&atomic.scope),
+                    self.empty_name.as_ptr(),
+                );
+            }
+            Instr::GetElementPtr {
+                inbounds,
+                element_type,
+                pointer,
+                indices,
+                result,
+            } => {
+                let element_type = self.get_type(element_type);
+
+                let pointer = self.get_value(pointer);
+                let mut indices = indices
+                    .iter()
+                    .map(|index| self.get_value(index))
+                    .collect::<Vec<_>>();
+
+                let build = if *inbounds {
+                    llvm_sys::core::LLVMBuildInBoundsGEP2
+                } else {
+                    llvm_sys::core::LLVMBuildGEP2
+                };
+
+                let value = build(
+                    self.builder,
+                    element_type,
+                    pointer,
+                    indices.as_mut_ptr(),
+                    indices.len().try_into().expect("too many indices"),
+                    self.empty_name.as_ptr(),
+                );
+
+                self.registers.insert(result.clone(), value);
+            }
+        }
+    }
+
+    unsafe fn lower_block(&mut self, block: &block::BasicBlock) {
+        let block_reference = self.blocks[&(block as *const block::BasicBlock)];
+        llvm_sys::core::LLVMPositionBuilderAtEnd(self.builder, block_reference);
+
+        let metadata = block.metadata();
+        for (index, instruction) in block.instructions().iter().enumerate() {
+            self.lower_instruction(instruction);
+
+            if let Some(result) = instruction.result() {
+                if let Some(name) = result.name() {
+                    llvm_sys::core::LLVMSetValueName2(
+                        self.registers[result],
+                        name.as_ptr() as *const _,
+                        name.len(),
+                    );
+                }
+            }
+
+            for (_, kind, node) in metadata.iter().filter(|(i, _, _)| *i == index) {
+                let instruction_reference = llvm_sys::core::LLVMGetLastInstruction(block_reference);
+                let node =
+                    llvm_sys::core::LLVMMetadataAsValue(self.context, self.get_metadata_node(node));
+                llvm_sys::core::LLVMSetMetadata(
+                    instruction_reference,
+                    llvm_sys::core::LLVMGetMDKindIDInContext(
+                        self.context,
+                        kind.as_ptr() as *const _,
+                        kind.len()
+                            .try_into()
+                            .expect("metadata kind name is too long"),
+                    ),
+                    node,
+                );
+            }
+        }
+    }
+
+    unsafe fn get_metadata_node(&mut self, node: &metadata::Node) -> LLVMMetadataRef {
+        let mut operands = node
+            .operands()
+            .iter()
+            .map(|operand| match operand {
+                Some(metadata::Metadata::String(string)) => llvm_sys::core::LLVMMDStringInContext2(
+                    self.context,
+                    string.as_ptr() as *const _,
+                    string.len(),
+                ),
+                Some(metadata::Metadata::Value(value)) => {
+                    llvm_sys::core::LLVMValueAsMetadata(self.get_value(value))
+                }
+                Some(metadata::Metadata::Node(node)) => self.get_metadata_node(node),
+                None => std::ptr::null_mut(),
+            })
+            .collect::<Vec<_>>();
+
+        llvm_sys::core::LLVMMDNodeInContext2(self.context, operands.as_mut_ptr(), operands.len())
+    }
+
+    /// Adds a global variable to the module without its initializer, allowing its address to be used.
+    unsafe fn add_variable(
+        &mut self,
+        module: LLVMModuleRef,
+        variable: &global::Variable,
+    ) -> LLVMValueRef {
+        let variable_reference = llvm_sys::core::LLVMAddGlobal(
+            module,
+            self.get_type(variable.value_type()),
+            variable.symbol().as_c_str().as_ptr(),
+        );
+
+        self.variables.insert(variable, variable_reference);
+        variable_reference
+    }
+
+    unsafe fn lower_variable(&mut self, module: LLVMModuleRef, variable: &global::Variable) {
+        let variable_reference = match self.variables.get(&(variable as *const _)) {
+            Some(existing) => *existing,
+            None => self.add_variable(module, variable),
+        };
+
+        if let Some(initializer) = variable.get_initializer() {
+            let initializer = self.get_value(&initializer);
+            llvm_sys::core::LLVMSetInitializer(variable_reference, initializer);
+        }
+
+        llvm_sys::core::LLVMSetGlobalConstant(variable_reference, variable.is_constant().into());
+        llvm_sys::core::LLVMSetLinkage(variable_reference, variable.get_linkage().into());
+        llvm_sys::core::LLVMSetVisibility(variable_reference, variable.get_visibility().into());
+        llvm_sys::core::LLVMSetUnnamedAddress(
+            variable_reference,
+            unnamed_address(variable.get_unnamed_address()),
+        );
+        set_placement(
+            module,
+            variable_reference,
+            variable.get_section(),
+            variable.get_comdat(),
+        );
+        set_alignment(variable_reference, variable.get_alignment());
+
+        if let Some(mode) = variable.get_thread_local_mode() {
+            llvm_sys::core::LLVMSetThreadLocalMode(variable_reference, mode.into());
+        }
+
+        llvm_sys::core::LLVMSetExternallyInitialized(
+            variable_reference,
+            variable.get_externally_initialized().into(),
+        );
+    }
+
+    /// Adds a function to the module, allowing it to be called by the functions that are lowered.
+    unsafe fn add_function(&mut self, module: LLVMModuleRef, function: &Rc<global::Function>) {
+        let function_reference = llvm_sys::core::LLVMAddFunction(
+            module,
+            function.symbol().as_c_str().as_ptr(),
+            self.get_function_type(function.signature()),
+        );
+
+        llvm_sys::core::LLVMSetFunctionCallConv(
+            function_reference,
+            function.get_calling_convention().value(),
+        );
+
+        llvm_sys::core::LLVMSetLinkage(function_reference, function.get_linkage().into());
+        llvm_sys::core::LLVMSetVisibility(function_reference, function.get_visibility().into());
+        llvm_sys::core::LLVMSetUnnamedAddress(
+            function_reference,
+            unnamed_address(function.get_unnamed_address()),
+        );
+        // The C API does not provide a way to mark globals as dso_local, so it is only included in the textual output.
+        set_placement(
+            module,
+            function_reference,
+            function.get_section(),
+            function.get_comdat(),
+        );
+        set_alignment(function_reference, function.get_alignment());
+
+        for (index, parameter) in function.parameters().iter().enumerate() {
+            if let Some(name) = parameter.name() {
+                llvm_sys::core::LLVMSetValueName2(
+                    llvm_sys::core::LLVMGetParam(
+                        function_reference,
+                        index.try_into().expect("too many parameters"),
+                    ),
+                    name.as_ptr() as *const _,
+                    name.len(),
+                );
+            }
+        }
+
+        self.functions.insert(
+            Rc::as_ptr(function),
+            (Rc::downgrade(function), function_reference),
+        );
+    }
+
+    /// Adds an indirect function to the module, whose resolver must already have been added.
+    unsafe fn add_ifunc(&mut self, module: LLVMModuleRef, ifunc: &global::IFunc) {
+        let resolver = match self.functions.get(&Rc::as_ptr(&ifunc.resolver())) {
+            Some((_, resolver)) => *resolver,
+            None => panic!(
+                "resolver @{} of @{} must be added to the module before the indirect function",
+                ifunc.resolver().name(),
+                ifunc.name()
+            ),
+        };
+
+        let name = ifunc.symbol().as_c_str();
+        let ifunc_reference = llvm_sys::core::LLVMAddGlobalIFunc(
+            module,
+            name.as_ptr(),
+            name.to_bytes().len(),
+            self.get_function_type(ifunc.signature()),
+            0,
+            resolver,
+        );
+
+        llvm_sys::core::LLVMSetLinkage(ifunc_reference, ifunc.get_linkage().into());
+        llvm_sys::core::LLVMSetVisibility(ifunc_reference, ifunc.get_visibility().into());
+        self.ifuncs.insert(ifunc, ifunc_reference);
+    }
+
+    /// Appends all of the blocks of a function, without lowering their instructions.
+    unsafe fn append_blocks(
+        &self,
+        function: &global::Function,
+        function_reference: LLVMValueRef,
+    ) -> Vec<LLVMBasicBlockRef> {
+        function
+            .basic_blocks()
+            .iter()
+            .map(|block| {
+                // Unnamed blocks are left unnamed, so that LLVM numbers them in the same way as the textual output.
+                let block_name = match block.name() {
+                    Some(_) => block
+                        .label()
+                        .expect("blocks in a function should have a label")
+                        .into_c_string(),
+                    None => CString::default(),
+                };
+
+                llvm_sys::core::LLVMAppendBasicBlockInContext(
+                    self.context,
+                    function_reference,
+                    block_name.as_ptr(),
+                )
+            })
+            .collect()
+    }
+
+    unsafe fn lower_function(&mut self, function: &Rc<global::Function>) {
+        let function_reference = self.functions[&Rc::as_ptr(function)].1;
+
+        // All blocks are appended first, since branches may refer to blocks that come later.
+        let block_references = match self.appended_blocks.remove(&Rc::as_ptr(function)) {
+            Some(block_references) => block_references,
+            None if llvm_sys::core::LLVMCountBasicBlocks(function_reference) > 0 => {
+                panic!("function @{} was already lowered", function.name())
+            }
+            None => self.append_blocks(function, function_reference),
+        };
+
+        // Registers and blocks are local to each function.
+        self.registers.clear();
+        self.blocks.clear();
+
+        for (index, parameter) in function.parameters().iter().enumerate() {
+            self.registers.insert(
+                parameter.clone(),
+                llvm_sys::core::LLVMGetParam(
+                    function_reference,
+                    index.try_into().expect("too many parameters"),
+                ),
+            );
+        }
+
+        let basic_blocks = function.basic_blocks();
+
+        for (block, block_reference) in basic_blocks.iter().zip(block_references) {
+            self.blocks.insert(Rc::as_ptr(block), block_reference);
+        }
+
+        for block in basic_blocks.iter() {
+            self.lower_block(block);
+        }
+
+        for (phi, incoming) in std::mem::take(&mut self.phis).into_iter() {
+            let (mut values, mut blocks): (Vec<_>, Vec<_>) = incoming
+                .iter()
+                .map(|(value, block)| (self.get_value(value), self.get_block(block)))
+                .unzip();
+
+            llvm_sys::core::LLVMAddIncoming(
+                phi,
+                values.as_mut_ptr(),
+                blocks.as_mut_ptr(),
+                values.len().try_into().expect("too many incoming values"),
+            );
+        }
+
+        // TODO: Function attributes and other things.
+    }
+}
+
+impl Drop for Lowering {
+    fn drop(&mut self) {
+        unsafe {
+            // Safety: Builder was created when the lowering state was created.
+            llvm_sys::core::LLVMDisposeBuilder(self.builder)
         }
     }
 }
@@ -29,6 +1354,19 @@ impl From<global::Linkage> for llvm_sys::LLVMLinkage {
 pub enum BuildError {
     /// An unknown error produced by LLVM.
     Unknown(interop::Message),
+    /// The module was rejected by the LLVM verifier, contains the messages describing what is invalid.
+    Invalid(interop::Message),
+    /// The module was rejected by the model-level verifier before it was lowered.
+    Model(Vec<crate::verify::Diagnostic>),
+    /// An attempt was made to lower a function whose basic blocks were already lowered.
+    AlreadyLowered(Identifier),
+    /// A function calls another function that was not added to the module.
+    UndeclaredCallee(Identifier),
+    /// The address of a function or global variable that was not added to the module was used.
+    UndeclaredGlobal(Identifier),
+    /// A function contains an instruction that cannot be built with the LLVM 13 C API, such as a `callbr` instruction, a
+    /// `musttail` call, or an atomic operation with a named synchronization scope.
+    Unsupported(String),
 }
 
 crate::enum_case_from!(BuildError, Unknown, interop::Message);
@@ -39,6 +1377,7 @@ crate::enum_case_from!(BuildError, Unknown, interop::Message);
 pub struct Builder<'t> {
     target: &'t interop::target::Target,
     module: crate::Module<'t>,
+    verify: bool,
 }
 
 impl<'t> Builder<'t> {
@@ -47,6 +1386,7 @@ impl<'t> Builder<'t> {
         Self {
             target,
             module: crate::Module::new(name, target.target()),
+            verify: false,
         }
     }
 
@@ -55,21 +1395,111 @@ impl<'t> Builder<'t> {
         self.target
     }
 
+    /// The name of the module.
+    pub fn name(&self) -> &Id {
+        self.module.name()
+    }
+
     /// Used to mutate the contents of the module.
     pub fn module(&mut self) -> &mut crate::Module<'t> {
         &mut self.module
     }
 
+    /// Gets a value indicating whether the module is checked by the LLVM verifier after it is built.
+    pub fn get_verify(&self) -> bool {
+        self.verify
+    }
+
+    /// Sets whether the module is checked by the LLVM verifier after it is built, causing invalid modules to result in a
+    /// [`BuildError::Invalid`].
+    pub fn set_verify(&mut self, verify: bool) {
+        self.verify = verify;
+    }
+
     /// Transforms the contents of this module into an `LLVMModuleRef` suitable for use with the LLVM C APIs.
+    pub fn into_reference(self, context: &interop::Context) -> Result<Wrapper<'_>, BuildError> {
+        self.build(context)
+    }
+
+    /// Checks the contents of this module before transforming them into an `LLVMModuleRef`, returning an error instead of
+    /// panicking if the module is invalid.
+    pub fn try_build<'c>(&self, context: &'c interop::Context) -> Result<Wrapper<'c>, BuildError> {
+        crate::verify::verify_module(&self.module).map_err(BuildError::Model)?;
+
+        let globals = self
+            .module
+            .global_values()
+            .iter()
+            .map(global::Value::as_ptr)
+            .collect::<std::collections::HashSet<_>>();
+
+        let is_known = |global| globals.contains(&global);
+        for value in self.module.global_values() {
+            match value {
+                global::Value::Function(function) => {
+                    check_supported(function)?;
+                    check_callees(function, is_known)?
+                }
+                global::Value::Variable(variable) => {
+                    if let Some(initializer) = variable.get_initializer() {
+                        check_global_addresses(&initializer, &is_known)?;
+                    }
+                }
+                global::Value::IFunc(ifunc) => {
+                    if !is_known(Rc::as_ptr(&ifunc.resolver()).cast()) {
+                        return Err(BuildError::UndeclaredGlobal(
+                            ifunc.resolver().name().to_owned(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        self.build(context)
+    }
+
+    /// Transforms the contents of this module into an `LLVMModuleRef` without consuming the module, allowing the same
+    /// module to be built multiple times, such as when emitting code for multiple targets.
+    pub fn build<'c>(&self, context: &'c interop::Context) -> Result<Wrapper<'c>, BuildError> {
+        unsafe {
+            // Safety: The wrapper cannot outlive the context.
+            self.build_in(context.reference())
+        }
+    }
+
+    /// Transforms the contents of this module into a module owned by the specified context.
     ///
     /// # Safety
-    /// Callers must ensure that the context reference is a valid pointer and that the context has not been disposed.
-    pub unsafe fn into_reference(
-        mut self,
+    /// Callers must ensure that the context reference is valid, and that the context outlives the returned wrapper.
+    pub(crate) unsafe fn build_in<'c>(
+        &self,
         context: llvm_sys::prelude::LLVMContextRef,
-    ) -> Result<Wrapper, BuildError> {
-        let empty_string = std::ffi::CString::default();
+    ) -> Result<Wrapper<'c>, BuildError> {
+        for value in self.module.global_values() {
+            if let global::Value::Function(function) = value {
+                check_supported(function)?;
+            }
+        }
 
+        self.build_incremental_in(context).finish()
+    }
+
+    /// Lowers the current contents of this module, returning a builder that allows functions to be lowered one at a time
+    /// as they are completed, so that functions can be dropped once they are lowered instead of keeping the entire module
+    /// in memory.
+    pub fn build_incremental<'c>(&self, context: &'c interop::Context) -> IncrementalBuilder<'c> {
+        unsafe {
+            // Safety: The builder cannot outlive the context.
+            self.build_incremental_in(context.reference())
+        }
+    }
+
+    /// # Safety
+    /// Callers must ensure that the context reference is valid, and that the context outlives the returned builder.
+    unsafe fn build_incremental_in<'c>(
+        &self,
+        context: llvm_sys::prelude::LLVMContextRef,
+    ) -> IncrementalBuilder<'c> {
         // Safety: module name is newly allocated and is valid.
         let reference = {
             let module_identfier = self.module.name().to_c_string();
@@ -98,179 +1528,435 @@ impl<'t> Builder<'t> {
             self.target.data_layout().reference(),
         );
 
-        let mut type_cache = hash_map::HashMap::new();
-        let mut get_type = |t: Rc<types::FirstClass>| match type_cache.entry(t) {
-            hash_map::Entry::Occupied(occupied) => *occupied.get(),
-            hash_map::Entry::Vacant(vacant) => {
-                let type_reference = match std::convert::AsRef::as_ref(vacant.key()) {
-                    types::FirstClass::Single(single_value_type) => match single_value_type {
-                        types::SingleValue::Integer(integer_size) => {
-                            llvm_sys::core::LLVMIntType(integer_size.bits())
-                        }
-                        _ => todo!("single value type not yet supported"),
-                    },
-                    _ => todo!("type not yet supported"),
-                };
+        let mut lowering = Lowering::new(reference.context());
+
+        let global_values = self.module.global_values();
 
-                *vacant.insert(type_reference)
+        // All functions are added first, since calls and block addresses may refer to functions that come later.
+        for global in global_values.iter() {
+            if let global::Value::Function(function) = global {
+                lowering.add_function(reference.reference(), function);
             }
-        };
+        }
 
-        let mut function_type_cache = hash_map::HashMap::new();
-        let mut get_function_type =
-            |function_type: Rc<types::Function>| match function_type_cache.entry(function_type) {
-                hash_map::Entry::Occupied(occupied) => *occupied.get(),
-                hash_map::Entry::Vacant(vacant) => {
-                    let function_type = vacant.key();
+        // Indirect functions are added after their resolvers, and before any functions or initializers that use them are
+        // lowered.
+        for global in global_values.iter() {
+            if let global::Value::IFunc(ifunc) = global {
+                lowering.add_ifunc(reference.reference(), ifunc);
+            }
+        }
 
-                    let return_type = match function_type.return_type() {
-                        types::Return::Void => {
-                            llvm_sys::core::LLVMVoidTypeInContext(reference.context())
-                        }
-                        types::Return::FirstClass(actual_return_type) => {
-                            get_type(actual_return_type.clone())
-                        }
-                    };
-
-                    let mut parameter_type_buffer = function_type
-                        .parameter_types()
-                        .iter()
-                        .map(|parameter_type| get_type(parameter_type.clone()))
-                        .collect::<Vec<_>>();
-
-                    *vacant.insert(llvm_sys::core::LLVMFunctionType(
-                        return_type,
-                        parameter_type_buffer.as_mut_ptr(),
-                        parameter_type_buffer
-                            .len()
-                            .try_into()
-                            .expect("too many parameters"),
-                        0,
-                    ))
-                }
-            };
+        // Variables are added before their initializers are lowered, since initializers may use the addresses of variables
+        // that come later.
+        for global in global_values.iter() {
+            if let global::Value::Variable(variable) = global {
+                lowering.add_variable(reference.reference(), variable);
+            }
+        }
 
-        // Safety: Builder is disposed after all functions are generated.
-        let instruction_builder = llvm_sys::core::LLVMCreateBuilderInContext(reference.context());
+        for global in global_values.iter() {
+            if let global::Value::Variable(variable) = global {
+                lowering.lower_variable(reference.reference(), variable);
+            }
+        }
 
-        //LLVMConstIntOfArbitraryPrecision for values
+        for global in global_values.iter() {
+            if let global::Value::Function(function) = global {
+                lowering.lower_function(function);
+            }
+        }
 
-        for global in self.module.drain_global_values() {
-            match global {
-                global::Value::Function(function) => {
-                    let function_reference = llvm_sys::core::LLVMAddFunction(
-                        reference.reference(),
-                        function.name().to_c_string().as_ptr(),
-                        get_function_type(function.signature().clone()),
-                    );
+        IncrementalBuilder {
+            module: reference,
+            lowering,
+            verify: self.verify,
+        }
+    }
 
-                    llvm_sys::core::LLVMSetFunctionCallConv(
-                        function_reference,
-                        function.get_calling_convention().value(),
-                    );
+    /// Writes the string representation of the LLVM module into a message.
+    pub fn into_message(self, context: &interop::Context) -> Result<interop::Message, BuildError> {
+        let module = self.into_reference(context)?;
+        unsafe {
+            // Safety: String representation is an LLVM message that is disposed when the message is dropped.
+            Ok(interop::Message::from_ptr(
+                llvm_sys::core::LLVMPrintModuleToString(module.reference()),
+            ))
+        }
+    }
 
-                    llvm_sys::core::LLVMSetLinkage(
-                        function_reference,
-                        function.get_linkage().into(),
-                    );
+    /// Emits assembly code or an object file for the module's target machine into a memory buffer.
+    pub fn emit_target_code_to_buffer(
+        self,
+        context: &interop::Context,
+        file_type: EmitType,
+    ) -> Result<interop::MemoryBuffer, BuildError> {
+        let target_machine = self.target.machine();
+        let module = self.into_reference(context)?;
+        Ok(module.emit_to_buffer(target_machine, file_type)?)
+    }
 
-                    // TODO: Iterate over all blocks
-                    for block in function.take_basic_blocks().drain(..) {
-                        let block_reference = llvm_sys::core::LLVMAppendBasicBlockInContext(
-                            reference.context(),
-                            function_reference,
-                            empty_string.as_ptr(),
-                        );
-
-                        llvm_sys::core::LLVMPositionBuilderAtEnd(
-                            instruction_builder,
-                            block_reference,
-                        );
-
-                        for instruction in block.take_instructions().drain(..) {
-                            use block::Instruction as Instr;
-
-                            match instruction {
-                                Instr::Ret(None) => {
-                                    llvm_sys::core::LLVMBuildRetVoid(instruction_builder);
-                                }
-                                _ => todo!("bad instr"),
-                            }
-                        }
-                    }
+    /// Emits assembly code and/or object files for the module's target machine into memory buffers, lowering the module
+    /// only once.
+    ///
+    /// The returned buffers are in the same order as the `file_types`.
+    pub fn emit_target_code_to_buffers(
+        &self,
+        context: &interop::Context,
+        file_types: &[EmitType],
+    ) -> Result<Vec<interop::MemoryBuffer>, BuildError> {
+        let module = self.build(context)?;
+        file_types
+            .iter()
+            .map(|file_type| Ok(module.emit_to_buffer(self.target.machine(), *file_type)?))
+            .collect()
+    }
+}
 
-                    // TODO: Function attributes and other things.
+/// Returns an error if a function calls another function, or uses the address of a global value, that is not known to the
+/// module.
+fn check_callees<K>(function: &global::Function, is_known: K) -> Result<(), BuildError>
+where
+    K: Fn(*const ()) -> bool,
+{
+    for block in function.basic_blocks().iter() {
+        for instruction in block.instructions().iter() {
+            for operand in instruction.operands() {
+                check_global_addresses(operand, &is_known)?;
+            }
+
+            if let block::Instruction::Call {
+                callee: block::Callee::Function { function, name },
+                ..
+            }
+            | block::Instruction::CallBr {
+                callee: block::Callee::Function { function, name },
+                ..
+            } = instruction
+            {
+                if !is_known(function.as_ptr().cast()) {
+                    return Err(BuildError::UndeclaredCallee(name.as_id().to_owned()));
                 }
             }
         }
+    }
 
-        llvm_sys::core::LLVMDisposeBuilder(instruction_builder);
+    Ok(())
+}
 
-        // TODO: Validate module?
+/// Checks that the addresses of global values used in a value, including in the elements of constants, refer to global
+/// values that were added to the module.
+fn check_global_addresses<K>(value: &value::Value, is_known: &K) -> Result<(), BuildError>
+where
+    K: Fn(*const ()) -> bool,
+{
+    let elements = match value {
+        value::Value::GlobalAddress(address) => {
+            return if is_known(address.global_ptr()) {
+                Ok(())
+            } else {
+                Err(BuildError::UndeclaredGlobal(
+                    address.symbol().as_id().to_owned(),
+                ))
+            };
+        }
+        value::Value::Aggregate(aggregate) => aggregate.elements(),
+        value::Value::Vector(vector) => vector.elements(),
+        _ => return Ok(()),
+    };
 
-        Ok(reference)
+    for element in elements {
+        check_global_addresses(element, is_known)?;
     }
 
-    /// Writes the string representation of the LLVM module into a message.
+    Ok(())
+}
+
+/// Lowers global values into an LLVM module one at a time, allowing functions to be dropped once they are lowered.
+///
+/// Functions must be added with [`IncrementalBuilder::declare_function`] or lowered before any calls to them are lowered.
+pub struct IncrementalBuilder<'c> {
+    module: Wrapper<'c>,
+    lowering: Lowering,
+    verify: bool,
+}
+
+impl<'c> IncrementalBuilder<'c> {
+    /// The module that global values are lowered into.
+    pub fn module(&self) -> &Wrapper<'c> {
+        &self.module
+    }
+
+    /// Adds a global variable to the module without lowering its initializer, allowing its address to be used.
     ///
-    /// # Safety
-    /// Callers must ensure that the LLVM context reference is not null and has not been disposed.
-    pub unsafe fn into_message(
-        self,
-        context: llvm_sys::prelude::LLVMContextRef,
-    ) -> Result<interop::Message, BuildError> {
-        let module = self.into_reference(context)?;
-        // Safety: String representation is an LLVM message that is disposed when the message is dropped.
-        Ok(interop::Message::from_ptr(
-            llvm_sys::core::LLVMPrintModuleToString(module.reference()),
-        ))
+    /// Global variables that were already added are ignored.
+    pub fn declare_variable(&mut self, variable: &global::Variable) {
+        if !self
+            .lowering
+            .variables
+            .contains_key(&(variable as *const _))
+        {
+            unsafe {
+                // Safety: The module is owned by this builder.
+                self.lowering
+                    .add_variable(self.module.reference(), variable);
+            }
+        }
     }
 
-    /// Emits assembly code or an object file for the module's target machine into a memory buffer.
+    /// Adds a global variable to the module, lowering its initializer if it was already declared.
+    pub fn add_variable(&mut self, variable: &global::Variable) {
+        unsafe {
+            // Safety: The module is owned by this builder.
+            self.lowering
+                .lower_variable(self.module.reference(), variable)
+        }
+    }
+
+    /// Adds a function to the module without lowering its basic blocks, allowing calls to the function to be lowered.
     ///
-    /// # Safety
-    /// See [`into_reference`].
-    pub unsafe fn emit_target_code_to_buffer(
-        self,
-        context: llvm_sys::prelude::LLVMContextRef,
-        file_type: EmitType,
-    ) -> Result<interop::MemoryBuffer, BuildError> {
-        let target_machine = self.target.machine();
-        let module = self.into_reference(context)?;
+    /// Functions that were already added are ignored.
+    pub fn declare_function(&mut self, function: &Rc<global::Function>) {
+        if !self.lowering.functions.contains_key(&Rc::as_ptr(function)) {
+            unsafe {
+                // Safety: The module is owned by this builder.
+                self.lowering
+                    .add_function(self.module.reference(), function)
+            }
+        }
+    }
 
-        let mut buffer: llvm_sys::prelude::LLVMMemoryBufferRef = std::ptr::null_mut();
-        let mut error: *mut i8 = std::ptr::null_mut();
+    /// Lowers the basic blocks of a function, adding it to the module if it was not already declared.
+    ///
+    /// # Panics
+    /// Panics if the function was already lowered, if it calls a function or uses the address of a global value that was not
+    /// added to the module, or if it contains an instruction that cannot be lowered.
+    pub fn lower_function(&mut self, function: &Rc<global::Function>) {
+        self.declare_function(function);
+        unsafe {
+            // Safety: The function was added to the module owned by this builder.
+            self.lowering.lower_function(function)
+        }
+    }
 
-        // Don't know if 1 or 0 means success, so the buffer is just checked instead.
-        // Safety: Error is wrapped in a Message later so it is properly disposed.
-        llvm_sys::target_machine::LLVMTargetMachineEmitToMemoryBuffer(
-            target_machine.reference(),
-            module.reference(),
-            file_type,
-            &mut error as *mut _,
-            &mut buffer as *mut llvm_sys::prelude::LLVMMemoryBufferRef,
-        );
+    /// Checks a function before lowering its basic blocks, returning an error instead of panicking if the function is
+    /// invalid, was already lowered, calls a function that was not added to the module, or contains an instruction that
+    /// cannot be lowered.
+    pub fn try_lower_function(
+        &mut self,
+        function: &Rc<global::Function>,
+    ) -> Result<(), BuildError> {
+        crate::verify::verify_function(function).map_err(BuildError::Model)?;
+        check_supported(function)?;
+
+        if let Some((_, reference)) = self.lowering.functions.get(&Rc::as_ptr(function)) {
+            if unsafe { llvm_sys::core::LLVMCountBasicBlocks(*reference) } > 0 {
+                return Err(BuildError::AlreadyLowered(function.name().to_owned()));
+            }
+        }
+
+        self.declare_function(function);
+        let lowering = &self.lowering;
+        check_callees(function, |global| {
+            lowering.functions.contains_key(&global.cast())
+                || lowering.variables.contains_key(&global.cast())
+                || lowering.ifuncs.contains_key(&global.cast())
+        })?;
+
+        unsafe {
+            // Safety: The function was added to the module owned by this builder.
+            self.lowering.lower_function(function)
+        }
+
+        Ok(())
+    }
+
+    /// Adds an indirect function to the module.
+    ///
+    /// Indirect functions that were already added are ignored.
+    ///
+    /// # Panics
+    /// Panics if the resolver function was not added to the module.
+    pub fn add_ifunc(&mut self, ifunc: &global::IFunc) {
+        if !self.lowering.ifuncs.contains_key(&(ifunc as *const _)) {
+            unsafe {
+                // Safety: The module is owned by this builder.
+                self.lowering.add_ifunc(self.module.reference(), ifunc)
+            }
+        }
+    }
+
+    /// Lowers a global value, adding it to the module.
+    pub fn add_global_value(&mut self, value: &global::Value) {
+        match value {
+            global::Value::Variable(variable) => self.add_variable(variable),
+            global::Value::Function(function) => self.lower_function(function),
+            global::Value::IFunc(ifunc) => self.add_ifunc(ifunc),
+        }
+    }
+
+    /// Returns the module containing all of the global values that were lowered, checking it with the LLVM verifier if
+    /// [`Builder::set_verify`] was set.
+    pub fn finish(self) -> Result<Wrapper<'c>, BuildError> {
+        if self.verify {
+            self.module.verify().map_err(BuildError::Invalid)?;
+        }
+
+        Ok(self.module)
+    }
+}
+
+impl std::fmt::Debug for IncrementalBuilder<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("IncrementalBuilder")
+            .field("module", &self.module)
+            .field("verify", &self.verify)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns an error if a function contains an instruction that cannot be built with the LLVM 13 C API.
+fn check_supported(function: &global::Function) -> Result<(), BuildError> {
+    for block in function.basic_blocks().iter() {
+        for instruction in block.instructions().iter() {
+            let scope = match instruction {
+                block::Instruction::Call {
+                    tail_call:
+                        tail_call @ (block::TailCallKind::MustTail | block::TailCallKind::NoTail),
+                    ..
+                } => {
+                    return Err(BuildError::Unsupported(format!(
+                        "{}calls cannot be lowered",
+                        tail_call
+                    )))
+                }
+                // The C API for LLVM 13 does not provide a way to build callbr instructions.
+                block::Instruction::CallBr { .. } => {
+                    return Err(BuildError::Unsupported(String::from(
+                        "callbr instructions cannot be lowered",
+                    )))
+                }
+                block::Instruction::Load {
+                    atomic: Some(atomic),
+                    ..
+                }
+                | block::Instruction::Store {
+                    atomic: Some(atomic),
+                    ..
+                }
+                | block::Instruction::AtomicRmw { atomic, .. }
+                | block::Instruction::CmpXchg {
+                    success: atomic, ..
+                }
+                | block::Instruction::Fence(atomic) => &atomic.scope,
+                _ => continue,
+            };
 
-        if buffer.is_null() {
-            Err(BuildError::Unknown(interop::Message::from_ptr(error)))
-        } else {
-            Ok(interop::MemoryBuffer::from_reference_unchecked(buffer))
+            if let block::SyncScope::Named(_) = scope {
+                return Err(BuildError::Unsupported(format!(
+                    "atomic operations with the scope {}cannot be lowered",
+                    scope
+                )));
+            }
         }
     }
+
+    Ok(())
 }
 
-/// A wrapper over an LLVM module reference.
+/// A wrapper over an LLVM module reference, which cannot outlive the context that owns the module.
 #[derive(Debug)]
 #[repr(transparent)]
-pub struct Wrapper(std::ptr::NonNull<llvm_sys::LLVMModule>);
+pub struct Wrapper<'c> {
+    module: std::ptr::NonNull<llvm_sys::LLVMModule>,
+    context: std::marker::PhantomData<&'c interop::Context>,
+}
 
-impl Wrapper {
+impl<'c> Wrapper<'c> {
     /// Creates a wrapper over a module reference.
     ///
     /// # Safety
-    /// Callers must ensure that the module reference is valid.
+    /// Callers must ensure that the module reference is valid, and that the module's context outlives the wrapper.
     pub unsafe fn new_unchecked(module: llvm_sys::prelude::LLVMModuleRef) -> Self {
-        Self(std::ptr::NonNull::new_unchecked(module))
+        Self {
+            module: std::ptr::NonNull::new_unchecked(module),
+            context: std::marker::PhantomData,
+        }
+    }
+
+    /// Parses a module from a buffer containing LLVM IR in its textual form, returning the diagnostic message if the IR
+    /// could not be parsed.
+    pub fn parse_ir(
+        context: &'c interop::Context,
+        buffer: interop::MemoryBuffer,
+    ) -> interop::Result<Self> {
+        let mut module: LLVMModuleRef = std::ptr::null_mut();
+        let mut message: *mut std::os::raw::c_char = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: The buffer is owned by the parser, and the error message is wrapped so that it is disposed.
+            let failed = llvm_sys::ir_reader::LLVMParseIRInContext(
+                context.reference(),
+                buffer.into_reference(),
+                &mut module as *mut _,
+                &mut message as *mut _,
+            );
+
+            if failed != 0 {
+                Err(interop::Message::from_ptr(message))
+            } else {
+                Ok(Self::new_unchecked(module))
+            }
+        }
+    }
+
+    /// Parses a module from a string containing LLVM IR in its textual form.
+    pub fn parse_ir_str(context: &'c interop::Context, ir: &str) -> interop::Result<Self> {
+        Self::parse_ir(context, interop::MemoryBuffer::from(ir.as_bytes()))
+    }
+
+    /// Parses a module from a buffer containing LLVM bitcode, returning the diagnostic message if the bitcode is invalid.
+    pub fn from_bitcode(
+        context: &'c interop::Context,
+        buffer: &interop::MemoryBuffer,
+    ) -> interop::Result<Self> {
+        let mut module: LLVMModuleRef = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: The buffer is not owned by the reader, and is only borrowed while parsing.
+            let (failed, message) = capture_diagnostics(context.reference(), || {
+                llvm_sys::bit_reader::LLVMParseBitcodeInContext2(
+                    context.reference(),
+                    buffer.reference(),
+                    &mut module as *mut _,
+                )
+            });
+
+            if failed != 0 {
+                Err(message.unwrap_or_else(|| create_message("invalid bitcode")))
+            } else {
+                Ok(Self::new_unchecked(module))
+            }
+        }
+    }
+
+    /// Links the contents of another module into this module, returning the diagnostic message if the modules could not be
+    /// linked.
+    ///
+    /// The other module is always destroyed, even if linking fails.
+    pub fn link_in(&mut self, other: Wrapper<'c>) -> interop::Result<()> {
+        unsafe {
+            // Safety: Ownership of the other module is transferred to the linker.
+            let (failed, message) = capture_diagnostics(self.context(), || {
+                llvm_sys::linker::LLVMLinkModules2(self.reference(), other.into_reference())
+            });
+
+            if failed != 0 {
+                Err(message.unwrap_or_else(|| create_message("modules could not be linked")))
+            } else {
+                Ok(())
+            }
+        }
     }
 
     /// Gets the underlying module reference.
@@ -278,7 +1964,7 @@ impl Wrapper {
     /// # Safety
     /// Callers must ensure that the reference is used for the lifetime of the wrapper.
     pub unsafe fn reference(&self) -> llvm_sys::prelude::LLVMModuleRef {
-        self.0.as_ptr()
+        self.module.as_ptr()
     }
 
     /// Returns the underlying reference to the module.
@@ -286,7 +1972,106 @@ impl Wrapper {
     /// # Safety
     /// Callers are responsible for disposing the returned module reference by calling [`llvm_sys::core::LLVMDisposeModule`].
     pub unsafe fn into_reference(self) -> llvm_sys::prelude::LLVMModuleRef {
-        self.reference()
+        let module = self.reference();
+        std::mem::forget(self);
+        module
+    }
+
+    /// Checks that the module is valid using the LLVM verifier, returning the verifier messages if it is not.
+    pub fn verify(&self) -> interop::Result<()> {
+        let mut message: *mut std::os::raw::c_char = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: module reference is assumed to be valid, and the message is wrapped so that it is disposed.
+            let failed = llvm_sys::analysis::LLVMVerifyModule(
+                self.reference(),
+                llvm_sys::analysis::LLVMVerifierFailureAction::LLVMReturnStatusAction,
+                &mut message as *mut _,
+            );
+
+            let message = if message.is_null() {
+                None
+            } else {
+                Some(interop::Message::from_ptr(message))
+            };
+
+            match message {
+                Some(message) if failed != 0 => Err(message),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Runs a pipeline of optimization passes on the module, such as `default<O2>`, using the new pass manager.
+    ///
+    /// See [the LLVM documentation on the syntax of pass pipelines here](https://llvm.org/docs/NewPassManager.html#invoking-opt).
+    pub fn run_passes(
+        &mut self,
+        pipeline: &Id,
+        target_machine: &interop::target::TargetMachine,
+    ) -> interop::Result<()> {
+        unsafe {
+            // Safety: Options are disposed after the passes are run.
+            let options = llvm_sys::transforms::pass_builder::LLVMCreatePassBuilderOptions();
+            let error = llvm_sys::transforms::pass_builder::LLVMRunPasses(
+                self.reference(),
+                pipeline.to_c_string().as_ptr(),
+                target_machine.reference(),
+                options,
+            );
+
+            llvm_sys::transforms::pass_builder::LLVMDisposePassBuilderOptions(options);
+
+            if error.is_null() {
+                Ok(())
+            } else {
+                Err(interop::Message::from_error(error))
+            }
+        }
+    }
+
+    /// Emits assembly code or an object file for the specified target machine into a memory buffer.
+    ///
+    /// Since code generation modifies the module, code is emitted from a copy of the module, allowing this method to be
+    /// called multiple times on the same module.
+    pub fn emit_to_buffer(
+        &self,
+        target_machine: &interop::target::TargetMachine,
+        file_type: EmitType,
+    ) -> interop::Result<interop::MemoryBuffer> {
+        let mut buffer: llvm_sys::prelude::LLVMMemoryBufferRef = std::ptr::null_mut();
+        let mut error: *mut i8 = std::ptr::null_mut();
+
+        unsafe {
+            // Safety: The copy is owned by the same context, and is disposed when it is dropped.
+            let module = Self::new_unchecked(llvm_sys::core::LLVMCloneModule(self.reference()));
+
+            // Don't know if 1 or 0 means success, so the buffer is just checked instead.
+            // Safety: Error is wrapped in a Message later so it is properly disposed.
+            llvm_sys::target_machine::LLVMTargetMachineEmitToMemoryBuffer(
+                target_machine.reference(),
+                module.reference(),
+                file_type,
+                &mut error as *mut _,
+                &mut buffer as *mut llvm_sys::prelude::LLVMMemoryBufferRef,
+            );
+
+            if buffer.is_null() {
+                Err(interop::Message::from_ptr(error))
+            } else {
+                Ok(interop::MemoryBuffer::from_reference_unchecked(buffer))
+            }
+        }
+    }
+
+    /// Writes the bitcode representation of the module into a memory buffer.
+    pub fn write_bitcode_to_buffer(&self) -> interop::MemoryBuffer {
+        unsafe {
+            // Safety: The returned buffer is disposed when it is dropped.
+            interop::MemoryBuffer::from_reference_unchecked(
+                llvm_sys::bit_writer::LLVMWriteBitcodeToMemoryBuffer(self.reference()),
+            )
+        }
     }
 
     /// Returns the context associated with the module.
@@ -298,7 +2083,7 @@ impl Wrapper {
     }
 }
 
-impl std::ops::Drop for Wrapper {
+impl std::ops::Drop for Wrapper<'_> {
     fn drop(&mut self) {
         unsafe {
             // Safety: module reference is assumed to be valid.
@@ -3,6 +3,7 @@
 use crate::identifier;
 use crate::interop::llvm_sys as interop;
 use crate::target;
+use crate::types;
 use std::borrow::Cow;
 use std::ptr;
 
@@ -19,7 +20,7 @@ pub use llvm_sys::{
 /// Callers must ensure that they dispose of the returned target reference.
 ///
 /// This function also depends on the initialization of targets LLVM can use by ensuring functions such
-/// as by calling [`llvm_sys::target::LLVM_InitializeAllTargets`] and [`llvm_sys::target::LLVM_InitializeAllTargetInfos`].
+/// as by calling [`interop::init::all_targets`].
 pub unsafe fn identifier_to_target_ref(triple: &identifier::Id) -> interop::Result<LLVMTargetRef> {
     let mut error = ptr::null_mut();
     let mut target = ptr::null_mut();
@@ -209,7 +210,7 @@ impl TargetMachine {
     ///
     /// # Safety
     /// Relies on the initialization of target information for the host machine, such as with
-    /// [`llvm_sys::target::LLVM_InitializeNativeTarget`].
+    /// [`interop::init::native_target`].
     pub unsafe fn host_machine(
         optimization_level: target::CodeGenerationOptimization,
         relocation_mode: target::RelocationMode,
@@ -243,7 +244,8 @@ impl TargetMachine {
             machine: target::Machine::new(
                 host_triple.triple().clone(),
                 cpu_name.to_identifier(),
-                features.to_identifier(),
+                target::FeatureSet::try_from(features.to_identifier().as_id())
+                    .expect("host CPU features should be valid"),
                 optimization_level,
                 relocation_mode,
                 code_model,
@@ -272,7 +274,11 @@ impl TryFrom<target::Machine> for TargetMachine {
                         .into_c_string()
                         .as_ptr(),
                     target_machine.cpu_name().to_c_string().as_ptr(),
-                    target_machine.features().to_c_string().as_ptr(),
+                    target_machine
+                        .features()
+                        .to_feature_string()
+                        .into_c_string()
+                        .as_ptr(),
                     target_machine.code_generation_optimization_level().into(),
                     target_machine.relocation_mode().into(),
                     target_machine.code_model().into(),
@@ -333,6 +339,55 @@ impl TargetLayout {
             reference: target_layout,
         })
     }
+
+    /// Performs a query on the LLVM type corresponding to the specified type, which is created in a temporary context.
+    fn query_type<T, F: FnOnce(llvm_sys::prelude::LLVMTypeRef) -> T>(
+        &self,
+        t: &types::FirstClass,
+        query: F,
+    ) -> T {
+        let context = interop::Context::new();
+        unsafe {
+            // Safety: The type is only used while the context is alive.
+            query(interop::module::type_reference(context.reference(), t))
+        }
+    }
+
+    /// Gets the size, in bytes, of a value of the specified type, including any padding needed to align consecutive values.
+    pub fn abi_size_of(&self, t: &types::FirstClass) -> u64 {
+        self.query_type(t, |type_reference| unsafe {
+            llvm_sys::target::LLVMABISizeOfType(self.reference(), type_reference)
+        })
+    }
+
+    /// Gets the minimum alignment required for a value of the specified type.
+    pub fn abi_alignment_of(&self, t: &types::FirstClass) -> target::layout::Alignment {
+        let alignment = self.query_type(t, |type_reference| unsafe {
+            llvm_sys::target::LLVMABIAlignmentOfType(self.reference(), type_reference)
+        });
+
+        target::layout::Alignment::new(alignment).expect("alignment should be a power of two")
+    }
+
+    /// Gets the offset, in bytes, of the member at the specified index in a struct.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds.
+    pub fn offset_of_element(&self, structure: &types::Struct, index: u32) -> u64 {
+        if index as usize >= structure.member_types().len() {
+            panic!(
+                "index {} is out of bounds for struct type {}",
+                index, structure
+            );
+        }
+
+        let structure_type =
+            types::FirstClass::Aggregate(types::Aggregate::Struct(structure.clone()));
+
+        self.query_type(&structure_type, |type_reference| unsafe {
+            llvm_sys::target::LLVMOffsetOfElement(self.reference(), type_reference, index)
+        })
+    }
 }
 
 impl TryFrom<&TargetMachine> for TargetLayout {
@@ -358,7 +413,22 @@ impl TryFrom<&TargetMachine> for TargetLayout {
     }
 }
 
-//impl TryFrom<target::Layout> for TargetLayout { }
+impl TryFrom<target::Layout> for TargetLayout {
+    type Error = identifier::Error;
+
+    fn try_from(layout: target::Layout) -> Result<Self, Self::Error> {
+        let layout_string = identifier::Identifier::try_from(layout.to_string())?.into_c_string();
+
+        unsafe {
+            // Safety: Layout string is valid for the duration of this function call, and the target layout reference is
+            // disposed when the returned value is dropped.
+            Ok(Self {
+                reference: llvm_sys::target::LLVMCreateTargetData(layout_string.as_ptr()),
+                layout,
+            })
+        }
+    }
+}
 
 impl Drop for TargetLayout {
     fn drop(&mut self) {
@@ -6,3 +6,9 @@ pub mod inkwell;
 
 #[cfg(feature = "llvm_sys_interop")]
 pub mod llvm_sys;
+
+#[cfg(feature = "num_bigint_interop")]
+pub mod num_bigint;
+
+#[cfg(feature = "serde")]
+pub mod serde;
@@ -0,0 +1,95 @@
+//! Contains conversions between integer constants and the arbitrary-precision integers provided by
+//! [`num-bigint`](https://crates.io/crates/num-bigint).
+
+use crate::types::IntegerSize;
+use crate::value::{self, Integer};
+use num_bigint::{BigInt, BigUint, Sign};
+
+fn integer_size(bits: u64) -> IntegerSize {
+    u32::try_from(bits.max(1))
+        .ok()
+        .and_then(IntegerSize::new)
+        .unwrap_or_else(|| panic!("{} bits is too large for an integer type", bits))
+}
+
+fn to_big_uint(words: &[u64]) -> BigUint {
+    BigUint::new(
+        words
+            .iter()
+            .flat_map(|word| [*word as u32, (*word >> 32) as u32])
+            .collect(),
+    )
+}
+
+impl Integer {
+    /// Creates an integer value from an arbitrary-precision unsigned integer, zero extending or truncating it to the
+    /// specified type.
+    pub fn from_big_uint(integer_type: IntegerSize, value: &BigUint) -> Self {
+        Self::from_words(integer_type, &value.to_u64_digits())
+    }
+
+    /// Creates an integer value from an arbitrary-precision signed integer, sign extending or truncating it to the
+    /// specified type.
+    pub fn from_big_int(integer_type: IntegerSize, value: &BigInt) -> Self {
+        let (sign, mut words) = value.to_u64_digits();
+        words.resize(value::word_count(integer_type.bits()), 0);
+        if sign == Sign::Minus {
+            value::negate_words(&mut words);
+        }
+        Self::from_words(integer_type, &words)
+    }
+}
+
+/// Converts an unsigned integer into an integer constant of the smallest type that can contain it.
+///
+/// # Panics
+/// Panics if the integer has more than `2^23` bits.
+impl From<BigUint> for Integer {
+    fn from(value: BigUint) -> Self {
+        Self::from_big_uint(integer_size(value.bits()), &value)
+    }
+}
+
+/// Converts a signed integer into an integer constant of the smallest type that can contain it in two's complement form.
+///
+/// # Panics
+/// Panics if the integer needs more than `2^23` bits.
+impl From<BigInt> for Integer {
+    fn from(value: BigInt) -> Self {
+        let magnitude_bits = value.bits();
+        let bits = match value.sign() {
+            // The magnitude of negative powers of two fits in the same number of bits, since the sign bit is set.
+            Sign::Minus
+                if value
+                    .magnitude()
+                    .to_u64_digits()
+                    .iter()
+                    .map(|word| word.count_ones())
+                    .sum::<u32>()
+                    == 1 =>
+            {
+                magnitude_bits
+            }
+            Sign::NoSign => 1,
+            Sign::Minus | Sign::Plus => magnitude_bits + 1,
+        };
+
+        Self::from_big_int(integer_size(bits), &value)
+    }
+}
+
+/// Interprets the bits of an integer constant as an unsigned value.
+impl From<&Integer> for BigUint {
+    fn from(value: &Integer) -> Self {
+        to_big_uint(&value.words()[..value::word_count(value.integer_type().bits())])
+    }
+}
+
+/// Interprets the bits of an integer constant as a signed value in two's complement form.
+impl From<&Integer> for BigInt {
+    fn from(value: &Integer) -> Self {
+        let (negative, magnitude) = value.signed_magnitude();
+        let sign = if negative { Sign::Minus } else { Sign::Plus };
+        BigInt::from_biguint(sign, to_big_uint(&magnitude))
+    }
+}
@@ -0,0 +1,127 @@
+//! Contains implementations of [`serde`](https://crates.io/crates/serde)'s `Serialize` and `Deserialize` traits for types
+//! that are not simply derived, allowing IR to be cached or sent between processes without emitting and parsing text.
+//!
+//! Most types derive these traits when the `serde` feature is enabled. Global values, basic blocks, and registers refer to
+//! each other, so they are instead serialized as part of a [`Fragment`](crate::detached::Fragment), where references are
+//! replaced with names and indices. Modules are serialized as a [`Snapshot`], since a deserialized module would have to
+//! borrow its target from somewhere.
+
+use crate::detached::Snapshot;
+use crate::identifier::Identifier;
+use crate::module::Module;
+use crate::target::layout::{Alignment, BitSize, ByteSize};
+use crate::target::{FeatureSet, Triple};
+use crate::types::IntegerSize;
+use crate::value::Integer;
+use serde::de::{Deserialize, Deserializer, Error as _};
+use serde::ser::Error as _;
+use serde::{Serialize, Serializer};
+
+impl Serialize for Identifier {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Identifier {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::try_from(String::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+/// Integer sizes are serialized as their number of bits.
+impl Serialize for IntegerSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.bits())
+    }
+}
+
+impl<'de> Deserialize<'de> for IntegerSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bits = u32::deserialize(deserializer)?;
+        Self::new(bits)
+            .ok_or_else(|| D::Error::custom(format!("{} is not a valid integer size", bits)))
+    }
+}
+
+/// Alignments are serialized as their number of bytes.
+impl Serialize for Alignment {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u32(self.bytes().get())
+    }
+}
+
+impl<'de> Deserialize<'de> for Alignment {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = u32::deserialize(deserializer)?;
+        Self::new(bytes)
+            .ok_or_else(|| D::Error::custom(format!("alignment {} is not a power of two", bytes)))
+    }
+}
+
+/// Sizes in bytes are serialized as their number of bits.
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        BitSize::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::try_from(BitSize::deserialize(deserializer)?).map_err(|size| {
+            D::Error::custom(format!("{} bits is not a multiple of 8 bits", size.bits()))
+        })
+    }
+}
+
+/// Integer constants are serialized as their type and their 64-bit words, with the least significant word first.
+impl Serialize for Integer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (self.integer_type(), self.words()).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Integer {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (integer_type, words) = <(IntegerSize, Vec<u64>)>::deserialize(deserializer)?;
+        Ok(Self::from_words(integer_type, &words))
+    }
+}
+
+/// Target triples are serialized as strings, such as `x86_64-pc-linux-gnu`.
+impl Serialize for Triple {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Triple {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Identifier::deserialize(deserializer).map(Self::from)
+    }
+}
+
+/// Feature sets are serialized as comma separated strings, such as `+sse4.2,-avx512f`.
+impl Serialize for FeatureSet {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for FeatureSet {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let features = Identifier::deserialize(deserializer)?;
+        Self::try_from(features.as_id()).map_err(D::Error::custom)
+    }
+}
+
+/// Modules are serialized as a [`Snapshot`], which should be used to deserialize them.
+///
+/// Serialization fails if a global value in the module cannot be copied, see [`Snapshot::try_new`].
+impl Serialize for Module<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        Snapshot::try_new(self)
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
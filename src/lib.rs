@@ -10,17 +10,26 @@
 
 #![deny(missing_docs, missing_debug_implementations)]
 
+pub mod analysis;
 pub mod block;
+pub mod detached;
+mod dsl;
 pub mod global;
 pub mod identifier;
 pub mod interop;
+pub mod metadata;
 pub mod module;
+mod numbering;
+pub mod parser;
+pub mod ssa;
 pub mod target;
+pub mod transform;
 pub mod types;
 pub mod value;
+pub mod verify;
 
 pub use block::BasicBlock;
-pub use identifier::{Id, Identifier};
+pub use identifier::{Id, Identifier, Symbol};
 pub use module::Module;
 pub use target::Target;
 
@@ -0,0 +1,82 @@
+//! Metadata provides additional information about instructions that is used by optimizations and code generation, but
+//! does not change the meaning of the program.
+//!
+//! See [the LLVM documentation on metadata here](https://llvm.org/docs/LangRef.html#metadata).
+
+use crate::identifier::{Identifier, Quoted};
+use crate::value::Value;
+use std::fmt::{Display, Formatter, Write as _};
+use std::rc::Rc;
+
+/// A metadata operand.
+#[derive(Clone, Debug)]
+pub enum Metadata {
+    /// A metadata string, such as `!"hello"`.
+    String(Identifier),
+    /// A constant value, such as `i32 5`.
+    Value(Value),
+    /// A metadata node containing other metadata.
+    Node(Rc<Node>),
+}
+
+impl Display for Metadata {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::String(string) => write!(f, "!{}", Quoted(string.as_id())),
+            Self::Value(value) => Display::fmt(value, f),
+            Self::Node(node) => Display::fmt(node, f),
+        }
+    }
+}
+
+crate::enum_case_from!(Metadata, String, Identifier);
+crate::enum_case_from!(Metadata, Value, Value);
+crate::enum_case_from!(Metadata, Node, Rc<Node>);
+
+/// A metadata node, which is a tuple of metadata operands.
+#[derive(Debug)]
+pub struct Node {
+    operands: Vec<Option<Metadata>>,
+}
+
+impl Node {
+    /// Creates a metadata node containing the specified operands, where `None` indicates a `null` operand.
+    ///
+    /// # Panics
+    /// Panics if any of the operands are values that are not constants.
+    pub fn new(operands: impl Into<Vec<Option<Metadata>>>) -> Rc<Self> {
+        let operands = operands.into();
+
+        for operand in operands.iter() {
+            if let Some(Metadata::Value(value)) = operand {
+                if !value.is_constant() {
+                    panic!("expected constant metadata operand, but got {}", value);
+                }
+            }
+        }
+
+        Rc::new(Self { operands })
+    }
+
+    /// Gets the operands of this metadata node.
+    pub fn operands(&self) -> &[Option<Metadata>] {
+        &self.operands
+    }
+}
+
+impl Display for Node {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("!{")?;
+        for (index, operand) in self.operands.iter().enumerate() {
+            if index > 0 {
+                f.write_str(", ")?;
+            }
+
+            match operand {
+                Some(operand) => Display::fmt(operand, f)?,
+                None => f.write_str("null")?,
+            }
+        }
+        f.write_char('}')
+    }
+}
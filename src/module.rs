@@ -2,9 +2,79 @@
 //!
 //! [See the LLVM documentation on modules](https://llvm.org/docs/LangRef.html#module-structure).
 
+use crate::block::{Callee, Instruction};
+use crate::detached;
 use crate::global;
-use crate::identifier::{Id, Identifier};
+use crate::identifier::{Id, Identifier, Symbol, SymbolTable};
 use crate::target;
+use crate::types;
+use crate::value;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// Error used when a global value cannot be added to a module, since another global value with the same name exists.
+#[derive(Debug, thiserror::Error)]
+#[error("a global value named @{} already exists", .value.symbol())]
+pub struct DuplicateSymbolError {
+    value: global::Value,
+}
+
+impl DuplicateSymbolError {
+    /// Gets the global value that could not be added.
+    pub fn into_value(self) -> global::Value {
+        self.value
+    }
+}
+
+/// Error used when a global value in a module cannot be replaced.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ReplaceGlobalError {
+    /// A global value with the same name as the replacement does not exist.
+    #[error("a global value named @{0} does not exist")]
+    NotFound(Identifier),
+    /// The replacement is of a different kind or type than the existing global value, such as a global variable replacing
+    /// a function, or a function with a different signature.
+    #[error("@{0} cannot be replaced with a global value of a different kind or type")]
+    TypeMismatch(Identifier),
+}
+
+/// Replaces the addresses of a global value within a constant or operand, returning `true` if any were replaced.
+fn replace_global_address(
+    value: &mut value::Value,
+    old: *const (),
+    new: &value::GlobalAddress,
+) -> bool {
+    match value {
+        value::Value::GlobalAddress(address) if address.global_ptr() == old => {
+            *address = new.clone();
+            true
+        }
+        value::Value::Aggregate(aggregate) => {
+            let mut elements = aggregate.elements().to_vec();
+            let mut replaced = false;
+            for element in elements.iter_mut() {
+                replaced |= replace_global_address(element, old, new);
+            }
+            if replaced {
+                *aggregate = value::Aggregate::new(aggregate.aggregate_type().clone(), elements);
+            }
+            replaced
+        }
+        value::Value::Vector(vector) => {
+            let mut elements = vector.elements().to_vec();
+            let mut replaced = false;
+            for element in elements.iter_mut() {
+                replaced |= replace_global_address(element, old, new);
+            }
+            if replaced {
+                *vector = value::Vector::new(vector.vector_type().clone(), elements);
+            }
+            replaced
+        }
+        _ => false,
+    }
+}
 
 /// An LLVM module, containing global values and their symbols.
 pub struct Module<'t> {
@@ -12,6 +82,10 @@ pub struct Module<'t> {
     //source_file_name: Identifier,
     target: &'t target::Target,
     global_values: Vec<global::Value>,
+    /// Maps the names of global values to their indices in `global_values`.
+    global_indices: HashMap<Symbol, usize>,
+    symbols: SymbolTable,
+    opaque_pointers: bool,
 }
 
 impl<'t> Module<'t> {
@@ -21,6 +95,38 @@ impl<'t> Module<'t> {
             name,
             target,
             global_values: Vec::new(),
+            global_indices: HashMap::new(),
+            symbols: SymbolTable::new(),
+            opaque_pointers: false,
+        }
+    }
+
+    /// Gets a symbol with the specified name from this module's symbol table, allowing names used by global values to be
+    /// stored only once.
+    pub fn intern(&self, name: &Id) -> Symbol {
+        self.symbols.intern(name)
+    }
+
+    /// Gets a symbol with the specified name if it is not used by a global value in this module, or with the first numeric
+    /// suffix (`name.1`, `name.2`, ...) that is not used otherwise, in the same way that LLVM renames private symbols
+    /// that conflict.
+    ///
+    /// This allows private global values, such as string constants, to be added without checking for conflicts.
+    pub fn intern_unique(&self, name: &Id) -> Symbol {
+        if !self.global_indices.contains_key(name.as_str()) {
+            return self.intern(name);
+        }
+
+        let mut suffix = 1usize;
+        loop {
+            let unique_name = format!("{}.{}", name.as_str(), suffix);
+            if !self.global_indices.contains_key(unique_name.as_str()) {
+                return self.intern(unsafe {
+                    // Safety: The suffix does not contain null bytes.
+                    Identifier::new_unchecked(unique_name).as_id()
+                });
+            }
+            suffix += 1;
         }
     }
 
@@ -49,14 +155,361 @@ impl<'t> Module<'t> {
         self.target.layout()
     }
 
-    /// Adds a global value to this module, without checking for duplicate symbols.
+    /// Returns `true` if pointer types are printed as opaque pointers (`ptr`) when this module is printed.
+    pub fn get_opaque_pointers(&self) -> bool {
+        self.opaque_pointers
+    }
+
+    /// Sets whether pointer types are printed as opaque pointers (`ptr` or `ptr addrspace(N)`) when this module is printed,
+    /// which is required by LLVM 15 and later. Instructions such as `load`, `store`, and `getelementptr` always include
+    /// the types of the values they access, so they remain valid in either mode.
+    ///
+    /// This only affects the textual representation of the module, and not modules built with the LLVM C API.
+    pub fn set_opaque_pointers(&mut self, opaque: bool) {
+        self.opaque_pointers = opaque;
+    }
+
+    /// Adds a global value to this module.
+    ///
+    /// # Panics
+    /// Panics if another global value with the same name was already added, see [`Module::try_add_global_value`].
     pub fn add_global_value<G: Into<global::Value>>(&mut self, value: G) {
-        self.global_values.push(value.into())
+        if let Err(error) = self.try_add_global_value(value) {
+            panic!("{}", error)
+        }
+    }
+
+    /// Adds a global value to this module, returning an error if another global value with the same name was already
+    /// added.
+    ///
+    /// Names for private global values that do not conflict can be obtained with [`Module::intern_unique`].
+    pub fn try_add_global_value<G: Into<global::Value>>(
+        &mut self,
+        value: G,
+    ) -> Result<(), DuplicateSymbolError> {
+        let value = value.into();
+        if self.global_indices.contains_key(value.symbol()) {
+            Err(DuplicateSymbolError { value })
+        } else {
+            self.global_indices
+                .insert(value.symbol().clone(), self.global_values.len());
+            self.global_values.push(value);
+            Ok(())
+        }
+    }
+
+    /// Removes the global value with the specified name from this module, returning it if it existed.
+    ///
+    /// Calls and addresses that refer to the removed global value are not changed, so they must be removed or replaced
+    /// before the module is lowered.
+    pub fn remove_global_value(&mut self, name: &Id) -> Option<global::Value> {
+        let index = self.global_indices.remove(name.as_str())?;
+        for other in self.global_indices.values_mut() {
+            if *other > index {
+                *other -= 1;
+            }
+        }
+        Some(self.global_values.remove(index))
+    }
+
+    /// Replaces the global value with the same name, such as a function declaration with its definition, returning the
+    /// global value that was replaced.
+    ///
+    /// Calls, addresses, and resolvers of indirect functions in this module that refer to the replaced global value are
+    /// updated to refer to the replacement. Addresses used in metadata nodes are not updated.
+    ///
+    /// # Panics
+    /// Panics if a global value with the same name does not exist, or if it is of a different kind or type, see
+    /// [`Module::try_replace_global_value`].
+    pub fn replace_global_value<G: Into<global::Value>>(&mut self, value: G) -> global::Value {
+        self.try_replace_global_value(value)
+            .unwrap_or_else(|error| panic!("{}", error))
     }
 
-    #[cfg(feature = "_internal_deconstructors")]
-    pub(crate) fn drain_global_values(&mut self) -> std::vec::Drain<'_, global::Value> {
-        self.global_values.drain(..)
+    /// Replaces the global value with the same name, returning an error if it does not exist, or if it is of a different
+    /// kind or type than the replacement.
+    pub fn try_replace_global_value<G: Into<global::Value>>(
+        &mut self,
+        value: G,
+    ) -> Result<global::Value, ReplaceGlobalError> {
+        let value = value.into();
+        let name = value.symbol().as_id();
+        let index = match self.global_indices.get(name.as_str()) {
+            Some(index) => *index,
+            None => return Err(ReplaceGlobalError::NotFound(name.to_owned())),
+        };
+
+        let compatible = match (&self.global_values[index], &value) {
+            (global::Value::Function(old), global::Value::Function(new)) => {
+                old.signature() == new.signature()
+            }
+            (global::Value::Variable(old), global::Value::Variable(new)) => {
+                old.value_type() == new.value_type()
+            }
+            (global::Value::IFunc(old), global::Value::IFunc(new)) => {
+                old.signature() == new.signature()
+            }
+            _ => false,
+        };
+
+        if !compatible {
+            return Err(ReplaceGlobalError::TypeMismatch(name.to_owned()));
+        }
+
+        let old = std::mem::replace(&mut self.global_values[index], value.clone());
+        self.replace_uses(&old, &value);
+        Ok(old)
+    }
+
+    /// Updates the calls, addresses, and resolvers that refer to a global value to refer to its replacement instead.
+    fn replace_uses(&self, old: &global::Value, new: &global::Value) {
+        let old_ptr = old.as_ptr();
+        let new_address = value::GlobalAddress::new(new);
+
+        for global in self.global_values.iter() {
+            match global {
+                global::Value::Function(function) => {
+                    for block in function.basic_blocks().iter() {
+                        for instruction in block.instructions_mut().iter_mut() {
+                            for operand in instruction.operands_mut() {
+                                replace_global_address(operand, old_ptr, &new_address);
+                            }
+
+                            if let (
+                                Instruction::Call { callee, .. }
+                                | Instruction::CallBr { callee, .. },
+                                global::Value::Function(new_function),
+                            ) = (instruction, new)
+                            {
+                                if matches!(callee, Callee::Function { function, .. } if function.as_ptr().cast() == old_ptr)
+                                {
+                                    *callee = Callee::function(new_function);
+                                }
+                            }
+                        }
+                    }
+                }
+                global::Value::Variable(variable) => {
+                    if let Some(mut initializer) = variable.get_initializer() {
+                        if replace_global_address(&mut initializer, old_ptr, &new_address) {
+                            variable.set_initializer(Some(initializer));
+                        }
+                    }
+                }
+                global::Value::IFunc(ifunc) => {
+                    if let global::Value::Function(new_function) = new {
+                        if Rc::as_ptr(&ifunc.resolver()).cast() == old_ptr {
+                            ifunc.set_resolver(new_function.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Gets the declaration of the `llvm.va_start` intrinsic, which initializes the variable argument list pointed to
+    /// by its `i8*` argument, adding it to this module if it was not already declared.
+    pub fn va_start_intrinsic(&mut self) -> Rc<global::Function> {
+        self.get_or_declare_list_intrinsic(crate::id!("llvm.va_start"), 1)
+    }
+
+    /// Gets the declaration of the `llvm.va_end` intrinsic, which destroys a variable argument list previously
+    /// initialized by `llvm.va_start` or `llvm.va_copy`, adding it to this module if it was not already declared.
+    pub fn va_end_intrinsic(&mut self) -> Rc<global::Function> {
+        self.get_or_declare_list_intrinsic(crate::id!("llvm.va_end"), 1)
+    }
+
+    /// Gets the declaration of the `llvm.va_copy` intrinsic, which copies the variable argument list pointed to by its
+    /// second argument into the list pointed to by its first argument, adding it to this module if it was not already
+    /// declared.
+    pub fn va_copy_intrinsic(&mut self) -> Rc<global::Function> {
+        self.get_or_declare_list_intrinsic(crate::id!("llvm.va_copy"), 2)
+    }
+
+    /// Declares an intrinsic returning `void` that takes the specified number of `i8*` arguments.
+    fn get_or_declare_list_intrinsic(
+        &mut self,
+        name: Identifier,
+        parameter_count: usize,
+    ) -> Rc<global::Function> {
+        let list_pointer = Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+            types::Pointer::new(Rc::new(types::FirstClass::Single(
+                types::SingleValue::Integer(types::IntegerSize::SIZE_8),
+            ))),
+        )));
+        self.get_or_declare_intrinsic(
+            name,
+            types::Function::new(types::Return::Void, vec![list_pointer; parameter_count]),
+        )
+    }
+
+    /// Gets the function with the specified name, or declares an intrinsic function with the specified signature if it
+    /// does not exist.
+    pub(crate) fn get_or_declare_intrinsic(
+        &mut self,
+        name: Identifier,
+        signature: types::Function,
+    ) -> Rc<global::Function> {
+        self.get_function(name.as_id()).unwrap_or_else(|| {
+            let function = global::Function::new(self.intern(name.as_id()), signature);
+            self.add_global_value(function.clone());
+            function
+        })
+    }
+
+    /// Registers a function that is called when the program starts, before `main` is called, by adding it to the
+    /// `@llvm.global_ctors` array.
+    ///
+    /// Constructors with a lower priority are called first, and `65535` is used by Clang for constructors that do not
+    /// specify a priority. The associated data pointer of each entry is `null`.
+    ///
+    /// # Panics
+    /// Panics if the function does not have the signature `void ()`.
+    pub fn add_global_constructor(&mut self, function: &Rc<global::Function>, priority: u16) {
+        self.add_structor(crate::id!("llvm.global_ctors"), function, priority)
+    }
+
+    /// Registers a function that is called when the program exits, by adding it to the `@llvm.global_dtors` array.
+    ///
+    /// Destructors with a lower priority are called first, see [`Module::add_global_constructor`].
+    ///
+    /// # Panics
+    /// Panics if the function does not have the signature `void ()`.
+    pub fn add_global_destructor(&mut self, function: &Rc<global::Function>, priority: u16) {
+        self.add_structor(crate::id!("llvm.global_dtors"), function, priority)
+    }
+
+    /// Adds an entry to an array of constructors or destructors, replacing the existing array with one that is large
+    /// enough to contain the new entry.
+    fn add_structor(&mut self, name: Identifier, function: &Rc<global::Function>, priority: u16) {
+        let signature = function.signature();
+        if !matches!(signature.return_type(), types::Return::Void)
+            || !signature.parameter_types().is_empty()
+            || signature.is_variadic()
+        {
+            panic!(
+                "expected @{} in @{} to have the signature void (), but got {}",
+                function.name(),
+                name,
+                signature
+            );
+        }
+
+        // Each entry is a structure of type { i32, void ()*, i8* }, or { i32, ptr, ptr } with opaque pointers.
+        let data_pointer = types::Pointer::new(Rc::new(types::FirstClass::Single(
+            types::SingleValue::Integer(types::IntegerSize::I8),
+        )));
+        let entry_type = types::Struct::new(
+            vec![
+                Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+                    types::IntegerSize::I32,
+                ))),
+                Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+                    types::Pointer::new(signature.clone()),
+                ))),
+                Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+                    data_pointer.clone(),
+                ))),
+            ],
+            false,
+        );
+
+        let existing = self.global_indices.get(name.as_str()).copied();
+        let mut entries = match existing.map(|index| &self.global_values[index]) {
+            Some(global::Value::Variable(variable)) => match variable.get_initializer() {
+                Some(value::Value::Aggregate(array)) => array.elements().to_vec(),
+                _ => Vec::new(),
+            },
+            Some(_) => panic!("@{} is not a global variable", name),
+            None => Vec::new(),
+        };
+
+        entries.push(value::Value::Aggregate(value::Aggregate::new(
+            types::Aggregate::Struct(entry_type.clone()),
+            vec![
+                value::Integer::from_u64(types::IntegerSize::I32, priority.into()).into(),
+                value::GlobalAddress::function(function).into(),
+                value::Value::Null(data_pointer),
+            ],
+        )));
+
+        let array_type = types::Array::new(
+            Rc::new(types::FirstClass::Aggregate(types::Aggregate::Struct(
+                entry_type,
+            ))),
+            u32::try_from(entries.len()).expect("too many entries"),
+        );
+
+        let array = global::Variable::new(
+            self.intern(name.as_id()),
+            Rc::new(types::FirstClass::Aggregate(types::Aggregate::Array(
+                array_type.clone(),
+            ))),
+            false,
+        );
+        array.set_linkage(global::Linkage::Appending);
+        array.set_initializer(Some(value::Value::Aggregate(value::Aggregate::new(
+            types::Aggregate::Array(array_type),
+            entries,
+        ))));
+
+        match existing {
+            Some(index) => self.global_values[index] = global::Value::Variable(array),
+            None => self.add_global_value(array),
+        }
+    }
+
+    /// Gets the global value with the specified name.
+    pub fn get_global(&self, name: &Id) -> Option<&global::Value> {
+        self.global_indices
+            .get(name.as_str())
+            .map(|index| &self.global_values[*index])
+    }
+
+    /// Gets the function with the specified name, or `None` if it does not exist or is not a function.
+    pub fn get_function(&self, name: &Id) -> Option<Rc<global::Function>> {
+        match self.get_global(name)? {
+            global::Value::Function(function) => Some(function.clone()),
+            _ => None,
+        }
+    }
+
+    /// Gets the global variable with the specified name, or `None` if it does not exist or is not a global variable.
+    pub fn get_variable(&self, name: &Id) -> Option<Rc<global::Variable>> {
+        match self.get_global(name)? {
+            global::Value::Variable(variable) => Some(variable.clone()),
+            _ => None,
+        }
+    }
+
+    /// Gets the global values in this module, in the order that they were added.
+    pub fn global_values(&self) -> &[global::Value] {
+        &self.global_values
+    }
+
+    /// Returns an iterator over the functions in this module, in the order that they were added.
+    pub fn functions(&self) -> impl Iterator<Item = &Rc<global::Function>> + '_ {
+        self.global_values.iter().filter_map(|value| match value {
+            global::Value::Function(function) => Some(function),
+            _ => None,
+        })
+    }
+
+    /// Returns an iterator over the global variables in this module, in the order that they were added.
+    pub fn variables(&self) -> impl Iterator<Item = &Rc<global::Variable>> + '_ {
+        self.global_values.iter().filter_map(|value| match value {
+            global::Value::Variable(variable) => Some(variable),
+            _ => None,
+        })
+    }
+
+    /// Adds the functions and global variables that were copied into a fragment, which may have been built on another
+    /// thread.
+    ///
+    /// Declarations in the module are reused by global values with the same name, and function definitions in the fragment
+    /// are appended to existing function declarations. Functions that are called but not defined are declared.
+    pub fn merge(&mut self, fragment: detached::Fragment) -> Result<(), detached::MergeError> {
+        fragment.merge_into(self)
     }
 }
 
@@ -74,9 +527,21 @@ impl std::fmt::Display for Module<'_> {
         writeln!(f, "; ModuleID = '{}'", self.name())?;
         writeln!(f, "target triple = \"{}\"", self.target_triple())?;
         writeln!(f, "target datalayout = \"{}\"", self.target_layout())?;
-        for global in self.global_values.iter() {
-            writeln!(f, "{}", global)?;
+        let mut comdats = HashSet::new();
+        for comdat in self
+            .global_values
+            .iter()
+            .filter_map(global::Value::get_comdat)
+        {
+            if comdats.insert(comdat.symbol().clone()) {
+                writeln!(f, "{}", comdat)?;
+            }
         }
-        Ok(())
+        types::with_opaque_pointers(self.opaque_pointers, || {
+            for global in self.global_values.iter() {
+                writeln!(f, "{}", global)?;
+            }
+            Ok(())
+        })
     }
 }
@@ -0,0 +1,153 @@
+//! Assigns sequential numbers to the unnamed values and blocks of a function when it is printed, following the rules used
+//! by LLVM's textual format.
+//!
+//! Unnamed parameters are numbered first, followed by each unnamed block and the results of the instructions within it,
+//! in the order that they appear in the function. Named registers share a namespace with the labels of blocks, so a suffix
+//! is added to names that are used more than once.
+
+use crate::block::BasicBlock;
+use crate::global::Function;
+use crate::identifier::Identifier;
+use crate::value::Register;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// The name or number used to refer to a register when its function is printed.
+#[derive(Clone, Debug)]
+pub(crate) enum Label {
+    Number(usize),
+    Name(Identifier),
+}
+
+impl std::fmt::Display for Label {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Number(number) => write!(f, "%{}", number),
+            Self::Name(name) => write!(f, "%{}", name),
+        }
+    }
+}
+
+/// The numbers assigned to the unnamed values and blocks of a function, and the unique names of its named registers.
+#[derive(Debug, Default)]
+pub(crate) struct Numbering {
+    registers: HashMap<Register, Label>,
+    blocks: HashMap<*const BasicBlock, usize>,
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Numbering>> = const { RefCell::new(None) };
+}
+
+impl Numbering {
+    pub(crate) fn new(function: &Function) -> Self {
+        let mut numbering = Self::default();
+        let mut next = 0;
+        let basic_blocks = function.basic_blocks();
+
+        let mut names = basic_blocks
+            .iter()
+            .filter(|block| block.name().is_some())
+            .filter_map(|block| block.label())
+            .map(|label| label.to_string())
+            .collect::<HashSet<_>>();
+
+        for parameter in function.parameters() {
+            numbering.insert_register(parameter, &mut names, &mut next);
+        }
+
+        for block in basic_blocks.iter() {
+            if block.name().is_none() {
+                numbering.blocks.insert(Rc::as_ptr(block), next);
+                next += 1;
+            }
+
+            for instruction in block.instructions().iter() {
+                if let Some(result) = instruction.result() {
+                    numbering.insert_register(result, &mut names, &mut next);
+                }
+            }
+        }
+
+        numbering
+    }
+
+    /// Assigns the next number to an unnamed register, or a unique name to a named register.
+    fn insert_register(
+        &mut self,
+        register: &Register,
+        names: &mut HashSet<String>,
+        next: &mut usize,
+    ) {
+        let label = match register.name() {
+            Some(name) => {
+                let base_name = name.to_string();
+                let mut unique_name = base_name.clone();
+                let mut suffix = 0usize;
+                while names.contains(&unique_name) {
+                    suffix += 1;
+                    unique_name = format!("{}.{}", base_name, suffix);
+                }
+
+                names.insert(unique_name.clone());
+                Label::Name(unsafe {
+                    // Safety: Register names are identifiers, and the suffixes do not contain null bytes.
+                    Identifier::new_unchecked(unique_name)
+                })
+            }
+            None => {
+                *next += 1;
+                Label::Number(*next - 1)
+            }
+        };
+
+        self.registers.insert(register.clone(), label);
+    }
+
+    /// Gets the name or number assigned to a register.
+    pub(crate) fn register_label(&self, register: &Register) -> Option<&Label> {
+        self.registers.get(register)
+    }
+
+    /// Gets the number assigned to an unnamed block.
+    pub(crate) fn block_number(&self, block: &BasicBlock) -> Option<usize> {
+        self.blocks.get(&(block as *const BasicBlock)).copied()
+    }
+
+    /// Uses this numbering when printing registers and blocks within the closure.
+    pub(crate) fn scope<R, F: FnOnce() -> R>(self, f: F) -> R {
+        /// Restores the previous numbering, even if formatting panics.
+        struct Restore(Option<Numbering>);
+
+        impl Drop for Restore {
+            fn drop(&mut self) {
+                let previous = self.0.take();
+                CURRENT.with(|current| *current.borrow_mut() = previous);
+            }
+        }
+
+        let _restore = Restore(CURRENT.with(|current| current.borrow_mut().replace(self)));
+        f()
+    }
+}
+
+/// Gets the name or number assigned to a register in the function that is currently being printed.
+pub(crate) fn register_label(register: &Register) -> Option<Label> {
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .and_then(|numbering| numbering.register_label(register).cloned())
+    })
+}
+
+/// Gets the number assigned to an unnamed block in the function that is currently being printed.
+pub(crate) fn block_number(block: *const BasicBlock) -> Option<usize> {
+    CURRENT.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .and_then(|numbering| numbering.blocks.get(&block).copied())
+    })
+}
@@ -0,0 +1,322 @@
+//! Splits LLVM assembly into tokens.
+
+use super::{Error, ErrorKind};
+
+/// The kinds of tokens in LLVM assembly.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Token {
+    /// A keyword or type name, such as `define`, `i32`, or `nsw`.
+    Word(String),
+    /// A label of a basic block, such as `entry:` or `3:`.
+    Label(String),
+    /// A local name, such as `%x`, `%"x y"`, or `%3`.
+    Local(String),
+    /// A global name, such as `@main`.
+    Global(String),
+    /// The name of a comdat, such as `$f`.
+    Comdat(String),
+    /// The name of a metadata node or named metadata, such as `!0` or `!llvm.ident`.
+    Metadata(String),
+    /// A reference to an attribute group, such as `#0`.
+    AttributeGroup(String),
+    /// A string, with escape sequences replaced by the bytes they represent.
+    String(Vec<u8>),
+    /// A string of bytes used as an array constant, such as `c"hello\00"`.
+    CString(Vec<u8>),
+    /// A metadata string, such as `!"clang version 13.0.0"`.
+    MetadataString(Vec<u8>),
+    /// An integer in decimal, with an optional sign.
+    Integer(String),
+    /// A floating-point value in decimal or hexadecimal.
+    Float(String),
+    /// `...`
+    Ellipsis,
+    /// Any other single character, such as `=`, `,`, or `{`.
+    Punctuation(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Word(word) => f.write_str(word),
+            Self::Label(label) => write!(f, "{}:", label),
+            Self::Local(name) => write!(f, "%{}", name),
+            Self::Global(name) => write!(f, "@{}", name),
+            Self::Comdat(name) => write!(f, "${}", name),
+            Self::Metadata(name) => write!(f, "!{}", name),
+            Self::AttributeGroup(name) => write!(f, "#{}", name),
+            Self::String(_) => f.write_str("string"),
+            Self::CString(_) => f.write_str("c string"),
+            Self::MetadataString(_) => f.write_str("metadata string"),
+            Self::Integer(digits) | Self::Float(digits) => f.write_str(digits),
+            Self::Ellipsis => f.write_str("..."),
+            Self::Punctuation(c) => write!(f, "'{}'", c),
+        }
+    }
+}
+
+/// A token, along with the line and column where it starts.
+#[derive(Clone, Debug)]
+pub(super) struct Located {
+    pub(super) token: Token,
+    pub(super) line: usize,
+    pub(super) column: usize,
+}
+
+fn is_name_character(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, b'-' | b'$' | b'.' | b'_')
+}
+
+struct Lexer<'a> {
+    source: &'a [u8],
+    offset: usize,
+    line: usize,
+    line_start: usize,
+}
+
+impl Lexer<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.source.get(self.offset).copied()
+    }
+
+    fn peek_at(&self, distance: usize) -> Option<u8> {
+        self.source.get(self.offset + distance).copied()
+    }
+
+    fn column(&self) -> usize {
+        self.offset - self.line_start + 1
+    }
+
+    fn error(&self, kind: ErrorKind) -> Error {
+        Error {
+            line: self.line,
+            column: self.column(),
+            kind,
+        }
+    }
+
+    fn take_while<P: Fn(u8) -> bool>(&mut self, predicate: P) -> &str {
+        let start = self.offset;
+        while matches!(self.peek(), Some(c) if predicate(c)) {
+            self.offset += 1;
+        }
+        // Only ASCII characters are accepted by the predicates, so the slice is always valid UTF-8.
+        std::str::from_utf8(&self.source[start..self.offset]).unwrap_or_default()
+    }
+
+    /// Reads a quoted string starting at the opening quote, replacing `\XX` escape sequences with the bytes they
+    /// represent.
+    fn string(&mut self) -> Result<Vec<u8>, Error> {
+        let start = self.error(ErrorKind::UnterminatedString);
+        self.offset += 1;
+        let mut contents = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(b'\n') => return Err(start),
+                Some(b'"') => {
+                    self.offset += 1;
+                    return Ok(contents);
+                }
+                Some(b'\\') => {
+                    let escaped = self
+                        .source
+                        .get(self.offset + 1..self.offset + 3)
+                        .and_then(|digits| std::str::from_utf8(digits).ok())
+                        .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+                    match escaped {
+                        Some(byte) => {
+                            contents.push(byte);
+                            self.offset += 3;
+                        }
+                        None if self.peek_at(1) == Some(b'\\') => {
+                            contents.push(b'\\');
+                            self.offset += 2;
+                        }
+                        None => return Err(self.error(ErrorKind::InvalidEscape)),
+                    }
+                }
+                Some(byte) => {
+                    contents.push(byte);
+                    self.offset += 1;
+                }
+            }
+        }
+    }
+
+    /// Reads a name following a sigil such as `%` or `@`, which is either quoted or a sequence of name characters.
+    fn name(&mut self) -> Result<String, Error> {
+        if self.peek() == Some(b'"') {
+            let contents = self.string()?;
+            String::from_utf8(contents).map_err(|_| self.error(ErrorKind::InvalidString))
+        } else {
+            let name = self.take_while(is_name_character);
+            if name.is_empty() {
+                Err(self.error(ErrorKind::ExpectedName))
+            } else {
+                Ok(name.to_string())
+            }
+        }
+    }
+
+    fn number(&mut self) -> Token {
+        if self.peek() == Some(b'0') && self.peek_at(1) == Some(b'x') {
+            self.offset += 2;
+            let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+            return Token::Float(format!("0x{}", digits));
+        }
+
+        let start = self.offset;
+        if matches!(self.peek(), Some(b'-' | b'+')) {
+            self.offset += 1;
+        }
+        self.take_while(|c| c.is_ascii_digit());
+
+        let mut float = false;
+        if self.peek() == Some(b'.') {
+            float = true;
+            self.offset += 1;
+            self.take_while(|c| c.is_ascii_digit());
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            float = true;
+            self.offset += 1;
+            if matches!(self.peek(), Some(b'-' | b'+')) {
+                self.offset += 1;
+            }
+            self.take_while(|c| c.is_ascii_digit());
+        }
+
+        let digits = String::from_utf8_lossy(&self.source[start..self.offset]).into_owned();
+        if float {
+            Token::Float(digits)
+        } else {
+            Token::Integer(digits)
+        }
+    }
+
+    fn next(&mut self) -> Result<Option<Located>, Error> {
+        loop {
+            match self.peek() {
+                Some(b'\n') => {
+                    self.offset += 1;
+                    self.line += 1;
+                    self.line_start = self.offset;
+                }
+                Some(c) if c.is_ascii_whitespace() => self.offset += 1,
+                Some(b';') => {
+                    self.take_while(|c| c != b'\n');
+                }
+                _ => break,
+            }
+        }
+
+        let (line, column) = (self.line, self.column());
+        let c = match self.peek() {
+            Some(c) => c,
+            None => return Ok(None),
+        };
+
+        let token = match c {
+            b'%' | b'@' | b'$' | b'#' => {
+                self.offset += 1;
+                let name = self.name()?;
+                match c {
+                    b'%' => Token::Local(name),
+                    b'@' => Token::Global(name),
+                    b'$' => Token::Comdat(name),
+                    _ => Token::AttributeGroup(name),
+                }
+            }
+            b'!' => {
+                self.offset += 1;
+                match self.peek() {
+                    Some(b'"') => Token::MetadataString(self.string()?),
+                    Some(c) if is_name_character(c) || c == b'\\' => {
+                        Token::Metadata(self.take_while(is_name_character).to_string())
+                    }
+                    _ => Token::Punctuation('!'),
+                }
+            }
+            b'"' => {
+                let contents = self.string()?;
+                if self.peek() == Some(b':') {
+                    self.offset += 1;
+                    Token::Label(
+                        String::from_utf8(contents)
+                            .map_err(|_| self.error(ErrorKind::InvalidString))?,
+                    )
+                } else {
+                    Token::String(contents)
+                }
+            }
+            b'c' if self.peek_at(1) == Some(b'"') => {
+                self.offset += 1;
+                Token::CString(self.string()?)
+            }
+            b'.' if self.peek_at(1) == Some(b'.') && self.peek_at(2) == Some(b'.') => {
+                self.offset += 3;
+                Token::Ellipsis
+            }
+            b'0'..=b'9' | b'-' | b'+' => {
+                let start = self.offset;
+                let number = self.number();
+                if matches!(number, Token::Integer(_)) && self.peek() == Some(b':') {
+                    self.offset += 1;
+                    Token::Label(
+                        String::from_utf8_lossy(&self.source[start..self.offset - 1]).into_owned(),
+                    )
+                } else if self.offset == start + 1 && !c.is_ascii_digit() {
+                    return Err(Error {
+                        line,
+                        column,
+                        kind: ErrorKind::UnexpectedCharacter(char::from(c)),
+                    });
+                } else {
+                    number
+                }
+            }
+            c if c.is_ascii_alphabetic() || matches!(c, b'$' | b'.' | b'_') => {
+                let word = self.take_while(is_name_character).to_string();
+                if self.peek() == Some(b':') {
+                    self.offset += 1;
+                    Token::Label(word)
+                } else {
+                    Token::Word(word)
+                }
+            }
+            b'=' | b',' | b'(' | b')' | b'[' | b']' | b'{' | b'}' | b'<' | b'>' | b'*' | b'|' => {
+                self.offset += 1;
+                Token::Punctuation(char::from(c))
+            }
+            _ => {
+                return Err(Error {
+                    line,
+                    column,
+                    kind: ErrorKind::UnexpectedCharacter(char::from(c)),
+                })
+            }
+        };
+
+        Ok(Some(Located {
+            token,
+            line,
+            column,
+        }))
+    }
+}
+
+/// Splits LLVM assembly into tokens, skipping whitespace and comments.
+pub(super) fn tokenize(source: &str) -> Result<Vec<Located>, Error> {
+    let mut lexer = Lexer {
+        source: source.as_bytes(),
+        offset: 0,
+        line: 1,
+        line_start: 0,
+    };
+
+    let mut tokens = Vec::new();
+    while let Some(token) = lexer.next()? {
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
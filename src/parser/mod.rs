@@ -0,0 +1,3112 @@
+//! Reads modules written in LLVM assembly.
+//!
+//! The parser accepts the subset of LLVM's textual IR that can be represented by a [`Module`] and uses typed pointers.
+//! This includes the output of the [`Display`](std::fmt::Display) implementation of [`Module`], unless opaque pointers
+//! were enabled with [`Module::set_opaque_pointers`], and typical IR produced by `clang` for LLVM 13 and earlier.
+//!
+//! Some constructs cannot be represented, and result in an [`ErrorKind::Unsupported`] error. These include opaque
+//! pointers (`ptr`), constant expressions, `undef` and `poison` values, aliases, recursive or opaque named structure
+//! types, specialized metadata nodes such as `!DILocation`, and instructions that have no corresponding builder method,
+//! such as `select`, `bitcast`, or `invoke`.
+//!
+//! Other information is read but discarded, since modules do not model it:
+//! - Parameter, return value, and function attributes, including attribute groups (`#0`) and the `align` attributes of
+//!   call arguments.
+//! - The `nuw`, `nsw`, and `exact` flags of integer operations, fast-math flags of `fcmp` and `call` instructions, and the
+//!   alignment of `atomicrmw` and `cmpxchg` instructions.
+//! - Named metadata, such as `!llvm.module.flags`, and metadata attached to global variables and functions.
+//! - The `source_filename`, `target triple`, and `target datalayout` of the module. The latter two can be read with
+//!   [`parse_target`].
+
+mod lexer;
+
+use crate::block::{self, BasicBlock};
+use crate::global;
+use crate::identifier::{Identifier, Symbol};
+use crate::metadata;
+use crate::module::Module;
+use crate::target::{self, layout, Target};
+use crate::types;
+use crate::value::{self, Register, Value};
+use lexer::{Located, Token};
+use std::collections::HashMap;
+use std::num::NonZeroU32;
+use std::rc::Rc;
+
+/// Describes why LLVM assembly could not be parsed.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A character that cannot start a token was encountered.
+    #[error("unexpected character {0:?}")]
+    UnexpectedCharacter(char),
+    /// A string was not closed before the end of the line.
+    #[error("unterminated string")]
+    UnterminatedString,
+    /// A backslash in a string was not followed by two hexadecimal digits or another backslash.
+    #[error("invalid escape sequence, expected two hexadecimal digits or a backslash")]
+    InvalidEscape,
+    /// A sigil such as `%` or `@` was not followed by a name.
+    #[error("expected name following sigil")]
+    ExpectedName,
+    /// A name or string was not valid UTF-8, or contained a null character.
+    #[error("names and strings must be valid UTF-8 and cannot contain null characters")]
+    InvalidString,
+    /// A token other than the expected one was encountered.
+    #[error("expected {expected}, but got {found}")]
+    Expected {
+        /// Describes what was expected.
+        expected: String,
+        /// The token that was encountered instead.
+        found: String,
+    },
+    /// A type, value, block, comdat, or metadata node was referred to but never defined.
+    #[error("{0} is not defined")]
+    Undefined(String),
+    /// A type, value, block, comdat, or metadata node was defined more than once.
+    #[error("{0} is already defined")]
+    Redefinition(String),
+    /// A value or type did not have the expected type.
+    #[error("{0}")]
+    TypeMismatch(String),
+    /// An integer literal could not be parsed.
+    #[error(transparent)]
+    InvalidInteger(#[from] value::ParseIntegerError),
+    /// A floating-point literal could not be parsed.
+    #[error("{0} is not a valid floating-point value")]
+    InvalidFloat(String),
+    /// An alignment was not a power of two.
+    #[error("{0} is not a valid alignment")]
+    InvalidAlignment(u32),
+    /// An instruction was not valid.
+    #[error(transparent)]
+    Instruction(#[from] block::Error),
+    /// The linkage of a global value was not valid.
+    #[error(transparent)]
+    Linkage(#[from] global::LinkageError),
+    /// The data layout string of the module was not valid.
+    #[error(transparent)]
+    Layout(#[from] layout::ParseError),
+    /// The construct cannot be represented by a [`Module`].
+    #[error("{0} are not supported")]
+    Unsupported(String),
+}
+
+/// Error used when LLVM assembly could not be parsed.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{line}:{column}: {kind}")]
+pub struct Error {
+    line: usize,
+    column: usize,
+    kind: ErrorKind,
+}
+
+impl Error {
+    /// Gets the line where the error occured, starting at `1`.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Gets the column where the error occured, in bytes starting at `1`.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Gets the reason why the error occured.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+type Location = (usize, usize);
+
+fn error_at(location: Location, kind: ErrorKind) -> Error {
+    Error {
+        line: location.0,
+        column: location.1,
+        kind,
+    }
+}
+
+fn is_number(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|c| c.is_ascii_digit())
+}
+
+fn identifier(contents: Vec<u8>, location: Location) -> Result<Identifier, Error> {
+    String::from_utf8(contents)
+        .ok()
+        .and_then(|contents| Identifier::try_from(contents).ok())
+        .ok_or_else(|| error_at(location, ErrorKind::InvalidString))
+}
+
+fn built<T>(location: Location, result: Result<T, block::Error>) -> Result<T, Error> {
+    result.map_err(|error| error_at(location, error.into()))
+}
+
+fn type_mismatch(
+    location: Location,
+    expected: &types::FirstClass,
+    found: impl std::fmt::Display,
+) -> Error {
+    error_at(
+        location,
+        ErrorKind::TypeMismatch(format!(
+            "expected value of type {}, but got {}",
+            expected, found
+        )),
+    )
+}
+
+/// Reads the `target triple` and `target datalayout` of a module written in LLVM assembly.
+///
+/// The target machine uses the `generic` CPU with no additional features, and a missing triple or data layout is
+/// replaced with its default value.
+///
+/// # Errors
+/// Returns an error if the source could not be split into tokens, or if the data layout string is not valid.
+pub fn parse_target(source: &str) -> Result<Target, Error> {
+    let tokens = lexer::tokenize(source)?;
+    let mut triple = target::Triple::default();
+    let mut layout = target::Layout::default();
+    for window in tokens.windows(4) {
+        if let [Located {
+            token: Token::Word(keyword),
+            ..
+        }, Located {
+            token: Token::Word(kind),
+            ..
+        }, Located {
+            token: Token::Punctuation('='),
+            ..
+        }, Located {
+            token: Token::String(contents),
+            line,
+            column,
+        }] = window
+        {
+            if keyword != "target" {
+                continue;
+            }
+
+            let location = (*line, *column);
+            let contents = identifier(contents.clone(), location)?;
+            match kind.as_str() {
+                "triple" => triple = target::Triple::from(contents),
+                "datalayout" => {
+                    layout = target::Layout::try_from(contents.as_id())
+                        .map_err(|error| error_at(location, error.into()))?
+                }
+                _ => (),
+            }
+        }
+    }
+
+    Ok(Target::new(
+        target::Machine::with_defaults(
+            triple,
+            Identifier::sanitized("generic"),
+            target::FeatureSet::new(),
+        ),
+        layout,
+    ))
+}
+
+/// Reads a module written in LLVM assembly, see the [module documentation](self) for the constructs that are supported.
+///
+/// The name of the module is taken from the `; ModuleID = '...'` comment written by LLVM, and is empty if the comment is
+/// missing.
+///
+/// # Errors
+/// Returns an error if the source is not valid LLVM assembly, or if it contains constructs that cannot be represented.
+pub fn parse_module<'t>(source: &str, target: &'t Target) -> Result<Module<'t>, Error> {
+    let name = source
+        .lines()
+        .map(str::trim)
+        .take_while(|line| line.is_empty() || line.starts_with(';'))
+        .find_map(|line| line.strip_prefix("; ModuleID = '")?.strip_suffix('\''))
+        .map(Identifier::sanitized)
+        .unwrap_or_default();
+
+    let mut parser = Parser {
+        tokens: lexer::tokenize(source)?,
+        position: 0,
+        module: Module::new(name, target),
+        types: HashMap::new(),
+        metadata: HashMap::new(),
+        comdats: HashMap::new(),
+        globals: HashMap::new(),
+        labels: HashMap::new(),
+        locals: None,
+    };
+
+    parser.parse()?;
+    Ok(parser.module)
+}
+
+/// Attributes that may precede a parameter or return value, which are skipped since they are not modeled.
+const PARAMETER_ATTRIBUTES: &[&str] = &[
+    "align",
+    "alignstack",
+    "byref",
+    "byval",
+    "dereferenceable",
+    "dereferenceable_or_null",
+    "elementtype",
+    "immarg",
+    "inalloca",
+    "inreg",
+    "nest",
+    "noalias",
+    "nocapture",
+    "nofree",
+    "nonnull",
+    "noundef",
+    "preallocated",
+    "returned",
+    "signext",
+    "sret",
+    "swiftasync",
+    "swifterror",
+    "swiftself",
+    "zeroext",
+];
+
+/// A type or metadata node that can be referred to before it is defined, and is parsed when it is first used.
+enum Definition<T> {
+    Unresolved(usize),
+    Resolving,
+    Resolved(T),
+}
+
+enum ParsedType {
+    Void,
+    FirstClass(Rc<types::FirstClass>),
+    Function(Rc<types::Function>),
+}
+
+enum Shape {
+    Struct { packed: bool },
+    Array,
+    Vector,
+}
+
+enum CallTarget {
+    Global(String),
+    Local(String),
+    Asm {
+        assembly: Identifier,
+        constraints: Identifier,
+        side_effects: bool,
+        align_stack: bool,
+        dialect: value::AsmDialect,
+    },
+}
+
+struct IFuncHeader {
+    name: String,
+    location: Location,
+    linkage: Option<(global::Linkage, Location)>,
+    visibility: global::Visibility,
+    start: usize,
+}
+
+enum Header {
+    Variable(String, Rc<global::Variable>, Option<usize>),
+    IFunc(IFuncHeader),
+}
+
+struct FunctionBody {
+    function: Rc<global::Function>,
+    name: String,
+    /// Index of the first token after the opening brace.
+    start: usize,
+    /// The names of the parameters, with unnamed parameters given the number assigned to them.
+    parameter_keys: Vec<String>,
+    /// The number assigned to the entry block if it has no label.
+    next_number: usize,
+    blocks: Vec<Rc<BasicBlock>>,
+    labels: HashMap<String, Rc<BasicBlock>>,
+    implicit_entry: bool,
+}
+
+/// The local values of the function body being parsed.
+struct Locals {
+    registers: HashMap<String, Register>,
+    blocks: HashMap<String, Rc<BasicBlock>>,
+    /// Placeholders for local values used before they are defined.
+    forward: HashMap<String, (Register, Location)>,
+    replacements: HashMap<Register, Register>,
+}
+
+struct Parser<'t> {
+    tokens: Vec<Located>,
+    position: usize,
+    module: Module<'t>,
+    types: HashMap<String, Definition<Rc<types::FirstClass>>>,
+    metadata: HashMap<String, Definition<Rc<metadata::Node>>>,
+    comdats: HashMap<String, global::Comdat>,
+    globals: HashMap<String, global::Value>,
+    /// The labeled blocks of each function definition, used by `blockaddress` constants.
+    labels: HashMap<String, HashMap<String, Rc<BasicBlock>>>,
+    locals: Option<Locals>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.peek_at(0)
+    }
+
+    fn peek_at(&self, distance: usize) -> Option<&Token> {
+        self.tokens
+            .get(self.position + distance)
+            .map(|located| &located.token)
+    }
+
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(actual)) if actual == word)
+    }
+
+    fn location(&self) -> Location {
+        self.tokens
+            .get(self.position)
+            .or_else(|| self.tokens.last())
+            .map_or((1, 1), |located| (located.line, located.column))
+    }
+
+    fn error(&self, kind: ErrorKind) -> Error {
+        error_at(self.location(), kind)
+    }
+
+    fn expected(&self, expected: &str) -> Error {
+        self.error(ErrorKind::Expected {
+            expected: expected.to_string(),
+            found: self
+                .peek()
+                .map_or_else(|| String::from("end of input"), ToString::to_string),
+        })
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.peek()?.clone();
+        self.position += 1;
+        Some(token)
+    }
+
+    fn eat_punctuation(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Punctuation(c));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.peek_word(word);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_punctuation(&mut self, c: char) -> Result<(), Error> {
+        if self.eat_punctuation(c) {
+            Ok(())
+        } else {
+            Err(self.expected(&format!("'{}'", c)))
+        }
+    }
+
+    fn expect_word(&mut self, word: &str) -> Result<(), Error> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(self.expected(word))
+        }
+    }
+
+    fn expect_any_word(&mut self, expected: &str) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Word(word)) => {
+                let word = word.clone();
+                self.position += 1;
+                Ok(word)
+            }
+            _ => Err(self.expected(expected)),
+        }
+    }
+
+    fn expect_integer<T: std::str::FromStr>(&mut self) -> Result<T, Error> {
+        match self.peek() {
+            Some(Token::Integer(digits)) => match digits.parse() {
+                Ok(value) => {
+                    self.position += 1;
+                    Ok(value)
+                }
+                Err(_) => Err(self.expected("integer in range")),
+            },
+            _ => Err(self.expected("integer")),
+        }
+    }
+
+    fn expect_string(&mut self) -> Result<Identifier, Error> {
+        let location = self.location();
+        match self.peek() {
+            Some(Token::String(contents)) => {
+                let contents = contents.clone();
+                self.position += 1;
+                identifier(contents, location)
+            }
+            _ => Err(self.expected("string")),
+        }
+    }
+
+    fn expect_global(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Global(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.expected("global name")),
+        }
+    }
+
+    fn expect_local(&mut self) -> Result<String, Error> {
+        match self.peek() {
+            Some(Token::Local(name)) => {
+                let name = name.clone();
+                self.position += 1;
+                Ok(name)
+            }
+            _ => Err(self.expected("local name")),
+        }
+    }
+
+    fn expect_alignment(&mut self) -> Result<layout::Alignment, Error> {
+        let location = self.location();
+        let bytes = self.expect_integer::<u32>()?;
+        layout::Alignment::new(bytes)
+            .ok_or_else(|| error_at(location, ErrorKind::InvalidAlignment(bytes)))
+    }
+
+    /// Parses an `addrspace(N)` modifier, which can only refer to the default address space.
+    fn expect_default_address_space(&mut self, what: &str) -> Result<(), Error> {
+        let location = self.location();
+        self.expect_word("addrspace")?;
+        self.expect_punctuation('(')?;
+        let address_space = self.expect_integer::<u32>()?;
+        self.expect_punctuation(')')?;
+        if address_space == 0 {
+            Ok(())
+        } else {
+            Err(error_at(
+                location,
+                ErrorKind::Unsupported(format!("{} in non-default address spaces", what)),
+            ))
+        }
+    }
+
+    fn symbol(&self, name: &str, location: Location) -> Result<Symbol, Error> {
+        Ok(self
+            .module
+            .intern(identifier(name.as_bytes().to_vec(), location)?.as_id()))
+    }
+
+    /// Determines whether the token at the specified index starts a top-level definition.
+    fn is_item_start(&self, index: usize) -> bool {
+        let token = |offset: usize| {
+            self.tokens
+                .get(index + offset)
+                .map(|located| &located.token)
+        };
+        let equals = || token(1) == Some(&Token::Punctuation('='));
+        match token(0) {
+            Some(Token::Word(word)) => matches!(
+                word.as_str(),
+                "attributes" | "declare" | "define" | "source_filename" | "target"
+            ),
+            Some(Token::Global(_) | Token::Comdat(_) | Token::Metadata(_)) => equals(),
+            Some(Token::Local(_)) => {
+                equals() && matches!(token(2), Some(Token::Word(word)) if word == "type")
+            }
+            _ => false,
+        }
+    }
+
+    fn is_end_of_item(&self) -> bool {
+        self.position >= self.tokens.len() || self.is_item_start(self.position)
+    }
+
+    fn skip_item(&mut self) {
+        self.position += 1;
+        while !self.is_end_of_item() {
+            self.position += 1;
+        }
+    }
+
+    /// Skips a token along with any tokens enclosed by the brackets that it opens.
+    fn skip_balanced(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.advance() {
+            match token {
+                Token::Punctuation('(' | '[' | '{' | '<') => depth += 1,
+                Token::Punctuation(')' | ']' | '}' | '>') => depth = depth.saturating_sub(1),
+                _ => (),
+            }
+
+            if depth == 0 {
+                return;
+            }
+        }
+    }
+
+    /// Skips an attribute, such as `nounwind`, `align 4`, or `dereferenceable(8)`.
+    fn skip_attribute(&mut self) {
+        let align = self.peek_word("align");
+        self.position += 1;
+        if self.peek() == Some(&Token::Punctuation('(')) {
+            self.skip_balanced();
+        } else if align && matches!(self.peek(), Some(Token::Integer(_))) {
+            self.position += 1;
+        }
+    }
+
+    fn skip_parameter_attributes(&mut self) {
+        while matches!(self.peek(), Some(Token::Word(word)) if PARAMETER_ATTRIBUTES.contains(&word.as_str()))
+        {
+            self.skip_attribute();
+        }
+    }
+
+    /// Skips a metadata attachment following its kind, such as the `!0` in `!dbg !0`.
+    fn skip_metadata(&mut self) {
+        if self.peek() == Some(&Token::Punctuation('!')) {
+            self.position += 1;
+        }
+        self.skip_balanced();
+    }
+
+    fn parse(&mut self) -> Result<(), Error> {
+        let items = self.scan()?;
+        let mut slots = Vec::with_capacity(items.len());
+        let mut bodies = Vec::new();
+        let mut initializers = Vec::new();
+        let mut ifuncs = Vec::new();
+
+        for start in items {
+            self.position = start;
+            let location = self.location();
+            let (name, global) = if matches!(self.peek(), Some(Token::Global(_))) {
+                match self.parse_global_header()? {
+                    Header::Variable(name, variable, initializer) => {
+                        if let Some(initializer) = initializer {
+                            initializers.push((variable.clone(), initializer));
+                        }
+                        (name, global::Value::Variable(variable))
+                    }
+                    Header::IFunc(header) => {
+                        ifuncs.push((slots.len(), header));
+                        slots.push(None);
+                        continue;
+                    }
+                }
+            } else {
+                let (name, function, body) = self.parse_function_header()?;
+                bodies.extend(body);
+                (name, global::Value::Function(function))
+            };
+
+            self.declare_global(name, location, global.clone())?;
+            slots.push(Some(global));
+        }
+
+        // Indirect functions are created last, since their resolvers can be defined after them.
+        for (slot, header) in ifuncs {
+            let (name, location) = (header.name.clone(), header.location);
+            let global = global::Value::IFunc(self.parse_ifunc(header)?);
+            self.declare_global(name, location, global.clone())?;
+            slots[slot] = Some(global);
+        }
+
+        for global in slots.into_iter().flatten() {
+            self.module.add_global_value(global);
+        }
+
+        for body in bodies.iter_mut() {
+            self.create_blocks(body)?;
+        }
+
+        for body in bodies.iter() {
+            self.parse_body(body)?;
+        }
+
+        for (variable, start) in initializers {
+            self.position = start;
+            let initializer = self.parse_value(variable.value_type())?;
+            variable.set_initializer(Some(initializer));
+        }
+
+        Ok(())
+    }
+
+    fn declare_global(
+        &mut self,
+        name: String,
+        location: Location,
+        global: global::Value,
+    ) -> Result<(), Error> {
+        if self.globals.contains_key(&name) {
+            return Err(error_at(
+                location,
+                ErrorKind::Redefinition(format!("@{}", name)),
+            ));
+        }
+
+        self.globals.insert(name, global);
+        Ok(())
+    }
+
+    /// Records the locations of type and metadata definitions, parses comdats, and returns the locations of global values,
+    /// since types, metadata, and global values can be referred to before they are defined.
+    fn scan(&mut self) -> Result<Vec<usize>, Error> {
+        let mut items = Vec::new();
+        while let Some(token) = self.peek().cloned() {
+            let start = self.position;
+            match token {
+                Token::Word(word) if matches!(word.as_str(), "declare" | "define") => {
+                    items.push(start)
+                }
+                Token::Word(word)
+                    if matches!(word.as_str(), "attributes" | "source_filename" | "target") => {}
+                Token::Global(_) if self.is_item_start(start) => items.push(start),
+                Token::Local(name) if self.is_item_start(start) => {
+                    if self
+                        .types
+                        .insert(name.clone(), Definition::Unresolved(start + 3))
+                        .is_some()
+                    {
+                        return Err(self.error(ErrorKind::Redefinition(format!("%{}", name))));
+                    }
+                }
+                Token::Comdat(name) if self.is_item_start(start) => {
+                    self.parse_comdat(name)?;
+                    if !self.is_end_of_item() {
+                        return Err(self.expected("end of comdat"));
+                    }
+                    continue;
+                }
+                Token::Metadata(name) if self.is_item_start(start) => {
+                    // Named metadata, such as `!llvm.ident`, cannot be represented by modules and is ignored.
+                    if is_number(&name)
+                        && self
+                            .metadata
+                            .insert(name.clone(), Definition::Unresolved(start + 2))
+                            .is_some()
+                    {
+                        return Err(self.error(ErrorKind::Redefinition(format!("!{}", name))));
+                    }
+                }
+                Token::Word(word) if matches!(word.as_str(), "module" | "uselistorder") => {
+                    return Err(self.error(ErrorKind::Unsupported(format!(
+                        "top-level {} directives",
+                        word
+                    ))))
+                }
+                _ => return Err(self.expected("top-level definition")),
+            }
+            self.skip_item();
+        }
+        Ok(items)
+    }
+
+    fn parse_comdat(&mut self, name: String) -> Result<(), Error> {
+        let location = self.location();
+        self.position += 1;
+        self.expect_punctuation('=')?;
+        self.expect_word("comdat")?;
+        let selection_kind = match self.peek() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "any" => global::ComdatSelectionKind::Any,
+                "exactmatch" => global::ComdatSelectionKind::ExactMatch,
+                "largest" => global::ComdatSelectionKind::Largest,
+                "nodeduplicate" | "noduplicates" => global::ComdatSelectionKind::NoDeduplicate,
+                "samesize" => global::ComdatSelectionKind::SameSize,
+                _ => return Err(self.expected("comdat selection kind")),
+            },
+            _ => return Err(self.expected("comdat selection kind")),
+        };
+        self.position += 1;
+
+        let comdat = global::Comdat::new(self.symbol(&name, location)?, selection_kind);
+        if self.comdats.insert(name.clone(), comdat).is_some() {
+            return Err(error_at(
+                location,
+                ErrorKind::Redefinition(format!("${}", name)),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Parses the comdat of a global value, which is named after the global value if no name is specified.
+    fn parse_comdat_reference(&mut self, global_name: &str) -> Result<global::Comdat, Error> {
+        let location = self.location();
+        let name = if self.eat_punctuation('(') {
+            let name = match self.advance() {
+                Some(Token::Comdat(name)) => name,
+                _ => {
+                    self.position -= 1;
+                    return Err(self.expected("comdat name"));
+                }
+            };
+            self.expect_punctuation(')')?;
+            name
+        } else {
+            global_name.to_string()
+        };
+
+        self.comdats
+            .get(&name)
+            .cloned()
+            .ok_or_else(|| error_at(location, ErrorKind::Undefined(format!("${}", name))))
+    }
+
+    fn parse_linkage(&mut self) -> Option<(global::Linkage, Location)> {
+        let linkage = match self.peek() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "private" => global::Linkage::Private,
+                "internal" => global::Linkage::Internal,
+                "available_externally" => global::Linkage::AvailableExternally,
+                "linkonce" => global::Linkage::LinkOnce,
+                "weak" => global::Linkage::Weak,
+                "common" => global::Linkage::Common,
+                "appending" => global::Linkage::Appending,
+                "extern_weak" => global::Linkage::ExternWeak,
+                "linkonce_odr" => global::Linkage::LinkOnceODR,
+                "weak_odr" => global::Linkage::WeakODR,
+                "external" => global::Linkage::External,
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        let location = self.location();
+        self.position += 1;
+        Some((linkage, location))
+    }
+
+    fn parse_dso_local(&mut self) -> bool {
+        if self.eat_word("dso_local") {
+            true
+        } else {
+            self.eat_word("dso_preemptable");
+            false
+        }
+    }
+
+    fn parse_visibility(&mut self) -> global::Visibility {
+        let visibility = match self.peek() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "default" => global::Visibility::Default,
+                "hidden" => global::Visibility::Hidden,
+                "protected" => global::Visibility::Protected,
+                _ => return global::Visibility::default(),
+            },
+            _ => return global::Visibility::default(),
+        };
+
+        self.position += 1;
+        visibility
+    }
+
+    fn reject_dll_storage_class(&self) -> Result<(), Error> {
+        if self.peek_word("dllimport") || self.peek_word("dllexport") {
+            Err(self.error(ErrorKind::Unsupported(String::from("DLL storage classes"))))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn parse_calling_convention(&mut self) -> Result<Option<global::CallingConvention>, Error> {
+        use global::CallingConvention;
+
+        let calling_convention = match self.peek() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "ccc" => CallingConvention::C,
+                "fastcc" => CallingConvention::Fast,
+                "coldcc" => CallingConvention::Cold,
+                "ghccc" => CallingConvention::GHC,
+                "webkit_jscc" => CallingConvention::WebKitJS,
+                "anyregcc" => CallingConvention::AnyReg,
+                "preserve_mostcc" => CallingConvention::PreserveMost,
+                "preserve_allcc" => CallingConvention::PreserveAll,
+                "swiftcc" => CallingConvention::Swift,
+                "cxx_fast_tlscc" => CallingConvention::CxxFastTLS,
+                "cc" => {
+                    self.position += 1;
+                    let value = self.expect_integer::<u32>()?;
+                    return Ok(Some(
+                        [
+                            CallingConvention::C,
+                            CallingConvention::Fast,
+                            CallingConvention::Cold,
+                            CallingConvention::GHC,
+                            CallingConvention::HiPE,
+                            CallingConvention::WebKitJS,
+                            CallingConvention::AnyReg,
+                            CallingConvention::PreserveMost,
+                            CallingConvention::PreserveAll,
+                            CallingConvention::Swift,
+                            CallingConvention::CxxFastTLS,
+                        ]
+                        .into_iter()
+                        .find(|known| known.value() == value)
+                        .unwrap_or(CallingConvention::Custom(value)),
+                    ));
+                }
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+
+        self.position += 1;
+        Ok(Some(calling_convention))
+    }
+
+    fn parse_function_header(
+        &mut self,
+    ) -> Result<(String, Rc<global::Function>, Option<FunctionBody>), Error> {
+        let declaration = self.eat_word("declare");
+        if !declaration {
+            self.expect_word("define")?;
+        }
+
+        let linkage = self.parse_linkage();
+        let dso_local = self.parse_dso_local();
+        let visibility = self.parse_visibility();
+        self.reject_dll_storage_class()?;
+        let calling_convention = self.parse_calling_convention()?;
+        self.skip_parameter_attributes();
+        let return_type = self.parse_return_type()?;
+
+        let name_location = self.location();
+        let name = self.expect_global()?;
+        self.expect_punctuation('(')?;
+        let mut parameter_types = Vec::new();
+        let mut parameter_names = Vec::new();
+        let mut variadic = false;
+        if !self.eat_punctuation(')') {
+            loop {
+                if self.peek() == Some(&Token::Ellipsis) {
+                    self.position += 1;
+                    variadic = true;
+                    self.expect_punctuation(')')?;
+                    break;
+                }
+
+                parameter_types.push(self.parse_first_class_type()?);
+                let mut parameter_name = None;
+                loop {
+                    match self.peek() {
+                        None | Some(Token::Punctuation(',' | ')')) => break,
+                        Some(Token::Local(name)) => {
+                            parameter_name = Some((name.clone(), self.location()));
+                            self.position += 1;
+                        }
+                        Some(Token::Punctuation('(' | '[' | '{' | '<')) => self.skip_balanced(),
+                        Some(_) => self.position += 1,
+                    }
+                }
+                parameter_names.push(parameter_name);
+
+                if self.eat_punctuation(')') {
+                    break;
+                }
+                self.expect_punctuation(',')?;
+            }
+        }
+
+        let signature = Rc::new(if variadic {
+            types::Function::new_variadic(return_type, parameter_types)
+        } else {
+            types::Function::new(return_type, parameter_types)
+        });
+
+        let function = global::Function::new(self.symbol(&name, name_location)?, signature);
+        if let Some((linkage, location)) = linkage {
+            function
+                .try_set_linkage(linkage)
+                .map_err(|error| error_at(location, error.into()))?;
+        }
+        function.set_dso_local(dso_local);
+        function.set_visibility(visibility);
+        if let Some(calling_convention) = calling_convention {
+            function.set_calling_convention(calling_convention);
+        }
+
+        let mut parameter_keys = Vec::with_capacity(parameter_names.len());
+        let mut next_number = 0usize;
+        let mut names = Vec::with_capacity(parameter_names.len());
+        for parameter_name in parameter_names {
+            match parameter_name {
+                Some((name, location)) if !is_number(&name) => {
+                    names.push(Some(identifier(name.as_bytes().to_vec(), location)?));
+                    parameter_keys.push(name);
+                }
+                Some((number, _)) => {
+                    next_number = number
+                        .parse::<usize>()
+                        .map_or(next_number, |number| number + 1);
+                    names.push(None);
+                    parameter_keys.push(number);
+                }
+                None => {
+                    names.push(None);
+                    parameter_keys.push(next_number.to_string());
+                    next_number += 1;
+                }
+            }
+        }
+        function.set_parameter_names(names);
+
+        loop {
+            if self.is_end_of_item() {
+                break;
+            }
+
+            match self.peek().cloned() {
+                Some(Token::Word(word)) => match word.as_str() {
+                    "unnamed_addr" => {
+                        self.position += 1;
+                        function.set_unnamed_address(Some(global::UnnamedAddress::Global));
+                    }
+                    "local_unnamed_addr" => {
+                        self.position += 1;
+                        function.set_unnamed_address(Some(global::UnnamedAddress::Local));
+                    }
+                    "section" => {
+                        self.position += 1;
+                        function.set_section(Some(self.expect_string()?));
+                    }
+                    "comdat" => {
+                        self.position += 1;
+                        function.set_comdat(Some(self.parse_comdat_reference(&name)?));
+                    }
+                    "align" => {
+                        self.position += 1;
+                        function.set_alignment(Some(self.expect_alignment()?));
+                    }
+                    "addrspace" => self.expect_default_address_space("functions")?,
+                    "gc" | "partition" | "personality" | "prefix" | "prologue" => {
+                        return Err(self.error(ErrorKind::Unsupported(format!(
+                            "{} clauses of functions",
+                            word
+                        ))))
+                    }
+                    _ => self.skip_attribute(),
+                },
+                Some(Token::AttributeGroup(_)) => self.position += 1,
+                Some(Token::String(_)) => {
+                    self.position += 1;
+                    if self.eat_punctuation('=') {
+                        self.expect_string()?;
+                    }
+                }
+                Some(Token::Metadata(_)) => {
+                    // Metadata attached to functions is ignored.
+                    self.position += 1;
+                    self.skip_metadata();
+                }
+                _ => break,
+            }
+        }
+
+        let body = if self.peek() == Some(&Token::Punctuation('{')) && !declaration {
+            Some(FunctionBody {
+                function: function.clone(),
+                name: name.clone(),
+                start: self.position + 1,
+                parameter_keys,
+                next_number,
+                blocks: Vec::new(),
+                labels: HashMap::new(),
+                implicit_entry: false,
+            })
+        } else if declaration && self.is_end_of_item() {
+            None
+        } else {
+            return Err(self.expected(if declaration {
+                "end of function declaration"
+            } else {
+                "function body"
+            }));
+        };
+
+        Ok((name, function, body))
+    }
+
+    fn parse_global_header(&mut self) -> Result<Header, Error> {
+        let location = self.location();
+        let name = self.expect_global()?;
+        self.expect_punctuation('=')?;
+        let linkage = self.parse_linkage();
+        let dso_local = self.parse_dso_local();
+        let visibility = self.parse_visibility();
+        self.reject_dll_storage_class()?;
+
+        if self.eat_word("ifunc") {
+            return Ok(Header::IFunc(IFuncHeader {
+                name,
+                location,
+                linkage,
+                visibility,
+                start: self.position,
+            }));
+        } else if self.peek_word("alias") {
+            return Err(self.error(ErrorKind::Unsupported(String::from("aliases"))));
+        }
+
+        let thread_local_mode = if self.eat_word("thread_local") {
+            Some(if self.eat_punctuation('(') {
+                let mode = match self.expect_any_word("thread local mode")?.as_str() {
+                    "localdynamic" => global::ThreadLocalMode::LocalDynamic,
+                    "initialexec" => global::ThreadLocalMode::InitialExec,
+                    "localexec" => global::ThreadLocalMode::LocalExec,
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected("thread local mode"));
+                    }
+                };
+                self.expect_punctuation(')')?;
+                mode
+            } else {
+                global::ThreadLocalMode::GeneralDynamic
+            })
+        } else {
+            None
+        };
+
+        let unnamed_address = if self.eat_word("unnamed_addr") {
+            Some(global::UnnamedAddress::Global)
+        } else if self.eat_word("local_unnamed_addr") {
+            Some(global::UnnamedAddress::Local)
+        } else {
+            None
+        };
+
+        if self.peek_word("addrspace") {
+            self.expect_default_address_space("global variables")?;
+        }
+
+        let externally_initialized = self.eat_word("externally_initialized");
+        let constant = self.eat_word("constant");
+        if !constant {
+            self.expect_word("global")?;
+        }
+
+        let value_type = self.parse_first_class_type()?;
+        let variable = global::Variable::new(self.symbol(&name, location)?, value_type, constant);
+        if let Some((linkage, location)) = linkage {
+            variable
+                .try_set_linkage(linkage)
+                .map_err(|error| error_at(location, error.into()))?;
+        }
+        variable.set_dso_local(dso_local);
+        variable.set_visibility(visibility);
+        variable.set_thread_local_mode(thread_local_mode);
+        variable.set_unnamed_address(unnamed_address);
+        variable.set_externally_initialized(externally_initialized);
+
+        // As in LLVM, only `external` and `extern_weak` variables are declarations, and all other variables must have an
+        // initializer.
+        let declaration = matches!(
+            linkage,
+            Some((global::Linkage::External | global::Linkage::ExternWeak, _))
+        );
+
+        let initializer = if declaration {
+            None
+        } else if self.peek() == Some(&Token::Punctuation(',')) || self.is_end_of_item() {
+            return Err(self.expected("initializer"));
+        } else {
+            let start = self.position;
+            let mut depth = 0usize;
+            while !(depth == 0
+                && (self.peek() == Some(&Token::Punctuation(',')) || self.is_end_of_item()))
+            {
+                match self.peek() {
+                    Some(Token::Punctuation('(' | '[' | '{' | '<')) => depth += 1,
+                    Some(Token::Punctuation(')' | ']' | '}' | '>')) => {
+                        depth = depth.saturating_sub(1)
+                    }
+                    None => break,
+                    _ => (),
+                }
+                self.position += 1;
+            }
+            Some(start)
+        };
+
+        while self.eat_punctuation(',') {
+            match self.peek().cloned() {
+                Some(Token::Word(word)) if word == "section" => {
+                    self.position += 1;
+                    variable.set_section(Some(self.expect_string()?));
+                }
+                Some(Token::Word(word)) if word == "comdat" => {
+                    self.position += 1;
+                    variable.set_comdat(Some(self.parse_comdat_reference(&name)?));
+                }
+                Some(Token::Word(word)) if word == "align" => {
+                    self.position += 1;
+                    variable.set_alignment(Some(self.expect_alignment()?));
+                }
+                Some(Token::Metadata(_)) => {
+                    // Metadata attached to global variables is ignored.
+                    self.position += 1;
+                    self.skip_metadata();
+                }
+                _ => return Err(self.expected("section, comdat, or alignment")),
+            }
+        }
+
+        if !self.is_end_of_item() {
+            return Err(self.expected("end of global variable"));
+        }
+
+        Ok(Header::Variable(name, variable, initializer))
+    }
+
+    fn parse_ifunc(&mut self, header: IFuncHeader) -> Result<Rc<global::IFunc>, Error> {
+        self.position = header.start;
+        let signature_location = self.location();
+        let signature = match self.parse_type()? {
+            ParsedType::Function(signature) => signature,
+            _ => {
+                return Err(error_at(
+                    signature_location,
+                    ErrorKind::TypeMismatch(format!(
+                        "expected function type for indirect function @{}",
+                        header.name
+                    )),
+                ))
+            }
+        };
+
+        self.expect_punctuation(',')?;
+        let resolver_type = self.parse_first_class_type()?;
+        let resolver_location = self.location();
+        let resolver_name = self.expect_global()?;
+        let resolver = match self.globals.get(&resolver_name) {
+            Some(global::Value::Function(function)) => function.clone(),
+            Some(_) => {
+                return Err(error_at(
+                    resolver_location,
+                    ErrorKind::TypeMismatch(format!(
+                        "resolver @{} is not a function",
+                        resolver_name
+                    )),
+                ))
+            }
+            None => {
+                return Err(error_at(
+                    resolver_location,
+                    ErrorKind::Undefined(format!("@{}", resolver_name)),
+                ))
+            }
+        };
+
+        let actual_type = Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+            types::Pointer::new(resolver.signature().clone()),
+        )));
+        if resolver_type != actual_type {
+            return Err(type_mismatch(
+                resolver_location,
+                &resolver_type,
+                format!("@{} of type {}", resolver_name, actual_type),
+            ));
+        }
+
+        if !matches!(
+            resolver.signature().return_type(),
+            types::Return::FirstClass(return_type)
+                if matches!(return_type.as_ref(), types::FirstClass::Single(types::SingleValue::Pointer(_)))
+        ) {
+            return Err(error_at(
+                resolver_location,
+                ErrorKind::TypeMismatch(format!(
+                    "expected resolver @{} to return a pointer",
+                    resolver_name
+                )),
+            ));
+        }
+
+        if !self.is_end_of_item() {
+            return Err(self.expected("end of indirect function"));
+        }
+
+        let ifunc = global::IFunc::new(
+            self.symbol(&header.name, header.location)?,
+            signature,
+            resolver,
+        );
+        if let Some((linkage, location)) = header.linkage {
+            ifunc
+                .try_set_linkage(linkage)
+                .map_err(|error| error_at(location, error.into()))?;
+        }
+        ifunc.set_visibility(header.visibility);
+        Ok(ifunc)
+    }
+
+    fn parse_type(&mut self) -> Result<ParsedType, Error> {
+        let location = self.location();
+        let token = match self.advance() {
+            Some(token) => token,
+            None => return Err(self.expected("type")),
+        };
+
+        let mut parsed = match token {
+            Token::Word(word) => match word.as_str() {
+                "void" => ParsedType::Void,
+                "half" => ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                    types::SingleValue::Float(types::Float::Half),
+                ))),
+                "float" => ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                    types::SingleValue::Float(types::Float::Float),
+                ))),
+                "double" => ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                    types::SingleValue::Float(types::Float::Double),
+                ))),
+                "token" => ParsedType::FirstClass(Rc::new(types::FirstClass::Token)),
+                "metadata" => ParsedType::FirstClass(Rc::new(types::FirstClass::Metadata)),
+                "ptr" => {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Unsupported(String::from("opaque pointer types")),
+                    ))
+                }
+                "bfloat" | "fp128" | "label" | "opaque" | "ppc_fp128" | "x86_amx" | "x86_fp80"
+                | "x86_mmx" => {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Unsupported(format!("{} types", word)),
+                    ))
+                }
+                _ => match word
+                    .strip_prefix('i')
+                    .and_then(|bits| bits.parse::<u32>().ok())
+                    .and_then(types::IntegerSize::new)
+                {
+                    Some(size) => ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                        types::SingleValue::Integer(size),
+                    ))),
+                    None => {
+                        self.position -= 1;
+                        return Err(self.expected("type"));
+                    }
+                },
+            },
+            Token::Local(name) => ParsedType::FirstClass(self.named_type(&name, location)?),
+            Token::Punctuation('{') => ParsedType::FirstClass(self.parse_struct_type(false)?),
+            Token::Punctuation('<') if self.eat_punctuation('{') => {
+                let packed = self.parse_struct_type(true)?;
+                self.expect_punctuation('>')?;
+                ParsedType::FirstClass(packed)
+            }
+            Token::Punctuation('<') => {
+                let count_location = self.location();
+                let count = self.expect_integer::<u32>()?;
+                self.expect_word("x")?;
+                let element_type = self.parse_first_class_type()?;
+                self.expect_punctuation('>')?;
+                let count = NonZeroU32::new(count).ok_or_else(|| {
+                    error_at(
+                        count_location,
+                        ErrorKind::Expected {
+                            expected: String::from("non-zero vector length"),
+                            found: String::from("0"),
+                        },
+                    )
+                })?;
+                ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                    types::SingleValue::Vector(types::Vector::new(element_type, count)),
+                )))
+            }
+            Token::Punctuation('[') => {
+                let count = self.expect_integer::<u32>()?;
+                self.expect_word("x")?;
+                let element_type = self.parse_first_class_type()?;
+                self.expect_punctuation(']')?;
+                ParsedType::FirstClass(Rc::new(types::FirstClass::Aggregate(
+                    types::Aggregate::Array(types::Array::new(element_type, count)),
+                )))
+            }
+            _ => {
+                self.position -= 1;
+                return Err(self.expected("type"));
+            }
+        };
+
+        loop {
+            let address_space = if self.eat_punctuation('*') {
+                layout::AddressSpace::default()
+            } else if self.peek_word("addrspace")
+                && self.peek_at(1) == Some(&Token::Punctuation('('))
+            {
+                self.position += 2;
+                let address_space = layout::AddressSpace(self.expect_integer()?);
+                self.expect_punctuation(')')?;
+                self.expect_punctuation('*')?;
+                address_space
+            } else if self.peek() == Some(&Token::Punctuation('('))
+                && !matches!(parsed, ParsedType::Function(_))
+            {
+                self.position += 1;
+                parsed = ParsedType::Function(Rc::new(self.parse_function_type(parsed, location)?));
+                continue;
+            } else {
+                break;
+            };
+
+            let pointer = match parsed {
+                ParsedType::FirstClass(pointee) => {
+                    types::Pointer::in_address_space(pointee, address_space)
+                }
+                ParsedType::Function(pointee) => {
+                    types::Pointer::in_address_space(pointee, address_space)
+                }
+                ParsedType::Void => {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Expected {
+                            expected: String::from("pointee type"),
+                            found: String::from("void"),
+                        },
+                    ))
+                }
+            };
+            parsed = ParsedType::FirstClass(Rc::new(types::FirstClass::Single(
+                types::SingleValue::Pointer(pointer),
+            )));
+        }
+
+        Ok(parsed)
+    }
+
+    /// Parses the parameter types of a function type, following the opening parenthesis.
+    fn parse_function_type(
+        &mut self,
+        return_type: ParsedType,
+        location: Location,
+    ) -> Result<types::Function, Error> {
+        let return_type = match return_type {
+            ParsedType::Void => types::Return::Void,
+            ParsedType::FirstClass(return_type) => types::Return::FirstClass(return_type),
+            ParsedType::Function(signature) => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Expected {
+                        expected: String::from("return type"),
+                        found: signature.to_string(),
+                    },
+                ))
+            }
+        };
+
+        let mut parameter_types = Vec::new();
+        if self.eat_punctuation(')') {
+            return Ok(types::Function::new(return_type, parameter_types));
+        }
+
+        loop {
+            if self.peek() == Some(&Token::Ellipsis) {
+                self.position += 1;
+                self.expect_punctuation(')')?;
+                return Ok(types::Function::new_variadic(return_type, parameter_types));
+            }
+
+            parameter_types.push(self.parse_first_class_type()?);
+            if self.eat_punctuation(')') {
+                return Ok(types::Function::new(return_type, parameter_types));
+            }
+            self.expect_punctuation(',')?;
+        }
+    }
+
+    fn parse_first_class_type(&mut self) -> Result<Rc<types::FirstClass>, Error> {
+        let location = self.location();
+        let found = match self.parse_type()? {
+            ParsedType::FirstClass(first_class) => return Ok(first_class),
+            ParsedType::Void => String::from("void"),
+            ParsedType::Function(signature) => signature.to_string(),
+        };
+
+        Err(error_at(
+            location,
+            ErrorKind::Expected {
+                expected: String::from("first-class type"),
+                found,
+            },
+        ))
+    }
+
+    fn parse_return_type(&mut self) -> Result<types::Return, Error> {
+        let location = self.location();
+        match self.parse_type()? {
+            ParsedType::Void => Ok(types::Return::Void),
+            ParsedType::FirstClass(return_type) => Ok(types::Return::FirstClass(return_type)),
+            ParsedType::Function(signature) => Err(error_at(
+                location,
+                ErrorKind::Expected {
+                    expected: String::from("return type"),
+                    found: signature.to_string(),
+                },
+            )),
+        }
+    }
+
+    /// Parses the member types of a structure type, following the opening brace.
+    fn parse_struct_type(&mut self, packed: bool) -> Result<Rc<types::FirstClass>, Error> {
+        let mut member_types = Vec::new();
+        if !self.eat_punctuation('}') {
+            loop {
+                member_types.push(self.parse_first_class_type()?);
+                if self.eat_punctuation('}') {
+                    break;
+                }
+                self.expect_punctuation(',')?;
+            }
+        }
+
+        Ok(Rc::new(types::FirstClass::Aggregate(
+            types::Aggregate::Struct(types::Struct::new(member_types, packed)),
+        )))
+    }
+
+    fn named_type(
+        &mut self,
+        name: &str,
+        location: Location,
+    ) -> Result<Rc<types::FirstClass>, Error> {
+        let start = match self.types.get(name) {
+            Some(Definition::Resolved(named)) => return Ok(named.clone()),
+            Some(Definition::Resolving) => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Unsupported(format!("recursive types such as %{}", name)),
+                ))
+            }
+            Some(Definition::Unresolved(start)) => *start,
+            None => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Undefined(format!("%{}", name)),
+                ))
+            }
+        };
+
+        self.types.insert(name.to_string(), Definition::Resolving);
+        let previous = std::mem::replace(&mut self.position, start);
+        let named = self.parse_first_class_type();
+        self.position = previous;
+        let named = named?;
+        self.types
+            .insert(name.to_string(), Definition::Resolved(named.clone()));
+        Ok(named)
+    }
+
+    fn metadata_definition(
+        &mut self,
+        name: &str,
+        location: Location,
+    ) -> Result<Rc<metadata::Node>, Error> {
+        let start = match self.metadata.get(name) {
+            Some(Definition::Resolved(node)) => return Ok(node.clone()),
+            Some(Definition::Resolving) => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Unsupported(format!("cyclic metadata nodes such as !{}", name)),
+                ))
+            }
+            Some(Definition::Unresolved(start)) => *start,
+            None => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Undefined(format!("!{}", name)),
+                ))
+            }
+        };
+
+        self.metadata
+            .insert(name.to_string(), Definition::Resolving);
+        let previous = std::mem::replace(&mut self.position, start);
+        self.eat_word("distinct");
+        let node = self.parse_metadata_node();
+        self.position = previous;
+        let node = node?;
+        self.metadata
+            .insert(name.to_string(), Definition::Resolved(node.clone()));
+        Ok(node)
+    }
+
+    fn parse_metadata_node(&mut self) -> Result<Rc<metadata::Node>, Error> {
+        let location = self.location();
+        match self.peek().cloned() {
+            Some(Token::Metadata(name)) if is_number(&name) => {
+                self.position += 1;
+                self.metadata_definition(&name, location)
+            }
+            Some(Token::Metadata(name)) => Err(error_at(
+                location,
+                ErrorKind::Unsupported(format!("specialized metadata nodes such as !{}", name)),
+            )),
+            Some(Token::Punctuation('!')) => {
+                self.position += 1;
+                self.expect_punctuation('{')?;
+                let mut operands = Vec::new();
+                if self.eat_punctuation('}') {
+                    return Ok(metadata::Node::new(operands));
+                }
+
+                loop {
+                    let location = self.location();
+                    operands.push(match self.peek().cloned() {
+                        Some(Token::Word(word)) if word == "null" => {
+                            self.position += 1;
+                            None
+                        }
+                        Some(Token::MetadataString(contents)) => {
+                            self.position += 1;
+                            Some(metadata::Metadata::String(identifier(contents, location)?))
+                        }
+                        Some(Token::Metadata(_) | Token::Punctuation('!')) => {
+                            Some(metadata::Metadata::Node(self.parse_metadata_node()?))
+                        }
+                        _ => {
+                            let (_, operand) = self.parse_typed_value()?;
+                            if !operand.is_constant() {
+                                return Err(error_at(
+                                    location,
+                                    ErrorKind::Expected {
+                                        expected: String::from("constant"),
+                                        found: operand.to_string(),
+                                    },
+                                ));
+                            }
+                            Some(metadata::Metadata::Value(operand))
+                        }
+                    });
+
+                    if self.eat_punctuation('}') {
+                        return Ok(metadata::Node::new(operands));
+                    }
+                    self.expect_punctuation(',')?;
+                }
+            }
+            _ => Err(self.expected("metadata node")),
+        }
+    }
+
+    fn parse_typed_value(&mut self) -> Result<(Rc<types::FirstClass>, Value), Error> {
+        let value_type = self.parse_first_class_type()?;
+        let value = self.parse_value(&value_type)?;
+        Ok((value_type, value))
+    }
+
+    fn parse_value(&mut self, expected: &Rc<types::FirstClass>) -> Result<Value, Error> {
+        let location = self.location();
+        let token = match self.advance() {
+            Some(token) => token,
+            None => return Err(self.expected("value")),
+        };
+
+        let value = match token {
+            Token::Local(name) => return self.local_value(name, expected, location),
+            Token::Global(name) => match self.globals.get(&name) {
+                Some(global) => Value::GlobalAddress(value::GlobalAddress::new(global)),
+                None => {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Undefined(format!("@{}", name)),
+                    ))
+                }
+            },
+            Token::Integer(digits) => match expected.as_ref() {
+                types::FirstClass::Single(types::SingleValue::Integer(size)) => Value::Integer(
+                    value::Integer::from_str_radix(*size, &digits, 10)
+                        .map_err(|error| error_at(location, error.into()))?,
+                ),
+                _ => return Err(type_mismatch(location, expected, digits)),
+            },
+            Token::Float(digits) => Value::Float(Self::float_value(expected, &digits, location)?),
+            Token::CString(contents) => {
+                let elements = contents
+                    .into_iter()
+                    .map(|byte| {
+                        Value::Integer(value::Integer::from_u64(
+                            types::IntegerSize::I8,
+                            u64::from(byte),
+                        ))
+                    })
+                    .collect();
+                Self::aggregate_value(expected, Shape::Array, elements, location)?
+            }
+            Token::Punctuation('{') => {
+                let elements = self.parse_elements('}')?;
+                Self::aggregate_value(
+                    expected,
+                    Shape::Struct { packed: false },
+                    elements,
+                    location,
+                )?
+            }
+            Token::Punctuation('<') if self.eat_punctuation('{') => {
+                let elements = self.parse_elements('}')?;
+                self.expect_punctuation('>')?;
+                Self::aggregate_value(expected, Shape::Struct { packed: true }, elements, location)?
+            }
+            Token::Punctuation('<') => {
+                let elements = self.parse_elements('>')?;
+                Self::aggregate_value(expected, Shape::Vector, elements, location)?
+            }
+            Token::Punctuation('[') => {
+                let elements = self.parse_elements(']')?;
+                Self::aggregate_value(expected, Shape::Array, elements, location)?
+            }
+            Token::Word(word) => match word.as_str() {
+                "true" | "false" => Value::Integer(value::Integer::from_u64(
+                    types::IntegerSize::I1,
+                    u64::from(word == "true"),
+                )),
+                "null" => match expected.as_ref() {
+                    types::FirstClass::Single(types::SingleValue::Pointer(pointer)) => {
+                        Value::Null(pointer.clone())
+                    }
+                    _ => return Err(type_mismatch(location, expected, word)),
+                },
+                "zeroinitializer" => {
+                    zero_value(expected).ok_or_else(|| type_mismatch(location, expected, word))?
+                }
+                "blockaddress" => self.parse_block_address(location)?,
+                "undef" | "poison" => {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Unsupported(format!("{} values", word)),
+                    ))
+                }
+                _ if self.peek() == Some(&Token::Punctuation('('))
+                    || self.peek_word("inbounds") =>
+                {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Unsupported(format!("constant expressions such as {}", word)),
+                    ))
+                }
+                _ => {
+                    self.position -= 1;
+                    return Err(self.expected("value"));
+                }
+            },
+            _ => {
+                self.position -= 1;
+                return Err(self.expected("value"));
+            }
+        };
+
+        if &value.value_type() != expected {
+            return Err(type_mismatch(location, expected, &value));
+        }
+        Ok(value)
+    }
+
+    fn local_value(
+        &mut self,
+        name: String,
+        expected: &Rc<types::FirstClass>,
+        location: Location,
+    ) -> Result<Value, Error> {
+        let locals = match self.locals.as_mut() {
+            Some(locals) => locals,
+            None => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Expected {
+                        expected: String::from("constant"),
+                        found: format!("%{}", name),
+                    },
+                ))
+            }
+        };
+
+        let register = match locals.registers.get(&name).or_else(|| {
+            locals
+                .forward
+                .get(&name)
+                .map(|(placeholder, _)| placeholder)
+        }) {
+            Some(register) => register.clone(),
+            None => {
+                let placeholder = Register::new(expected.clone());
+                locals
+                    .forward
+                    .insert(name.clone(), (placeholder.clone(), location));
+                placeholder
+            }
+        };
+
+        if register.value_type() != expected {
+            return Err(type_mismatch(
+                location,
+                expected,
+                format!("%{} of type {}", name, register.value_type()),
+            ));
+        }
+        Ok(Value::Register(register))
+    }
+
+    fn float_value(
+        expected: &types::FirstClass,
+        digits: &str,
+        location: Location,
+    ) -> Result<value::Float, Error> {
+        let float_type = match expected {
+            types::FirstClass::Single(types::SingleValue::Float(float_type)) => float_type,
+            _ => return Err(type_mismatch(location, expected, digits)),
+        };
+
+        let invalid = || error_at(location, ErrorKind::InvalidFloat(digits.to_string()));
+        match (float_type, digits.strip_prefix("0x")) {
+            (types::Float::Half, Some(hexadecimal)) => hexadecimal
+                .strip_prefix('H')
+                .and_then(|bits| u16::from_str_radix(bits, 16).ok())
+                .map(value::Float::Half)
+                .ok_or_else(invalid),
+            (_, Some(hexadecimal)) => {
+                let bits = u64::from_str_radix(hexadecimal, 16).map_err(|_| invalid())?;
+                let value = f64::from_bits(bits);
+                Ok(match float_type {
+                    types::Float::Float => value::Float::Float(value as f32),
+                    _ => value::Float::Double(value),
+                })
+            }
+            (types::Float::Half, None) => Err(error_at(
+                location,
+                ErrorKind::Unsupported(String::from("decimal half constants")),
+            )),
+            (types::Float::Float, None) => digits
+                .parse()
+                .map(value::Float::Float)
+                .map_err(|_| invalid()),
+            (types::Float::Double, None) => digits
+                .parse()
+                .map(value::Float::Double)
+                .map_err(|_| invalid()),
+        }
+    }
+
+    /// Parses the typed elements of an aggregate or vector constant, following the opening bracket.
+    fn parse_elements(&mut self, close: char) -> Result<Vec<Value>, Error> {
+        let mut elements = Vec::new();
+        if self.eat_punctuation(close) {
+            return Ok(elements);
+        }
+
+        loop {
+            let location = self.location();
+            let (_, element) = self.parse_typed_value()?;
+            if !element.is_constant() {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Expected {
+                        expected: String::from("constant"),
+                        found: element.to_string(),
+                    },
+                ));
+            }
+            elements.push(element);
+
+            if self.eat_punctuation(close) {
+                return Ok(elements);
+            }
+            self.expect_punctuation(',')?;
+        }
+    }
+
+    fn aggregate_value(
+        expected: &Rc<types::FirstClass>,
+        shape: Shape,
+        elements: Vec<Value>,
+        location: Location,
+    ) -> Result<Value, Error> {
+        let length = elements.len();
+        let element_types = match (&shape, expected.as_ref()) {
+            (
+                Shape::Struct { packed },
+                types::FirstClass::Aggregate(types::Aggregate::Struct(struct_type)),
+            ) if struct_type.is_packed() == *packed => struct_type.member_types().to_vec(),
+            (Shape::Array, types::FirstClass::Aggregate(types::Aggregate::Array(array))) => {
+                vec![array.element_type().clone(); array.count() as usize]
+            }
+            (Shape::Vector, types::FirstClass::Single(types::SingleValue::Vector(vector))) => {
+                vec![vector.element_type().clone(); vector.count() as usize]
+            }
+            _ => Vec::new(),
+        };
+
+        let matches = element_types.len() == length
+            && (length > 0 || !matches!(shape, Shape::Vector))
+            && element_types
+                .iter()
+                .zip(elements.iter())
+                .all(|(element_type, element)| &element.value_type() == element_type);
+
+        if !matches {
+            return Err(type_mismatch(
+                location,
+                expected,
+                format!("constant with {} elements", length),
+            ));
+        }
+
+        Ok(match expected.as_ref() {
+            types::FirstClass::Single(types::SingleValue::Vector(vector)) => {
+                Value::Vector(value::Vector::new(vector.clone(), elements))
+            }
+            types::FirstClass::Aggregate(aggregate) => {
+                Value::Aggregate(value::Aggregate::new(aggregate.clone(), elements))
+            }
+            _ => return Err(type_mismatch(location, expected, "aggregate constant")),
+        })
+    }
+
+    fn parse_block_address(&mut self, location: Location) -> Result<Value, Error> {
+        self.expect_punctuation('(')?;
+        let function_location = self.location();
+        let function_name = self.expect_global()?;
+        self.expect_punctuation(',')?;
+        let block_location = self.location();
+        let block_name = self.expect_local()?;
+        self.expect_punctuation(')')?;
+
+        let function = match self.globals.get(&function_name) {
+            Some(global::Value::Function(function)) => function.clone(),
+            Some(_) => {
+                return Err(error_at(
+                    function_location,
+                    ErrorKind::TypeMismatch(format!("@{} is not a function", function_name)),
+                ))
+            }
+            None => {
+                return Err(error_at(
+                    function_location,
+                    ErrorKind::Undefined(format!("@{}", function_name)),
+                ))
+            }
+        };
+
+        let block = self
+            .labels
+            .get(&function_name)
+            .and_then(|labels| labels.get(&block_name))
+            .cloned()
+            .ok_or_else(|| {
+                error_at(
+                    block_location,
+                    ErrorKind::Undefined(format!("%{}", block_name)),
+                )
+            })?;
+
+        if matches!(function.basic_blocks().first(), Some(entry) if Rc::ptr_eq(entry, &block)) {
+            return Err(error_at(
+                location,
+                ErrorKind::Expected {
+                    expected: String::from("block other than the entry block"),
+                    found: format!("%{}", block_name),
+                },
+            ));
+        }
+
+        Ok(Value::BlockAddress(value::BlockAddress::new(
+            &function, &block,
+        )))
+    }
+
+    /// Creates the blocks of a function definition, since branches can refer to blocks that are defined after them.
+    fn create_blocks(&mut self, body: &mut FunctionBody) -> Result<(), Error> {
+        self.position = body.start;
+        let mut depth = 0usize;
+        let mut labels = Vec::new();
+        loop {
+            let location = self.location();
+            match self.advance() {
+                Some(Token::Punctuation('{')) => depth += 1,
+                Some(Token::Punctuation('}')) if depth == 0 => break,
+                Some(Token::Punctuation('}')) => depth -= 1,
+                Some(Token::Label(label)) if depth == 0 => labels.push((label, location)),
+                Some(_) => (),
+                None => return Err(self.expected("'}'")),
+            }
+        }
+
+        body.implicit_entry = !matches!(
+            self.tokens.get(body.start).map(|located| &located.token),
+            Some(Token::Label(_))
+        );
+
+        if body.implicit_entry {
+            let block = BasicBlock::new();
+            body.labels
+                .insert(body.next_number.to_string(), block.clone());
+            body.blocks.push(block);
+        }
+
+        for (label, location) in labels {
+            let block = if is_number(&label) {
+                BasicBlock::new()
+            } else {
+                BasicBlock::with_name(identifier(label.as_bytes().to_vec(), location)?)
+            };
+
+            if body.labels.insert(label.clone(), block.clone()).is_some() {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Redefinition(format!("%{}", label)),
+                ));
+            }
+            body.blocks.push(block);
+        }
+
+        for block in body.blocks.iter() {
+            body.function.append_basic_block(block.clone());
+        }
+
+        self.labels.insert(body.name.clone(), body.labels.clone());
+        Ok(())
+    }
+
+    fn parse_body(&mut self, body: &FunctionBody) -> Result<(), Error> {
+        let registers = body
+            .parameter_keys
+            .iter()
+            .cloned()
+            .zip(body.function.parameters().iter().cloned())
+            .collect();
+
+        self.locals = Some(Locals {
+            registers,
+            blocks: body.labels.clone(),
+            forward: HashMap::new(),
+            replacements: HashMap::new(),
+        });
+
+        self.position = body.start;
+        let mut blocks = body.blocks.iter();
+        let mut current = if body.implicit_entry {
+            blocks.next().cloned()
+        } else {
+            None
+        };
+        loop {
+            match self.peek() {
+                Some(Token::Punctuation('}')) => {
+                    self.position += 1;
+                    break;
+                }
+                Some(Token::Label(_)) => {
+                    self.position += 1;
+                    current = blocks.next().cloned();
+                }
+                Some(_) => {
+                    let block = current
+                        .clone()
+                        .ok_or_else(|| self.expected("block label"))?;
+                    self.parse_instruction(&block)?;
+                }
+                None => return Err(self.expected("'}'")),
+            }
+        }
+
+        if !self.is_end_of_item() {
+            return Err(self.expected("end of function definition"));
+        }
+
+        let locals = match self.locals.take() {
+            Some(locals) => locals,
+            None => return Ok(()),
+        };
+
+        if let Some((name, (_, location))) = locals
+            .forward
+            .iter()
+            .min_by_key(|(_, (_, location))| *location)
+        {
+            return Err(error_at(
+                *location,
+                ErrorKind::Undefined(format!("%{}", name)),
+            ));
+        }
+
+        if !locals.replacements.is_empty() {
+            for block in body.blocks.iter() {
+                for instruction in block.instructions_mut().iter_mut() {
+                    for operand in instruction.operands_mut() {
+                        if let Value::Register(register) = operand {
+                            if let Some(replacement) = locals.replacements.get(register) {
+                                *operand = Value::Register(replacement.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn define_register(
+        &mut self,
+        name: String,
+        location: Location,
+        register: Register,
+    ) -> Result<(), Error> {
+        let locals = match self.locals.as_mut() {
+            Some(locals) => locals,
+            None => return Ok(()),
+        };
+
+        if locals.registers.contains_key(&name) || locals.blocks.contains_key(&name) {
+            return Err(error_at(
+                location,
+                ErrorKind::Redefinition(format!("%{}", name)),
+            ));
+        }
+
+        if let Some((placeholder, _)) = locals.forward.remove(&name) {
+            if placeholder.value_type() != register.value_type() {
+                return Err(error_at(
+                    location,
+                    ErrorKind::TypeMismatch(format!(
+                        "%{} is used as a value of type {}, but is defined as a value of type {}",
+                        name,
+                        placeholder.value_type(),
+                        register.value_type()
+                    )),
+                ));
+            }
+            locals.replacements.insert(placeholder, register.clone());
+        }
+
+        if !is_number(&name) {
+            register.set_name(Some(identifier(name.as_bytes().to_vec(), location)?));
+        }
+        locals.registers.insert(name, register);
+        Ok(())
+    }
+
+    fn parse_block(&mut self) -> Result<Rc<BasicBlock>, Error> {
+        let location = self.location();
+        let name = self.expect_local()?;
+        self.locals
+            .as_ref()
+            .and_then(|locals| locals.blocks.get(&name))
+            .cloned()
+            .ok_or_else(|| error_at(location, ErrorKind::Undefined(format!("%{}", name))))
+    }
+
+    fn parse_label(&mut self) -> Result<Rc<BasicBlock>, Error> {
+        self.expect_word("label")?;
+        self.parse_block()
+    }
+
+    fn parse_ordering(&mut self) -> Result<block::AtomicOrdering, Error> {
+        let ordering = match self.peek() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "unordered" => block::AtomicOrdering::Unordered,
+                "monotonic" => block::AtomicOrdering::Monotonic,
+                "acquire" => block::AtomicOrdering::Acquire,
+                "release" => block::AtomicOrdering::Release,
+                "acq_rel" => block::AtomicOrdering::AcquireRelease,
+                "seq_cst" => block::AtomicOrdering::SequentiallyConsistent,
+                _ => return Err(self.expected("atomic ordering")),
+            },
+            _ => return Err(self.expected("atomic ordering")),
+        };
+
+        self.position += 1;
+        Ok(ordering)
+    }
+
+    fn parse_atomic(&mut self) -> Result<block::Atomic, Error> {
+        let scope = if self.eat_word("syncscope") {
+            self.expect_punctuation('(')?;
+            let name = self.expect_string()?;
+            self.expect_punctuation(')')?;
+            if name.as_str() == "singlethread" {
+                block::SyncScope::SingleThread
+            } else {
+                block::SyncScope::Named(name)
+            }
+        } else {
+            block::SyncScope::System
+        };
+
+        Ok(block::Atomic {
+            scope,
+            ordering: self.parse_ordering()?,
+        })
+    }
+
+    fn parse_alignment_suffix(&mut self) -> Result<Option<layout::Alignment>, Error> {
+        if self.peek() == Some(&Token::Punctuation(','))
+            && matches!(self.peek_at(1), Some(Token::Word(word)) if word == "align")
+        {
+            self.position += 2;
+            Ok(Some(self.expect_alignment()?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn parse_fast_math_flags(&mut self) -> block::FastMathFlags {
+        let mut flags = block::FastMathFlags::NONE;
+        while let Some(Token::Word(word)) = self.peek() {
+            flags |= match word.as_str() {
+                "nnan" => block::FastMathFlags::NO_NANS,
+                "ninf" => block::FastMathFlags::NO_INFS,
+                "nsz" => block::FastMathFlags::NO_SIGNED_ZEROS,
+                "arcp" => block::FastMathFlags::ALLOW_RECIPROCAL,
+                "contract" => block::FastMathFlags::ALLOW_CONTRACT,
+                "afn" => block::FastMathFlags::APPROXIMATE_FUNCTIONS,
+                "reassoc" => block::FastMathFlags::ALLOW_REASSOCIATION,
+                "fast" => block::FastMathFlags::FAST,
+                _ => break,
+            };
+            self.position += 1;
+        }
+        flags
+    }
+
+    /// Skips the function attributes following the arguments of a call, which are on the same line as the closing
+    /// parenthesis.
+    fn skip_call_attributes(&mut self) {
+        let line = match self
+            .position
+            .checked_sub(1)
+            .and_then(|index| self.tokens.get(index))
+        {
+            Some(located) => located.line,
+            None => return,
+        };
+
+        loop {
+            match self.tokens.get(self.position) {
+                Some(Located {
+                    token: Token::AttributeGroup(_),
+                    ..
+                }) => self.position += 1,
+                Some(Located {
+                    token: Token::Word(_),
+                    line: attribute_line,
+                    ..
+                }) if *attribute_line == line && !self.is_item_start(self.position) => {
+                    if self.peek_word("to") {
+                        return;
+                    }
+                    self.skip_attribute();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Value>, Error> {
+        self.expect_punctuation('(')?;
+        let mut arguments = Vec::new();
+        if self.eat_punctuation(')') {
+            return Ok(arguments);
+        }
+
+        loop {
+            let argument_type = self.parse_first_class_type()?;
+            self.skip_parameter_attributes();
+            arguments.push(self.parse_value(&argument_type)?);
+            if self.eat_punctuation(')') {
+                return Ok(arguments);
+            }
+            self.expect_punctuation(',')?;
+        }
+    }
+
+    /// Parses the callee, signature, and arguments of a `call` or `callbr` instruction, following the calling convention.
+    fn parse_call_target(
+        &mut self,
+    ) -> Result<(CallTarget, Rc<types::Function>, Location, Vec<Value>), Error> {
+        self.skip_parameter_attributes();
+        let call_type = self.parse_type()?;
+        let callee_location = self.location();
+        let target = match self.advance() {
+            Some(Token::Global(name)) => CallTarget::Global(name),
+            Some(Token::Local(name)) => CallTarget::Local(name),
+            Some(Token::Word(word)) if word == "asm" => {
+                let mut side_effects = false;
+                let mut align_stack = false;
+                let mut dialect = value::AsmDialect::ATT;
+                loop {
+                    if self.eat_word("sideeffect") {
+                        side_effects = true;
+                    } else if self.eat_word("alignstack") {
+                        align_stack = true;
+                    } else if self.eat_word("inteldialect") {
+                        dialect = value::AsmDialect::Intel;
+                    } else if self.peek_word("unwind") {
+                        return Err(self.error(ErrorKind::Unsupported(String::from(
+                            "inline assembly expressions that can unwind",
+                        ))));
+                    } else {
+                        break;
+                    }
+                }
+
+                let assembly = self.expect_string()?;
+                self.expect_punctuation(',')?;
+                CallTarget::Asm {
+                    assembly,
+                    constraints: self.expect_string()?,
+                    side_effects,
+                    align_stack,
+                    dialect,
+                }
+            }
+            _ => {
+                self.position = self.position.saturating_sub(1);
+                return Err(self.expected("callee"));
+            }
+        };
+
+        let arguments = self.parse_arguments()?;
+        self.skip_call_attributes();
+        if self.peek() == Some(&Token::Punctuation('[')) {
+            return Err(self.error(ErrorKind::Unsupported(String::from("operand bundles"))));
+        }
+
+        let signature = match call_type {
+            ParsedType::Function(signature) => signature,
+            ParsedType::Void => Rc::new(types::Function::new(
+                types::Return::Void,
+                arguments.iter().map(Value::value_type).collect::<Vec<_>>(),
+            )),
+            ParsedType::FirstClass(return_type) => Rc::new(types::Function::new(
+                types::Return::FirstClass(return_type),
+                arguments.iter().map(Value::value_type).collect::<Vec<_>>(),
+            )),
+        };
+
+        Ok((target, signature, callee_location, arguments))
+    }
+
+    /// Gets the value of a callee that is not a function with a matching signature.
+    fn indirect_callee(
+        &mut self,
+        target: CallTarget,
+        signature: &Rc<types::Function>,
+        location: Location,
+    ) -> Result<Result<Rc<global::Function>, Value>, Error> {
+        match target {
+            CallTarget::Global(name) => match self.globals.get(&name) {
+                Some(global::Value::Function(function)) if function.signature() == signature => {
+                    Ok(Ok(function.clone()))
+                }
+                Some(global) => Ok(Err(Value::GlobalAddress(value::GlobalAddress::new(global)))),
+                None => Err(error_at(
+                    location,
+                    ErrorKind::Undefined(format!("@{}", name)),
+                )),
+            },
+            CallTarget::Local(name) => {
+                let pointer_type = Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+                    types::Pointer::new(signature.clone()),
+                )));
+                Ok(Err(self.local_value(name, &pointer_type, location)?))
+            }
+            CallTarget::Asm { .. } => Err(error_at(
+                location,
+                ErrorKind::Unsupported(String::from("inline assembly with calling conventions")),
+            )),
+        }
+    }
+
+    fn inline_asm(
+        target: CallTarget,
+        signature: &Rc<types::Function>,
+    ) -> Result<Rc<value::InlineAsm>, CallTarget> {
+        match target {
+            CallTarget::Asm {
+                assembly,
+                constraints,
+                side_effects,
+                align_stack,
+                dialect,
+            } => {
+                let mut expression =
+                    value::InlineAsm::new(signature.clone(), assembly, constraints)
+                        .with_dialect(dialect);
+                if side_effects {
+                    expression = expression.with_side_effects();
+                }
+                if align_stack {
+                    expression = expression.with_align_stack();
+                }
+                Ok(Rc::new(expression))
+            }
+            target => Err(target),
+        }
+    }
+
+    fn parse_call(
+        &mut self,
+        block: &Rc<BasicBlock>,
+        location: Location,
+        tail_call: block::TailCallKind,
+    ) -> Result<Option<Register>, Error> {
+        // Fast-math flags of calls are not modeled.
+        self.parse_fast_math_flags();
+        let calling_convention = self.parse_calling_convention()?;
+        let (target, signature, callee_location, arguments) = self.parse_call_target()?;
+        let target = match Self::inline_asm(target, &signature) {
+            Ok(expression) => {
+                if calling_convention.unwrap_or_default() != global::CallingConvention::C
+                    || tail_call != block::TailCallKind::None
+                {
+                    return Err(error_at(
+                        location,
+                        ErrorKind::Unsupported(String::from(
+                            "calling conventions and tail call markers on inline assembly calls",
+                        )),
+                    ));
+                }
+                return built(location, block.try_call_asm(&expression, arguments));
+            }
+            Err(target) => target,
+        };
+
+        let calling_convention = calling_convention.unwrap_or_default();
+        match self.indirect_callee(target, &signature, callee_location)? {
+            Ok(function) => built(
+                location,
+                block.try_call_with(&function, arguments, calling_convention, tail_call),
+            ),
+            Err(callee) => built(
+                location,
+                block.try_call_indirect_with(
+                    callee,
+                    signature,
+                    arguments,
+                    calling_convention,
+                    tail_call,
+                ),
+            ),
+        }
+    }
+
+    fn parse_callbr(
+        &mut self,
+        block: &Rc<BasicBlock>,
+        location: Location,
+    ) -> Result<Option<Register>, Error> {
+        let calling_convention = self.parse_calling_convention()?;
+        let (target, signature, callee_location, arguments) = self.parse_call_target()?;
+        self.expect_word("to")?;
+        let fallthrough_target = self.parse_label()?;
+        self.expect_punctuation('[')?;
+        let mut indirect_targets = Vec::new();
+        if !self.eat_punctuation(']') {
+            loop {
+                indirect_targets.push(self.parse_label()?);
+                if self.eat_punctuation(']') {
+                    break;
+                }
+                self.expect_punctuation(',')?;
+            }
+        }
+
+        let indirect_targets = indirect_targets.iter().collect::<Vec<_>>();
+        let calling_convention = calling_convention.unwrap_or_default();
+        let target = match Self::inline_asm(target, &signature) {
+            Ok(expression) if calling_convention == global::CallingConvention::C => {
+                return built(
+                    location,
+                    block.try_callbr_asm(
+                        &expression,
+                        arguments,
+                        &fallthrough_target,
+                        indirect_targets,
+                    ),
+                )
+            }
+            Ok(_) => {
+                return Err(error_at(
+                    location,
+                    ErrorKind::Unsupported(String::from(
+                        "calling conventions on inline assembly calls",
+                    )),
+                ))
+            }
+            Err(target) => target,
+        };
+        let callee = self.indirect_callee(target, &signature, callee_location)?;
+
+        match callee {
+            Ok(function) if function.get_calling_convention() == calling_convention => built(
+                location,
+                block.try_callbr(&function, arguments, &fallthrough_target, indirect_targets),
+            ),
+            Err(callee) if calling_convention == global::CallingConvention::C => built(
+                location,
+                block.try_callbr_indirect(
+                    callee,
+                    signature,
+                    arguments,
+                    &fallthrough_target,
+                    indirect_targets,
+                ),
+            ),
+            _ => Err(error_at(
+                location,
+                ErrorKind::Unsupported(String::from(
+                    "callbr instructions with a calling convention that differs from the callee",
+                )),
+            )),
+        }
+    }
+
+    fn parse_instruction(&mut self, block: &Rc<BasicBlock>) -> Result<(), Error> {
+        let result_name = match (self.peek(), self.peek_at(1)) {
+            (Some(Token::Local(name)), Some(Token::Punctuation('='))) => {
+                let result_name = (name.clone(), self.location());
+                self.position += 2;
+                Some(result_name)
+            }
+            _ => None,
+        };
+
+        let location = self.location();
+        let opcode = self.expect_any_word("instruction")?;
+        let result = self.parse_operation(block, &opcode, location)?;
+        match (result_name, result) {
+            (Some((name, name_location)), Some(register)) => {
+                self.define_register(name, name_location, register)?
+            }
+            (Some((name, name_location)), None) => {
+                return Err(error_at(
+                    name_location,
+                    ErrorKind::TypeMismatch(format!(
+                        "{} does not produce a value that can be assigned to %{}",
+                        opcode, name
+                    )),
+                ))
+            }
+            (None, _) => (),
+        }
+
+        while self.peek() == Some(&Token::Punctuation(',')) {
+            let kind_location = (self.position + 1 < self.tokens.len()).then(|| {
+                let located = &self.tokens[self.position + 1];
+                (located.line, located.column)
+            });
+            let kind = match (self.peek_at(1), kind_location) {
+                (Some(Token::Metadata(kind)), Some(kind_location)) => {
+                    identifier(kind.as_bytes().to_vec(), kind_location)?
+                }
+                _ => return Err(self.expected("end of instruction")),
+            };
+            self.position += 2;
+            let node = self.parse_metadata_node()?;
+            if let Some(kind_location) = kind_location {
+                built(kind_location, block.try_attach_metadata(kind, node))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_operation(
+        &mut self,
+        block: &Rc<BasicBlock>,
+        opcode: &str,
+        location: Location,
+    ) -> Result<Option<Register>, Error> {
+        use block::{ConversionOperation, FcmpPredicate, IcmpPredicate, IntegerBinaryOperation};
+
+        let integer_operation = match opcode {
+            "add" => Some(IntegerBinaryOperation::Add),
+            "sub" => Some(IntegerBinaryOperation::Sub),
+            "mul" => Some(IntegerBinaryOperation::Mul),
+            "udiv" => Some(IntegerBinaryOperation::UDiv),
+            "sdiv" => Some(IntegerBinaryOperation::SDiv),
+            "urem" => Some(IntegerBinaryOperation::URem),
+            "srem" => Some(IntegerBinaryOperation::SRem),
+            "shl" => Some(IntegerBinaryOperation::Shl),
+            "lshr" => Some(IntegerBinaryOperation::LShr),
+            "ashr" => Some(IntegerBinaryOperation::AShr),
+            "and" => Some(IntegerBinaryOperation::And),
+            "or" => Some(IntegerBinaryOperation::Or),
+            "xor" => Some(IntegerBinaryOperation::Xor),
+            _ => None,
+        };
+
+        if let Some(operation) = integer_operation {
+            // The nuw, nsw, and exact flags are not modeled.
+            while self.eat_word("nuw") || self.eat_word("nsw") || self.eat_word("exact") {}
+            let (operand_type, x) = self.parse_typed_value()?;
+            self.expect_punctuation(',')?;
+            let y = self.parse_value(&operand_type)?;
+            return built(location, block.try_integer_binary(operation, x, y)).map(Some);
+        }
+
+        let float_operation = match opcode {
+            "fadd" => Some(block::FloatBinaryOperation::Add),
+            "fsub" => Some(block::FloatBinaryOperation::Sub),
+            "fmul" => Some(block::FloatBinaryOperation::Mul),
+            "fdiv" => Some(block::FloatBinaryOperation::Div),
+            "frem" => Some(block::FloatBinaryOperation::Rem),
+            _ => None,
+        };
+
+        if let Some(operation) = float_operation {
+            let flags = self.parse_fast_math_flags();
+            let (operand_type, x) = self.parse_typed_value()?;
+            self.expect_punctuation(',')?;
+            let y = self.parse_value(&operand_type)?;
+            return built(
+                location,
+                block.try_float_binary_with(operation, flags, x, y),
+            )
+            .map(Some);
+        }
+
+        let conversion = match opcode {
+            "trunc" => Some(ConversionOperation::Trunc),
+            "zext" => Some(ConversionOperation::ZExt),
+            "sext" => Some(ConversionOperation::SExt),
+            "fptrunc" => Some(ConversionOperation::FPTrunc),
+            "fpext" => Some(ConversionOperation::FPExt),
+            "fptoui" => Some(ConversionOperation::FPToUI),
+            "fptosi" => Some(ConversionOperation::FPToSI),
+            "uitofp" => Some(ConversionOperation::UIToFP),
+            "sitofp" => Some(ConversionOperation::SIToFP),
+            _ => None,
+        };
+
+        if let Some(operation) = conversion {
+            let (_, operand) = self.parse_typed_value()?;
+            self.expect_word("to")?;
+            let result_type = self.parse_first_class_type()?;
+            return built(
+                location,
+                block.try_conversion(operation, operand, result_type),
+            )
+            .map(Some);
+        }
+
+        match opcode {
+            "ret" => {
+                if self.eat_word("void") {
+                    built(location, block.try_ret(None))?;
+                } else {
+                    let (_, value) = self.parse_typed_value()?;
+                    built(location, block.try_ret(Some(value)))?;
+                }
+                Ok(None)
+            }
+            "br" => {
+                if self.peek_word("label") {
+                    let target = self.parse_label()?;
+                    built(location, block.try_br(&target))?;
+                } else {
+                    let (_, condition) = self.parse_typed_value()?;
+                    self.expect_punctuation(',')?;
+                    let true_target = self.parse_label()?;
+                    self.expect_punctuation(',')?;
+                    let false_target = self.parse_label()?;
+                    built(
+                        location,
+                        block.try_br_if(condition, &true_target, &false_target),
+                    )?;
+                }
+                Ok(None)
+            }
+            "switch" => {
+                let (value_type, value) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let default_target = self.parse_label()?;
+                self.expect_punctuation('[')?;
+                let mut cases = Vec::new();
+                let mut targets = Vec::new();
+                while !self.eat_punctuation(']') {
+                    let case_location = self.location();
+                    let case_type = self.parse_first_class_type()?;
+                    if case_type != value_type {
+                        return Err(type_mismatch(case_location, &value_type, case_type));
+                    }
+                    match self.parse_value(&case_type)? {
+                        Value::Integer(case) => cases.push(case),
+                        case => return Err(type_mismatch(case_location, &value_type, case)),
+                    }
+                    self.expect_punctuation(',')?;
+                    targets.push(self.parse_label()?);
+                }
+                built(
+                    location,
+                    block.try_switch(
+                        value,
+                        &default_target,
+                        cases.into_iter().zip(targets.iter()).collect(),
+                    ),
+                )?;
+                Ok(None)
+            }
+            "indirectbr" => {
+                let (_, address) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                self.expect_punctuation('[')?;
+                let mut destinations = Vec::new();
+                if !self.eat_punctuation(']') {
+                    loop {
+                        destinations.push(self.parse_label()?);
+                        if self.eat_punctuation(']') {
+                            break;
+                        }
+                        self.expect_punctuation(',')?;
+                    }
+                }
+                built(
+                    location,
+                    block.try_indirectbr(address, destinations.iter().collect()),
+                )?;
+                Ok(None)
+            }
+            "phi" => {
+                // Fast-math flags of phi instructions are not modeled.
+                self.parse_fast_math_flags();
+                let value_type = self.parse_first_class_type()?;
+                let mut values = Vec::new();
+                let mut predecessors = Vec::new();
+                loop {
+                    self.expect_punctuation('[')?;
+                    values.push(self.parse_value(&value_type)?);
+                    self.expect_punctuation(',')?;
+                    predecessors.push(self.parse_block()?);
+                    self.expect_punctuation(']')?;
+                    if !(self.peek() == Some(&Token::Punctuation(','))
+                        && self.peek_at(1) == Some(&Token::Punctuation('[')))
+                    {
+                        break;
+                    }
+                    self.position += 1;
+                }
+                built(
+                    location,
+                    block.try_phi(
+                        value_type,
+                        values.into_iter().zip(predecessors.iter()).collect(),
+                    ),
+                )
+                .map(Some)
+            }
+            "call" => self.parse_call(block, location, block::TailCallKind::None),
+            "tail" | "musttail" | "notail" => {
+                self.expect_word("call")?;
+                let tail_call = match opcode {
+                    "tail" => block::TailCallKind::Tail,
+                    "musttail" => block::TailCallKind::MustTail,
+                    _ => block::TailCallKind::NoTail,
+                };
+                self.parse_call(block, location, tail_call)
+            }
+            "callbr" => self.parse_callbr(block, location),
+            "fneg" => {
+                let flags = self.parse_fast_math_flags();
+                let (_, operand) = self.parse_typed_value()?;
+                built(location, block.try_fneg_with(flags, operand)).map(Some)
+            }
+            "icmp" => {
+                let predicate = match self.expect_any_word("comparison predicate")?.as_str() {
+                    "eq" => IcmpPredicate::Equal,
+                    "ne" => IcmpPredicate::NotEqual,
+                    "ugt" => IcmpPredicate::UnsignedGreaterThan,
+                    "uge" => IcmpPredicate::UnsignedGreaterOrEqual,
+                    "ult" => IcmpPredicate::UnsignedLessThan,
+                    "ule" => IcmpPredicate::UnsignedLessOrEqual,
+                    "sgt" => IcmpPredicate::SignedGreaterThan,
+                    "sge" => IcmpPredicate::SignedGreaterOrEqual,
+                    "slt" => IcmpPredicate::SignedLessThan,
+                    "sle" => IcmpPredicate::SignedLessOrEqual,
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected("comparison predicate"));
+                    }
+                };
+                let (operand_type, x) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let y = self.parse_value(&operand_type)?;
+                built(location, block.try_icmp(predicate, x, y)).map(Some)
+            }
+            "fcmp" => {
+                // Fast-math flags of comparisons are not modeled.
+                self.parse_fast_math_flags();
+                let predicate = match self.expect_any_word("comparison predicate")?.as_str() {
+                    "false" => FcmpPredicate::False,
+                    "oeq" => FcmpPredicate::OrderedEqual,
+                    "ogt" => FcmpPredicate::OrderedGreaterThan,
+                    "oge" => FcmpPredicate::OrderedGreaterOrEqual,
+                    "olt" => FcmpPredicate::OrderedLessThan,
+                    "ole" => FcmpPredicate::OrderedLessOrEqual,
+                    "one" => FcmpPredicate::OrderedNotEqual,
+                    "ord" => FcmpPredicate::Ordered,
+                    "ueq" => FcmpPredicate::UnorderedEqual,
+                    "ugt" => FcmpPredicate::UnorderedGreaterThan,
+                    "uge" => FcmpPredicate::UnorderedGreaterOrEqual,
+                    "ult" => FcmpPredicate::UnorderedLessThan,
+                    "ule" => FcmpPredicate::UnorderedLessOrEqual,
+                    "une" => FcmpPredicate::UnorderedNotEqual,
+                    "uno" => FcmpPredicate::Unordered,
+                    "true" => FcmpPredicate::True,
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected("comparison predicate"));
+                    }
+                };
+                let (operand_type, x) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let y = self.parse_value(&operand_type)?;
+                built(location, block.try_fcmp(predicate, x, y)).map(Some)
+            }
+            "va_arg" => {
+                let (_, list) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let value_type = self.parse_first_class_type()?;
+                built(location, block.try_va_arg(list, value_type)).map(Some)
+            }
+            "alloca" => {
+                if self.peek_word("inalloca") {
+                    return Err(
+                        self.error(ErrorKind::Unsupported(String::from("inalloca allocations")))
+                    );
+                }
+                let allocated_type = self.parse_first_class_type()?;
+                let mut count = None;
+                let mut alignment = None;
+                while self.peek() == Some(&Token::Punctuation(',')) {
+                    match self.peek_at(1) {
+                        Some(Token::Word(word)) if word == "align" => {
+                            self.position += 2;
+                            alignment = Some(self.expect_alignment()?);
+                        }
+                        Some(Token::Word(word)) if word == "addrspace" => {
+                            self.position += 1;
+                            self.expect_default_address_space("allocations")?;
+                        }
+                        Some(Token::Metadata(_)) => break,
+                        _ => {
+                            self.position += 1;
+                            count = Some(self.parse_typed_value()?.1);
+                        }
+                    }
+                }
+                built(location, block.try_alloca(allocated_type, count, alignment)).map(Some)
+            }
+            "load" => {
+                let atomic = self.eat_word("atomic");
+                let volatile = self.eat_word("volatile");
+                let loaded_type = self.parse_first_class_type()?;
+                self.expect_punctuation(',')?;
+                let pointer_location = self.location();
+                let (pointer_type, pointer) = self.parse_typed_value()?;
+                if !matches!(
+                    pointer_type.as_ref(),
+                    types::FirstClass::Single(types::SingleValue::Pointer(pointer))
+                        if pointer.pointee_type() == Some(&loaded_type)
+                ) {
+                    return Err(error_at(
+                        pointer_location,
+                        ErrorKind::TypeMismatch(format!(
+                            "expected pointer to {}, but got {}",
+                            loaded_type, pointer_type
+                        )),
+                    ));
+                }
+
+                if atomic {
+                    let ordering = self.parse_atomic()?;
+                    let alignment = match self.parse_alignment_suffix()? {
+                        Some(alignment) => alignment,
+                        None => return Err(self.expected("alignment of atomic load")),
+                    };
+                    built(
+                        location,
+                        block.try_atomic_load(pointer, alignment, volatile, ordering),
+                    )
+                    .map(Some)
+                } else {
+                    let alignment = self.parse_alignment_suffix()?;
+                    built(location, block.try_load(pointer, alignment, volatile)).map(Some)
+                }
+            }
+            "store" => {
+                let atomic = self.eat_word("atomic");
+                let volatile = self.eat_word("volatile");
+                let (_, value) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let (_, pointer) = self.parse_typed_value()?;
+                if atomic {
+                    let ordering = self.parse_atomic()?;
+                    let alignment = match self.parse_alignment_suffix()? {
+                        Some(alignment) => alignment,
+                        None => return Err(self.expected("alignment of atomic store")),
+                    };
+                    built(
+                        location,
+                        block.try_atomic_store(value, pointer, alignment, volatile, ordering),
+                    )?;
+                } else {
+                    let alignment = self.parse_alignment_suffix()?;
+                    built(
+                        location,
+                        block.try_store(value, pointer, alignment, volatile),
+                    )?;
+                }
+                Ok(None)
+            }
+            "atomicrmw" => {
+                let volatile = self.eat_word("volatile");
+                let operation = match self.expect_any_word("atomicrmw operation")?.as_str() {
+                    "xchg" => block::AtomicRmwOperation::Xchg,
+                    "add" => block::AtomicRmwOperation::Add,
+                    "sub" => block::AtomicRmwOperation::Sub,
+                    "and" => block::AtomicRmwOperation::And,
+                    "nand" => block::AtomicRmwOperation::Nand,
+                    "or" => block::AtomicRmwOperation::Or,
+                    "xor" => block::AtomicRmwOperation::Xor,
+                    "max" => block::AtomicRmwOperation::Max,
+                    "min" => block::AtomicRmwOperation::Min,
+                    "umax" => block::AtomicRmwOperation::UMax,
+                    "umin" => block::AtomicRmwOperation::UMin,
+                    "fadd" => block::AtomicRmwOperation::FAdd,
+                    "fsub" => block::AtomicRmwOperation::FSub,
+                    _ => {
+                        self.position -= 1;
+                        return Err(self.expected("atomicrmw operation"));
+                    }
+                };
+                let (_, pointer) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let (_, value) = self.parse_typed_value()?;
+                let atomic = self.parse_atomic()?;
+                self.parse_alignment_suffix()?;
+                built(
+                    location,
+                    block.try_atomicrmw(operation, pointer, value, volatile, atomic),
+                )
+                .map(Some)
+            }
+            "cmpxchg" => {
+                let weak = self.eat_word("weak");
+                let volatile = self.eat_word("volatile");
+                let (_, pointer) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let (_, expected) = self.parse_typed_value()?;
+                self.expect_punctuation(',')?;
+                let (_, replacement) = self.parse_typed_value()?;
+                let success = self.parse_atomic()?;
+                let failure_ordering = self.parse_ordering()?;
+                self.parse_alignment_suffix()?;
+                built(
+                    location,
+                    if weak {
+                        block.try_cmpxchg_weak(
+                            pointer,
+                            expected,
+                            replacement,
+                            volatile,
+                            success,
+                            failure_ordering,
+                        )
+                    } else {
+                        block.try_cmpxchg(
+                            pointer,
+                            expected,
+                            replacement,
+                            volatile,
+                            success,
+                            failure_ordering,
+                        )
+                    },
+                )
+                .map(Some)
+            }
+            "fence" => {
+                let atomic = self.parse_atomic()?;
+                built(location, block.try_fence(atomic))?;
+                Ok(None)
+            }
+            "getelementptr" => {
+                let inbounds = self.eat_word("inbounds");
+                let element_type = self.parse_first_class_type()?;
+                self.expect_punctuation(',')?;
+                let (_, pointer) = self.parse_typed_value()?;
+                let mut indices = Vec::new();
+                while self.peek() == Some(&Token::Punctuation(','))
+                    && !matches!(self.peek_at(1), Some(Token::Metadata(_)))
+                {
+                    self.position += 1;
+                    if self.peek_word("inrange") {
+                        return Err(
+                            self.error(ErrorKind::Unsupported(String::from("inrange indices")))
+                        );
+                    }
+                    indices.push(self.parse_typed_value()?.1);
+                }
+                built(
+                    location,
+                    block.try_getelementptr_with_type(element_type, pointer, indices, inbounds),
+                )
+                .map(Some)
+            }
+            _ => Err(error_at(
+                location,
+                ErrorKind::Unsupported(format!("{} instructions", opcode)),
+            )),
+        }
+    }
+}
+
+fn zero_value(value_type: &types::FirstClass) -> Option<Value> {
+    Some(match value_type {
+        types::FirstClass::Single(types::SingleValue::Integer(size)) => {
+            Value::Integer(value::Integer::zero(*size))
+        }
+        types::FirstClass::Single(types::SingleValue::Float(float_type)) => {
+            Value::Float(match float_type {
+                types::Float::Half => value::Float::Half(0),
+                types::Float::Float => value::Float::Float(0.0),
+                types::Float::Double => value::Float::Double(0.0),
+            })
+        }
+        types::FirstClass::Single(types::SingleValue::Pointer(pointer)) => {
+            Value::Null(pointer.clone())
+        }
+        types::FirstClass::Single(types::SingleValue::Vector(vector)) => Value::Vector(
+            value::Vector::splat(vector.clone(), zero_value(vector.element_type())?),
+        ),
+        types::FirstClass::Aggregate(aggregate) => {
+            let elements = match aggregate {
+                types::Aggregate::Array(array) => {
+                    vec![zero_value(array.element_type())?; array.count() as usize]
+                }
+                types::Aggregate::Struct(struct_type) => struct_type
+                    .member_types()
+                    .iter()
+                    .map(|member_type| zero_value(member_type))
+                    .collect::<Option<Vec<_>>>()?,
+            };
+            Value::Aggregate(value::Aggregate::new(aggregate.clone(), elements))
+        }
+        types::FirstClass::Token | types::FirstClass::Metadata => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A module accepted by `llvm-as`, which uses globals, declarations, and phi instructions.
+    const MODULE: &str = r#"; ModuleID = 'example'
+target datalayout = "e-m:e-p270:32:32-p271:32:32-p272:64:64-i64:64-f80:128-n8:16:32:64-S128"
+target triple = "x86_64-unknown-linux-gnu"
+
+@counter = internal global i32 0, align 4
+@message = private unnamed_addr constant [6 x i8] c"hello\00"
+@ext = external global i32
+@weak = extern_weak global i64
+@defined = global i32 5
+
+declare i32 @puts(i8*)
+
+define i32 @max(i32 %a, i32 %b) {
+entry:
+  %c = icmp sgt i32 %a, %b
+  br i1 %c, label %then, label %join
+
+then:
+  br label %join
+
+join:
+  %r = phi i32 [ %a, %then ], [ %b, %entry ]
+  ret i32 %r
+}
+
+define void @count(i32 %x) {
+  switch i32 %x, label %done [
+    i32 1, label %done
+    i32 2, label %done
+  ]
+
+done:
+  %p = phi i32 [ %x, %0 ], [ %x, %0 ], [ %x, %0 ]
+  %v = load i32, i32* @counter, align 4
+  %s = add i32 %v, %p
+  store i32 %s, i32* @counter, align 4
+  ret void
+}
+"#;
+
+    fn parse(source: &str) -> Result<String, Error> {
+        let target = parse_target(source)?;
+        let module = parse_module(source, &target)?;
+        Ok(module.to_string())
+    }
+
+    fn expected_error(source: &str) -> String {
+        match parse(source).map_err(|error| error.kind().clone()) {
+            Err(ErrorKind::Expected { expected, .. }) => expected,
+            result => panic!("expected syntax error, but got {:?}", result),
+        }
+    }
+
+    #[test]
+    fn printed_module_round_trips() {
+        let printed = parse(MODULE).unwrap();
+        assert!(printed.contains("@defined = global i32 5"));
+        assert!(printed.contains("@ext = external global i32\n"));
+        assert!(printed.contains("declare external ccc i32 @puts (i8*)\n"));
+        assert!(printed.contains("%p = phi i32 [ %x, %0 ], [ %x, %0 ], [ %x, %0 ]"));
+        assert_eq!(parse(&printed).unwrap(), printed);
+    }
+
+    #[test]
+    fn define_without_body_is_rejected() {
+        assert_eq!(expected_error("define void @f(i32 %x)\n"), "function body");
+    }
+
+    #[test]
+    fn declare_with_body_is_rejected() {
+        assert_eq!(
+            expected_error("declare void @f() {\n  ret void\n}\n"),
+            "end of function declaration"
+        );
+    }
+
+    #[test]
+    fn external_variable_with_initializer_is_rejected() {
+        assert_eq!(
+            expected_error("@g = external global i32 5\n"),
+            "end of global variable"
+        );
+    }
+
+    #[test]
+    fn variable_without_initializer_is_rejected() {
+        assert_eq!(expected_error("@g = global i32\n"), "initializer");
+        assert_eq!(expected_error("@g = internal global i32\n"), "initializer");
+    }
+}
@@ -0,0 +1,219 @@
+//! Helpers for converting mutable local variables into static single assignment form.
+//!
+//! Front-ends can write to and read from [`Variable`]s while building the blocks of a function, and the [`Builder`] inserts
+//! any `phi` instructions needed to select the current value of a variable when control flow merges. This uses the
+//! algorithm described in [Simple and Efficient Construction of Static Single Assignment Form](https://doi.org/10.1007/978-3-642-37051-9_6)
+//! by Braun et al.
+
+use crate::block::BasicBlock;
+use crate::global::Function;
+use crate::types;
+use crate::value::{Register, Value};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// A mutable local variable, whose value is tracked by a [`Builder`].
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Variable(usize);
+
+/// Tracks the values of variables within the basic blocks of a function, inserting `phi` instructions when needed.
+///
+/// A block should be sealed with [`Builder::seal_block`] once all of its predecessors have been terminated, since `phi`
+/// instructions need an incoming value for each edge from a predecessor. Reading a variable in a block that is not yet
+/// sealed creates a `phi` instruction whose incoming values are added once the block is sealed.
+#[derive(Debug)]
+pub struct Builder {
+    function: Rc<Function>,
+    variable_types: Vec<Rc<types::FirstClass>>,
+    definitions: HashMap<(Variable, *const BasicBlock), Value>,
+    sealed_blocks: HashSet<*const BasicBlock>,
+    incomplete_phis: HashMap<*const BasicBlock, Vec<(Variable, Register)>>,
+}
+
+impl Builder {
+    /// Creates a builder used to track variables in the basic blocks of the specified function.
+    pub fn new(function: Rc<Function>) -> Self {
+        Self {
+            function,
+            variable_types: Vec::new(),
+            definitions: HashMap::new(),
+            sealed_blocks: HashSet::new(),
+            incomplete_phis: HashMap::new(),
+        }
+    }
+
+    /// Gets the function containing the blocks whose variables are tracked.
+    pub fn function(&self) -> &Rc<Function> {
+        &self.function
+    }
+
+    /// Declares a new variable containing values of the specified type.
+    pub fn declare_variable(&mut self, value_type: Rc<types::FirstClass>) -> Variable {
+        self.variable_types.push(value_type);
+        Variable(self.variable_types.len() - 1)
+    }
+
+    /// Gets the type of the values stored in a variable.
+    ///
+    /// # Panics
+    /// Panics if the variable was not declared by this builder.
+    pub fn variable_type(&self, variable: Variable) -> &Rc<types::FirstClass> {
+        self.variable_types
+            .get(variable.0)
+            .expect("variable was not declared by this builder")
+    }
+
+    /// Assigns a value to a variable at the current end of a block.
+    ///
+    /// # Panics
+    /// Panics if the value is not of the same type as the variable.
+    pub fn write_variable(&mut self, variable: Variable, block: &Rc<BasicBlock>, value: Value) {
+        let variable_type = self.variable_type(variable);
+        if &value.value_type() != variable_type {
+            panic!(
+                "expected value of type {} for variable, but got {}",
+                variable_type, value
+            );
+        }
+
+        self.definitions
+            .insert((variable, Rc::as_ptr(block)), value);
+    }
+
+    /// Gets the value of a variable at the current end of a block, inserting `phi` instructions as needed.
+    ///
+    /// # Panics
+    /// Panics if the block is not contained in the function, or if the variable is read in a block without any
+    /// predecessors before any value is written to it.
+    pub fn read_variable(&mut self, variable: Variable, block: &Rc<BasicBlock>) -> Value {
+        match self.definitions.get(&(variable, Rc::as_ptr(block))) {
+            Some(value) => value.clone(),
+            None => self.read_variable_recursive(variable, block),
+        }
+    }
+
+    fn read_variable_recursive(&mut self, variable: Variable, block: &Rc<BasicBlock>) -> Value {
+        let value = if !self.sealed_blocks.contains(&Rc::as_ptr(block)) {
+            let phi = block.insert_phi(self.variable_type(variable).clone());
+            self.incomplete_phis
+                .entry(Rc::as_ptr(block))
+                .or_default()
+                .push((variable, phi.clone()));
+            Value::Register(phi)
+        } else {
+            // A phi needs one incoming value for each edge, even if several edges come from the same predecessor.
+            let predecessors = self.function.predecessor_edges_of(block);
+            match predecessors.as_slice() {
+                [] => panic!(
+                    "variable was read in block {:?} before a value was written to it",
+                    block.label()
+                ),
+                [predecessor, others @ ..]
+                    if others.iter().all(|other| Rc::ptr_eq(other, predecessor)) =>
+                {
+                    self.read_variable(variable, predecessor)
+                }
+                _ => {
+                    // The phi is defined before its incoming values are read, to handle loops.
+                    let phi = block.insert_phi(self.variable_type(variable).clone());
+                    self.write_variable(variable, block, Value::Register(phi.clone()));
+                    self.add_phi_operands(variable, block, &phi, &predecessors);
+                    Value::Register(phi)
+                }
+            }
+        };
+
+        self.write_variable(variable, block, value.clone());
+        value
+    }
+
+    fn add_phi_operands(
+        &mut self,
+        variable: Variable,
+        block: &Rc<BasicBlock>,
+        phi: &Register,
+        predecessors: &[Rc<BasicBlock>],
+    ) {
+        for predecessor in predecessors.iter() {
+            let value = self.read_variable(variable, predecessor);
+            block.add_phi_incoming(phi, value, predecessor);
+        }
+    }
+
+    /// Indicates that all predecessors of a block have been terminated, completing any `phi` instructions that were
+    /// inserted into the block.
+    ///
+    /// # Panics
+    /// Panics if the block was already sealed, or if the block is not contained in the function.
+    pub fn seal_block(&mut self, block: &Rc<BasicBlock>) {
+        if !self.sealed_blocks.insert(Rc::as_ptr(block)) {
+            panic!("block {:?} was already sealed", block.label());
+        }
+
+        let predecessors = self.function.predecessor_edges_of(block);
+        for (variable, phi) in self
+            .incomplete_phis
+            .remove(&Rc::as_ptr(block))
+            .unwrap_or_default()
+        {
+            self.add_phi_operands(variable, block, &phi, &predecessors);
+        }
+    }
+
+    /// Returns `true` if the block was sealed.
+    pub fn is_sealed(&self, block: &Rc<BasicBlock>) -> bool {
+        self.sealed_blocks.contains(&Rc::as_ptr(block))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block;
+    use crate::value;
+
+    #[test]
+    fn phi_has_one_incoming_value_per_edge() {
+        let double_type = Rc::new(types::FirstClass::Single(types::SingleValue::Float(
+            types::Float::Double,
+        )));
+        let function = Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::FirstClass(double_type.clone()), Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        let other = BasicBlock::new();
+        let join = BasicBlock::new();
+        function.append_basic_block(entry.clone());
+        function.append_basic_block(other.clone());
+        function.append_basic_block(join.clone());
+
+        let constant = |value| Value::Float(value::Float::Double(value));
+
+        let mut builder = Builder::new(function.clone());
+        let variable = builder.declare_variable(double_type);
+        builder.write_variable(variable, &entry, constant(1.0));
+        let condition = entry.fcmp(
+            block::FcmpPredicate::OrderedLessThan,
+            constant(1.0),
+            constant(2.0),
+        );
+        entry.br_if(Value::Register(condition), &join, &join);
+        builder.seal_block(&entry);
+        builder.seal_block(&other);
+        builder.write_variable(variable, &other, constant(2.0));
+        other.br(&join);
+        builder.seal_block(&join);
+
+        let result = builder.read_variable(variable, &join);
+        join.ret(Some(result));
+
+        let instructions = join.instructions();
+        match &instructions[0] {
+            block::Instruction::Phi { incoming, .. } => assert_eq!(incoming.len(), 3),
+            instruction => panic!("expected phi, but got {}", instruction),
+        }
+        drop(instructions);
+        assert!(crate::verify::verify_function(&function).is_ok());
+    }
+}
@@ -0,0 +1,1400 @@
+//! Contains structures used to specify the layout of data for an LLVM target triple.
+
+use crate::identifier::{Id, Identifier};
+use crate::types;
+use std::collections::hash_map;
+use std::fmt::{Debug, Display, Formatter, Write as _};
+use std::num::{NonZeroU32, NonZeroU8};
+
+/// Specifies whether data is laid out in big-endian or little-endian form.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Endianness {
+    /// The least signficiant bits have the lowest address (`0xABCD = 0xCD 0xAB`).
+    Little,
+    /// The least significant bits have the highest address (`0xABCD = 0xAB 0xCD`).
+    Big,
+}
+
+impl Display for Endianness {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_char(match self {
+            Self::Little => 'e',
+            Self::Big => 'E',
+        })
+    }
+}
+
+/// An LLVM address space.
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[repr(transparent)]
+pub struct AddressSpace(pub u32);
+
+impl AddressSpace {
+    /// The LLVM address space `0`, which corresponds to a Von-Neumann architecture where code and data are in the same address
+    /// space.
+    pub const VON_NEUMANN_DEFAULT: Self = Self(0);
+}
+
+impl Display for AddressSpace {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Specifies the size of an integer or pointer, in bits.
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[repr(transparent)]
+pub struct BitSize {
+    bits: NonZeroU32,
+}
+
+impl BitSize {
+    /// 1-bit, used in LLVM for boolean values.
+    pub const SIZE_1: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(1) },
+    };
+
+    /// 8 bits, or 1 byte.
+    pub const SIZE_8: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(8) },
+    };
+
+    /// 16 bits, or 2 bytes.
+    pub const SIZE_16: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(16) },
+    };
+
+    /// 32 bits, or 4 bytes.
+    pub const SIZE_32: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(32) },
+    };
+
+    /// 64 bits, or 8 bytes.
+    pub const SIZE_64: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(64) },
+    };
+
+    /// 128 bits, or 16 bytes.
+    pub const SIZE_128: Self = Self {
+        bits: unsafe { NonZeroU32::new_unchecked(128) },
+    };
+
+    /// Creates a size from a value, in bytes.
+    pub fn from_bytes(size: NonZeroU8) -> Self {
+        Self {
+            bits: // Safety: size is guaranteed to be non-zero.
+                unsafe { NonZeroU32::new_unchecked(u32::from(size.get()) * 8) }
+        }
+    }
+
+    /// Gets the size, in bits.
+    pub fn bits(self) -> NonZeroU32 {
+        self.bits
+    }
+
+    /// Gets the number of bytes needed to store a value of this size, rounding up to the nearest whole byte.
+    pub fn to_bytes(self) -> NonZeroU32 {
+        // Safety: bits is non-zero, so rounding up always results in at least 1 byte.
+        unsafe { NonZeroU32::new_unchecked((self.bits.get() - 1) / 8 + 1) }
+    }
+
+    /// Adds two sizes together, returning `None` if an overflow occured.
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        self.bits
+            .get()
+            .checked_add(other.bits.get())
+            .and_then(NonZeroU32::new)
+            .map(Self::from)
+    }
+
+    /// Subtracts a size from this size, returning `None` if the result would be zero or negative.
+    pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.bits
+            .get()
+            .checked_sub(other.bits.get())
+            .and_then(NonZeroU32::new)
+            .map(Self::from)
+    }
+
+    /// Multiplies this size by a count, such as the number of elements in an array, returning `None` if an overflow occured.
+    pub fn checked_mul(self, count: NonZeroU32) -> Option<Self> {
+        self.bits
+            .get()
+            .checked_mul(count.get())
+            .and_then(NonZeroU32::new)
+            .map(Self::from)
+    }
+
+    /// Rounds this size up to the nearest multiple of an alignment, returning `None` if an overflow occured.
+    pub fn align_to(self, alignment: Self) -> Option<Self> {
+        let alignment = alignment.bits.get();
+        match self.bits.get() % alignment {
+            0 => Some(self),
+            remainder => self
+                .bits
+                .get()
+                .checked_add(alignment - remainder)
+                .and_then(NonZeroU32::new)
+                .map(Self::from),
+        }
+    }
+
+    fn unwrap_bits(size: Option<Self>) -> u32 {
+        size.map(|value| value.bits.get()).unwrap_or_default()
+    }
+}
+
+impl From<NonZeroU32> for BitSize {
+    fn from(bits: NonZeroU32) -> Self {
+        Self { bits }
+    }
+}
+
+impl Debug for BitSize {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.bits(), f)
+    }
+}
+
+impl From<ByteSize> for BitSize {
+    fn from(size: ByteSize) -> Self {
+        size.bits
+    }
+}
+
+/// Specifies a size or alignment that is a multiple of 8 bits, such as the alignment of the stack.
+#[derive(Copy, Clone, Eq, Hash, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct ByteSize {
+    bits: BitSize,
+}
+
+impl ByteSize {
+    /// 1 byte, or 8 bits.
+    pub const SIZE_1: Self = Self {
+        bits: BitSize::SIZE_8,
+    };
+
+    /// 2 bytes, or 16 bits.
+    pub const SIZE_2: Self = Self {
+        bits: BitSize::SIZE_16,
+    };
+
+    /// 4 bytes, or 32 bits.
+    pub const SIZE_4: Self = Self {
+        bits: BitSize::SIZE_32,
+    };
+
+    /// 8 bytes, or 64 bits.
+    pub const SIZE_8: Self = Self {
+        bits: BitSize::SIZE_64,
+    };
+
+    /// 16 bytes, or 128 bits.
+    pub const SIZE_16: Self = Self {
+        bits: BitSize::SIZE_128,
+    };
+
+    /// Creates a size from a value, in bytes, returning `None` if the equivalent size in bits cannot be represented.
+    pub fn new(bytes: NonZeroU32) -> Option<Self> {
+        bytes.get().checked_mul(8).map(|bits| Self {
+            bits: BitSize {
+                // Safety: bytes is non-zero, so the product is also non-zero.
+                bits: unsafe { NonZeroU32::new_unchecked(bits) },
+            },
+        })
+    }
+
+    /// Gets the size, in bytes.
+    pub fn bytes(self) -> NonZeroU32 {
+        // Safety: The size in bits is a non-zero multiple of 8.
+        unsafe { NonZeroU32::new_unchecked(self.bits.bits.get() / 8) }
+    }
+
+    /// Gets the size, in bits.
+    pub fn bits(self) -> NonZeroU32 {
+        self.bits.bits
+    }
+}
+
+impl TryFrom<BitSize> for ByteSize {
+    type Error = BitSize;
+
+    /// Converts a size in bits to a size in bytes, returning the original size if it is not a multiple of 8 bits.
+    fn try_from(size: BitSize) -> Result<Self, Self::Error> {
+        if size.bits.get().is_multiple_of(8) {
+            Ok(Self { bits: size })
+        } else {
+            Err(size)
+        }
+    }
+}
+
+impl Debug for ByteSize {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.bytes(), f)
+    }
+}
+
+/// The alignment of a value in memory, in bytes, which is always a power of two.
+#[derive(Copy, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct Alignment {
+    bytes: NonZeroU32,
+}
+
+impl Alignment {
+    /// An alignment of 1 byte, meaning that a value can be placed at any address.
+    pub const BYTE: Self = Self {
+        bytes: NonZeroU32::MIN,
+    };
+
+    /// Creates an alignment value, returning `None` if the alignment is not a power of two.
+    pub const fn new(bytes: u32) -> Option<Self> {
+        if bytes.is_power_of_two() {
+            Some(Self {
+                // Safety: Powers of two are never zero.
+                bytes: unsafe { NonZeroU32::new_unchecked(bytes) },
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the alignment, in bytes.
+    pub const fn bytes(self) -> NonZeroU32 {
+        self.bytes
+    }
+}
+
+impl Debug for Alignment {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.bytes, f)
+    }
+}
+
+impl Display for Alignment {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.bytes, f)
+    }
+}
+
+/// Specifies an ABI and an optional preferred alignment. If the preferred alignment is omitted, the ABI alignment is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlignmentPair {
+    abi: Option<BitSize>,
+    preferred: Option<BitSize>,
+}
+
+impl AlignmentPair {
+    /// An ABI alignment value of 64 bits, with an omitted preferred alignment.
+    pub const ALIGN_64_BITS: Self = Self::new(BitSize::SIZE_64);
+
+    /// Creates a new alignment value, omitting the preferred alignment value.
+    pub const fn new(abi_alignment: BitSize) -> Self {
+        Self {
+            abi: Some(abi_alignment),
+            preferred: None,
+        }
+    }
+
+    /// Creates a new alignment value.
+    pub const fn with_preferred_alignment(
+        abi_alignment: BitSize,
+        preferred_alignment: BitSize,
+    ) -> Self {
+        Self {
+            abi: Some(abi_alignment),
+            preferred: Some(preferred_alignment),
+        }
+    }
+
+    /// Creates a new alignment value, with an ABI alignment of zero.
+    pub const fn with_preferred_only(preferred_alignment: BitSize) -> Self {
+        Self {
+            abi: None,
+            preferred: Some(preferred_alignment),
+        }
+    }
+
+    /// Indicates if the preferred alignment value is omitted.
+    pub const fn is_preferred_omitted(&self) -> bool {
+        self.preferred.is_none()
+    }
+
+    /// Gets the ABI alignment value, in bits.
+    pub fn abi_alignment(&self) -> u32 {
+        BitSize::unwrap_bits(self.abi)
+    }
+
+    /// Gets the preferred alignment value in bits, defaulting to the ABI alignment if the former is omitted.
+    pub fn preferred_alignment(&self) -> u32 {
+        self.preferred
+            .map(|size| size.bits().get())
+            .unwrap_or_else(|| self.abi_alignment())
+    }
+}
+
+/// Specifies the layout of a pointer in memory for a particular address space.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointerLayout {
+    address_space: AddressSpace,
+    size: BitSize,
+    alignment: AlignmentPair,
+    index_size: Option<BitSize>,
+}
+
+impl PointerLayout {
+    /// A 64-bit pointer that is 64-bit aligned.
+    pub const LAYOUT_64_BIT: Self = Self {
+        address_space: AddressSpace::VON_NEUMANN_DEFAULT,
+        size: BitSize::SIZE_64,
+        alignment: AlignmentPair::ALIGN_64_BITS,
+        index_size: None,
+    };
+
+    /// Retrieves the address space that this pointer layout applies to.
+    pub const fn address_space(&self) -> AddressSpace {
+        self.address_space
+    }
+
+    /// Gets the size of pointers, in bits.
+    pub const fn size(&self) -> BitSize {
+        self.size
+    }
+
+    /// Gets the alignment of pointers.
+    pub const fn alignment(&self) -> &AlignmentPair {
+        &self.alignment
+    }
+
+    /// Gets the index size, which defaults to the pointer size if it is unspecified.
+    pub fn index_size(&self) -> BitSize {
+        self.index_size.unwrap_or(self.size)
+    }
+}
+
+/// Describes the layout of pointers for a particular address space.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointerLayoutMap {
+    layouts: hash_map::HashMap<AddressSpace, PointerLayout>,
+}
+
+impl PointerLayoutMap {
+    /// The default pointer layouts used by LLVM, where pointers in all address spaces have the same layout as a 64-bit pointer
+    /// in the default address space.
+    pub fn all_default() -> Self {
+        Self {
+            layouts: hash_map::HashMap::default(),
+        }
+    }
+
+    /// Gets a value indicating if the default pointer layouts is being used, meaning that pointers in all address spaces have
+    /// the same layout as the pointer in the default address space.
+    pub fn is_all_default(&self) -> bool {
+        self.layouts.is_empty()
+    }
+
+    /// Creates a pointer layout from a single layout value.
+    pub fn from_layout(layout: PointerLayout) -> Self {
+        let mut layouts = std::collections::HashMap::with_capacity(1);
+        layouts.insert(layout.address_space, layout);
+        Self { layouts }
+    }
+
+    /// Inserts a pointer layout for a particular address space.
+    pub fn insert(&mut self, layout: PointerLayout) -> Result<&PointerLayout, PointerLayout> {
+        if self.is_all_default() {
+            Ok(&PointerLayout::LAYOUT_64_BIT)
+        } else {
+            match self.layouts.entry(layout.address_space) {
+                hash_map::Entry::Vacant(vacant) => Ok(vacant.insert(layout)),
+                hash_map::Entry::Occupied(occupied) => Err(occupied.get().clone()),
+            }
+        }
+    }
+
+    /// Gets the pointer layout used for the given address space.
+    ///
+    /// Prefer using `PointerLayoutMap::get_or_default` for determining the pointer layout for a given address space.
+    pub fn get(&self, address_space: AddressSpace) -> Option<&PointerLayout> {
+        self.layouts.get(&address_space)
+    }
+
+    /// Gets the pointer layout used for a given address space, returning the default layout value if it is not specified.
+    pub fn get_or_default(&self, address_space: AddressSpace) -> &PointerLayout {
+        self.get(address_space)
+            .unwrap_or(&PointerLayout::LAYOUT_64_BIT)
+    }
+}
+
+/// Describes the alignment for integer, vector, or floating-point types of particular sizes.
+///
+/// For floating-point type layouts, sizes of 32 or 64 bits are supported on all targets, while more exotic targets may not be
+/// supported.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrimitiveAlignmentMap {
+    layouts: hash_map::HashMap<BitSize, AlignmentPair>,
+}
+
+lazy_static::lazy_static! {
+    static ref INTEGER_ALIGNMENT_DEFAULTS: PrimitiveAlignmentMap = PrimitiveAlignmentMap {
+        layouts: hash_map::HashMap::from([
+            (BitSize::SIZE_1, AlignmentPair::new(BitSize::SIZE_8)),
+            (BitSize::SIZE_8, AlignmentPair::new(BitSize::SIZE_8)),
+            (BitSize::SIZE_16, AlignmentPair::new(BitSize::SIZE_16)),
+            (BitSize::SIZE_32, AlignmentPair::new(BitSize::SIZE_32)),
+            (BitSize::SIZE_64, AlignmentPair::new(BitSize::SIZE_64)),
+        ])
+    };
+
+    static ref FLOAT_ALIGNMENT_DEFAULTS: PrimitiveAlignmentMap = PrimitiveAlignmentMap {
+        layouts: hash_map::HashMap::from([
+            (BitSize::SIZE_16, AlignmentPair::new(BitSize::SIZE_16)),
+            (BitSize::SIZE_32, AlignmentPair::new(BitSize::SIZE_32)),
+            (BitSize::SIZE_64, AlignmentPair::new(BitSize::SIZE_64)),
+            (BitSize::SIZE_128, AlignmentPair::new(BitSize::SIZE_128)),
+        ])
+    };
+
+    static ref VECTOR_ALIGNMENT_DEFAULTS: PrimitiveAlignmentMap = PrimitiveAlignmentMap {
+        layouts: hash_map::HashMap::from([
+            (BitSize::SIZE_64, AlignmentPair::new(BitSize::SIZE_64)),
+            (BitSize::SIZE_128, AlignmentPair::new(BitSize::SIZE_128)),
+        ])
+    };
+}
+
+impl PrimitiveAlignmentMap {
+    /// The default alignment values used for integers.
+    pub fn integer_defaults() -> &'static Self {
+        &INTEGER_ALIGNMENT_DEFAULTS
+    }
+
+    /// The default alignment values used for floating-point types.
+    pub fn float_defaults() -> &'static Self {
+        &FLOAT_ALIGNMENT_DEFAULTS
+    }
+
+    /// The default alignment values used for vectors.
+    pub fn vector_defaults() -> &'static Self {
+        &VECTOR_ALIGNMENT_DEFAULTS
+    }
+
+    /// Inserts alignment values corresponding to a particular size.
+    pub fn try_insert(
+        &mut self,
+        size: BitSize,
+        alignment: AlignmentPair,
+    ) -> Result<&AlignmentPair, AlignmentPair> {
+        match self.layouts.entry(size) {
+            hash_map::Entry::Vacant(vacant) => Ok(vacant.insert(alignment)),
+            hash_map::Entry::Occupied(occupied) => Err(occupied.get().clone()),
+        }
+    }
+
+    /// Inserts an alignment value for a particular size, overwritting any previous value.
+    pub fn insert_or_replace(&mut self, size: BitSize, alignment: AlignmentPair) {
+        self.layouts.insert(size, alignment);
+    }
+
+    /// Gets the alignment for a value of a particular size.
+    pub fn get(&self, size: BitSize) -> Option<&AlignmentPair> {
+        self.layouts.get(&size)
+    }
+
+    /// Gets the alignment for a value of a particular size, using the alignment of the smallest larger size if the size
+    /// does not have an alignment, or the alignment of the largest size if all sizes are smaller.
+    ///
+    /// This is the fallback used for integer types, as described in
+    /// [the LLVM language reference](https://llvm.org/docs/LangRef.html#data-layout).
+    pub fn get_or_nearest(&self, size: BitSize) -> Option<&AlignmentPair> {
+        self.layouts
+            .iter()
+            .filter(|(other, _)| other.bits >= size.bits)
+            .min_by_key(|(other, _)| other.bits)
+            .or_else(|| self.layouts.iter().max_by_key(|(other, _)| other.bits))
+            .map(|(_, alignment)| alignment)
+    }
+}
+
+/// Indicates the type of alignment used for function pointers.
+#[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FunctionAlignmentType {
+    /// Indicates that the alignment of function pointers is independent of functions.
+    Independent,
+    /// Indicates that the alignment of function pointers is a multiple of the alignment for functions.
+    Multiple,
+}
+
+impl Display for FunctionAlignmentType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_char(match self {
+            Self::Independent => 'i',
+            Self::Multiple => 'n',
+        })
+    }
+}
+
+/// Describes the alignment of function pointers.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionAlignment {
+    alignment_type: FunctionAlignmentType,
+    abi_alignment: BitSize,
+}
+
+impl FunctionAlignment {
+    /// Creates a new function alignment value.
+    pub const fn new(alignment_type: FunctionAlignmentType, abi_alignment: BitSize) -> Self {
+        Self {
+            alignment_type,
+            abi_alignment,
+        }
+    }
+
+    /// Gets a value indicating how function pointers are aligned.
+    pub const fn alignment_type(&self) -> FunctionAlignmentType {
+        self.alignment_type
+    }
+
+    /// The alignment for function pointers.
+    pub const fn abi_alignment(&self) -> BitSize {
+        self.abi_alignment
+    }
+}
+
+/// Indicates how symbols are mangled.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Mangling {
+    /// The Executable and Linkable Format used in Unix-like systems, which uses the prefix `.L` for private symbols.
+    ELF,
+    /// IBM's Generalized Object File Format, which uses the prefix `@` for private symbols.
+    GOFF,
+    /// `$`
+    MIPS,
+    /// Apple's Mach object file format, which uses the prefix `L` for private symbols.
+    MachO,
+    /// See LLVM documentation for more information.
+    WindowsX86COFF,
+    /// Similar to [`Mangling::WindowsX86COFF`].
+    WindowsCOFF,
+    /// A `L..` prefix is used for private symbols.
+    XCOFF,
+}
+
+impl Mangling {
+    /// Gets the prefix added to the names of all global symbols, such as `_` for Mach-O.
+    pub fn global_prefix(&self) -> Option<char> {
+        match self {
+            Self::MachO | Self::WindowsX86COFF => Some('_'),
+            _ => None,
+        }
+    }
+
+    /// Gets the prefix added to the names of private symbols, which are not included in the symbol table of the object
+    /// file.
+    pub fn private_prefix(&self) -> &'static str {
+        match self {
+            Self::ELF | Self::WindowsCOFF => ".L",
+            Self::GOFF => "@",
+            Self::MIPS => "$",
+            Self::MachO | Self::WindowsX86COFF => "L",
+            Self::XCOFF => "L..",
+        }
+    }
+
+    /// Gets the name of a global symbol in the object file, with the global prefix applied.
+    pub fn mangle_global(&self, name: &Id) -> Identifier {
+        let mut mangled = String::with_capacity(name.len() + 1);
+        mangled.extend(self.global_prefix());
+        mangled.push_str(name);
+        unsafe {
+            // Safety: Prefixes do not contain null bytes.
+            Identifier::new_unchecked(mangled)
+        }
+    }
+
+    /// Gets the name of a private symbol in the object file, with the private and global prefixes applied.
+    pub fn mangle_private(&self, name: &Id) -> Identifier {
+        let mut mangled = String::from(self.private_prefix());
+        mangled.push_str(self.mangle_global(name).as_str());
+        unsafe {
+            // Safety: Prefixes do not contain null bytes.
+            Identifier::new_unchecked(mangled)
+        }
+    }
+}
+
+/// Indicates how data is laid out in memory for a specific target.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct Layout {
+    /// Specifies the byte endianness of the target.
+    pub endianness: Endianness,
+    /// Specifies the natual stack alignment.
+    pub stack_alignment: Option<ByteSize>,
+    /// Specifies which address space corresponds to program memory.
+    pub program_address_space: AddressSpace,
+    /// Specifies which address space corresponds to program memory.
+    pub global_address_space: AddressSpace,
+    /// Specifies the address space used by the `alloca` instruction.
+    pub alloca_address_space: AddressSpace,
+    /// Indicates the layout of pointers for certain address spaces.
+    pub pointer_layouts: PointerLayoutMap,
+    /// Indicates how integers of certain sizes are aligned.
+    pub integer_alignments: PrimitiveAlignmentMap,
+    /// Indicates how vectors of certain sizes are aligned.
+    pub vector_alignments: PrimitiveAlignmentMap,
+    /// Indicates how floating-point types of certain sizes are aligned.
+    pub float_alignments: PrimitiveAlignmentMap,
+    /// Specifies the alignment for aggregate types.
+    pub aggregate_object_alignment: AlignmentPair,
+    /// Indicates how function pointers are aligned.
+    pub function_pointer_alignment: Option<FunctionAlignment>,
+    /// Specifies how symbol names are mangled in the output.
+    pub mangling: Option<Mangling>,
+    /// Indicates the native integer widths for the target CPU.
+    pub native_integer_widths: Vec<BitSize>,
+    //pub non_integral_pointer_types: ,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Self {
+            endianness: Endianness::Little,
+            stack_alignment: None,
+            program_address_space: AddressSpace::VON_NEUMANN_DEFAULT,
+            global_address_space: AddressSpace::VON_NEUMANN_DEFAULT,
+            alloca_address_space: AddressSpace::VON_NEUMANN_DEFAULT,
+            pointer_layouts: PointerLayoutMap::all_default(),
+            integer_alignments: PrimitiveAlignmentMap::integer_defaults().clone(),
+            vector_alignments: PrimitiveAlignmentMap::vector_defaults().clone(),
+            float_alignments: PrimitiveAlignmentMap::float_defaults().clone(),
+            aggregate_object_alignment: AlignmentPair::with_preferred_only(BitSize::SIZE_64),
+            function_pointer_alignment: None,
+            mangling: None,
+            native_integer_widths: Vec::default(),
+        }
+    }
+}
+
+impl Layout {
+    /// Converts an alignment in bits to an alignment in bytes, where an omitted alignment of zero is treated as `1`.
+    fn alignment_from_bits(bits: u32) -> Alignment {
+        Alignment::new((bits / 8).max(1))
+            .unwrap_or_else(|| panic!("alignment of {} bits is not a power of two", bits))
+    }
+
+    /// Gets the alignment used for types without an explicitly specified alignment, which is the smallest power of two
+    /// that is greater than or equal to the store size of the type.
+    fn natural_alignment(store_size: u64) -> Alignment {
+        Alignment::new(
+            store_size
+                .max(1)
+                .next_power_of_two()
+                .try_into()
+                .expect("type is too large to be aligned"),
+        )
+        .expect("alignment should be a power of two")
+    }
+
+    fn pointer_layout(&self, pointer: &types::Pointer) -> &PointerLayout {
+        self.pointer_layouts.get_or_default(pointer.address_space())
+    }
+
+    /// Gets the size of a single value type, in bits.
+    fn bit_size_of(&self, t: &types::SingleValue) -> u64 {
+        match t {
+            types::SingleValue::Integer(size) => u64::from(size.bits()),
+            types::SingleValue::Float(types::Float::Half) => 16,
+            types::SingleValue::Float(types::Float::Float) => 32,
+            types::SingleValue::Float(types::Float::Double) => 64,
+            types::SingleValue::Pointer(pointer) => {
+                u64::from(self.pointer_layout(pointer).size().bits().get())
+            }
+            types::SingleValue::Vector(vector) => match vector.element_type().as_ref() {
+                types::FirstClass::Single(element_type) => {
+                    self.bit_size_of(element_type) * u64::from(vector.count())
+                }
+                element_type => panic!("{} is not a valid vector element type", element_type),
+            },
+        }
+    }
+
+    /// Gets the maximum number of bytes that may be overwritten when storing a value of the specified type, which excludes
+    /// any padding needed to align consecutive values.
+    ///
+    /// # Panics
+    /// Panics if the type is a `token` or `metadata` type, which do not have a size.
+    pub fn store_size_of(&self, t: &types::FirstClass) -> u64 {
+        match t {
+            types::FirstClass::Single(single) => self.bit_size_of(single).div_ceil(8),
+            types::FirstClass::Aggregate(_) => self.size_of(t),
+            types::FirstClass::Token | types::FirstClass::Metadata => {
+                panic!("{} types do not have a size", t)
+            }
+        }
+    }
+
+    /// Gets the size, in bytes, of a value of the specified type, including any padding needed to align consecutive
+    /// values, such as the elements of an array.
+    ///
+    /// This corresponds to the size of memory allocated by an `alloca` instruction for the type.
+    ///
+    /// # Panics
+    /// Panics if the type is or contains a `token` or `metadata` type, which do not have a size.
+    pub fn size_of(&self, t: &types::FirstClass) -> u64 {
+        match t {
+            types::FirstClass::Aggregate(types::Aggregate::Array(array)) => {
+                self.size_of(array.element_type()) * u64::from(array.count())
+            }
+            types::FirstClass::Aggregate(types::Aggregate::Struct(structure)) => {
+                // Like LLVM's StructLayout, padding is only added for the alignment of the members, and not for the
+                // alignment specified for aggregates.
+                let (_, end) = self.struct_member_offsets(structure);
+                align_offset(end, self.struct_member_alignment(structure))
+            }
+            _ => align_offset(self.store_size_of(t), self.abi_alignment_of(t)),
+        }
+    }
+
+    /// Gets the minimum alignment required for a value of the specified type.
+    ///
+    /// Integer types without an explicitly specified alignment use the alignment of the next largest integer type, or of
+    /// the largest integer type if none are larger, while floating-point and vector types without an explicitly specified
+    /// alignment are aligned to the smallest power of two greater than or equal to their size. Packed structs are always
+    /// aligned to 1 byte.
+    ///
+    /// # Panics
+    /// Panics if the type is or contains a `token` or `metadata` type, which do not have an alignment.
+    pub fn abi_alignment_of(&self, t: &types::FirstClass) -> Alignment {
+        match t {
+            types::FirstClass::Single(types::SingleValue::Integer(size)) => {
+                let size = BitSize::from(
+                    NonZeroU32::new(size.bits()).expect("integer sizes should be non-zero"),
+                );
+
+                match self.integer_alignments.get_or_nearest(size) {
+                    Some(alignment) => Self::alignment_from_bits(alignment.abi_alignment()),
+                    None => Self::natural_alignment(self.store_size_of(t)),
+                }
+            }
+            types::FirstClass::Single(types::SingleValue::Float(_)) => {
+                let size = NonZeroU32::new(self.store_size_of(t) as u32 * 8)
+                    .expect("floating-point sizes should be non-zero");
+
+                match self.float_alignments.get(BitSize::from(size)) {
+                    Some(alignment) => Self::alignment_from_bits(alignment.abi_alignment()),
+                    None => Self::natural_alignment(self.store_size_of(t)),
+                }
+            }
+            types::FirstClass::Single(types::SingleValue::Pointer(pointer)) => {
+                Self::alignment_from_bits(self.pointer_layout(pointer).alignment().abi_alignment())
+            }
+            types::FirstClass::Single(single @ types::SingleValue::Vector(_)) => {
+                let alignment = u32::try_from(self.bit_size_of(single))
+                    .ok()
+                    .and_then(NonZeroU32::new)
+                    .and_then(|size| self.vector_alignments.get(BitSize::from(size)));
+
+                match alignment {
+                    Some(alignment) => Self::alignment_from_bits(alignment.abi_alignment()),
+                    None => Self::natural_alignment(self.store_size_of(t)),
+                }
+            }
+            types::FirstClass::Aggregate(types::Aggregate::Array(array)) => {
+                self.abi_alignment_of(array.element_type())
+            }
+            types::FirstClass::Aggregate(types::Aggregate::Struct(structure)) => {
+                // Packed structs can be placed at any address.
+                if structure.is_packed() {
+                    return Alignment::BYTE;
+                }
+
+                let aggregate_alignment = match self.aggregate_object_alignment.abi_alignment() {
+                    0 => Alignment::BYTE,
+                    bits => Self::alignment_from_bits(bits),
+                };
+
+                aggregate_alignment.max(self.struct_member_alignment(structure))
+            }
+            types::FirstClass::Token | types::FirstClass::Metadata => {
+                panic!("{} types do not have an alignment", t)
+            }
+        }
+    }
+
+    /// Gets the largest alignment of the members of a struct, or an alignment of 1 byte if the struct is packed.
+    fn struct_member_alignment(&self, structure: &types::Struct) -> Alignment {
+        if structure.is_packed() {
+            Alignment::BYTE
+        } else {
+            structure
+                .member_types()
+                .iter()
+                .map(|member_type| self.abi_alignment_of(member_type))
+                .fold(Alignment::BYTE, Alignment::max)
+        }
+    }
+
+    /// Computes the offsets, in bytes, of each member of a struct, along with the offset just past the end of the last
+    /// member.
+    fn struct_member_offsets(&self, structure: &types::Struct) -> (Vec<u64>, u64) {
+        let mut offsets = Vec::with_capacity(structure.member_types().len());
+        let mut end = 0;
+        for member_type in structure.member_types() {
+            if !structure.is_packed() {
+                end = align_offset(end, self.abi_alignment_of(member_type));
+            }
+
+            offsets.push(end);
+            end += self.size_of(member_type);
+        }
+        (offsets, end)
+    }
+
+    /// Gets the offset, in bytes, of the member at the specified index in a struct.
+    ///
+    /// # Panics
+    /// Panics if the index is out of bounds, or if the struct contains a `token` or `metadata` type.
+    pub fn offset_of_element(&self, structure: &types::Struct, index: u32) -> u64 {
+        match self.struct_member_offsets(structure).0.get(index as usize) {
+            Some(offset) => *offset,
+            None => panic!(
+                "index {} is out of bounds for struct type {}",
+                index, structure
+            ),
+        }
+    }
+}
+
+/// Rounds an offset up to the nearest multiple of an alignment.
+fn align_offset(offset: u64, alignment: Alignment) -> u64 {
+    let alignment = u64::from(alignment.bytes().get());
+    offset.div_ceil(alignment) * alignment
+}
+
+/// Error used when a layout could not be parsed.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// Used when an unknown specification was parsed.
+    #[error("'{0}' is not a valid specification")]
+    InvalidSpecification(char),
+    /// Used when an integer could not be parsed.
+    #[error(transparent)]
+    InvalidInteger(#[from] std::num::ParseIntError),
+    /// Used when the specification ends after a `:`.
+    #[error("missing information after colon")]
+    MissingInformation,
+    /// Used when remaining characters in a specification could not be parsed.
+    #[error("expected end, but got {0}")]
+    ExpectedEnd(String),
+    /// Used when more than one `p` specification for a particular address space.
+    #[error("duplicate pointer layout specified for address space {0}")]
+    DuplicatePointerLayout(AddressSpace),
+    /// Used when a non-zero size was expected in a particular specification.
+    #[error("expected non-zero size value in specification '{0}'")]
+    ExpectedNonZeroSize(char),
+    /// Used when an `m` specification exists that did not specify any option.
+    #[error("a mangling specification exists but did not specify any option")]
+    MissingManglingValue,
+    /// Used when an `m` specification uses an invalid option.
+    #[error("{0} is not a valid mangling specification option")]
+    InvalidManglingValue(char),
+    /// Used when an `i`, `v`, or `f` specification is duplicated for a particular size.
+    #[error("duplicate '{specification}' specification for size {size:?}")]
+    DuplicatePrimitiveAlignment {
+        /// The duplicated specification.
+        specification: char,
+        /// The duplicate size value.
+        size: BitSize,
+    },
+    /// Used when an `F` specification does not specify `i` or `n` as the function pointer alignment type.
+    #[error("{0} is not a valid function pointer alignment type")]
+    InvalidFunctionAlignmentType(char),
+    /// Used when a specification string is empty.
+    #[error("specifications must not be empty")]
+    EmptySpecification,
+    /// Used when a size value that must be a multiple of 8 bits was not, such as the stack alignment.
+    #[error("expected size value that is a multiple of 8 bits in specification '{0}'")]
+    ExpectedByteMultiple(char),
+}
+
+impl TryFrom<&Id> for Layout {
+    type Error = ParseError;
+
+    fn try_from(layout: &Id) -> Result<Self, Self::Error> {
+        // TODO: Check for some duplicate specifications.
+
+        type ParseResult<'a, T> = Result<(&'a [char], T), ParseError>;
+
+        fn parse_integer<T: std::str::FromStr<Err = std::num::ParseIntError>>(
+            s: &[char],
+        ) -> ParseResult<'_, T> {
+            let mut digits = String::new();
+            let mut parse_count = 0;
+
+            for d in s.iter().take_while(|c| c.is_ascii_digit()) {
+                digits.push(*d);
+                parse_count += 1;
+            }
+
+            let value = T::from_str(&digits)?;
+
+            Ok((&s[parse_count..], value))
+        }
+
+        fn parse_bit_size(s: &[char]) -> ParseResult<Option<BitSize>> {
+            let (remaining, value) = parse_integer::<u32>(s)?;
+            Ok((remaining, NonZeroU32::new(value).map(BitSize::from)))
+        }
+
+        fn parse_address_space(s: &[char]) -> ParseResult<AddressSpace> {
+            let (remaining, value) = parse_integer::<u32>(s)?;
+            Ok((remaining, AddressSpace(value)))
+        }
+
+        fn parse_information<T, P: FnOnce(&[char]) -> ParseResult<T>>(
+            parser: P,
+            s: &[char],
+        ) -> ParseResult<Option<T>> {
+            match s.first() {
+                Some(':') => {
+                    let (remaining, value) = parser(&s[1..])?;
+                    Ok((remaining, Some(value)))
+                }
+                Some(_) => Err(ParseError::ExpectedEnd(s.iter().skip(1).collect())),
+                None => Ok((&[], None)),
+            }
+        }
+
+        fn parse_information_or<
+            T,
+            P: FnOnce(&[char]) -> ParseResult<T>,
+            E: FnOnce() -> ParseError,
+        >(
+            parser: P,
+            error: E,
+            s: &[char],
+        ) -> ParseResult<T> {
+            match parse_information(parser, s)? {
+                (remaining, Some(value)) => Ok((remaining, value)),
+                (_, None) => Err(error()),
+            }
+        }
+
+        fn parse_primitive_alignment<'a>(
+            specification: char,
+            lookup: &mut PrimitiveAlignmentMap,
+            s: &'a [char],
+        ) -> ParseResult<'a, ()> {
+            let (remaining, size) = parse_bit_size(s)?;
+            let (remaining, abi) =
+                parse_information_or(parse_bit_size, || ParseError::MissingInformation, remaining)?;
+            let (remaining, pref) = parse_information(parse_bit_size, remaining)?;
+
+            // TODO: Better way to replace duplicate primitive alignment.
+            lookup.insert_or_replace(
+                size.ok_or(ParseError::ExpectedNonZeroSize(specification))?,
+                AlignmentPair {
+                    abi,
+                    preferred: pref.flatten(),
+                },
+            );
+
+            Ok((remaining, ()))
+        }
+
+        fn parse_specification(layout: &mut Layout, s: &[char]) -> Result<(), ParseError> {
+            if let Some(kind) = s.first() {
+                let information = &s[1..];
+
+                macro_rules! set_address_space {
+                    ($name: ident) => {{
+                        let (remaining, address_space) = parse_address_space(information)?;
+                        layout.$name = address_space;
+                        remaining
+                    }};
+                }
+
+                let remaining = match kind {
+                    'E' => {
+                        layout.endianness = Endianness::Big;
+                        &s[1..]
+                    }
+                    'e' => {
+                        layout.endianness = Endianness::Little;
+                        &s[1..]
+                    }
+                    'S' => {
+                        let (remaining, alignment) = parse_bit_size(information)?;
+                        layout.stack_alignment = alignment
+                            .map(ByteSize::try_from)
+                            .transpose()
+                            .map_err(|_| ParseError::ExpectedByteMultiple('S'))?;
+                        remaining
+                    }
+                    'P' => set_address_space!(program_address_space),
+                    'G' => set_address_space!(global_address_space),
+                    'A' => set_address_space!(alloca_address_space),
+                    'p' => {
+                        // Peek to see if an address space is specified.
+                        let (remaining, address_space) = match information.first() {
+                            Some(':') => (information, AddressSpace::VON_NEUMANN_DEFAULT),
+                            Some(_) => parse_address_space(information)?,
+                            _ => return Err(ParseError::MissingInformation),
+                        };
+
+                        let (remaining, size) = parse_information_or(
+                            parse_bit_size,
+                            || ParseError::MissingInformation,
+                            remaining,
+                        )?;
+                        let (remaining, abi) = parse_information_or(
+                            parse_bit_size,
+                            || ParseError::MissingInformation,
+                            remaining,
+                        )?;
+                        let (remaining, pref) = parse_information(parse_bit_size, remaining)?;
+                        let (remaining, idx) = parse_information(parse_bit_size, remaining)?;
+
+                        match layout.pointer_layouts.insert(PointerLayout {
+                            address_space,
+                            alignment: AlignmentPair {
+                                abi,
+                                preferred: pref.flatten(),
+                            },
+                            size: size.ok_or(ParseError::ExpectedNonZeroSize('p'))?,
+                            index_size: idx.flatten(),
+                        }) {
+                            Ok(_) => remaining,
+                            Err(_) => {
+                                return Err(ParseError::DuplicatePointerLayout(address_space))
+                            }
+                        }
+                    }
+                    'i' => {
+                        let (remaining, ()) = parse_primitive_alignment(
+                            'i',
+                            &mut layout.integer_alignments,
+                            information,
+                        )?;
+                        remaining
+                    }
+                    'v' => {
+                        let (remaining, ()) = parse_primitive_alignment(
+                            'i',
+                            &mut layout.vector_alignments,
+                            information,
+                        )?;
+                        remaining
+                    }
+                    'f' => {
+                        let (remaining, ()) = parse_primitive_alignment(
+                            'i',
+                            &mut layout.float_alignments,
+                            information,
+                        )?;
+                        remaining
+                    }
+                    'a' => {
+                        let (remaining, abi) = parse_information_or(
+                            parse_bit_size,
+                            || ParseError::MissingInformation,
+                            information,
+                        )?;
+                        let (remaining, pref) = parse_information(parse_bit_size, remaining)?;
+                        layout.aggregate_object_alignment = AlignmentPair {
+                            abi,
+                            preferred: pref.flatten(),
+                        };
+                        remaining
+                    }
+                    'F' => {
+                        let alignment_type = match information.first() {
+                            Some('i') => FunctionAlignmentType::Independent,
+                            Some('n') => FunctionAlignmentType::Multiple,
+                            Some(alignment_type) => {
+                                return Err(ParseError::InvalidFunctionAlignmentType(
+                                    *alignment_type,
+                                ))
+                            }
+                            None => return Err(ParseError::MissingInformation),
+                        };
+                        let (remaining, abi) = parse_bit_size(&information[1..])?;
+                        layout.function_pointer_alignment = Some(FunctionAlignment::new(
+                            alignment_type,
+                            abi.ok_or(ParseError::ExpectedNonZeroSize('F'))?,
+                        ));
+                        remaining
+                    }
+                    'm' => {
+                        let (remaining, mangling) = parse_information_or(
+                            |s| {
+                                if let Some(mangling_option) = s.first() {
+                                    let remaining = &s[1..];
+                                    match mangling_option {
+                                        'e' => Ok((remaining, Mangling::ELF)),
+                                        'l' => Ok((remaining, Mangling::GOFF)),
+                                        'm' => Ok((remaining, Mangling::MIPS)),
+                                        'o' => Ok((remaining, Mangling::MachO)),
+                                        'x' => Ok((remaining, Mangling::WindowsX86COFF)),
+                                        'w' => Ok((remaining, Mangling::WindowsCOFF)),
+                                        'a' => Ok((remaining, Mangling::XCOFF)),
+                                        _ => {
+                                            Err(ParseError::InvalidManglingValue(*mangling_option))
+                                        }
+                                    }
+                                } else {
+                                    Err(ParseError::MissingManglingValue)
+                                }
+                            },
+                            || ParseError::MissingManglingValue,
+                            information,
+                        )?;
+
+                        layout.mangling = Some(mangling);
+                        remaining
+                    }
+                    'n' => {
+                        let (mut remaining, first_size) = parse_bit_size(information)?;
+
+                        let mut push_integer_width =
+                            |size: Option<BitSize>| -> Result<(), ParseError> {
+                                layout
+                                    .native_integer_widths
+                                    .push(size.ok_or(ParseError::ExpectedNonZeroSize('n'))?);
+                                Ok(())
+                            };
+
+                        push_integer_width(first_size)?;
+
+                        while let (next_remaining, Some(next_size)) =
+                            parse_information(parse_bit_size, remaining)?
+                        {
+                            push_integer_width(next_size)?;
+                            remaining = next_remaining;
+                        }
+
+                        remaining
+                    }
+                    _ => return Err(ParseError::InvalidSpecification(*kind)),
+                };
+
+                if remaining.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ParseError::ExpectedEnd(remaining.iter().collect()))
+                }
+            } else {
+                Err(ParseError::EmptySpecification)
+            }
+        }
+
+        let specifications = layout.split('-');
+        let mut buffer: Vec<char> = Vec::new();
+        let mut layout = Self::default();
+
+        for spec in specifications {
+            buffer.clear();
+            buffer.extend(spec.chars());
+            parse_specification(&mut layout, &buffer)?;
+        }
+
+        Ok(layout)
+    }
+}
+
+impl TryFrom<Identifier> for Layout {
+    type Error = ParseError;
+
+    fn try_from(layout: Identifier) -> Result<Self, Self::Error> {
+        Self::try_from(layout.as_id())
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let mut specifications: Vec<String> = vec![self.endianness.to_string()];
+
+        macro_rules! write_specification {
+            ($dst: expr, $($arg:tt)*) => {
+                specifications.push({
+                    let mut buffer = String::new();
+                    write!(&mut buffer, $dst, $($arg)*)?;
+                    buffer
+                });
+            };
+        }
+
+        write_specification!(
+            "S{}",
+            BitSize::unwrap_bits(self.stack_alignment.map(BitSize::from))
+        );
+        write_specification!("P{}", self.program_address_space);
+        write_specification!("G{}", self.global_address_space);
+        write_specification!("A{}", self.alloca_address_space);
+
+        for layout in self.pointer_layouts.layouts.values() {
+            let mut buffer = String::new();
+
+            write!(
+                &mut buffer,
+                "p{}:{}:{}",
+                layout.address_space,
+                layout.size.bits(),
+                BitSize::unwrap_bits(layout.alignment.abi)
+            )?;
+
+            if let Some(preferred_alignment) = layout.alignment.preferred {
+                write!(&mut buffer, ":{}", preferred_alignment.bits())?;
+            }
+
+            if let Some(index_size) = layout.index_size {
+                write!(&mut buffer, ":{}", index_size.bits())?;
+            }
+
+            specifications.push(buffer);
+        }
+
+        let mut write_primitive_alignments =
+            |s: char, alignments: &PrimitiveAlignmentMap| -> std::fmt::Result {
+                for (size, pair) in alignments.layouts.iter() {
+                    let mut buffer = String::new();
+
+                    write!(
+                        &mut buffer,
+                        "{}{}:{}",
+                        s,
+                        size.bits(),
+                        BitSize::unwrap_bits(pair.abi)
+                    )?;
+
+                    if let Some(preferred_alignment) = pair.preferred {
+                        write!(&mut buffer, ":{}", preferred_alignment.bits())?;
+                    }
+
+                    specifications.push(buffer);
+                }
+
+                Ok(())
+            };
+
+        write_primitive_alignments('i', &self.integer_alignments)?;
+        write_primitive_alignments('v', &self.integer_alignments)?;
+        write_primitive_alignments('f', &self.integer_alignments)?;
+
+        specifications.push({
+            let mut buffer = String::new();
+            write!(
+                &mut buffer,
+                "a:{}",
+                BitSize::unwrap_bits(self.aggregate_object_alignment.abi)
+            )?;
+            if let Some(preferred_alignment) = self.aggregate_object_alignment.preferred {
+                write!(&mut buffer, ":{}", preferred_alignment.bits())?;
+            }
+            buffer
+        });
+
+        if let Some(function_pointer_alignment) = &self.function_pointer_alignment {
+            write_specification!(
+                "F{}{}",
+                function_pointer_alignment.alignment_type(),
+                function_pointer_alignment.abi_alignment().bits()
+            );
+        }
+
+        //m
+
+        //n
+
+        //ni
+
+        for (index, s) in specifications.iter().enumerate() {
+            if index > 0 {
+                f.write_char('-')?;
+            }
+
+            f.write_str(s)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    fn integer(size: types::IntegerSize) -> Rc<types::FirstClass> {
+        Rc::new(types::FirstClass::Single(types::SingleValue::Integer(size)))
+    }
+
+    fn structure(packed: bool) -> types::Struct {
+        types::Struct::new(
+            vec![
+                integer(types::IntegerSize::I8),
+                integer(types::IntegerSize::I32),
+                integer(types::IntegerSize::I16),
+            ],
+            packed,
+        )
+    }
+
+    fn layout(s: &str) -> Layout {
+        Layout::try_from(Identifier::try_from(s).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn struct_members_are_aligned() {
+        let layout = Layout::default();
+        let structure = structure(false);
+        let struct_type = types::FirstClass::Aggregate(types::Aggregate::Struct(structure.clone()));
+
+        assert_eq!(layout.offset_of_element(&structure, 0), 0);
+        assert_eq!(layout.offset_of_element(&structure, 1), 4);
+        assert_eq!(layout.offset_of_element(&structure, 2), 8);
+        assert_eq!(layout.size_of(&struct_type), 12);
+        assert_eq!(layout.abi_alignment_of(&struct_type).bytes().get(), 4);
+    }
+
+    #[test]
+    fn packed_struct_members_are_not_aligned() {
+        let layout = layout("a:64");
+        let structure = structure(true);
+        let struct_type = types::FirstClass::Aggregate(types::Aggregate::Struct(structure.clone()));
+
+        assert_eq!(layout.offset_of_element(&structure, 1), 1);
+        assert_eq!(layout.offset_of_element(&structure, 2), 5);
+        assert_eq!(layout.size_of(&struct_type), 7);
+        assert_eq!(layout.abi_alignment_of(&struct_type), Alignment::BYTE);
+    }
+
+    #[test]
+    fn struct_size_is_not_padded_to_aggregate_alignment() {
+        let layout = layout("a:64");
+        let struct_type = types::FirstClass::Aggregate(types::Aggregate::Struct(structure(false)));
+
+        assert_eq!(layout.size_of(&struct_type), 12);
+        assert_eq!(layout.abi_alignment_of(&struct_type).bytes().get(), 8);
+    }
+}
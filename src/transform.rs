@@ -0,0 +1,386 @@
+//! Simple transformations of functions, which front-ends can use to clean up generated code before lowering it.
+
+use crate::analysis::{ControlFlowGraph, DominatorTree};
+use crate::block::{BasicBlock, Instruction};
+use crate::global::Function;
+use crate::value::{fold, Register, Value};
+use std::rc::Rc;
+
+/// Replaces every use of a register in the instructions of a function with another value, returning the number of operands
+/// that were replaced.
+///
+/// # Panics
+/// Panics if the new value is not of the same type as the register.
+pub fn replace_all_uses_with(function: &Function, register: &Register, new_value: &Value) -> usize {
+    if &new_value.value_type() != register.value_type() {
+        panic!(
+            "cannot replace uses of {} with {}, since the types differ",
+            register, new_value
+        );
+    }
+
+    let mut count = 0;
+    for block in function.basic_blocks().iter() {
+        for instruction in block.instructions_mut().iter_mut() {
+            for operand in instruction.operands_mut() {
+                if matches!(operand, Value::Register(used) if used == register) {
+                    *operand = new_value.clone();
+                    count += 1;
+                }
+            }
+        }
+    }
+    count
+}
+
+/// Removes the incoming values of the `phi` instructions in a block that come from the specified predecessor.
+fn remove_phi_incoming(block: &BasicBlock, predecessor: *const BasicBlock) {
+    for instruction in block.instructions_mut().iter_mut() {
+        if let Instruction::Phi { incoming, .. } = instruction {
+            incoming.retain(|(_, incoming_block)| incoming_block.as_ptr() != predecessor);
+        }
+    }
+}
+
+/// Removes all but the first incoming value of the `phi` instructions in a block that come from the specified predecessor,
+/// used when the edges from the predecessor are replaced by a single edge.
+fn merge_phi_incoming(block: &BasicBlock, predecessor: *const BasicBlock) {
+    for instruction in block.instructions_mut().iter_mut() {
+        if let Instruction::Phi { incoming, .. } = instruction {
+            let mut found = false;
+            incoming.retain(|(_, incoming_block)| {
+                if incoming_block.as_ptr() != predecessor {
+                    true
+                } else {
+                    !std::mem::replace(&mut found, true)
+                }
+            });
+        }
+    }
+}
+
+/// Removes the basic blocks that are unreachable from the entry block of a function, returning the number of blocks that
+/// were removed.
+///
+/// Incoming values of `phi` instructions that come from the removed blocks are also removed.
+pub fn eliminate_dead_blocks(function: &Function) -> usize {
+    let graph = ControlFlowGraph::new(function);
+    let dominators = DominatorTree::new(&graph);
+    let dead_blocks = graph
+        .blocks()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !dominators.is_reachable(*index))
+        .map(|(_, block)| Rc::as_ptr(block))
+        .collect::<Vec<_>>();
+
+    if dead_blocks.is_empty() {
+        return 0;
+    }
+
+    for (index, block) in graph.blocks().iter().enumerate() {
+        if dominators.is_reachable(index) {
+            for dead_block in dead_blocks.iter() {
+                remove_phi_incoming(block, *dead_block);
+            }
+        }
+    }
+
+    function.retain_basic_blocks(|block| !dead_blocks.contains(&Rc::as_ptr(block)));
+    dead_blocks.len()
+}
+
+/// Computes the value of an instruction whose operands are constants, returning the register containing its result and
+/// the value that it is replaced with.
+fn fold_instruction(instruction: &Instruction) -> Option<(Register, Value)> {
+    let folded = match instruction {
+        Instruction::IntegerBinary {
+            operation, x, y, ..
+        } => fold::integer_binary(*operation, x, y),
+        Instruction::FloatBinary {
+            operation, x, y, ..
+        } => fold::float_binary(*operation, x, y),
+        Instruction::ICmp {
+            predicate, x, y, ..
+        } => fold::icmp(*predicate, x, y),
+        Instruction::FCmp {
+            predicate, x, y, ..
+        } => fold::fcmp(*predicate, x, y),
+        Instruction::FNeg { operand, .. } => fold::fneg(operand),
+        Instruction::Conversion {
+            operation,
+            operand,
+            result,
+        } => fold::conversion(*operation, operand, result.value_type()),
+        // A phi whose incoming values are all the same, ignoring references to itself, can be replaced by that value.
+        Instruction::Phi { incoming, result } => {
+            let mut values = incoming
+                .iter()
+                .map(|(value, _)| value)
+                .filter(|value| !matches!(value, Value::Register(register) if register == result));
+            let first = values.next()?;
+            if values.all(|value| fold::constants_equal(value, first)) {
+                Some(first.clone())
+            } else {
+                None
+            }
+        }
+        _ => None,
+    };
+
+    Some((instruction.result()?.clone(), folded?))
+}
+
+/// Replaces a conditional terminator whose condition is a constant with an unconditional branch, returning `true` if the
+/// terminator was replaced.
+fn fold_terminator(block: &Rc<BasicBlock>) -> bool {
+    let (taken, not_taken) = {
+        let Some(terminator) = block.terminator() else {
+            return false;
+        };
+
+        match &*terminator {
+            Instruction::CondBr {
+                condition: Value::Integer(condition),
+                true_target,
+                false_target,
+            } => {
+                if condition.to_u128() == Some(1) {
+                    (true_target.clone(), vec![false_target.clone()])
+                } else {
+                    (false_target.clone(), vec![true_target.clone()])
+                }
+            }
+            Instruction::Switch {
+                value: Value::Integer(value),
+                default_target,
+                cases,
+            } => {
+                let taken = cases
+                    .iter()
+                    .find(|(case_value, _)| {
+                        matches!(case_value, Value::Integer(case_value) if case_value.words() == value.words())
+                    })
+                    .map_or(default_target, |(_, target)| target)
+                    .clone();
+                let not_taken = std::iter::once(default_target)
+                    .chain(cases.iter().map(|(_, target)| target))
+                    .cloned()
+                    .collect();
+                (taken, not_taken)
+            }
+            _ => return false,
+        }
+    };
+
+    // Blocks that are no longer successors must not have incoming values from this block, and the taken block must only
+    // have one incoming value for the remaining edge.
+    let mut removed = Vec::<*const BasicBlock>::new();
+    for target in not_taken.iter() {
+        if target.as_ptr() != taken.as_ptr() && !removed.contains(&target.as_ptr()) {
+            removed.push(target.as_ptr());
+            if let Some(target_block) = target.block() {
+                remove_phi_incoming(&target_block, Rc::as_ptr(block));
+            }
+        }
+    }
+
+    if let Some(taken_block) = taken.block() {
+        merge_phi_incoming(&taken_block, Rc::as_ptr(block));
+    }
+
+    let terminator_index = block.instructions().len() - 1;
+    block.replace_instruction(terminator_index, Instruction::Br(taken));
+    true
+}
+
+/// Evaluates instructions whose operands are constants, replacing the uses of their results with the computed values and
+/// removing them. This is repeated until no more instructions can be folded, returning the number of instructions that
+/// were folded.
+///
+/// Conditional branches and `switch` instructions on constant values are replaced with unconditional branches, which may
+/// leave blocks that are unreachable, which can be removed with [`eliminate_dead_blocks`].
+///
+/// Instructions are evaluated with the functions in [`value::fold`](crate::value::fold), so operations whose results are
+/// undefined or poison values, such as division by zero or shifts exceeding the bit width, are not folded.
+pub fn fold_constants(function: &Function) -> usize {
+    let mut count = 0;
+    let mut changed = true;
+
+    while changed {
+        changed = false;
+        let blocks = function.basic_blocks().to_vec();
+
+        for block in blocks.iter() {
+            let mut index = 0;
+            loop {
+                let folded = match block.instructions().get(index) {
+                    Some(instruction) => fold_instruction(instruction),
+                    None => break,
+                };
+
+                match folded {
+                    Some((result, value)) => {
+                        block.remove_instruction(index);
+                        replace_all_uses_with(function, &result, &value);
+                        count += 1;
+                        changed = true;
+                    }
+                    None => index += 1,
+                }
+            }
+
+            if fold_terminator(block) {
+                count += 1;
+                changed = true;
+            }
+        }
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types;
+    use crate::value;
+
+    fn integer_type() -> Rc<types::FirstClass> {
+        Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+            types::IntegerSize::SIZE_32,
+        )))
+    }
+
+    fn constant(value: u64) -> value::Integer {
+        value::Integer::from_u64(types::IntegerSize::SIZE_32, value)
+    }
+
+    /// Creates a function, appending blocks with the specified names.
+    fn function_with_blocks(names: &[&str]) -> (Rc<Function>, Vec<Rc<BasicBlock>>) {
+        let function = Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::FirstClass(integer_type()), Vec::new()),
+        );
+        let blocks = names
+            .iter()
+            .map(|name| {
+                let block = BasicBlock::with_name(crate::Identifier::sanitized(name));
+                function.append_basic_block(block.clone());
+                block
+            })
+            .collect();
+        (function, blocks)
+    }
+
+    /// Gets the blocks that the incoming values of the `phi` instruction at the start of a block come from.
+    fn phi_incoming_blocks(block: &BasicBlock) -> Vec<*const BasicBlock> {
+        match &block.instructions()[0] {
+            Instruction::Phi { incoming, .. } => incoming
+                .iter()
+                .map(|(_, incoming_block)| incoming_block.as_ptr())
+                .collect(),
+            instruction => panic!("expected phi, but got {}", instruction),
+        }
+    }
+
+    #[test]
+    fn folded_branch_removes_incoming_values_from_block_not_taken() {
+        let (function, blocks) = function_with_blocks(&["entry", "left", "join"]);
+        let [entry, left, join] = &blocks[..] else {
+            unreachable!()
+        };
+
+        entry.br_if(
+            Value::Integer(value::Integer::from_u64(types::IntegerSize::MIN, 1)),
+            left,
+            join,
+        );
+        left.br(join);
+        let result = join.phi(
+            integer_type(),
+            vec![
+                (Value::Integer(constant(1)), entry),
+                (Value::Integer(constant(2)), left),
+            ],
+        );
+        join.ret(Some(Value::Register(result)));
+
+        assert!(fold_terminator(entry));
+        assert_eq!(phi_incoming_blocks(join), vec![Rc::as_ptr(left)]);
+        assert!(crate::verify::verify_function(&function).is_ok());
+    }
+
+    #[test]
+    fn folded_switch_keeps_one_incoming_value_for_taken_block() {
+        let (function, blocks) = function_with_blocks(&["entry", "other", "join"]);
+        let [entry, other, join] = &blocks[..] else {
+            unreachable!()
+        };
+
+        entry.switch(
+            Value::Integer(constant(1)),
+            other,
+            vec![(constant(1), join), (constant(2), join)],
+        );
+        other.br(join);
+        let result = join.phi(
+            integer_type(),
+            vec![
+                (Value::Integer(constant(1)), entry),
+                (Value::Integer(constant(1)), entry),
+                (Value::Integer(constant(2)), other),
+            ],
+        );
+        join.ret(Some(Value::Register(result)));
+
+        assert!(fold_terminator(entry));
+        assert_eq!(
+            phi_incoming_blocks(join),
+            vec![Rc::as_ptr(entry), Rc::as_ptr(other)]
+        );
+        assert!(crate::verify::verify_function(&function).is_ok());
+
+        assert_eq!(eliminate_dead_blocks(&function), 1);
+        assert_eq!(phi_incoming_blocks(join), vec![Rc::as_ptr(entry)]);
+        assert!(crate::verify::verify_function(&function).is_ok());
+    }
+
+    #[test]
+    fn dead_blocks_are_removed_from_phi_instructions() {
+        let (function, blocks) = function_with_blocks(&["entry", "dead", "join"]);
+        let [entry, dead, join] = &blocks[..] else {
+            unreachable!()
+        };
+
+        entry.br(join);
+        dead.br(join);
+        let result = join.phi(
+            integer_type(),
+            vec![
+                (Value::Integer(constant(1)), entry),
+                (Value::Integer(constant(2)), dead),
+            ],
+        );
+        join.ret(Some(Value::Register(result)));
+
+        assert_eq!(eliminate_dead_blocks(&function), 1);
+        assert_eq!(function.basic_blocks().len(), 2);
+        assert_eq!(phi_incoming_blocks(join), vec![Rc::as_ptr(entry)]);
+        assert!(crate::verify::verify_function(&function).is_ok());
+    }
+
+    #[test]
+    fn fold_constants_does_not_fold_division_by_zero() {
+        let (function, blocks) = function_with_blocks(&["entry"]);
+        let entry = &blocks[0];
+        let quotient = entry.udiv(Value::Integer(constant(1)), Value::Integer(constant(0)));
+        let sum = entry.add(Value::Integer(constant(1)), Value::Integer(constant(2)));
+        let result = entry.add(Value::Register(quotient), Value::Register(sum));
+        entry.ret(Some(Value::Register(result)));
+
+        assert_eq!(fold_constants(&function), 1);
+        assert_eq!(entry.instructions().len(), 3);
+        assert!(function.to_string().contains("add i32 %0, 3"));
+    }
+}
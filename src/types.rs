@@ -1,5 +1,6 @@
 //! Model of the LLVM type system.
 
+use std::cell::Cell;
 use std::fmt::{Display, Formatter, Write as _};
 use std::num::NonZeroU32;
 use std::rc::Rc;
@@ -10,6 +11,16 @@ use std::rc::Rc;
 pub struct IntegerSize(NonZeroU32);
 
 impl IntegerSize {
+    /// Creates a new size value, returning `None` if the size is not in the range `1` to `2^23`.
+    pub const fn new(size: u32) -> Option<Self> {
+        if size == 0 || size > Self::MAX.bits() {
+            None
+        } else {
+            // Safety: Bounds are checked above.
+            Some(unsafe { Self::new_unchecked(size) })
+        }
+    }
+
     /// Creates a new size value, without any bounds checks.
     ///
     /// # Safety
@@ -21,6 +32,27 @@ impl IntegerSize {
     /// Minimum size value.
     pub const MIN: Self = unsafe { Self::new_unchecked(1) };
 
+    /// Maximum size value, `2^23`.
+    pub const MAX: Self = unsafe { Self::new_unchecked(1 << 23) };
+
+    /// Size of a 1-bit integer, used for boolean values.
+    pub const I1: Self = Self::MIN;
+
+    /// Size of an 8-bit integer.
+    pub const I8: Self = Self::SIZE_8;
+
+    /// Size of a 16-bit integer.
+    pub const I16: Self = Self::SIZE_16;
+
+    /// Size of a 32-bit integer.
+    pub const I32: Self = Self::SIZE_32;
+
+    /// Size of a 64-bit integer.
+    pub const I64: Self = Self::SIZE_64;
+
+    /// Size of a 128-bit integer.
+    pub const I128: Self = unsafe { Self::new_unchecked(128) };
+
     /// Size of a 1-byte integer, known as a byte.
     pub const SIZE_8: Self = unsafe { Self::new_unchecked(8) };
 
@@ -34,7 +66,7 @@ impl IntegerSize {
     pub const SIZE_64: Self = unsafe { Self::new_unchecked(64) };
 
     /// Gets the size, in bits.
-    pub fn bits(self) -> u32 {
+    pub const fn bits(self) -> u32 {
         self.0.get()
     }
 }
@@ -47,6 +79,7 @@ impl Display for IntegerSize {
 
 /// Represents a floating-point type.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Float {
     /// 16-bit, IEEE-754 `binary16`.
     Half,
@@ -68,30 +101,69 @@ impl Display for Float {
 
 pub use crate::target::layout::AddressSpace;
 
+/// The type of value pointed to by a pointer type.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Pointee {
+    /// Objects of a first class type.
+    FirstClass(Rc<FirstClass>),
+    /// Functions with a particular signature, allowing function pointers to be called indirectly.
+    Function(Rc<Function>),
+}
+
+crate::enum_case_from!(Pointee, FirstClass, Rc<FirstClass>);
+crate::enum_case_from!(Pointee, Function, Rc<Function>);
+
+impl Display for Pointee {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::FirstClass(first_class) => Display::fmt(first_class, f),
+            Self::Function(signature) => Display::fmt(signature, f),
+        }
+    }
+}
+
 /// A pointer type.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Pointer {
-    pointee_type: Rc<FirstClass>, // TODO: Allow function pointers, have an enum PointeeType?
+    pointee: Pointee,
     address_space: AddressSpace,
 }
 
 impl Pointer {
-    /// Creates a pointer type pointing to a objects of a particular type in a particular address space.
-    pub fn in_address_space(pointee_type: Rc<FirstClass>, address_space: AddressSpace) -> Self {
+    /// Creates a pointer type pointing to a objects or functions of a particular type in a particular address space.
+    pub fn in_address_space<P: Into<Pointee>>(pointee: P, address_space: AddressSpace) -> Self {
         Self {
-            pointee_type,
+            pointee: pointee.into(),
             address_space,
         }
     }
 
-    /// Creates a pointer type pointing to objects of a particular type.
-    pub fn new(pointee_type: Rc<FirstClass>) -> Self {
-        Self::in_address_space(pointee_type, AddressSpace::VON_NEUMANN_DEFAULT)
+    /// Creates a pointer type pointing to objects or functions of a particular type.
+    pub fn new<P: Into<Pointee>>(pointee: P) -> Self {
+        Self::in_address_space(pointee, AddressSpace::VON_NEUMANN_DEFAULT)
     }
 
-    /// The type of object that is pointed to by the pointer type.
-    pub fn pointee_type(&self) -> &Rc<FirstClass> {
-        &self.pointee_type
+    /// The type of object or function that is pointed to by the pointer type.
+    pub fn pointee(&self) -> &Pointee {
+        &self.pointee
+    }
+
+    /// The type of object that is pointed to by the pointer type, or `None` if this is a function pointer.
+    pub fn pointee_type(&self) -> Option<&Rc<FirstClass>> {
+        match &self.pointee {
+            Pointee::FirstClass(pointee_type) => Some(pointee_type),
+            Pointee::Function(_) => None,
+        }
+    }
+
+    /// The signature of the function that is pointed to by the pointer type, or `None` if this is not a function pointer.
+    pub fn function_signature(&self) -> Option<&Rc<Function>> {
+        match &self.pointee {
+            Pointee::Function(signature) => Some(signature),
+            Pointee::FirstClass(_) => None,
+        }
     }
 
     /// The address space of the pointer, indicating where the object pointed to resides.
@@ -100,9 +172,38 @@ impl Pointer {
     }
 }
 
+thread_local! {
+    /// Whether pointer types are currently printed as opaque pointers.
+    static OPAQUE_POINTERS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Prints pointer types within the closure as opaque pointers (`ptr`) if `opaque` is `true`, or as typed pointers
+/// otherwise.
+pub(crate) fn with_opaque_pointers<R, F: FnOnce() -> R>(opaque: bool, f: F) -> R {
+    /// Restores the previous mode, even if formatting panics.
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            OPAQUE_POINTERS.with(|current| current.set(self.0));
+        }
+    }
+
+    let _restore = Restore(OPAQUE_POINTERS.with(|current| current.replace(opaque)));
+    f()
+}
+
 impl Display for Pointer {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        Display::fmt(&self.pointee_type, f)?;
+        if OPAQUE_POINTERS.with(Cell::get) {
+            f.write_str("ptr")?;
+            if self.address_space.0 != 0 {
+                write!(f, " addrspace({})", self.address_space)?;
+            }
+            return Ok(());
+        }
+
+        Display::fmt(&self.pointee, f)?;
         if self.address_space.0 != 0 {
             write!(f, " addrspace({})", self.address_space)?;
         }
@@ -112,6 +213,7 @@ impl Display for Pointer {
 
 /// A vector of elements of a specified size.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     element_type: Rc<FirstClass>,
     count: NonZeroU32,
@@ -137,6 +239,11 @@ impl Vector {
         // -> Result<u32, u32> // Error if vscale?
         self.count.get()
     }
+
+    /// Creates a vector type with the same number of elements as this vector type, but with a different element type.
+    pub fn with_element_type(&self, element_type: Rc<FirstClass>) -> Self {
+        Self::new(element_type, self.count)
+    }
 }
 
 impl Display for Vector {
@@ -147,6 +254,7 @@ impl Display for Vector {
 
 /// A subset of the types that are valid in registers.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SingleValue {
     /// An integer type of an arbitrary bit width.
     Integer(IntegerSize),
@@ -171,6 +279,7 @@ impl Display for SingleValue {
 
 /// Describes the type of value returned by a function.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Return {
     /// A type representing no value.
     Void,
@@ -189,9 +298,11 @@ impl Display for Return {
 
 /// Represents a function type, which describes the return types and parameter types of a function.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Function {
     return_type: Return,
     parameter_types: Vec<Rc<FirstClass>>,
+    variadic: bool,
 }
 
 impl Function {
@@ -200,6 +311,19 @@ impl Function {
         Self {
             return_type,
             parameter_types: parameter_types.into(),
+            variadic: false,
+        }
+    }
+
+    /// Creates a function type that accepts a variable number of arguments after its fixed parameters, written as
+    /// `...` in LLVM IR.
+    pub fn new_variadic(
+        return_type: Return,
+        parameter_types: impl Into<Vec<Rc<FirstClass>>>,
+    ) -> Self {
+        Self {
+            variadic: true,
+            ..Self::new(return_type, parameter_types)
         }
     }
 
@@ -212,6 +336,11 @@ impl Function {
     pub fn parameter_types(&self) -> &[Rc<FirstClass>] {
         &self.parameter_types
     }
+
+    /// Returns `true` if additional arguments can be passed after the fixed parameters.
+    pub fn is_variadic(&self) -> bool {
+        self.variadic
+    }
 }
 
 impl Display for Function {
@@ -224,12 +353,19 @@ impl Display for Function {
             }
             Display::fmt(&parameter_type, f)?;
         }
+        if self.variadic {
+            if !self.parameter_types.is_empty() {
+                f.write_str(", ")?;
+            }
+            f.write_str("...")?;
+        }
         f.write_char(')')
     }
 }
 
 /// A type containing a fixed number of elements that are sequentially arranged in memory.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Array {
     element_type: Rc<FirstClass>,
     count: u32,
@@ -263,6 +399,7 @@ impl Display for Array {
 
 /// Structure types contain members, which each have their own types.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Struct {
     packed: bool,
     member_types: Vec<Rc<FirstClass>>,
@@ -291,7 +428,7 @@ impl Struct {
 impl Display for Struct {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         if self.packed {
-            f.write_char('>')?;
+            f.write_char('<')?;
         }
         f.write_str("{ ")?;
         for (index, member_type) in self.member_types.iter().enumerate() {
@@ -312,6 +449,7 @@ impl Display for Struct {
 ///
 /// Note that vector types are not aggregate types.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Aggregate {
     /// An array type containing a specific number of elements.
     Array(Array),
@@ -331,18 +469,40 @@ impl Display for Aggregate {
 
 /// Values of first class types "are the only ones that can be produced by instructions".
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum FirstClass {
     /// Single
     Single(SingleValue),
     /// Types that contain multiple members.
     Aggregate(Aggregate),
+    /// A value associated with an instruction that cannot be inspected or modified (`token`), used by exception handling
+    /// pads and some intrinsics.
+    Token,
+    /// Embedded metadata (`metadata`), only allowed as the type of function parameters, such as the parameters of
+    /// intrinsics.
+    Metadata,
 }
 
+impl FirstClass {
+    /// Gets the type of the elements if this is a vector type, or this type otherwise.
+    pub fn scalar_type(&self) -> &FirstClass {
+        match self {
+            Self::Single(SingleValue::Vector(vector)) => vector.element_type(),
+            _ => self,
+        }
+    }
+}
+
+crate::enum_case_from!(FirstClass, Single, SingleValue);
+crate::enum_case_from!(FirstClass, Aggregate, Aggregate);
+
 impl Display for FirstClass {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
             Self::Single(single) => Display::fmt(single, f),
             Self::Aggregate(aggregate) => Display::fmt(aggregate, f),
+            Self::Token => f.write_str("token"),
+            Self::Metadata => f.write_str("metadata"),
         }
     }
 }
@@ -0,0 +1,442 @@
+//! Evaluates operations on constant values, allowing front-ends to simplify expressions before emitting instructions.
+//!
+//! Each function returns `None` if the operation cannot be evaluated, such as when an operand is not a constant or when
+//! the result would be undefined behavior or a poison value. Operations on integers wider than 128 bits and on `half`
+//! values are mostly not evaluated.
+
+use crate::block::{
+    ConversionOperation, FcmpPredicate, FloatBinaryOperation, IcmpPredicate, IntegerBinaryOperation,
+};
+use crate::types;
+use crate::value::{self, Float, Integer, Value};
+
+/// Interprets the bits of an integer as a signed value.
+fn sign_extend(value: u128, bits: u32) -> i128 {
+    let shift = 128 - bits;
+    ((value << shift) as i128) >> shift
+}
+
+/// Creates an `i1` constant.
+fn boolean(value: bool) -> Value {
+    Value::Integer(Integer::from_u64(types::IntegerSize::MIN, u64::from(value)))
+}
+
+/// Evaluates an integer binary operation, such as `add` or `udiv`, on two integer constants of the same type.
+///
+/// Division by zero, signed division overflow, and shifts by amounts exceeding the bit width are not evaluated.
+pub fn integer_binary(operation: IntegerBinaryOperation, x: &Value, y: &Value) -> Option<Value> {
+    let (Value::Integer(x), Value::Integer(y)) = (x, y) else {
+        return None;
+    };
+
+    let integer_type = x.integer_type();
+    if y.integer_type() != integer_type {
+        return None;
+    }
+
+    let bits = integer_type.bits();
+    let (a, b) = (x.to_u128()?, y.to_u128()?);
+    let (signed_a, signed_b) = (sign_extend(a, bits), sign_extend(b, bits));
+
+    // Signed division overflows when the quotient does not fit in the integer type.
+    if matches!(
+        operation,
+        IntegerBinaryOperation::SDiv | IntegerBinaryOperation::SRem
+    ) && signed_a == sign_extend(1 << (bits - 1), bits)
+        && signed_b == -1
+    {
+        return None;
+    }
+
+    // Operations that are undefined behavior or produce poison values are not folded.
+    let result = match operation {
+        IntegerBinaryOperation::Add => a.wrapping_add(b),
+        IntegerBinaryOperation::Sub => a.wrapping_sub(b),
+        IntegerBinaryOperation::Mul => a.wrapping_mul(b),
+        IntegerBinaryOperation::UDiv => a.checked_div(b)?,
+        IntegerBinaryOperation::URem => a.checked_rem(b)?,
+        IntegerBinaryOperation::SDiv => signed_a.checked_div(signed_b)? as u128,
+        IntegerBinaryOperation::SRem => signed_a.checked_rem(signed_b)? as u128,
+        IntegerBinaryOperation::Shl if b < u128::from(bits) => a << b,
+        IntegerBinaryOperation::LShr if b < u128::from(bits) => a >> b,
+        IntegerBinaryOperation::AShr if b < u128::from(bits) => (signed_a >> b) as u128,
+        IntegerBinaryOperation::Shl
+        | IntegerBinaryOperation::LShr
+        | IntegerBinaryOperation::AShr => return None,
+        IntegerBinaryOperation::And => a & b,
+        IntegerBinaryOperation::Or => a | b,
+        IntegerBinaryOperation::Xor => a ^ b,
+    };
+
+    Some(Value::Integer(Integer::from_u128(integer_type, result)))
+}
+
+/// Evaluates an `icmp` comparison of two integer constants of the same type, returning an `i1` constant.
+pub fn icmp(predicate: IcmpPredicate, x: &Value, y: &Value) -> Option<Value> {
+    let (Value::Integer(x), Value::Integer(y)) = (x, y) else {
+        return None;
+    };
+
+    if x.integer_type() != y.integer_type() {
+        return None;
+    }
+
+    let bits = x.integer_type().bits();
+    let (a, b) = (x.to_u128()?, y.to_u128()?);
+    let (signed_a, signed_b) = (sign_extend(a, bits), sign_extend(b, bits));
+
+    let result = match predicate {
+        IcmpPredicate::Equal => a == b,
+        IcmpPredicate::NotEqual => a != b,
+        IcmpPredicate::UnsignedGreaterThan => a > b,
+        IcmpPredicate::UnsignedGreaterOrEqual => a >= b,
+        IcmpPredicate::UnsignedLessThan => a < b,
+        IcmpPredicate::UnsignedLessOrEqual => a <= b,
+        IcmpPredicate::SignedGreaterThan => signed_a > signed_b,
+        IcmpPredicate::SignedGreaterOrEqual => signed_a >= signed_b,
+        IcmpPredicate::SignedLessThan => signed_a < signed_b,
+        IcmpPredicate::SignedLessOrEqual => signed_a <= signed_b,
+    };
+
+    Some(boolean(result))
+}
+
+/// Evaluates a floating-point binary operation, such as `fadd` or `fdiv`, on two floating-point constants of the same
+/// type.
+pub fn float_binary(operation: FloatBinaryOperation, x: &Value, y: &Value) -> Option<Value> {
+    macro_rules! apply {
+        ($a: expr, $b: expr) => {
+            match operation {
+                FloatBinaryOperation::Add => $a + $b,
+                FloatBinaryOperation::Sub => $a - $b,
+                FloatBinaryOperation::Mul => $a * $b,
+                FloatBinaryOperation::Div => $a / $b,
+                FloatBinaryOperation::Rem => $a % $b,
+            }
+        };
+    }
+
+    // Half-precision values are not folded, since there is no native type to perform the operations with.
+    match (x, y) {
+        (Value::Float(Float::Float(a)), Value::Float(Float::Float(b))) => {
+            Some(Value::Float(Float::Float(apply!(a, b))))
+        }
+        (Value::Float(Float::Double(a)), Value::Float(Float::Double(b))) => {
+            Some(Value::Float(Float::Double(apply!(a, b))))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluates an `fcmp` comparison of two floating-point constants of the same type, returning an `i1` constant.
+pub fn fcmp(predicate: FcmpPredicate, x: &Value, y: &Value) -> Option<Value> {
+    let (a, b) = match (x, y) {
+        (Value::Float(Float::Float(a)), Value::Float(Float::Float(b))) => {
+            (f64::from(*a), f64::from(*b))
+        }
+        (Value::Float(Float::Double(a)), Value::Float(Float::Double(b))) => (*a, *b),
+        _ => return None,
+    };
+
+    // Comparisons involving NaN are always false, so only the unordered predicates need to check for NaN explicitly.
+    let unordered = a.is_nan() || b.is_nan();
+    let result = match predicate {
+        FcmpPredicate::False => false,
+        FcmpPredicate::OrderedEqual => a == b,
+        FcmpPredicate::OrderedGreaterThan => a > b,
+        FcmpPredicate::OrderedGreaterOrEqual => a >= b,
+        FcmpPredicate::OrderedLessThan => a < b,
+        FcmpPredicate::OrderedLessOrEqual => a <= b,
+        FcmpPredicate::OrderedNotEqual => !unordered && a != b,
+        FcmpPredicate::Ordered => !unordered,
+        FcmpPredicate::UnorderedEqual => unordered || a == b,
+        FcmpPredicate::UnorderedGreaterThan => unordered || a > b,
+        FcmpPredicate::UnorderedGreaterOrEqual => unordered || a >= b,
+        FcmpPredicate::UnorderedLessThan => unordered || a < b,
+        FcmpPredicate::UnorderedLessOrEqual => unordered || a <= b,
+        FcmpPredicate::UnorderedNotEqual => a != b,
+        FcmpPredicate::Unordered => unordered,
+        FcmpPredicate::True => true,
+    };
+
+    Some(boolean(result))
+}
+
+/// Evaluates the negation of a floating-point constant (`fneg`).
+pub fn fneg(operand: &Value) -> Option<Value> {
+    match operand {
+        Value::Float(Float::Half(bits)) => Some(Value::Float(Float::Half(bits ^ 0x8000))),
+        Value::Float(Float::Float(value)) => Some(Value::Float(Float::Float(-value))),
+        Value::Float(Float::Double(value)) => Some(Value::Float(Float::Double(-value))),
+        _ => None,
+    }
+}
+
+/// Evaluates the conversion of a constant to the specified type, such as `zext` or `sitofp`.
+///
+/// Integer truncations and extensions are evaluated for integers of any width. Conversions from floating-point values
+/// that do not fit in the integer type are not evaluated, since their results are poison values.
+pub fn conversion(
+    operation: ConversionOperation,
+    operand: &Value,
+    target_type: &types::FirstClass,
+) -> Option<Value> {
+    let target = match target_type {
+        types::FirstClass::Single(target) => target,
+        _ => return None,
+    };
+
+    match (operation, operand, target) {
+        (
+            ConversionOperation::Trunc,
+            Value::Integer(integer),
+            types::SingleValue::Integer(size),
+        ) if size.bits() < integer.integer_type().bits() => {
+            Some(Value::Integer(Integer::from_words(*size, integer.words())))
+        }
+        (ConversionOperation::ZExt, Value::Integer(integer), types::SingleValue::Integer(size))
+            if size.bits() > integer.integer_type().bits() =>
+        {
+            Some(Value::Integer(Integer::from_words(*size, integer.words())))
+        }
+        (ConversionOperation::SExt, Value::Integer(integer), types::SingleValue::Integer(size))
+            if size.bits() > integer.integer_type().bits() =>
+        {
+            let bits = integer.integer_type().bits();
+            let mut words = integer.words()[..value::word_count(bits)].to_vec();
+            let (negative, _) = integer.signed_magnitude();
+            if negative {
+                let last = words.len() - 1;
+                words[last] |= !value::most_significant_mask(bits);
+            }
+
+            Some(Value::Integer(Integer::from_extended_words(
+                *size,
+                &words,
+                value::sign_fill(negative),
+            )))
+        }
+        (
+            ConversionOperation::FPTrunc,
+            Value::Float(Float::Double(value)),
+            types::SingleValue::Float(types::Float::Float),
+        ) => Some(Value::Float(Float::Float(*value as f32))),
+        (
+            ConversionOperation::FPExt,
+            Value::Float(Float::Float(value)),
+            types::SingleValue::Float(types::Float::Double),
+        ) => Some(Value::Float(Float::Double(f64::from(*value)))),
+        (
+            ConversionOperation::FPToUI | ConversionOperation::FPToSI,
+            Value::Float(float),
+            types::SingleValue::Integer(size),
+        ) if size.bits() <= 128 => {
+            let value = match float {
+                Float::Float(value) => f64::from(*value),
+                Float::Double(value) => *value,
+                Float::Half(_) => return None,
+            }
+            .trunc();
+
+            // The bounds are powers of two, which are represented exactly.
+            let bits = i32::try_from(size.bits()).ok()?;
+            let (minimum, maximum) = if operation == ConversionOperation::FPToSI {
+                (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1))
+            } else {
+                (0.0, 2f64.powi(bits))
+            };
+
+            if !(value >= minimum && value < maximum) {
+                return None;
+            }
+
+            Some(Value::Integer(if value < 0.0 {
+                Integer::from_i128(*size, value as i128)
+            } else {
+                Integer::from_u128(*size, value as u128)
+            }))
+        }
+        (
+            ConversionOperation::UIToFP | ConversionOperation::SIToFP,
+            Value::Integer(integer),
+            types::SingleValue::Float(float_type),
+        ) => {
+            let bits = integer.integer_type().bits();
+            let unsigned = integer.to_u128()?;
+            let value = if operation == ConversionOperation::SIToFP {
+                let signed = sign_extend(unsigned, bits);
+                match float_type {
+                    types::Float::Float => Float::Float(signed as f32),
+                    types::Float::Double => Float::Double(signed as f64),
+                    types::Float::Half => return None,
+                }
+            } else {
+                match float_type {
+                    types::Float::Float => Float::Float(unsigned as f32),
+                    types::Float::Double => Float::Double(unsigned as f64),
+                    types::Float::Half => return None,
+                }
+            };
+
+            Some(Value::Float(value))
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` if two constant values are known to be equal.
+pub(crate) fn constants_equal(x: &Value, y: &Value) -> bool {
+    match (x, y) {
+        (Value::Integer(a), Value::Integer(b)) => {
+            a.integer_type() == b.integer_type() && a.words() == b.words()
+        }
+        (Value::Float(a), Value::Float(b)) => match (a, b) {
+            (Float::Half(a), Float::Half(b)) => a == b,
+            (Float::Float(a), Float::Float(b)) => a.to_bits() == b.to_bits(),
+            (Float::Double(a), Float::Double(b)) => a.to_bits() == b.to_bits(),
+            _ => false,
+        },
+        (Value::Null(a), Value::Null(b)) => a == b,
+        (Value::Register(a), Value::Register(b)) => a == b,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn integer(integer_type: types::IntegerSize, value: i64) -> Value {
+        Value::Integer(Integer::from_i64(integer_type, value))
+    }
+
+    fn bits(value: Option<Value>) -> Option<u128> {
+        match value? {
+            Value::Integer(integer) => integer.to_u128(),
+            value => panic!("expected integer, but got {}", value),
+        }
+    }
+
+    fn double(value: f64) -> Value {
+        Value::Float(Float::Double(value))
+    }
+
+    #[test]
+    fn signed_division_overflow_is_not_folded() {
+        let minimum = integer(types::IntegerSize::SIZE_8, -128);
+        let negative_one = integer(types::IntegerSize::SIZE_8, -1);
+
+        for operation in [IntegerBinaryOperation::SDiv, IntegerBinaryOperation::SRem] {
+            assert!(integer_binary(operation, &minimum, &negative_one).is_none());
+        }
+
+        assert_eq!(
+            bits(integer_binary(
+                IntegerBinaryOperation::SDiv,
+                &minimum,
+                &integer(types::IntegerSize::SIZE_8, 2)
+            )),
+            Some(0xC0)
+        );
+        assert_eq!(
+            bits(integer_binary(
+                IntegerBinaryOperation::SRem,
+                &integer(types::IntegerSize::SIZE_8, -7),
+                &integer(types::IntegerSize::SIZE_8, 2)
+            )),
+            Some(0xFF)
+        );
+    }
+
+    #[test]
+    fn division_by_zero_is_not_folded() {
+        let zero = integer(types::IntegerSize::SIZE_32, 0);
+        let one = integer(types::IntegerSize::SIZE_32, 1);
+
+        for operation in [
+            IntegerBinaryOperation::UDiv,
+            IntegerBinaryOperation::URem,
+            IntegerBinaryOperation::SDiv,
+            IntegerBinaryOperation::SRem,
+        ] {
+            assert!(integer_binary(operation, &one, &zero).is_none());
+        }
+    }
+
+    #[test]
+    fn shifts_by_bit_width_or_more_are_not_folded() {
+        let x = integer(types::IntegerSize::SIZE_8, -128);
+
+        for operation in [
+            IntegerBinaryOperation::Shl,
+            IntegerBinaryOperation::LShr,
+            IntegerBinaryOperation::AShr,
+        ] {
+            for amount in [8, 9, 255] {
+                let amount = integer(types::IntegerSize::SIZE_8, amount);
+                assert!(integer_binary(operation, &x, &amount).is_none());
+            }
+        }
+
+        let amount = integer(types::IntegerSize::SIZE_8, 7);
+        assert_eq!(
+            bits(integer_binary(
+                IntegerBinaryOperation::Shl,
+                &integer(types::IntegerSize::SIZE_8, 3),
+                &amount
+            )),
+            Some(0x80)
+        );
+        assert_eq!(
+            bits(integer_binary(IntegerBinaryOperation::LShr, &x, &amount)),
+            Some(1)
+        );
+        assert_eq!(
+            bits(integer_binary(IntegerBinaryOperation::AShr, &x, &amount)),
+            Some(0xFF)
+        );
+    }
+
+    #[test]
+    fn fcmp_with_nan_is_only_true_for_unordered_predicates() {
+        let nan = double(f64::NAN);
+        let one = double(1.0);
+
+        for (predicate, expected) in [
+            (FcmpPredicate::False, false),
+            (FcmpPredicate::OrderedEqual, false),
+            (FcmpPredicate::OrderedGreaterThan, false),
+            (FcmpPredicate::OrderedGreaterOrEqual, false),
+            (FcmpPredicate::OrderedLessThan, false),
+            (FcmpPredicate::OrderedLessOrEqual, false),
+            (FcmpPredicate::OrderedNotEqual, false),
+            (FcmpPredicate::Ordered, false),
+            (FcmpPredicate::UnorderedEqual, true),
+            (FcmpPredicate::UnorderedGreaterThan, true),
+            (FcmpPredicate::UnorderedGreaterOrEqual, true),
+            (FcmpPredicate::UnorderedLessThan, true),
+            (FcmpPredicate::UnorderedLessOrEqual, true),
+            (FcmpPredicate::UnorderedNotEqual, true),
+            (FcmpPredicate::Unordered, true),
+            (FcmpPredicate::True, true),
+        ] {
+            for (x, y) in [(&nan, &one), (&one, &nan), (&nan, &nan)] {
+                assert_eq!(
+                    bits(fcmp(predicate, x, y)),
+                    Some(u128::from(expected)),
+                    "fcmp {}",
+                    predicate
+                );
+            }
+        }
+
+        assert_eq!(
+            bits(fcmp(FcmpPredicate::UnorderedNotEqual, &one, &one)),
+            Some(0)
+        );
+        assert_eq!(
+            bits(fcmp(FcmpPredicate::OrderedNotEqual, &one, &double(2.0))),
+            Some(1)
+        );
+    }
+}
@@ -0,0 +1,1151 @@
+//! Types to model values in LLVM.
+
+use crate::block::{BasicBlock, BlockReference};
+use crate::global;
+use crate::identifier::{Identifier, Quoted, Symbol};
+use crate::types;
+use std::cell::RefCell;
+use std::fmt::{Debug, Display, Formatter, Write as _};
+use std::hash::{Hash, Hasher};
+use std::rc::{Rc, Weak};
+
+pub mod fold;
+
+#[derive(Clone, Debug)]
+enum IntegerValue {
+    Inline([u64; 2]),
+    Allocated(Box<[u64]>),
+}
+
+/// Error type used when parsing an integer value from a string.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ParseIntegerError {
+    /// The string did not contain any digits.
+    #[error("cannot parse integer from string without digits")]
+    Empty,
+    /// The string contained a character that is not a digit in the radix.
+    #[error("invalid digit {digit:?} at byte index {byte_index}")]
+    InvalidDigit {
+        /// The character that is not a valid digit.
+        digit: char,
+        /// The index of the invalid digit in the string.
+        byte_index: usize,
+    },
+    /// The parsed value does not fit in the integer type.
+    #[error("integer value is too large for type i{0}")]
+    Overflow(types::IntegerSize),
+}
+
+/// Integer value of a specified type.
+#[derive(Clone, Debug)]
+pub struct Integer {
+    integer_type: types::IntegerSize,
+    value: IntegerValue,
+}
+
+impl Integer {
+    /// Creates an integer value of the specified type with a bit pattern of all zeroes.
+    pub fn zero(integer_type: types::IntegerSize) -> Self {
+        Self::from_extended_words(integer_type, &[], 0)
+    }
+
+    /// Creates an integer value from the bits of a 64-bit value, zero extending or truncating it to the specified type.
+    pub fn from_u64(integer_type: types::IntegerSize, value: u64) -> Self {
+        Self::from_extended_words(integer_type, &[value], 0)
+    }
+
+    /// Creates an integer value from a signed 64-bit value, sign extending or truncating it to the specified type.
+    pub fn from_i64(integer_type: types::IntegerSize, value: i64) -> Self {
+        Self::from_extended_words(integer_type, &[value as u64], sign_fill(value < 0))
+    }
+
+    /// Creates an integer value from the bits of a 128-bit value, zero extending or truncating it to the specified type.
+    pub fn from_u128(integer_type: types::IntegerSize, value: u128) -> Self {
+        Self::from_extended_words(integer_type, &[value as u64, (value >> 64) as u64], 0)
+    }
+
+    /// Creates an integer value from a signed 128-bit value, sign extending or truncating it to the specified type.
+    pub fn from_i128(integer_type: types::IntegerSize, value: i128) -> Self {
+        Self::from_extended_words(
+            integer_type,
+            &[value as u64, (value >> 64) as u64],
+            sign_fill(value < 0),
+        )
+    }
+
+    /// Creates an integer value from 64-bit words, with the least significant word first. The words are zero extended or
+    /// truncated to the specified type.
+    pub fn from_words(integer_type: types::IntegerSize, words: &[u64]) -> Self {
+        Self::from_extended_words(integer_type, words, 0)
+    }
+
+    /// Parses an integer value of the specified type from a string of digits in the specified radix, with an optional
+    /// leading `+` or `-` sign. Negative values are stored in two's complement form.
+    ///
+    /// # Errors
+    /// Returns an error if the string contains no digits, contains characters that are not digits in the radix, or if
+    /// the magnitude of the value does not fit in the specified type.
+    ///
+    /// # Panics
+    /// Panics if the radix is not in the range `2` to `36`, like [`u64::from_str_radix`].
+    pub fn from_str_radix(
+        integer_type: types::IntegerSize,
+        value: &str,
+        radix: u32,
+    ) -> Result<Self, ParseIntegerError> {
+        assert!(
+            (2..=36).contains(&radix),
+            "radix must be in the range 2 to 36, but got {}",
+            radix
+        );
+
+        let (negative, digits, offset) = match value.as_bytes().first() {
+            Some(b'-') => (true, &value[1..], 1),
+            Some(b'+') => (false, &value[1..], 1),
+            _ => (false, value, 0),
+        };
+
+        if digits.is_empty() {
+            return Err(ParseIntegerError::Empty);
+        }
+
+        let bits = integer_type.bits();
+        let mut words = vec![0u64; word_count(bits)];
+        for (byte_index, digit) in digits.char_indices() {
+            let digit_value = digit
+                .to_digit(radix)
+                .ok_or(ParseIntegerError::InvalidDigit {
+                    digit,
+                    byte_index: byte_index + offset,
+                })?;
+
+            let mut carry = u128::from(digit_value);
+            for word in words.iter_mut() {
+                let product = u128::from(*word) * u128::from(radix) + carry;
+                *word = product as u64;
+                carry = product >> 64;
+            }
+
+            if carry != 0 || words[words.len() - 1] & !most_significant_mask(bits) != 0 {
+                return Err(ParseIntegerError::Overflow(integer_type));
+            }
+        }
+
+        if negative {
+            negate_words(&mut words);
+        }
+
+        Ok(Self::from_extended_words(integer_type, &words, 0))
+    }
+
+    /// Interprets this integer value as a signed value, returning whether it is negative along with the words of its
+    /// absolute value.
+    pub(crate) fn signed_magnitude(&self) -> (bool, Vec<u64>) {
+        let bits = self.integer_type.bits();
+        let mut magnitude = self.words()[..word_count(bits)].to_vec();
+        let last = magnitude.len() - 1;
+        let negative = (magnitude[last] >> ((bits - 1) % 64)) & 1 == 1;
+        if negative {
+            negate_words(&mut magnitude);
+            magnitude[last] &= most_significant_mask(bits);
+        }
+        (negative, magnitude)
+    }
+
+    /// Creates an integer value from the specified words, filling any remaining words with the specified value and
+    /// truncating the most significant word to the width of the type.
+    fn from_extended_words(integer_type: types::IntegerSize, words: &[u64], fill: u64) -> Self {
+        let bits = integer_type.bits();
+        let mut value = if bits <= 128 {
+            IntegerValue::Inline([0; 2])
+        } else {
+            IntegerValue::Allocated(vec![0; word_count(bits)].into_boxed_slice())
+        };
+
+        let destination: &mut [u64] = match &mut value {
+            IntegerValue::Inline(inline) => inline,
+            IntegerValue::Allocated(allocated) => allocated,
+        };
+
+        let significant_words = word_count(bits);
+        for (index, word) in destination.iter_mut().take(significant_words).enumerate() {
+            *word = words.get(index).copied().unwrap_or(fill);
+        }
+
+        destination[significant_words - 1] &= most_significant_mask(bits);
+
+        Self {
+            integer_type,
+            value,
+        }
+    }
+
+    /// Gets the type of this integer value.
+    pub fn integer_type(&self) -> types::IntegerSize {
+        self.integer_type
+    }
+
+    /// Gets the bits of this integer value as 64-bit words, with the least significant word first.
+    pub fn words(&self) -> &[u64] {
+        match &self.value {
+            IntegerValue::Inline(words) => words,
+            IntegerValue::Allocated(words) => words,
+        }
+    }
+
+    /// Gets the bits of this integer value as a 128-bit value, or `None` if the type is wider than 128 bits.
+    pub(crate) fn to_u128(&self) -> Option<u128> {
+        if self.integer_type.bits() > 128 {
+            return None;
+        }
+
+        let words = self.words();
+        let low = u128::from(words.first().copied().unwrap_or_default());
+        let high = u128::from(words.get(1).copied().unwrap_or_default());
+        Some(low | (high << 64))
+    }
+}
+
+impl Display for Integer {
+    /// Prints the integer value in decimal, interpreting it as a signed value like LLVM does. Values of type `i1` are
+    /// printed as `true` or `false`.
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        let bits = self.integer_type.bits();
+        if bits == 1 {
+            return f.write_str(if self.words()[0] & 1 == 1 {
+                "true"
+            } else {
+                "false"
+            });
+        }
+
+        let (negative, mut magnitude) = self.signed_magnitude();
+
+        // Digits are computed in chunks, using the largest power of ten that fits in a word.
+        const CHUNK: u128 = 10_000_000_000_000_000_000;
+        let mut chunks = Vec::new();
+        while magnitude.iter().any(|word| *word != 0) {
+            let mut remainder = 0u128;
+            for word in magnitude.iter_mut().rev() {
+                let dividend = (remainder << 64) | u128::from(*word);
+                *word = (dividend / CHUNK) as u64;
+                remainder = dividend % CHUNK;
+            }
+            chunks.push(remainder as u64);
+        }
+
+        if negative {
+            f.write_char('-')?;
+        }
+
+        match chunks.split_last() {
+            Some((most_significant, rest)) => {
+                write!(f, "{}", most_significant)?;
+                for chunk in rest.iter().rev() {
+                    write!(f, "{:019}", chunk)?;
+                }
+                Ok(())
+            }
+            None => f.write_char('0'),
+        }
+    }
+}
+
+/// Computes the two's complement negation of an integer stored as 64-bit words, with the least significant word first.
+pub(crate) fn negate_words(words: &mut [u64]) {
+    let mut carry = true;
+    for word in words.iter_mut() {
+        let (negated, overflowed) = (!*word).overflowing_add(u64::from(carry));
+        *word = negated;
+        carry = overflowed;
+    }
+}
+
+/// Gets the number of 64-bit words needed to store an integer with the specified number of bits.
+pub(crate) fn word_count(bits: u32) -> usize {
+    usize::try_from(bits.div_ceil(64)).expect("word count should fit in usize")
+}
+
+/// Gets the mask of the bits in the most significant word that are used by an integer with the specified number of bits.
+fn most_significant_mask(bits: u32) -> u64 {
+    u64::MAX >> ((64 - bits % 64) % 64)
+}
+
+/// Gets the word used to extend an integer value.
+fn sign_fill(negative: bool) -> u64 {
+    if negative {
+        u64::MAX
+    } else {
+        0
+    }
+}
+
+/// Floating-point constant of a specified type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Float {
+    /// A 16-bit, IEEE-754 `binary16` value, stored as its bit pattern.
+    Half(u16),
+    /// A 32-bit, IEEE-754 `binary32` value.
+    Float(f32),
+    /// A 64-bit, IEEE-754 `binary64` value.
+    Double(f64),
+}
+
+impl Float {
+    /// Gets the type of this floating-point value.
+    pub fn float_type(&self) -> types::Float {
+        match self {
+            Self::Half(_) => types::Float::Half,
+            Self::Float(_) => types::Float::Float,
+            Self::Double(_) => types::Float::Double,
+        }
+    }
+}
+
+impl Display for Float {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        // Floating-point constants are printed in hexadecimal, since their decimal representations may not be exact.
+        match self {
+            Self::Half(bits) => write!(f, "0xH{:04X}", bits),
+            Self::Float(value) => write!(f, "0x{:016X}", f64::from(*value).to_bits()),
+            Self::Double(value) => write!(f, "0x{:016X}", value.to_bits()),
+        }
+    }
+}
+
+/// Constant structure or array containing values for each of its elements.
+#[derive(Clone, Debug)]
+pub struct Aggregate {
+    aggregate_type: types::Aggregate,
+    elements: Vec<Value>,
+}
+
+impl Aggregate {
+    /// Creates a constant structure or array.
+    ///
+    /// # Panics
+    /// Panics if the number or types of the elements do not match the aggregate type, or if any of the elements are not
+    /// constants.
+    pub fn new(aggregate_type: types::Aggregate, elements: Vec<Value>) -> Self {
+        let element_types: Box<dyn Iterator<Item = &Rc<types::FirstClass>>> = match &aggregate_type
+        {
+            types::Aggregate::Array(array) => {
+                if usize::try_from(array.count()).ok() != Some(elements.len()) {
+                    panic!(
+                        "expected {} elements for array constant, but got {}",
+                        array.count(),
+                        elements.len()
+                    );
+                }
+                Box::new(std::iter::repeat(array.element_type()))
+            }
+            types::Aggregate::Struct(structure) => {
+                if structure.member_types().len() != elements.len() {
+                    panic!(
+                        "expected {} members for structure constant, but got {}",
+                        structure.member_types().len(),
+                        elements.len()
+                    );
+                }
+                Box::new(structure.member_types().iter())
+            }
+        };
+
+        for (element, element_type) in elements.iter().zip(element_types) {
+            if !element.is_constant() {
+                panic!("expected constant element, but got {}", element);
+            } else if &element.value_type() != element_type {
+                panic!(
+                    "expected element of type {}, but got {}",
+                    element_type, element
+                );
+            }
+        }
+
+        Self {
+            aggregate_type,
+            elements,
+        }
+    }
+
+    /// Creates a constant array of bytes containing the specified string followed by a `null` terminator, printed as
+    /// `[N x i8] c"...\00"`.
+    pub fn c_string(contents: impl AsRef<[u8]>) -> Self {
+        let contents = contents.as_ref();
+        let length = u32::try_from(contents.len() + 1).expect("string is too long");
+        let elements = contents
+            .iter()
+            .chain(std::iter::once(&0))
+            .map(|byte| {
+                Value::Integer(Integer::from_u64(
+                    types::IntegerSize::SIZE_8,
+                    u64::from(*byte),
+                ))
+            })
+            .collect();
+
+        Self::new(
+            types::Aggregate::Array(types::Array::new(
+                Rc::new(types::FirstClass::Single(types::SingleValue::Integer(
+                    types::IntegerSize::SIZE_8,
+                ))),
+                length,
+            )),
+            elements,
+        )
+    }
+
+    /// Gets the bytes of this constant if it is an array of `i8` values, which is printed as a string.
+    fn string_contents(&self) -> Option<Vec<u8>> {
+        match self.aggregate_type() {
+            types::Aggregate::Array(array)
+                if **array.element_type()
+                    == types::FirstClass::Single(types::SingleValue::Integer(
+                        types::IntegerSize::SIZE_8,
+                    )) =>
+            {
+                self.elements()
+                    .iter()
+                    .map(|element| match element {
+                        Value::Integer(byte) => Some(byte.words()[0] as u8),
+                        _ => None,
+                    })
+                    .collect()
+            }
+            _ => None,
+        }
+    }
+
+    /// Gets the type of this constant.
+    pub fn aggregate_type(&self) -> &types::Aggregate {
+        &self.aggregate_type
+    }
+
+    /// Gets the values of the elements of this constant.
+    pub fn elements(&self) -> &[Value] {
+        &self.elements
+    }
+}
+
+/// Constant vector containing values for each of its elements.
+#[derive(Clone, Debug)]
+pub struct Vector {
+    vector_type: types::Vector,
+    elements: Vec<Value>,
+}
+
+impl Vector {
+    /// Creates a constant vector.
+    ///
+    /// # Panics
+    /// Panics if the number or types of the elements do not match the vector type, or if any of the elements are not
+    /// constants.
+    pub fn new(vector_type: types::Vector, elements: Vec<Value>) -> Self {
+        if usize::try_from(vector_type.count()).ok() != Some(elements.len()) {
+            panic!(
+                "expected {} elements for vector constant, but got {}",
+                vector_type.count(),
+                elements.len()
+            );
+        }
+
+        for element in elements.iter() {
+            if !element.is_constant() {
+                panic!("expected constant element, but got {}", element);
+            } else if &element.value_type() != vector_type.element_type() {
+                panic!(
+                    "expected element of type {}, but got {}",
+                    vector_type.element_type(),
+                    element
+                );
+            }
+        }
+
+        Self {
+            vector_type,
+            elements,
+        }
+    }
+
+    /// Creates a constant vector where every element has the same value.
+    ///
+    /// # Panics
+    /// Panics if the type of the element does not match the vector type, or if the element is not a constant.
+    pub fn splat(vector_type: types::Vector, element: Value) -> Self {
+        let count = usize::try_from(vector_type.count()).expect("vector is too large");
+        Self::new(vector_type, vec![element; count])
+    }
+
+    /// Gets the type of this constant.
+    pub fn vector_type(&self) -> &types::Vector {
+        &self.vector_type
+    }
+
+    /// Gets the values of the elements of this constant.
+    pub fn elements(&self) -> &[Value] {
+        &self.elements
+    }
+}
+
+struct RegisterInformation {
+    value_type: Rc<types::FirstClass>,
+    name: RefCell<Option<Identifier>>,
+    /// The block containing the instruction that defines this register.
+    block: RefCell<Weak<BasicBlock>>,
+    /// The function and index of the parameter contained in this register, or `None` if it contains the result of an
+    /// instruction.
+    parameter: Option<(Weak<global::Function>, usize)>,
+}
+
+/// A local register containing the result of an instruction, or the value of a function parameter.
+///
+/// Registers are compared by reference, so two registers are only equal if they refer to the same instruction result or
+/// parameter.
+#[derive(Clone)]
+pub struct Register(Rc<RegisterInformation>);
+
+impl Register {
+    pub(crate) fn new(value_type: Rc<types::FirstClass>) -> Self {
+        Self(Rc::new(RegisterInformation {
+            value_type,
+            name: RefCell::default(),
+            block: RefCell::default(),
+            parameter: None,
+        }))
+    }
+
+    /// Creates a register containing the value of the parameter at the specified index.
+    pub(crate) fn parameter(
+        value_type: Rc<types::FirstClass>,
+        function: Weak<global::Function>,
+        index: usize,
+    ) -> Self {
+        Self(Rc::new(RegisterInformation {
+            value_type,
+            name: RefCell::default(),
+            block: RefCell::default(),
+            parameter: Some((function, index)),
+        }))
+    }
+
+    /// Gets the name of this register, or `None` if it is referred to by a number.
+    pub fn name(&self) -> Option<Identifier> {
+        self.0.name.borrow().clone()
+    }
+
+    /// Sets the name of this register, so that it is printed as `%name` instead of a number. If another register or block
+    /// in the same function has the same name, a suffix is added when the function is printed to make the name unique.
+    pub fn set_name(&self, name: Option<Identifier>) {
+        *self.0.name.borrow_mut() = name;
+    }
+
+    /// Gets the type of the value stored in this register.
+    pub fn value_type(&self) -> &Rc<types::FirstClass> {
+        &self.0.value_type
+    }
+
+    /// Gets the index of the function parameter contained in this register, or `None` if this register contains the
+    /// result of an instruction.
+    pub fn parameter_index(&self) -> Option<usize> {
+        self.0.parameter.as_ref().map(|(_, index)| *index)
+    }
+
+    /// Gets the block containing the instruction that defines this register, along with the index of that instruction, or
+    /// `None` if this register contains a parameter, or if the instruction was removed or the block was dropped.
+    pub fn definition(&self) -> Option<(Rc<BasicBlock>, usize)> {
+        let block = self.0.block.borrow().upgrade()?;
+        let index = block
+            .instructions()
+            .iter()
+            .position(|instruction| instruction.result() == Some(self))?;
+        Some((block, index))
+    }
+
+    /// Records the block containing the instruction that defines this register.
+    pub(crate) fn set_block(&self, block: Weak<BasicBlock>) {
+        *self.0.block.borrow_mut() = block;
+    }
+
+    /// Gets the function containing the instruction that defines this register if it was appended to one, or the function
+    /// whose parameter is contained in this register.
+    pub(crate) fn function(&self) -> Option<Rc<global::Function>> {
+        match &self.0.parameter {
+            Some((function, _)) => function.upgrade(),
+            None => self.0.block.borrow().upgrade()?.function(),
+        }
+    }
+}
+
+impl PartialEq for Register {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Register {}
+
+impl Hash for Register {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rc::as_ptr(&self.0).hash(state)
+    }
+}
+
+impl Debug for Register {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_tuple("Register")
+            .field(&format_args!("{}", self))
+            .field(self.value_type())
+            .finish()
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match crate::numbering::register_label(self) {
+            Some(label) => Display::fmt(&label, f),
+            None => match self.0.name.borrow().as_ref() {
+                Some(name) => write!(f, "%{}", name),
+                None => write!(f, "%R{:X}", Rc::as_ptr(&self.0) as usize),
+            },
+        }
+    }
+}
+
+/// The address of a basic block in a function (`blockaddress`), which can be used as the address operand of an
+/// `indirectbr` instruction to implement computed gotos.
+#[derive(Clone, Debug)]
+pub struct BlockAddress {
+    /// The function is weakly referenced, since the address of a block may be used within the function itself.
+    function: Weak<global::Function>,
+    function_name: Symbol,
+    block: BlockReference,
+}
+
+impl BlockAddress {
+    /// Creates a constant containing the address of a basic block in a function.
+    ///
+    /// # Panics
+    /// Panics if the block was not appended to the function, or if it is the entry block, whose address cannot be taken.
+    pub fn new(function: &Rc<global::Function>, block: &Rc<BasicBlock>) -> Self {
+        match function
+            .basic_blocks()
+            .iter()
+            .position(|existing| Rc::ptr_eq(existing, block))
+        {
+            Some(0) => panic!(
+                "cannot take the address of the entry block of @{}",
+                function.name()
+            ),
+            Some(_) => (),
+            None => panic!(
+                "cannot take the address of a block that is not in @{}",
+                function.name()
+            ),
+        }
+
+        Self {
+            function: Rc::downgrade(function),
+            function_name: function.symbol().clone(),
+            block: BlockReference::new(block),
+        }
+    }
+
+    /// Gets the function containing the block, or `None` if it was dropped.
+    pub fn function(&self) -> Option<Rc<global::Function>> {
+        self.function.upgrade()
+    }
+
+    /// Gets the name of the function containing the block.
+    pub fn function_name(&self) -> &Symbol {
+        &self.function_name
+    }
+
+    /// Gets the block whose address is taken.
+    pub fn block(&self) -> &BlockReference {
+        &self.block
+    }
+
+    /// Gets a pointer to the function, used to identify it.
+    #[cfg(feature = "llvm_sys_interop")]
+    pub(crate) fn function_ptr(&self) -> *const global::Function {
+        self.function.as_ptr()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum GlobalReference {
+    Function {
+        function: Weak<global::Function>,
+        signature: Rc<types::Function>,
+    },
+    Variable {
+        variable: Weak<global::Variable>,
+        value_type: Rc<types::FirstClass>,
+        constant: bool,
+    },
+    IFunc {
+        ifunc: Weak<global::IFunc>,
+        signature: Rc<types::Function>,
+    },
+}
+
+/// The address of a function, global variable, or indirect function, which can be stored in memory, passed as an argument, or used in the
+/// initializer of a global variable.
+#[derive(Clone, Debug)]
+pub struct GlobalAddress {
+    /// The global value is weakly referenced, since functions may refer to themselves and the initializers of global
+    /// variables may refer to the variable that they initialize.
+    global: GlobalReference,
+    symbol: Symbol,
+}
+
+impl GlobalAddress {
+    /// Creates a constant containing the address of a function.
+    pub fn function(function: &Rc<global::Function>) -> Self {
+        Self {
+            global: GlobalReference::Function {
+                function: Rc::downgrade(function),
+                signature: function.signature().clone(),
+            },
+            symbol: function.symbol().clone(),
+        }
+    }
+
+    /// Creates a constant containing the address of a global variable.
+    pub fn variable(variable: &Rc<global::Variable>) -> Self {
+        Self {
+            global: GlobalReference::Variable {
+                variable: Rc::downgrade(variable),
+                value_type: variable.value_type().clone(),
+                constant: variable.is_constant(),
+            },
+            symbol: variable.symbol().clone(),
+        }
+    }
+
+    /// Creates a constant containing the address of an indirect function, which can be called like a function pointer.
+    pub fn ifunc(ifunc: &Rc<global::IFunc>) -> Self {
+        Self {
+            global: GlobalReference::IFunc {
+                ifunc: Rc::downgrade(ifunc),
+                signature: ifunc.signature().clone(),
+            },
+            symbol: ifunc.symbol().clone(),
+        }
+    }
+
+    /// Creates a constant containing the address of a function, global variable, or indirect function.
+    pub fn new(global: &global::Value) -> Self {
+        match global {
+            global::Value::Function(function) => Self::function(function),
+            global::Value::Variable(variable) => Self::variable(variable),
+            global::Value::IFunc(ifunc) => Self::ifunc(ifunc),
+        }
+    }
+
+    /// Gets the function, global variable, or indirect function whose address is taken, or `None` if it was dropped.
+    pub fn global(&self) -> Option<global::Value> {
+        match &self.global {
+            GlobalReference::Function { function, .. } => {
+                function.upgrade().map(global::Value::Function)
+            }
+            GlobalReference::Variable { variable, .. } => {
+                variable.upgrade().map(global::Value::Variable)
+            }
+            GlobalReference::IFunc { ifunc, .. } => ifunc.upgrade().map(global::Value::IFunc),
+        }
+    }
+
+    /// Gets the name of the function, global variable, or indirect function.
+    pub fn symbol(&self) -> &Symbol {
+        &self.symbol
+    }
+
+    /// Gets the signature of the function or indirect function whose address is taken, or `None` if this is the address
+    /// of a global variable.
+    pub fn function_signature(&self) -> Option<&Rc<types::Function>> {
+        match &self.global {
+            GlobalReference::Function { signature, .. }
+            | GlobalReference::IFunc { signature, .. } => Some(signature),
+            GlobalReference::Variable { .. } => None,
+        }
+    }
+
+    /// Gets the type of the global variable whose address is taken and whether it is constant, or `None` if this is the
+    /// address of a function.
+    pub fn variable_type(&self) -> Option<(&Rc<types::FirstClass>, bool)> {
+        match &self.global {
+            GlobalReference::Function { .. } | GlobalReference::IFunc { .. } => None,
+            GlobalReference::Variable {
+                value_type,
+                constant,
+                ..
+            } => Some((value_type, *constant)),
+        }
+    }
+
+    /// Returns `true` if this is the address of an indirect function.
+    pub fn is_ifunc(&self) -> bool {
+        matches!(self.global, GlobalReference::IFunc { .. })
+    }
+
+    /// Gets a pointer to the global value, used to identify it.
+    pub(crate) fn global_ptr(&self) -> *const () {
+        match &self.global {
+            GlobalReference::Function { function, .. } => function.as_ptr().cast(),
+            GlobalReference::Variable { variable, .. } => variable.as_ptr().cast(),
+            GlobalReference::IFunc { ifunc, .. } => ifunc.as_ptr().cast(),
+        }
+    }
+
+    /// Gets a pointer to the function, or `None` if this is not the address of a function.
+    #[cfg(feature = "llvm_sys_interop")]
+    pub(crate) fn function_ptr(&self) -> Option<*const global::Function> {
+        match &self.global {
+            GlobalReference::Function { function, .. } => Some(function.as_ptr()),
+            _ => None,
+        }
+    }
+
+    /// Gets a pointer to the global variable, or `None` if this is not the address of a global variable.
+    #[cfg(feature = "llvm_sys_interop")]
+    pub(crate) fn variable_ptr(&self) -> Option<*const global::Variable> {
+        match &self.global {
+            GlobalReference::Variable { variable, .. } => Some(variable.as_ptr()),
+            _ => None,
+        }
+    }
+
+    /// Gets a pointer to the indirect function, or `None` if this is not the address of an indirect function.
+    #[cfg(feature = "llvm_sys_interop")]
+    pub(crate) fn ifunc_ptr(&self) -> Option<*const global::IFunc> {
+        match &self.global {
+            GlobalReference::IFunc { ifunc, .. } => Some(ifunc.as_ptr()),
+            _ => None,
+        }
+    }
+
+    /// Gets the type of the address.
+    pub fn pointer_type(&self) -> types::Pointer {
+        match &self.global {
+            GlobalReference::Function { signature, .. }
+            | GlobalReference::IFunc { signature, .. } => types::Pointer::new(signature.clone()),
+            GlobalReference::Variable { value_type, .. } => types::Pointer::new(value_type.clone()),
+        }
+    }
+}
+
+/// The syntax used by an inline assembly string.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AsmDialect {
+    /// AT&T syntax.
+    ATT,
+    /// Intel syntax (`inteldialect`).
+    Intel,
+}
+
+crate::enum_default!(AsmDialect, ATT);
+
+/// An inline assembly expression (`asm`), which can be used as the callee of a `call` or `callbr` instruction.
+///
+/// [See the LLVM documentation on inline assembler expressions](https://llvm.org/docs/LangRef.html#inline-assembler-expressions).
+#[derive(Clone, Debug)]
+pub struct InlineAsm {
+    signature: Rc<types::Function>,
+    assembly: Identifier,
+    constraints: Identifier,
+    side_effects: bool,
+    align_stack: bool,
+    dialect: AsmDialect,
+}
+
+impl InlineAsm {
+    /// Creates an inline assembly expression with the specified signature, assembly string, and constraint string, such
+    /// as `"=r,r,~{dirflag}"`.
+    pub fn new(
+        signature: Rc<types::Function>,
+        assembly: Identifier,
+        constraints: Identifier,
+    ) -> Self {
+        Self {
+            signature,
+            assembly,
+            constraints,
+            side_effects: false,
+            align_stack: false,
+            dialect: AsmDialect::default(),
+        }
+    }
+
+    /// Indicates that the assembly has side effects that are not visible in its constraints (`sideeffect`), preventing it
+    /// from being removed or reordered.
+    pub fn with_side_effects(mut self) -> Self {
+        self.side_effects = true;
+        self
+    }
+
+    /// Indicates that the assembly requires the stack to be aligned before it is executed (`alignstack`).
+    pub fn with_align_stack(mut self) -> Self {
+        self.align_stack = true;
+        self
+    }
+
+    /// Sets the syntax used by the assembly string.
+    pub fn with_dialect(mut self, dialect: AsmDialect) -> Self {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Gets the signature of the inline assembly, describing the types of its inputs and outputs.
+    pub fn signature(&self) -> &Rc<types::Function> {
+        &self.signature
+    }
+
+    /// Gets the assembly string.
+    pub fn assembly(&self) -> &Identifier {
+        &self.assembly
+    }
+
+    /// Gets the constraint string.
+    pub fn constraints(&self) -> &Identifier {
+        &self.constraints
+    }
+
+    /// Returns `true` if the assembly has side effects.
+    pub fn has_side_effects(&self) -> bool {
+        self.side_effects
+    }
+
+    /// Returns `true` if the stack must be aligned before the assembly is executed.
+    pub fn is_align_stack(&self) -> bool {
+        self.align_stack
+    }
+
+    /// Gets the syntax used by the assembly string.
+    pub fn dialect(&self) -> AsmDialect {
+        self.dialect
+    }
+}
+
+impl Display for InlineAsm {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str("asm ")?;
+        if self.side_effects {
+            f.write_str("sideeffect ")?;
+        }
+        if self.align_stack {
+            f.write_str("alignstack ")?;
+        }
+        if self.dialect == AsmDialect::Intel {
+            f.write_str("inteldialect ")?;
+        }
+        write!(
+            f,
+            "{}, {}",
+            Quoted(self.assembly.as_id()),
+            Quoted(self.constraints.as_id())
+        )
+    }
+}
+
+/// A value.
+#[derive(Clone, Debug)]
+pub enum Value {
+    /// An integer value.
+    Integer(Integer),
+    /// A floating-point value.
+    Float(Float),
+    /// A `null` pointer of the specified type.
+    Null(types::Pointer),
+    /// A constant structure or array.
+    Aggregate(Aggregate),
+    /// A constant vector.
+    Vector(Vector),
+    /// The address of a basic block.
+    BlockAddress(BlockAddress),
+    /// The address of a function, global variable, or indirect function.
+    GlobalAddress(GlobalAddress),
+    /// The value contained in a local register.
+    Register(Register),
+}
+
+impl Value {
+    /// Gets the type of this value.
+    pub fn value_type(&self) -> Rc<types::FirstClass> {
+        match self {
+            Self::Integer(integer) => Rc::new(types::FirstClass::Single(
+                types::SingleValue::Integer(integer.integer_type()),
+            )),
+            Self::Float(float) => Rc::new(types::FirstClass::Single(types::SingleValue::Float(
+                float.float_type(),
+            ))),
+            Self::Null(pointer_type) => Rc::new(types::FirstClass::Single(
+                types::SingleValue::Pointer(pointer_type.clone()),
+            )),
+            Self::Aggregate(aggregate) => Rc::new(types::FirstClass::Aggregate(
+                aggregate.aggregate_type().clone(),
+            )),
+            Self::Vector(vector) => Rc::new(types::FirstClass::Single(types::SingleValue::Vector(
+                vector.vector_type().clone(),
+            ))),
+            // Block addresses are always `i8*`, regardless of the function that contains the block.
+            Self::BlockAddress(_) => {
+                Rc::new(types::FirstClass::Single(types::SingleValue::Pointer(
+                    types::Pointer::new(Rc::new(types::FirstClass::Single(
+                        types::SingleValue::Integer(types::IntegerSize::SIZE_8),
+                    ))),
+                )))
+            }
+            Self::GlobalAddress(address) => Rc::new(types::FirstClass::Single(
+                types::SingleValue::Pointer(address.pointer_type()),
+            )),
+            Self::Register(register) => register.value_type().clone(),
+        }
+    }
+
+    /// Returns `true` if this value is a constant, and `false` if it is the result of an instruction.
+    pub fn is_constant(&self) -> bool {
+        !matches!(self, Self::Register(_))
+    }
+
+    /// Returns an object used to print this value without its type, such as when it is used as an operand of an
+    /// instruction.
+    pub(crate) fn untyped(&self) -> Untyped<'_> {
+        Untyped(self)
+    }
+}
+
+/// Prints a value without its type.
+pub(crate) struct Untyped<'a>(&'a Value);
+
+impl Display for Untyped<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self.0 {
+            Value::Integer(integer) => Display::fmt(integer, f),
+            Value::Float(float) => Display::fmt(float, f),
+            Value::Null(_) => f.write_str("null"),
+            Value::Aggregate(aggregate) => {
+                if let Some(contents) = aggregate.string_contents() {
+                    // Byte arrays are printed as strings, escaping any characters that are not printable.
+                    f.write_str("c\"")?;
+                    for byte in contents {
+                        if byte == b' ' || byte.is_ascii_graphic() && byte != b'"' && byte != b'\\'
+                        {
+                            f.write_char(char::from(byte))?;
+                        } else {
+                            write!(f, "\\{:02X}", byte)?;
+                        }
+                    }
+                    return f.write_char('"');
+                }
+
+                let (start, end) = match aggregate.aggregate_type() {
+                    types::Aggregate::Array(_) => ("[", "]"),
+                    types::Aggregate::Struct(structure) if structure.is_packed() => ("<{ ", " }>"),
+                    types::Aggregate::Struct(_) => ("{ ", " }"),
+                };
+
+                f.write_str(start)?;
+                for (index, element) in aggregate.elements().iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Display::fmt(element, f)?;
+                }
+                f.write_str(end)
+            }
+            Value::Vector(vector) => {
+                f.write_char('<')?;
+                for (index, element) in vector.elements().iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    Display::fmt(element, f)?;
+                }
+                f.write_char('>')
+            }
+            Value::BlockAddress(address) => {
+                write!(f, "blockaddress(@{}, ", address.function_name())?;
+                match (address.function(), address.block().block()) {
+                    // Unnamed blocks are numbered within their own function, which is not the one being printed when
+                    // the address is used in a global initializer or in another function.
+                    (Some(function), Some(block))
+                        if block.name().is_none()
+                            && crate::numbering::block_number(Rc::as_ptr(&block)).is_none() =>
+                    {
+                        crate::numbering::Numbering::new(&function)
+                            .scope(|| Display::fmt(address.block(), f))?
+                    }
+                    _ => Display::fmt(address.block(), f)?,
+                }
+                f.write_char(')')
+            }
+            Value::GlobalAddress(address) => write!(f, "@{}", address.symbol()),
+            Value::Register(register) => Display::fmt(register, f),
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "{} {}", self.value_type(), self.untyped())
+    }
+}
+
+crate::enum_case_from!(Value, Integer, Integer);
+crate::enum_case_from!(Value, Float, Float);
+crate::enum_case_from!(Value, Null, types::Pointer);
+crate::enum_case_from!(Value, Aggregate, Aggregate);
+crate::enum_case_from!(Value, Vector, Vector);
+crate::enum_case_from!(Value, BlockAddress, BlockAddress);
+crate::enum_case_from!(Value, GlobalAddress, GlobalAddress);
+crate::enum_case_from!(Value, Register, Register);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a function `@f` containing an unnamed entry block that branches to another unnamed block, returning the
+    /// address of the second block.
+    fn address_of_unnamed_block() -> (Rc<global::Function>, Value) {
+        let function = global::Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::Void, Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        let target = BasicBlock::new();
+        entry.br(&target);
+        target.ret(None);
+        function.append_basic_block(entry);
+        function.append_basic_block(target.clone());
+        let address = Value::BlockAddress(BlockAddress::new(&function, &target));
+        (function, address)
+    }
+
+    #[test]
+    fn block_address_in_global_initializer_uses_number_of_block() {
+        let (function, address) = address_of_unnamed_block();
+        let table = global::Variable::new(crate::id!("table"), address.value_type(), true);
+        table.set_initializer(Some(address));
+
+        assert!(function.to_string().contains("\n1:\n"));
+        assert!(table.to_string().contains("blockaddress(@f, %1)"));
+    }
+
+    #[test]
+    fn block_address_in_other_function_uses_number_of_block() {
+        let (_function, address) = address_of_unnamed_block();
+        let other = global::Function::new(
+            crate::id!("g"),
+            types::Function::new(types::Return::FirstClass(address.value_type()), Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        entry.ret(Some(address));
+        other.append_basic_block(entry);
+
+        assert!(other.to_string().contains("ret i8* blockaddress(@f, %1)"));
+    }
+}
@@ -0,0 +1,688 @@
+//! Checks that the contents of a module are valid before it is lowered, allowing errors to be reported without relying on
+//! the LLVM verifier.
+//!
+//! Although instruction building methods check their operands, instructions that are inserted or replaced later, or
+//! registers used in the wrong block, can still result in invalid IR.
+
+use crate::analysis::{ControlFlowGraph, DominatorTree, UseDefChains};
+use crate::block::{Callee, Instruction, TailCallKind};
+use crate::global::{self, BlockError, Linkage, Visibility};
+use crate::identifier::{Id, Identifier};
+use crate::module::Module;
+use crate::numbering::Numbering;
+use crate::types;
+use crate::value::{Register, Value};
+use std::rc::Rc;
+
+/// Describes a problem with the contents of a module.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Diagnostic {
+    /// A basic block that does not end with a single terminator instruction.
+    #[error("in function @{function}, {error}")]
+    MalformedBlock {
+        /// The name of the function containing the block.
+        function: Identifier,
+        /// Describes the problem with the block.
+        error: BlockError,
+    },
+    /// An instruction uses a register that is not defined in the function, or whose definition does not dominate the use.
+    #[error("in function @{function}, instruction {index} of block %{block} uses {register}, which is not defined before it")]
+    UseBeforeDefinition {
+        /// The name of the function containing the instruction.
+        function: Identifier,
+        /// The label of the block containing the instruction, or its number if it is unnamed.
+        block: Identifier,
+        /// The index of the instruction within the block.
+        index: usize,
+        /// The register that is used.
+        register: String,
+    },
+    /// An instruction whose operands or result are not of the expected types.
+    #[error("in function @{function}, instruction {index} of block %{block} has mismatched types: {message}")]
+    TypeMismatch {
+        /// The name of the function containing the instruction.
+        function: Identifier,
+        /// The label of the block containing the instruction, or its number if it is unnamed.
+        block: Identifier,
+        /// The index of the instruction within the block.
+        index: usize,
+        /// Describes the expected and actual types.
+        message: String,
+    },
+    /// A `phi` instruction whose incoming blocks do not match the edges from the predecessors of the block containing it.
+    #[error("in function @{function}, phi instruction {index} of block %{block} does not have exactly one incoming value for each edge from a predecessor")]
+    PhiPredecessorMismatch {
+        /// The name of the function containing the instruction.
+        function: Identifier,
+        /// The label of the block containing the instruction, or its number if it is unnamed.
+        block: Identifier,
+        /// The index of the instruction within the block.
+        index: usize,
+    },
+    /// A `musttail` call that cannot be guaranteed to be optimized into a tail call.
+    #[error("in function @{function}, musttail call instruction {index} of block %{block} is invalid, since {reason}")]
+    InvalidMustTail {
+        /// The name of the function containing the instruction.
+        function: Identifier,
+        /// The label of the block containing the instruction, or its number if it is unnamed.
+        block: Identifier,
+        /// The index of the instruction within the block.
+        index: usize,
+        /// Explains why the call cannot be a tail call.
+        reason: &'static str,
+    },
+    /// A global variable or function with a linkage type that is not allowed.
+    #[error("@{global} cannot have {linkage} linkage, since {reason}")]
+    InvalidLinkage {
+        /// The name of the global variable or function.
+        global: Identifier,
+        /// The linkage of the global.
+        linkage: Linkage,
+        /// Explains why the linkage is not allowed.
+        reason: &'static str,
+    },
+    /// A global variable or function with local linkage that does not have the default visibility.
+    #[error("@{global} cannot have {visibility} visibility, since it has {linkage} linkage")]
+    InvalidVisibility {
+        /// The name of the global variable or function.
+        global: Identifier,
+        /// The visibility of the global.
+        visibility: Visibility,
+        /// The linkage of the global.
+        linkage: Linkage,
+    },
+    /// An indirect function whose resolver is only a function declaration.
+    #[error("resolver @{resolver} of indirect function @{ifunc} must be a function definition")]
+    UndefinedResolver {
+        /// The name of the indirect function.
+        ifunc: Identifier,
+        /// The name of the resolver function.
+        resolver: Identifier,
+    },
+}
+
+/// Checks that globals with local linkage have the default visibility.
+fn check_visibility(
+    global: &Id,
+    linkage: Linkage,
+    visibility: Visibility,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if matches!(linkage, Linkage::Private | Linkage::Internal) && visibility != Visibility::Default
+    {
+        diagnostics.push(Diagnostic::InvalidVisibility {
+            global: global.to_owned(),
+            visibility,
+            linkage,
+        });
+    }
+}
+
+fn check_function_linkage(function: &global::Function, diagnostics: &mut Vec<Diagnostic>) {
+    let linkage = function.get_linkage();
+    let reason = if function.basic_blocks().is_empty() {
+        match linkage {
+            Linkage::External | Linkage::ExternWeak => None,
+            _ => Some("function declarations can only have external or extern_weak linkage"),
+        }
+    } else {
+        match linkage {
+            Linkage::ExternWeak => Some("extern_weak linkage is only allowed on declarations"),
+            _ => None,
+        }
+    };
+
+    if let Some(reason) = reason {
+        diagnostics.push(Diagnostic::InvalidLinkage {
+            global: function.name().to_owned(),
+            linkage,
+            reason,
+        });
+    }
+}
+
+fn check_variable_linkage(variable: &global::Variable, diagnostics: &mut Vec<Diagnostic>) {
+    let linkage = variable.get_linkage();
+    let reason = if variable.get_initializer().is_none() {
+        match linkage {
+            Linkage::External | Linkage::ExternWeak => None,
+            _ => Some("global variable declarations can only have external or extern_weak linkage"),
+        }
+    } else {
+        match linkage {
+            Linkage::ExternWeak => Some("extern_weak linkage is only allowed on declarations"),
+            _ => None,
+        }
+    };
+
+    if let Some(reason) = reason {
+        diagnostics.push(Diagnostic::InvalidLinkage {
+            global: variable.name().to_owned(),
+            linkage,
+            reason,
+        });
+    }
+}
+
+fn value_type_mismatch(expected: &types::FirstClass, actual: &Value) -> Option<String> {
+    if actual.value_type().as_ref() == expected {
+        None
+    } else {
+        Some(format!(
+            "expected a value of type {}, but got {}",
+            expected, actual
+        ))
+    }
+}
+
+fn pointee_type(pointer: &Value) -> Option<Rc<types::FirstClass>> {
+    match pointer.value_type().as_ref() {
+        types::FirstClass::Single(types::SingleValue::Pointer(pointer_type)) => {
+            pointer_type.pointee_type().cloned()
+        }
+        _ => None,
+    }
+}
+
+/// Checks that the operands and result of an instruction have the expected types, returning a message describing the first
+/// mismatch.
+fn check_instruction_types(
+    function: &global::Function,
+    instruction: &Instruction,
+) -> Option<String> {
+    match instruction {
+        Instruction::Ret(value) => match (function.signature().return_type(), value) {
+            (types::Return::Void, None) => None,
+            (types::Return::Void, Some(value)) => {
+                Some(format!("expected ret void, but got {}", value))
+            }
+            (types::Return::FirstClass(return_type), None) => {
+                Some(format!("expected a return value of type {}", return_type))
+            }
+            (types::Return::FirstClass(return_type), Some(value)) => {
+                value_type_mismatch(return_type, value)
+            }
+        },
+        Instruction::CondBr { condition, .. } => value_type_mismatch(
+            &types::FirstClass::Single(types::SingleValue::Integer(types::IntegerSize::MIN)),
+            condition,
+        ),
+        Instruction::Switch { value, cases, .. } => {
+            let value_type = value.value_type();
+            cases
+                .iter()
+                .find_map(|(case_value, _)| value_type_mismatch(&value_type, case_value))
+        }
+        Instruction::IndirectBr { address, .. } => match pointee_type(address) {
+            Some(_) => None,
+            None => Some(format!("expected a pointer, but got {}", address)),
+        },
+        Instruction::Phi { incoming, result } => incoming
+            .iter()
+            .find_map(|(value, _)| value_type_mismatch(result.value_type(), value)),
+        Instruction::Call {
+            callee,
+            signature,
+            arguments,
+            result,
+            ..
+        }
+        | Instruction::CallBr {
+            callee,
+            signature,
+            arguments,
+            result,
+            ..
+        } => {
+            if let Callee::Pointer(pointer) = callee {
+                if pointee_type(pointer).is_none() {
+                    return Some(format!("expected a pointer callee, but got {}", pointer));
+                }
+            }
+
+            if signature.is_variadic() {
+                if arguments.len() < signature.parameter_types().len() {
+                    return Some(format!(
+                        "expected at least {} arguments, but got {}",
+                        signature.parameter_types().len(),
+                        arguments.len()
+                    ));
+                }
+            } else if signature.parameter_types().len() != arguments.len() {
+                return Some(format!(
+                    "expected {} arguments, but got {}",
+                    signature.parameter_types().len(),
+                    arguments.len()
+                ));
+            }
+
+            signature
+                .parameter_types()
+                .iter()
+                .zip(arguments.iter())
+                .find_map(|(parameter_type, argument)| {
+                    value_type_mismatch(parameter_type, argument)
+                })
+                .or_else(|| match (signature.return_type(), result) {
+                    (types::Return::FirstClass(return_type), Some(result))
+                        if return_type != result.value_type() =>
+                    {
+                        Some(format!(
+                            "expected result of type {}, but got {}",
+                            return_type,
+                            result.value_type()
+                        ))
+                    }
+                    (types::Return::Void, Some(_)) | (types::Return::FirstClass(_), None) => Some(
+                        String::from("result does not match the return type of the callee"),
+                    ),
+                    _ => None,
+                })
+        }
+        Instruction::IntegerBinary { x, y, result, .. }
+        | Instruction::FloatBinary { x, y, result, .. } => {
+            value_type_mismatch(result.value_type(), x)
+                .or_else(|| value_type_mismatch(result.value_type(), y))
+        }
+        Instruction::FNeg {
+            operand, result, ..
+        } => value_type_mismatch(result.value_type(), operand),
+        Instruction::ICmp { x, y, .. } | Instruction::FCmp { x, y, .. } => {
+            value_type_mismatch(&x.value_type(), y)
+        }
+        Instruction::VaArg { list, .. } => match pointee_type(list) {
+            Some(_) => None,
+            None => Some(format!("expected a pointer, but got {}", list)),
+        },
+        Instruction::Load {
+            pointer, result, ..
+        } => match pointee_type(pointer) {
+            Some(pointee_type) if &pointee_type == result.value_type() => None,
+            _ => Some(format!(
+                "expected a pointer to {}, but got {}",
+                result.value_type(),
+                pointer
+            )),
+        },
+        Instruction::Store { value, pointer, .. }
+        | Instruction::AtomicRmw { value, pointer, .. } => match pointee_type(pointer) {
+            Some(pointee_type) => value_type_mismatch(&pointee_type, value),
+            None => Some(format!("expected a pointer, but got {}", pointer)),
+        },
+        Instruction::GetElementPtr {
+            element_type,
+            pointer,
+            ..
+        } => match pointee_type(pointer) {
+            Some(pointee_type) if &pointee_type == element_type => None,
+            _ => Some(format!(
+                "expected a pointer to {}, but got {}",
+                element_type, pointer
+            )),
+        },
+        Instruction::CmpXchg {
+            pointer,
+            expected,
+            replacement,
+            ..
+        } => match pointee_type(pointer) {
+            Some(pointee_type) => value_type_mismatch(&pointee_type, expected)
+                .or_else(|| value_type_mismatch(&pointee_type, replacement)),
+            None => Some(format!("expected a pointer, but got {}", pointer)),
+        },
+        _ => None,
+    }
+}
+
+/// Checks that a `musttail` call has the same signature and calling convention as the function containing it, and that
+/// it is immediately followed by a `ret` instruction returning the result of the call.
+fn check_must_tail(
+    function: &global::Function,
+    instruction: &Instruction,
+    next: Option<&Instruction>,
+) -> Option<&'static str> {
+    let (calling_convention, signature, result) = match instruction {
+        Instruction::Call {
+            tail_call: TailCallKind::MustTail,
+            calling_convention,
+            signature,
+            result,
+            ..
+        } => (calling_convention, signature, result),
+        _ => return None,
+    };
+
+    if signature.as_ref() != function.signature().as_ref() {
+        return Some("the signature of the callee does not match the signature of the caller");
+    }
+
+    if *calling_convention != function.get_calling_convention() {
+        return Some("the calling convention of the call does not match the calling convention of the caller");
+    }
+
+    match (next, result) {
+        (Some(Instruction::Ret(None)), None) => None,
+        (Some(Instruction::Ret(Some(Value::Register(returned)))), Some(result))
+            if returned == result =>
+        {
+            None
+        }
+        _ => Some(
+            "it is not immediately followed by a ret instruction returning the result of the call",
+        ),
+    }
+}
+
+/// Checks a function's blocks and instructions, returning all of the problems that were found.
+pub fn verify_function(function: &global::Function) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    let function_name = function.name().to_owned();
+
+    check_function_linkage(function, &mut diagnostics);
+    check_visibility(
+        function.name(),
+        function.get_linkage(),
+        function.get_visibility(),
+        &mut diagnostics,
+    );
+
+    if let Err(errors) = function.validate_blocks() {
+        diagnostics.extend(errors.into_iter().map(|error| Diagnostic::MalformedBlock {
+            function: function_name.clone(),
+            error,
+        }));
+    }
+
+    let graph = ControlFlowGraph::new(function);
+    let dominators = DominatorTree::new(&graph);
+    let chains = UseDefChains::new(&graph);
+    let blocks = graph.blocks();
+
+    // Blocks that are unreachable from the entry block are treated as being dominated by every block.
+    let dominates = |dominator: usize, block: usize| {
+        !dominators.is_reachable(block) || dominators.dominates(dominator, block)
+    };
+
+    // Parameters are available in every block of the function.
+    let is_parameter = |register: &Register| {
+        register
+            .parameter_index()
+            .and_then(|index| function.parameters().get(index))
+            == Some(register)
+    };
+
+    // Registers are printed using the same names and numbers as when the function is displayed.
+    let numbering = Numbering::new(function);
+    let register_name = |register: &Register| match numbering.register_label(register) {
+        Some(label) => label.to_string(),
+        None => register.to_string(),
+    };
+
+    for (current_block, block) in blocks.iter().enumerate() {
+        // Unnamed blocks are referred to by the same numbers as when the function is displayed.
+        let label = match numbering.block_number(block) {
+            Some(number) => unsafe {
+                // Safety: Numbers do not contain nul bytes.
+                Identifier::new_unchecked(number.to_string())
+            },
+            None => block
+                .label()
+                .expect("blocks in a function should have a label"),
+        };
+
+        let instructions = block.instructions();
+        for (index, instruction) in instructions.iter().enumerate() {
+            if let Some(message) = check_instruction_types(function, instruction) {
+                diagnostics.push(Diagnostic::TypeMismatch {
+                    function: function_name.clone(),
+                    block: label.clone(),
+                    index,
+                    message,
+                });
+            }
+
+            if let Some(reason) =
+                check_must_tail(function, instruction, instructions.get(index + 1))
+            {
+                diagnostics.push(Diagnostic::InvalidMustTail {
+                    function: function_name.clone(),
+                    block: label.clone(),
+                    index,
+                    reason,
+                });
+            }
+
+            let mut undefined_registers = Vec::new();
+
+            if let Instruction::Phi { incoming, .. } = instruction {
+                let mut incoming_blocks = incoming
+                    .iter()
+                    .map(|(_, incoming_block)| {
+                        incoming_block
+                            .block()
+                            .and_then(|block| graph.index_of(&block))
+                    })
+                    .collect::<Vec<_>>();
+                incoming_blocks.sort_unstable();
+
+                // A phi needs one incoming value for each edge from a predecessor, so a block that branches to this block
+                // more than once must appear the same number of times.
+                let mut expected_blocks = function
+                    .predecessor_edges_of(block)
+                    .iter()
+                    .map(|predecessor| graph.index_of(predecessor))
+                    .collect::<Vec<_>>();
+                expected_blocks.sort_unstable();
+
+                if incoming_blocks != expected_blocks {
+                    diagnostics.push(Diagnostic::PhiPredecessorMismatch {
+                        function: function_name.clone(),
+                        block: label.clone(),
+                        index,
+                    });
+                }
+
+                // Incoming values must be available at the end of the corresponding predecessor.
+                for (value, incoming_block) in incoming.iter() {
+                    if let Value::Register(register) = value {
+                        let available = match (
+                            chains.definition(register),
+                            incoming_block
+                                .block()
+                                .and_then(|block| graph.index_of(&block)),
+                        ) {
+                            (Some(definition), Some(incoming_block)) => {
+                                dominates(definition.block, incoming_block)
+                            }
+                            _ => is_parameter(register),
+                        };
+
+                        if !available {
+                            undefined_registers.push(register);
+                        }
+                    }
+                }
+            } else {
+                for operand in instruction.operands() {
+                    if let Value::Register(register) = operand {
+                        let available = match chains.definition(register) {
+                            Some(definition) if definition.block == current_block => {
+                                definition.instruction < index
+                            }
+                            Some(definition) => dominates(definition.block, current_block),
+                            None => is_parameter(register),
+                        };
+
+                        if !available {
+                            undefined_registers.push(register);
+                        }
+                    }
+                }
+            }
+
+            diagnostics.extend(undefined_registers.into_iter().map(|register| {
+                Diagnostic::UseBeforeDefinition {
+                    function: function_name.clone(),
+                    block: label.clone(),
+                    index,
+                    register: register_name(register),
+                }
+            }));
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// Checks a global variable, returning all of the problems that were found.
+pub fn verify_variable(variable: &global::Variable) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    check_variable_linkage(variable, &mut diagnostics);
+    check_visibility(
+        variable.name(),
+        variable.get_linkage(),
+        variable.get_visibility(),
+        &mut diagnostics,
+    );
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// Checks an indirect function, returning all of the problems that were found.
+pub fn verify_ifunc(ifunc: &global::IFunc) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+    check_visibility(
+        ifunc.name(),
+        ifunc.get_linkage(),
+        ifunc.get_visibility(),
+        &mut diagnostics,
+    );
+
+    if ifunc.resolver().basic_blocks().is_empty() {
+        diagnostics.push(Diagnostic::UndefinedResolver {
+            ifunc: ifunc.name().to_owned(),
+            resolver: ifunc.resolver().name().to_owned(),
+        });
+    }
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+/// Checks all of the global variables, functions, and indirect functions in a module, returning all of the problems that were found.
+pub fn verify_module(module: &Module) -> Result<(), Vec<Diagnostic>> {
+    let mut diagnostics = Vec::new();
+
+    for value in module.global_values() {
+        let result = match value {
+            global::Value::Variable(variable) => verify_variable(variable),
+            global::Value::Function(function) => verify_function(function),
+            global::Value::IFunc(ifunc) => verify_ifunc(ifunc),
+        };
+
+        if let Err(errors) = result {
+            diagnostics.extend(errors);
+        }
+    }
+
+    if diagnostics.is_empty() {
+        Ok(())
+    } else {
+        Err(diagnostics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{self, BasicBlock};
+    use crate::value;
+
+    /// Builds a conditional `br` whose targets are both `%join`, followed by a `phi` in `%join` with the specified number
+    /// of incoming values from the entry block.
+    fn branch_to_phi(incoming_count: usize) -> Rc<global::Function> {
+        let double_type = Rc::new(types::FirstClass::Single(types::SingleValue::Float(
+            types::Float::Double,
+        )));
+        let function = global::Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::FirstClass(double_type.clone()), Vec::new()),
+        );
+        let constant = |value| Value::Float(value::Float::Double(value));
+        let entry = BasicBlock::new();
+        let join = BasicBlock::with_name(crate::id!("join"));
+        let condition = entry.fcmp(
+            block::FcmpPredicate::OrderedLessThan,
+            constant(1.0),
+            constant(2.0),
+        );
+        entry.br_if(Value::Register(condition), &join, &join);
+
+        let result = join.phi(
+            double_type,
+            std::iter::repeat_n((constant(1.0), &entry), incoming_count).collect(),
+        );
+        join.ret(Some(Value::Register(result)));
+        function.append_basic_block(entry);
+        function.append_basic_block(join);
+        function
+    }
+
+    fn has_phi_mismatch(function: &global::Function) -> bool {
+        match verify_function(function) {
+            Ok(()) => false,
+            Err(diagnostics) => diagnostics
+                .iter()
+                .any(|diagnostic| matches!(diagnostic, Diagnostic::PhiPredecessorMismatch { .. })),
+        }
+    }
+
+    #[test]
+    fn phi_has_one_incoming_value_per_edge() {
+        assert!(!has_phi_mismatch(&branch_to_phi(2)));
+    }
+
+    #[test]
+    fn phi_missing_duplicate_edges_is_rejected() {
+        assert!(has_phi_mismatch(&branch_to_phi(1)));
+        assert!(has_phi_mismatch(&branch_to_phi(3)));
+    }
+
+    #[test]
+    fn unnamed_blocks_are_referred_to_by_number() {
+        let function = global::Function::new(
+            crate::id!("f"),
+            types::Function::new(types::Return::Void, Vec::new()),
+        );
+        let entry = BasicBlock::new();
+        let exit = BasicBlock::new();
+        entry.br(&exit);
+        exit.phi(
+            Rc::new(types::FirstClass::Single(types::SingleValue::Float(
+                types::Float::Double,
+            ))),
+            Vec::new(),
+        );
+        exit.ret(None);
+        function.append_basic_block(entry);
+        function.append_basic_block(exit);
+
+        let diagnostics = verify_function(&function).expect_err("phi should be rejected");
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "in function @f, phi instruction 0 of block %1 does not have exactly one incoming value for each edge from a predecessor"
+        );
+    }
+}